@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 地理位置信息
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Geo {
+    pub country: String,
+    pub province: String,
+    pub city: String,
+    pub isp: String,
+}
+
+/// 归一化后的资产记录
+///
+/// 各平台客户端都将原始返回映射到该结构，导出与合并只依赖这一套字段。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Asset {
+    pub ip: String,
+    pub port: Option<u16>,
+    /// 应用层协议（http / https / ssh ...）
+    pub protocol: String,
+    /// 访问主机名，优先域名，其次 IP
+    pub host: String,
+    pub domain: String,
+    pub url: String,
+    pub title: String,
+    pub server: String,
+    pub status_code: Option<u16>,
+    #[serde(flatten)]
+    pub geo: Geo,
+    pub asn: Option<u32>,
+    pub as_org: String,
+    pub os: String,
+    pub icp: String,
+    pub organization: String,
+    pub certificate: String,
+    /// 组件 / 产品指纹
+    pub fingerprints: Vec<String>,
+    /// 数据来源平台
+    pub platform: String,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+    /// 平台原始返回
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub raw: Value,
+}
+
+/// 单页搜索结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub total: u64,
+    pub results: Vec<Asset>,
+    /// 本次查询消耗的额度（部分平台返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consume_quota: Option<String>,
+    /// 剩余额度（部分平台返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rest_quota: Option<String>,
+    /// 下一页游标（游标分页的平台返回，为空表示没有更多数据）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// CSV 导出列，顺序固定
+pub const CSV_HEADERS: [&str; 23] = [
+    "ip",
+    "port",
+    "protocol",
+    "host",
+    "domain",
+    "url",
+    "title",
+    "server",
+    "status_code",
+    "country",
+    "province",
+    "city",
+    "isp",
+    "asn",
+    "as_org",
+    "os",
+    "icp",
+    "organization",
+    "certificate",
+    "fingerprints",
+    "platform",
+    "first_seen",
+    "last_seen",
+];
+
+impl Asset {
+    /// 以平台名称和原始返回创建空记录
+    pub fn new(platform: &str, raw: Value) -> Self {
+        Asset {
+            platform: platform.to_string(),
+            raw,
+            ..Default::default()
+        }
+    }
+
+    /// 补全 host 与 url：host 缺失时取域名或 IP，url 缺失时按协议拼接
+    pub fn finalize(mut self) -> Self {
+        if self.host.is_empty() {
+            self.host = if !self.domain.is_empty() {
+                self.domain.clone()
+            } else {
+                self.ip.clone()
+            };
+        }
+        if self.url.is_empty() {
+            self.url = build_url(&self.protocol, &self.host, self.port);
+        }
+        self
+    }
+
+    /// 按 [`CSV_HEADERS`] 的顺序输出一行
+    pub fn csv_record(&self) -> Vec<String> {
+        let optional = |v: Option<String>| v.unwrap_or_default();
+        vec![
+            self.ip.clone(),
+            optional(self.port.map(|p| p.to_string())),
+            self.protocol.clone(),
+            self.host.clone(),
+            self.domain.clone(),
+            self.url.clone(),
+            self.title.clone(),
+            self.server.clone(),
+            optional(self.status_code.map(|c| c.to_string())),
+            self.geo.country.clone(),
+            self.geo.province.clone(),
+            self.geo.city.clone(),
+            self.geo.isp.clone(),
+            optional(self.asn.map(|a| a.to_string())),
+            self.as_org.clone(),
+            self.os.clone(),
+            self.icp.clone(),
+            self.organization.clone(),
+            self.certificate.clone(),
+            self.fingerprints.join(", "),
+            self.platform.clone(),
+            optional(self.first_seen.clone()),
+            optional(self.last_seen.clone()),
+        ]
+    }
+}
+
+// 读取字符串字段，数字会被转成字符串，缺失时返回空串
+pub fn str_field(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+// 读取端口，兼容字符串与数字两种形式
+pub fn port_field(value: &Value) -> Option<u16> {
+    match value {
+        Value::Number(n) => n.as_u64().and_then(|p| u16::try_from(p).ok()),
+        Value::String(s) => s.trim().parse::<u16>().ok(),
+        _ => None,
+    }
+    .filter(|p| *p > 0)
+}
+
+// 读取数字字段（如 ASN、状态码），兼容字符串形式
+pub fn number_field<T: TryFrom<u64>>(value: &Value) -> Option<T> {
+    let n = match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().trim_start_matches("AS").parse::<u64>().ok(),
+        _ => None,
+    }?;
+    T::try_from(n).ok().filter(|_| n > 0)
+}
+
+// 统一协议名称（如 Quake 的 http/ssl）
+pub fn normalize_protocol(protocol: &str) -> String {
+    let protocol = protocol.trim().to_lowercase();
+    match protocol.as_str() {
+        "http/ssl" | "https/ssl" | "ssl/http" => "https".to_string(),
+        _ => protocol,
+    }
+}
+
+// 拼接访问地址，http:80 与 https:443 省略端口，IPv6 地址加方括号
+pub fn build_url(protocol: &str, host: &str, port: Option<u16>) -> String {
+    if host.is_empty() {
+        return String::new();
+    }
+    let host = match host.parse::<std::net::Ipv6Addr>() {
+        Ok(ip) => format!("[{}]", ip),
+        Err(_) => host.to_string(),
+    };
+    let protocol = if protocol.is_empty() {
+        match port {
+            Some(443) => "https",
+            _ => "http",
+        }
+    } else {
+        protocol
+    };
+    match port {
+        Some(80) if protocol == "http" => format!("{}://{}", protocol, host),
+        Some(443) if protocol == "https" => format!("{}://{}", protocol, host),
+        Some(port) => format!("{}://{}:{}", protocol, host, port),
+        None => format!("{}://{}", protocol, host),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_port_field_accepts_string_and_number() {
+        assert_eq!(port_field(&json!(8080)), Some(8080));
+        assert_eq!(port_field(&json!("443")), Some(443));
+        assert_eq!(port_field(&json!(0)), None);
+        assert_eq!(port_field(&json!(70000)), None);
+        assert_eq!(port_field(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_number_field_strips_as_prefix() {
+        assert_eq!(number_field::<u32>(&json!("AS4134")), Some(4134));
+        assert_eq!(number_field::<u32>(&json!(4837)), Some(4837));
+        assert_eq!(number_field::<u16>(&json!("")), None);
+    }
+
+    #[test]
+    fn test_build_url_omits_default_ports() {
+        assert_eq!(build_url("http", "a.com", Some(80)), "http://a.com");
+        assert_eq!(build_url("https", "a.com", Some(443)), "https://a.com");
+        assert_eq!(build_url("http", "a.com", Some(8080)), "http://a.com:8080");
+        assert_eq!(build_url("", "1.1.1.1", Some(443)), "https://1.1.1.1");
+        assert_eq!(build_url("http", "", Some(80)), "");
+        assert_eq!(build_url("http", "::1", Some(8080)), "http://[::1]:8080");
+    }
+
+    #[test]
+    fn test_finalize_prefers_domain_for_host() {
+        let asset = Asset {
+            ip: "1.1.1.1".to_string(),
+            domain: "example.com".to_string(),
+            protocol: "https".to_string(),
+            port: Some(8443),
+            ..Default::default()
+        }
+        .finalize();
+        assert_eq!(asset.host, "example.com");
+        assert_eq!(asset.url, "https://example.com:8443");
+    }
+
+    #[test]
+    fn test_csv_record_matches_headers() {
+        let asset = Asset {
+            fingerprints: vec!["nginx".to_string(), "php".to_string()],
+            ..Asset::new("hunter", Value::Null)
+        };
+        let record = asset.csv_record();
+        assert_eq!(record.len(), CSV_HEADERS.len());
+        assert_eq!(record[19], "nginx, php");
+        assert_eq!(record[20], "hunter");
+    }
+
+    #[test]
+    fn test_serialized_asset_is_flat() {
+        let asset = Asset {
+            geo: Geo {
+                country: "中国".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&asset).unwrap();
+        assert_eq!(value["country"], "中国");
+        assert!(value.get("raw").is_none());
+
+        let parsed: Asset = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, asset);
+    }
+}
@@ -0,0 +1,603 @@
+use super::asset::{
+    normalize_protocol, number_field, port_field, str_field, Asset, Geo, SearchPage,
+};
+use super::cost::{Billing, CostModel};
+use super::http;
+use super::key_manager;
+use super::source::{AssetSource, Pagination, SourceCapabilities};
+use crate::config;
+use crate::error::{retry_after_secs, ApiError};
+use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const API_BASE: &str = "https://search.censys.io/api";
+
+// Censys 单次最多返回 100 台主机
+const MAX_PER_PAGE: u32 = 100;
+
+// 游标缓存的键：(查询语句, 每页条数, 页码)
+type PageKey = (String, u32, u32);
+
+// 游标缓存的上限与有效期；Censys 的游标会过期，过期的游标不再复用
+const MAX_CACHED_CURSORS: usize = 1000;
+const CURSOR_TTL: Duration = Duration::from_secs(300);
+
+// 页码到游标（及取得时间）的缓存，避免按页码翻页时重复遍历前面的页
+static PAGE_CURSORS: Lazy<Mutex<HashMap<PageKey, (String, Instant)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// 找离目标页最近的已知游标，顺带清掉过期的游标；没有时从第 1 页开始
+fn nearest_cursor(query: &str, page_size: u32, page: u32) -> (u32, Option<String>) {
+    let mut cache = PAGE_CURSORS.lock().unwrap_or_else(|e| e.into_inner());
+    cache.retain(|_, (_, cached_at)| cached_at.elapsed() < CURSOR_TTL);
+    (2..=page)
+        .rev()
+        .find_map(|p| {
+            cache
+                .get(&(query.to_string(), page_size, p))
+                .map(|(cursor, _)| (p, Some(cursor.clone())))
+        })
+        .unwrap_or((1, None))
+}
+
+// 缓存某页的游标，超出上限时淘汰最早取得的游标
+fn remember_cursor(query: &str, page_size: u32, page: u32, cursor: &str) {
+    let mut cache = PAGE_CURSORS.lock().unwrap_or_else(|e| e.into_inner());
+    cache.insert(
+        (query.to_string(), page_size, page),
+        (cursor.to_string(), Instant::now()),
+    );
+    while cache.len() > MAX_CACHED_CURSORS {
+        let oldest = cache
+            .iter()
+            .min_by_key(|(_, (_, cached_at))| *cached_at)
+            .map(|(key, _)| key.clone());
+        match oldest {
+            Some(key) => cache.remove(&key),
+            None => break,
+        };
+    }
+}
+
+// 丢弃某个查询的全部游标：重新从第 1 页翻页或游标请求失败时调用
+fn forget_cursors(query: &str, page_size: u32) {
+    PAGE_CURSORS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|(q, size, _), _| !(q == query && *size == page_size));
+}
+
+// 解析 Censys 的错误响应；额度耗尽时 Censys 返回 429，需按提示文字与限流区分
+fn api_error(status: reqwest::StatusCode, retry_after: Option<u64>, body: &str) -> ApiError {
+    let detail = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|json| {
+            json["error"]
+                .as_str()
+                .or_else(|| json["status"].as_str())
+                .map(|s| s.to_string())
+        })
+        .unwrap_or_else(|| body.trim().to_string());
+
+    let lower = detail.to_lowercase();
+    if lower.contains("quota") {
+        ApiError::QuotaExhausted(format!("查询积分不足: {}", detail))
+    } else if status == reqwest::StatusCode::UNAUTHORIZED
+        || status == reqwest::StatusCode::FORBIDDEN
+    {
+        ApiError::Auth(format!("API密钥无效: {}", detail))
+    } else {
+        ApiError::from_status(
+            status.as_u16(),
+            retry_after,
+            format!("API返回错误({}): {}", status, detail),
+        )
+    }
+}
+
+// 以 API ID / Secret 发送 GET 请求
+async fn get_with_key(
+    url: &str,
+    api_id: &str,
+    api_secret: &str,
+    params: &[(&str, String)],
+) -> Result<Value, ApiError> {
+    // 发送请求
+    let client = crate::create_http_client().map_err(ApiError::Config)?;
+    let response = http::send(
+        "censys",
+        client
+            .get(url)
+            .basic_auth(api_id, Some(api_secret))
+            .query(params),
+    )
+    .await?;
+
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| ApiError::Network(format!("读取响应失败: {}", e)))?;
+
+    // 检查响应状态
+    if !status.is_success() {
+        return Err(api_error(status, retry_after, &response_text));
+    }
+
+    serde_json::from_str(&response_text)
+        .map_err(|e| ApiError::Parse(format!("解析JSON失败: {}", e)))
+}
+
+// 使用单个API凭据获取一页主机
+async fn search_with_key(
+    base_url: &str,
+    api_id: &str,
+    api_secret: &str,
+    query: &str,
+    cursor: Option<&str>,
+    per_page: u32,
+) -> Result<SearchPage, ApiError> {
+    let url = format!("{}/v2/hosts/search", base_url);
+    let mut params = vec![
+        ("q", query.to_string()),
+        ("per_page", per_page.min(MAX_PER_PAGE).to_string()),
+    ];
+    if let Some(cursor) = cursor.filter(|c| !c.is_empty()) {
+        params.push(("cursor", cursor.to_string()));
+    }
+
+    let response_json = get_with_key(&url, api_id, api_secret, &params).await?;
+    let result = &response_json["result"];
+
+    let results = result["hits"]
+        .as_array()
+        .map(|hits| hits.iter().flat_map(to_assets).collect())
+        .unwrap_or_default();
+
+    Ok(SearchPage {
+        total: result["total"].as_u64().unwrap_or(0),
+        results,
+        next_cursor: result["links"]["next"]
+            .as_str()
+            .filter(|next| !next.is_empty())
+            .map(|next| next.to_string()),
+        ..Default::default()
+    })
+}
+
+// 按游标搜索资产，cursor 为空表示第一页
+pub async fn search_cursor(
+    query: &str,
+    cursor: Option<&str>,
+    page_size: u32,
+) -> Result<SearchPage, ApiError> {
+    // 获取所有API凭据（API Secret 与 API ID）
+    let api_key_pairs = config::get_all_api_key_pairs("censys").map_err(ApiError::Config)?;
+
+    if api_key_pairs.is_empty() {
+        return Err(ApiError::Config("未配置Censys API密钥".to_string()));
+    }
+
+    // 将(secret, id)对转换为字符串格式，用于key_manager
+    let api_keys: Vec<String> = api_key_pairs
+        .iter()
+        .map(|(secret, id)| CensysSource.rotation_key(secret, id))
+        .collect();
+
+    // Clone data for the closure
+    let query = query.to_string();
+    let cursor = cursor.map(|c| c.to_string());
+
+    // 使用key_manager进行智能轮询
+    key_manager::execute_with_key_rotation("censys", &api_keys, |combined_key| {
+        let query = query.clone();
+        let cursor = cursor.clone();
+        let combined_key = combined_key.to_string();
+        async move {
+            // 分离 API ID 和 Secret
+            let (api_id, api_secret) = combined_key
+                .split_once(':')
+                .ok_or_else(|| ApiError::Config("API密钥格式错误".to_string()))?;
+
+            search_with_key(
+                API_BASE,
+                api_id,
+                api_secret,
+                &query,
+                cursor.as_deref(),
+                page_size,
+            )
+            .await
+        }
+    })
+    .await
+}
+
+// 按页码搜索资产：沿游标前进到目标页，途经的游标会被缓存
+pub async fn search(query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+    let page = page.max(1);
+
+    // 请求第 1 页意味着重新翻页，之前缓存的游标随之作废
+    if page == 1 {
+        forget_cursors(query, page_size);
+    }
+    let (mut current, mut cursor) = nearest_cursor(query, page_size, page);
+
+    loop {
+        let data = match search_cursor(query, cursor.as_deref(), page_size).await {
+            Ok(data) => data,
+            Err(e) => {
+                // 游标可能已失效，下次从第 1 页重新取得
+                if cursor.is_some() {
+                    forget_cursors(query, page_size);
+                }
+                return Err(e);
+            }
+        };
+        if current == page {
+            return Ok(data);
+        }
+
+        match data.next_cursor {
+            Some(next) => {
+                current += 1;
+                remember_cursor(query, page_size, current, &next);
+                cursor = Some(next);
+            }
+            // 目标页超出结果范围
+            None => {
+                return Ok(SearchPage {
+                    total: data.total,
+                    ..Default::default()
+                })
+            }
+        }
+    }
+}
+
+// 将一台主机按服务拆分为多条资产
+fn to_assets(hit: &Value) -> Vec<Asset> {
+    let location = &hit["location"];
+    let autonomous_system = &hit["autonomous_system"];
+    let first_name = |value: &Value| {
+        value
+            .as_array()
+            .and_then(|names| names.first())
+            .map(str_field)
+            .unwrap_or_default()
+    };
+    let mut host = first_name(&hit["dns"]["names"]);
+    if host.is_empty() {
+        host = first_name(&hit["dns"]["reverse_dns"]["names"]);
+    }
+
+    let base = Asset {
+        ip: str_field(&hit["ip"]),
+        host,
+        geo: Geo {
+            country: str_field(&location["country"]),
+            province: str_field(&location["province"]),
+            city: str_field(&location["city"]),
+            ..Default::default()
+        },
+        asn: number_field(&autonomous_system["asn"]),
+        as_org: str_field(&autonomous_system["name"]),
+        os: str_field(&hit["operating_system"]["product"]),
+        last_seen: Some(str_field(&hit["last_updated_at"])).filter(|s| !s.is_empty()),
+        ..Asset::new("censys", hit.clone())
+    };
+
+    let services = hit["services"].as_array().cloned().unwrap_or_default();
+    if services.is_empty() {
+        return vec![base.finalize()];
+    }
+
+    services
+        .iter()
+        .map(|service| {
+            // extended_service_name 区分 HTTP 与 HTTPS
+            let mut protocol = normalize_protocol(&str_field(&service["extended_service_name"]));
+            if protocol.is_empty() || protocol == "unknown" {
+                protocol = normalize_protocol(&str_field(&service["service_name"]));
+            }
+
+            Asset {
+                port: port_field(&service["port"]),
+                protocol,
+                ..base.clone()
+            }
+            .finalize()
+        })
+        .collect()
+}
+
+// 通过账户接口验证API凭据并查询剩余额度
+async fn validate_api_key_at(
+    base_url: &str,
+    api_id: &str,
+    api_secret: &str,
+) -> Result<ApiKeyValidationResult, String> {
+    let url = format!("{}/v1/account", base_url);
+
+    let response_json = match get_with_key(&url, api_id, api_secret, &[]).await {
+        Ok(response_json) => response_json,
+        Err(e) => {
+            return Ok(ApiKeyValidationResult {
+                valid: false,
+                message: Some(e.to_string()),
+                quota: None,
+                remaining: None,
+            })
+        }
+    };
+
+    let quota = &response_json["quota"];
+    let used = quota["used"].as_i64().unwrap_or(0);
+    // 没有 allowance 时额度未知，不能当作已用完
+    let allowance = quota["allowance"].as_i64();
+    let remaining = allowance.map(|allowance| (allowance - used).max(0));
+
+    Ok(ApiKeyValidationResult {
+        valid: true,
+        message: Some(format!(
+            "API密钥验证成功 (账号: {})",
+            response_json["email"]
+                .as_str()
+                .or_else(|| response_json["login"].as_str())
+                .unwrap_or("未知")
+        )),
+        quota: Some(match (remaining, allowance) {
+            (Some(remaining), Some(allowance)) => {
+                format!("剩余额度: {} / {}", remaining, allowance)
+            }
+            _ => "无法获取配额信息".to_string(),
+        }),
+        remaining: remaining.map(|remaining| remaining as u64),
+    })
+}
+
+// 验证API密钥（api_secret 与 api_id 成对使用）
+pub async fn validate_api_key(
+    api_secret: &str,
+    api_id: &str,
+) -> Result<ApiKeyValidationResult, String> {
+    validate_api_key_at(API_BASE, api_id, api_secret).await
+}
+
+// Censys 数据源
+pub struct CensysSource;
+
+#[async_trait]
+impl AssetSource for CensysSource {
+    fn name(&self) -> &'static str {
+        "censys"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            display_name: "Censys",
+            requires_email: true,
+            max_page_size: MAX_PER_PAGE,
+            pagination: Pagination::Cursor,
+            cost: CostModel {
+                unit: "查询次数",
+                billing: Billing::PerRequest,
+                credits: 1,
+            },
+        }
+    }
+
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+        search(query, page, page_size).await
+    }
+
+    async fn search_cursor(
+        &self,
+        query: &str,
+        cursor: Option<&str>,
+        page_size: u32,
+    ) -> Result<SearchPage, ApiError> {
+        search_cursor(query, cursor, page_size).await
+    }
+
+    // 轮询状态中以 "API ID:Secret" 标识一对凭据
+    fn rotation_key(&self, api_secret: &str, api_id: &str) -> String {
+        format!("{}:{}", api_id, api_secret)
+    }
+
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String> {
+        let api_id = email.ok_or("Censys平台需要提供API ID")?;
+        validate_api_key(api_key, api_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{basic_auth, method, path, query_param, query_param_is_missing};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn hit(ip: &str) -> Value {
+        json!({
+            "ip": ip,
+            "services": [
+                {"port": 443, "service_name": "HTTP", "extended_service_name": "HTTPS", "transport_protocol": "TCP"},
+                {"port": 22, "service_name": "SSH", "extended_service_name": "SSH", "transport_protocol": "TCP"}
+            ],
+            "location": {"country": "Germany", "province": "Hesse", "city": "Frankfurt am Main"},
+            "autonomous_system": {"asn": 24940, "name": "HETZNER-AS"},
+            "dns": {"reverse_dns": {"names": ["static.example.net"]}},
+            "operating_system": {"product": "Linux"},
+            "last_updated_at": "2024-06-01T12:00:00.000Z"
+        })
+    }
+
+    fn response(hits: Vec<Value>, next: &str) -> Value {
+        json!({
+            "code": 200,
+            "status": "OK",
+            "result": {
+                "total": 3,
+                "hits": hits,
+                "links": {"prev": "", "next": next}
+            }
+        })
+    }
+
+    #[test]
+    fn test_to_assets_splits_services() {
+        let assets = to_assets(&hit("1.2.3.4"));
+        assert_eq!(assets.len(), 2);
+        assert_eq!(assets[0].port, Some(443));
+        assert_eq!(assets[0].protocol, "https");
+        assert_eq!(assets[0].url, "https://static.example.net");
+        assert_eq!(assets[1].protocol, "ssh");
+        assert_eq!(assets[1].asn, Some(24940));
+        assert_eq!(assets[1].platform, "censys");
+    }
+
+    #[test]
+    fn test_cursor_cache_is_bounded_and_forgettable() {
+        let query = "services.port: 8443 and cursor-cache-test";
+        remember_cursor(query, 50, 2, "c2");
+        remember_cursor(query, 50, 3, "c3");
+        assert_eq!(nearest_cursor(query, 50, 5), (3, Some("c3".to_string())));
+        assert_eq!(nearest_cursor(query, 50, 2), (2, Some("c2".to_string())));
+        // 每页条数不同的游标互不复用
+        assert_eq!(nearest_cursor(query, 100, 3), (1, None));
+
+        forget_cursors(query, 50);
+        assert_eq!(nearest_cursor(query, 50, 5), (1, None));
+
+        // 超出上限时淘汰最早取得的游标
+        for p in 2..MAX_CACHED_CURSORS as u32 + 3 {
+            remember_cursor(query, 10, p, &format!("c{}", p));
+        }
+        assert!(PAGE_CURSORS.lock().unwrap().len() <= MAX_CACHED_CURSORS);
+        let (page, _) = nearest_cursor(query, 10, MAX_CACHED_CURSORS as u32 + 2);
+        assert_eq!(page, MAX_CACHED_CURSORS as u32 + 2);
+        forget_cursors(query, 10);
+    }
+
+    #[tokio::test]
+    async fn test_search_follows_next_cursor() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/hosts/search"))
+            .and(basic_auth("api-id", "api-secret"))
+            .and(query_param("q", "services.port: 443"))
+            .and(query_param_is_missing("cursor"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(response(vec![hit("1.1.1.1"), hit("2.2.2.2")], "c2")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/hosts/search"))
+            .and(query_param("cursor", "c2"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(response(vec![hit("3.3.3.3")], "")),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let first = search_with_key(
+            &server.uri(),
+            "api-id",
+            "api-secret",
+            "services.port: 443",
+            None,
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.total, 3);
+        assert_eq!(first.results.len(), 4);
+        assert_eq!(first.next_cursor.as_deref(), Some("c2"));
+
+        let second = search_with_key(
+            &server.uri(),
+            "api-id",
+            "api-secret",
+            "services.port: 443",
+            first.next_cursor.as_deref(),
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(second.results[0].ip, "3.3.3.3");
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_search_reports_exhausted_quota() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/hosts/search"))
+            .respond_with(ResponseTemplate::new(429).set_body_json(json!({
+                "code": 429,
+                "status": "Too Many Requests",
+                "error": "You have used your full quota for this billing period."
+            })))
+            .mount(&server)
+            .await;
+
+        let err = search_with_key(&server.uri(), "id", "secret", "ip: 1.1.1.1", None, 10)
+            .await
+            .unwrap_err();
+        // 额度耗尽同样返回 429，不能当作限流重试
+        assert!(err.is_key_exhausted(), "{:?}", err);
+        assert!(err.to_string().contains("积分"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_validate_api_key_reads_quota() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/account"))
+            .and(basic_auth("good-id", "good-secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "email": "user@example.com",
+                "login": "user",
+                "quota": {"used": 40, "allowance": 250, "resets_at": "2024-07-01 00:00:00"}
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/account"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "code": 401,
+                "status": "Unauthorized",
+                "error": "You must authenticate with a valid API ID and secret."
+            })))
+            .mount(&server)
+            .await;
+
+        let ok = validate_api_key_at(&server.uri(), "good-id", "good-secret")
+            .await
+            .unwrap();
+        assert!(ok.valid);
+        assert_eq!(ok.quota.as_deref(), Some("剩余额度: 210 / 250"));
+
+        let bad = validate_api_key_at(&server.uri(), "bad-id", "bad-secret")
+            .await
+            .unwrap();
+        assert!(!bad.valid);
+        assert!(bad.message.unwrap().contains("API密钥无效"));
+    }
+}
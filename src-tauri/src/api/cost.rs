@@ -0,0 +1,247 @@
+use super::asset::SearchPage;
+use super::key_manager;
+use super::source::AssetSource;
+use serde::Serialize;
+
+/// 计费方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Billing {
+    /// 按返回的条数计费
+    PerResult,
+    /// 按请求次数（页数）计费，与每页条数无关
+    PerRequest,
+}
+
+/// 平台的额度计费模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostModel {
+    /// 额度单位名称，如“积分”“F点”
+    pub unit: &'static str,
+    pub billing: Billing,
+    /// 每条结果或每次请求消耗的额度
+    pub credits: u64,
+}
+
+impl CostModel {
+    /// 请求一页预计消耗的额度
+    pub fn page_cost(&self, page_size: u32) -> u64 {
+        match self.billing {
+            Billing::PerResult => page_size as u64 * self.credits,
+            Billing::PerRequest => self.credits,
+        }
+    }
+
+    /// 按页数与每页条数预估总消耗，已知命中总数时不超过取完全部结果所需的额度
+    pub fn estimate(&self, pages: u32, page_size: u32, total: Option<u64>) -> u64 {
+        let page_size = page_size.max(1);
+        let pages = match total {
+            Some(total) => (pages as u64).min(total.div_ceil(page_size as u64)),
+            None => pages as u64,
+        };
+        match self.billing {
+            Billing::PerResult => {
+                let results = pages * page_size as u64;
+                total.map_or(results, |total| results.min(total)) * self.credits
+            }
+            Billing::PerRequest => pages * self.credits,
+        }
+    }
+
+    /// 在预算内可用的每页条数；按条计费且预算不足一页时缩小每页条数，
+    /// 所有页使用同一条数，页码翻页的偏移量才不会错位
+    pub fn fit_page_size(&self, page_size: u32, budget: Option<u64>) -> u32 {
+        match (self.billing, budget) {
+            (Billing::PerResult, Some(budget)) => {
+                let affordable = budget / self.credits.max(1);
+                page_size.min(affordable.min(u32::MAX as u64) as u32)
+            }
+            _ => page_size,
+        }
+    }
+
+    /// 一页实际消耗的额度：优先使用平台返回的消耗值，否则按返回条数推算
+    pub fn charged(&self, page: &SearchPage) -> u64 {
+        if let Some(consumed) = page.consume_quota.as_deref().and_then(parse_credits) {
+            return consumed;
+        }
+        match self.billing {
+            Billing::PerResult => page.results.len() as u64 * self.credits,
+            Billing::PerRequest => self.credits,
+        }
+    }
+}
+
+/// 从平台返回的额度文本中取出数值，如“今日剩余积分：483”取 483；没有数字时返回 `None`
+pub fn parse_credits(text: &str) -> Option<u64> {
+    let digits: String = text
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+    digits.parse().ok()
+}
+
+/// 单个密钥的剩余额度
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyBalance {
+    /// 密钥前缀，便于识别
+    pub key_hint: String,
+    pub valid: bool,
+    /// 剩余额度，平台未返回数值时为空
+    pub remaining: Option<u64>,
+    pub message: Option<String>,
+}
+
+/// 导出前的额度预估
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub platform: String,
+    pub unit: &'static str,
+    pub billing: Billing,
+    /// 预计消耗的额度
+    pub estimated: u64,
+    /// 所有有效密钥的剩余额度合计，有任一有效密钥未返回数值时为空
+    pub available: Option<u64>,
+    /// 剩余额度是否足够，无法判断时为空
+    pub sufficient: Option<bool>,
+    pub keys: Vec<KeyBalance>,
+}
+
+// 预估导出消耗，并逐个验证密钥取得剩余额度
+pub async fn estimate(
+    source: &dyn AssetSource,
+    pages: u32,
+    page_size: u32,
+    keys: &[(String, String)],
+) -> Result<CostEstimate, String> {
+    let capabilities = source.capabilities();
+    let cost = capabilities.cost;
+    let page_size = page_size.min(capabilities.max_page_size).max(1);
+    let estimated = cost.estimate(pages, page_size, None);
+
+    let mut balances = Vec::new();
+    for (key, email) in keys {
+        let email = (capabilities.requires_email && !email.is_empty()).then_some(email.as_str());
+        let key_hint = key_manager::key_hint(key);
+        let balance = match source.validate_api_key(key, email).await {
+            Ok(result) => KeyBalance {
+                key_hint,
+                valid: result.valid,
+                remaining: result.remaining,
+                message: result.message,
+            },
+            Err(e) => KeyBalance {
+                key_hint,
+                valid: false,
+                remaining: None,
+                message: Some(e),
+            },
+        };
+        balances.push(balance);
+    }
+
+    let available = available_credits(&balances);
+    Ok(CostEstimate {
+        platform: source.name().to_string(),
+        unit: cost.unit,
+        billing: cost.billing,
+        estimated,
+        available,
+        sufficient: available.map(|available| available >= estimated),
+        keys: balances,
+    })
+}
+
+// 汇总有效密钥的剩余额度
+fn available_credits(balances: &[KeyBalance]) -> Option<u64> {
+    let valid: Vec<&KeyBalance> = balances.iter().filter(|balance| balance.valid).collect();
+    if valid.is_empty() {
+        return Some(0);
+    }
+    valid.iter().map(|balance| balance.remaining).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Asset;
+
+    const PER_RESULT: CostModel = CostModel {
+        unit: "积分",
+        billing: Billing::PerResult,
+        credits: 1,
+    };
+    const PER_REQUEST: CostModel = CostModel {
+        unit: "查询积分",
+        billing: Billing::PerRequest,
+        credits: 1,
+    };
+
+    #[test]
+    fn test_estimate_by_billing() {
+        assert_eq!(PER_RESULT.estimate(5, 100, None), 500);
+        assert_eq!(PER_REQUEST.estimate(5, 100, None), 5);
+        // 命中总数不足时只计取完结果所需的页
+        assert_eq!(PER_RESULT.estimate(5, 100, Some(230)), 230);
+        assert_eq!(PER_REQUEST.estimate(5, 100, Some(230)), 3);
+    }
+
+    #[test]
+    fn test_fit_page_size_to_budget() {
+        assert_eq!(PER_RESULT.fit_page_size(100, Some(50)), 50);
+        assert_eq!(PER_RESULT.fit_page_size(100, Some(500)), 100);
+        assert_eq!(PER_RESULT.fit_page_size(100, None), 100);
+        assert_eq!(PER_REQUEST.fit_page_size(100, Some(1)), 100);
+    }
+
+    #[test]
+    fn test_charged_prefers_platform_value() {
+        let mut page = SearchPage {
+            results: vec![Asset::default(); 3],
+            ..SearchPage::default()
+        };
+        assert_eq!(PER_RESULT.charged(&page), 3);
+        assert_eq!(PER_REQUEST.charged(&page), 1);
+
+        page.consume_quota = Some("消耗积分：10".to_string());
+        assert_eq!(PER_RESULT.charged(&page), 10);
+    }
+
+    #[test]
+    fn test_parse_credits() {
+        assert_eq!(parse_credits("今日剩余积分：483"), Some(483));
+        assert_eq!(parse_credits("1,200"), Some(1200));
+        assert_eq!(parse_credits("F币: 0"), Some(0));
+        assert_eq!(parse_credits("未知"), None);
+    }
+
+    #[test]
+    fn test_available_credits() {
+        let balance = |valid, remaining| KeyBalance {
+            key_hint: "abcdefgh...".to_string(),
+            valid,
+            remaining,
+            message: None,
+        };
+        assert_eq!(
+            available_credits(&[balance(true, Some(100)), balance(true, Some(50))]),
+            Some(150)
+        );
+        // 无效密钥不计入
+        assert_eq!(
+            available_credits(&[balance(true, Some(100)), balance(false, None)]),
+            Some(100)
+        );
+        // 有效密钥未返回额度时无法判断
+        assert_eq!(
+            available_credits(&[balance(true, Some(100)), balance(true, None)]),
+            None
+        );
+        assert_eq!(available_credits(&[]), Some(0));
+    }
+}
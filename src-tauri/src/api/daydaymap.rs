@@ -1,6 +1,12 @@
+use super::asset::{port_field, str_field, Asset, Geo, SearchPage};
+use super::cost::{parse_credits, Billing, CostModel};
+use super::http;
 use super::key_manager;
+use super::source::{AssetSource, Pagination, SourceCapabilities};
 use crate::config;
+use crate::error::{retry_after_secs, ApiError};
 use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
 
 use serde_json::{json, Value};
@@ -11,7 +17,7 @@ async fn search_with_key(
     query: &str,
     page: u32,
     page_size: u32,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let base_url = "https://www.daydaymap.com/api/v1/raymap/search/all";
 
     // Base64编码查询字符串
@@ -30,48 +36,67 @@ async fn search_with_key(
     }
 
     // 发送请求
-    let client = crate::create_http_client()?;
-    let response = client
-        .post(base_url)
-        .header("API-Key", api_key)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    let client = crate::create_http_client().map_err(ApiError::Config)?;
+    let response = http::send(
+        "daydaymap",
+        client
+            .post(base_url)
+            .header("API-Key", api_key)
+            .header("Content-Type", "application/json")
+            .json(&request_body),
+    )
+    .await?;
 
     // 检查响应状态
-    if !response.status().is_success() {
-        return Err(format!("API返回错误状态码: {}", response.status()));
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ApiError::from_status(
+            status.as_u16(),
+            retry_after_secs(response.headers()),
+            format!("API返回错误状态码: {}", status),
+        ));
     }
 
     // 解析响应
     let response_text = response
         .text()
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+        .map_err(|e| ApiError::Network(format!("读取响应失败: {}", e)))?;
 
-    let response_json: Value =
-        serde_json::from_str(&response_text).map_err(|e| format!("解析JSON失败: {}", e))?;
+    let response_json: Value = serde_json::from_str(&response_text)
+        .map_err(|e| ApiError::Parse(format!("解析JSON失败: {}", e)))?;
 
     // 检查API返回的状态码
     let code = response_json["code"].as_u64().unwrap_or(0);
 
     if code != 200 {
         let msg = response_json["msg"].as_str().unwrap_or("未知错误");
-        return Err(format!("API返回错误({}): {}", code, msg));
+        return Err(api_error(code, format!("API返回错误({}): {}", code, msg)));
     }
 
     Ok(response_json)
 }
 
+// 将 DayDayMap 响应中的错误码映射为错误类型，未知错误码按提示文字归类
+fn api_error(code: u64, message: String) -> ApiError {
+    match code {
+        401 | 403 => ApiError::Auth(message),
+        429 => ApiError::RateLimited {
+            retry_after: None,
+            message,
+        },
+        2004 => ApiError::QuotaExhausted(message),
+        _ => ApiError::from_platform_message(message),
+    }
+}
+
 // 搜索资产 - 真实实现
-pub async fn search(query: &str, page: u32, page_size: u32) -> Result<Value, String> {
+pub async fn search(query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
     // 获取所有API密钥
-    let api_keys = config::get_all_daydaymap_api_keys()?;
+    let api_keys = config::get_all_api_keys("daydaymap").map_err(ApiError::Config)?;
 
     if api_keys.is_empty() {
-        return Err("未配置DayDayMap API密钥".to_string());
+        return Err(ApiError::Config("未配置DayDayMap API密钥".to_string()));
     }
 
     // Clone data for the closure
@@ -87,260 +112,47 @@ pub async fn search(query: &str, page: u32, page_size: u32) -> Result<Value, Str
 
     match result {
         Ok(response_json) => {
-            // 提取结果
             let data = &response_json["data"];
-            let total = data["total"].as_u64().unwrap_or(0);
-            let list = data["list"].as_array().unwrap_or(&Vec::new()).clone();
-
-            // 格式化结果
-            let results = list
-                .iter()
-                .map(|item| {
-                    // 智能推断协议：优先使用 API 返回的 protocol，否则根据端口判断
-                    let port = item["port"].as_i64().unwrap_or(80);
-                    let protocol = item["protocol"].as_str().unwrap_or(if port == 443 {
-                        "https"
-                    } else {
-                        "http"
-                    });
-                    // 优先使用域名，其次使用 IP
-                    let domain = item["domain"].as_str().unwrap_or("");
-                    let ip = item["ip"].as_str().unwrap_or("");
-                    let host = if !domain.is_empty() { domain } else { ip };
-                    // 省略默认端口号（http:80, https:443）
-                    let url = if (protocol == "https" && port == 443)
-                        || (protocol == "http" && port == 80)
-                    {
-                        format!("{}://{}", protocol, host)
-                    } else {
-                        format!("{}://{}:{}", protocol, host, port)
-                    };
-
-                    json!({
-                        "ip": ip,
-                        "port": port,
-                        "domain": domain,
-                        "web_title": item["title"].as_str().unwrap_or(""),
-                        "server": item["server"].as_str().unwrap_or(""),
-                        "country": item["country"].as_str().unwrap_or(""),
-                        "province": item["province"].as_str().unwrap_or(""),
-                        "city": item["city"].as_str().unwrap_or(""),
-                        "isp": item["isp"].as_str().unwrap_or(""),
-                        "url": url,
-                    })
-                })
-                .collect::<Vec<Value>>();
-
-            Ok(json!({
-                "total": total,
-                "results": results
-            }))
+            let results = data["list"]
+                .as_array()
+                .map(|list| list.iter().map(to_asset).collect())
+                .unwrap_or_default();
+
+            Ok(SearchPage {
+                total: data["total"].as_u64().unwrap_or(0),
+                results,
+                ..Default::default()
+            })
         }
         Err(e) => Err(e),
     }
 }
 
-// 导出资产 - 真实实现（带重试和部分导出）
-pub async fn export(
-    query: &str,
-    pages: u32,
-    page_size: u32,
-    _time_range: &str,
-    _start_date: Option<String>,
-    _end_date: Option<String>,
-    export_path: &str,
-) -> Result<(), String> {
-    let mut all_results = Vec::new();
-    let mut successful_pages = 0;
-    let mut last_error: Option<String> = None;
-
-    // 重试配置
-    const MAX_RETRIES: u32 = 3;
-    const RETRY_DELAY_SECS: u64 = 5;
-
-    // 分页获取所有数据
-    for page in 1..=pages {
-        eprintln!("正在导出第 {}/{} 页...", page, pages);
-
-        let mut retry_count = 0;
-        let mut page_success = false;
-
-        // 重试循环
-        while retry_count < MAX_RETRIES && !page_success {
-            match search(query, page, page_size).await {
-                Ok(data) => {
-                    if let Some(results) = data["results"].as_array() {
-                        all_results.extend(results.clone());
-                        successful_pages += 1;
-                        eprintln!("第 {} 页成功: 获取 {} 条数据", page, results.len());
-                        page_success = true;
-                    }
-                }
-                Err(e) => {
-                    last_error = Some(e.clone());
-
-                    // 检查是否是不可重试的错误
-                    if e.contains("积分不足")
-                        || e.contains("额度不足")
-                        || e.contains("API密钥无效")
-                        || e.contains("未授权")
-                    {
-                        eprintln!("第 {} 页失败（不可重试）: {}", page, e);
-                        eprintln!("检测到额度耗尽或权限问题，停止导出");
-                        break; // 跳出重试循环
-                    }
-
-                    retry_count += 1;
-                    if retry_count < MAX_RETRIES {
-                        eprintln!("第 {} 页失败（第 {} 次重试）: {}", page, retry_count, e);
-                        eprintln!("等待 {} 秒后重试...", RETRY_DELAY_SECS);
-                        tokio::time::sleep(tokio::time::Duration::from_secs(RETRY_DELAY_SECS))
-                            .await;
-                    } else {
-                        eprintln!("第 {} 页失败（已达最大重试次数）: {}", page, e);
-                    }
-                }
-            }
-        }
-
-        // 如果页面失败且不可重试，停止导出
-        if let Some(error) = last_error.as_ref().filter(|_| !page_success) {
-            if error.contains("积分不足")
-                || error.contains("额度不足")
-                || error.contains("API密钥无效")
-                || error.contains("未授权")
-            {
-                eprintln!("由于额度或权限问题，停止继续导出");
-                break; // 跳出页面循环
-            }
-        }
-
-        // 避免请求过快，增加延迟
-        if page < pages && page_success {
-            eprintln!("等待 2 秒后继续...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        }
-    }
-
-    // 检查是否有数据需要导出
-    if all_results.is_empty() {
-        return Err("没有成功获取任何数据".to_string());
-    }
-
-    let total_results = all_results.len(); // 保存长度
-
-    // 生成文件名（包含成功页数信息）
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let file_name = if successful_pages < pages {
-        format!(
-            "daydaymap_export_{}_partial_{}of{}_pages.csv",
-            timestamp, successful_pages, pages
-        )
-    } else {
-        format!("daydaymap_export_{}.csv", timestamp)
-    };
-    let file_path = format!("{}/{}", export_path, file_name);
-
-    eprintln!("开始写入文件: {}", file_path);
-    eprintln!(
-        "成功导出 {} 页，共 {} 条数据",
-        successful_pages, total_results
-    );
-
-    // 创建CSV文件
-    let mut wtr = csv::Writer::from_path(&file_path).map_err(|e| format!("创建文件失败: {}", e))?;
-
-    // 写入CSV头部
-    wtr.write_record([
-        "IP",
-        "端口",
-        "域名",
-        "标题",
-        "服务器",
-        "国家",
-        "省份",
-        "城市",
-        "URL",
-    ])
-    .map_err(|e| format!("写入CSV头部失败: {}", e))?;
-
-    // 写入数据
-    for result in all_results {
-        wtr.write_record([
-            result["ip"].as_str().unwrap_or(""),
-            &result["port"].as_i64().unwrap_or(0).to_string(),
-            result["domain"].as_str().unwrap_or(""),
-            result["web_title"].as_str().unwrap_or(""),
-            result["server"].as_str().unwrap_or(""),
-            result["country"].as_str().unwrap_or(""),
-            result["province"].as_str().unwrap_or(""),
-            result["city"].as_str().unwrap_or(""),
-            result["url"].as_str().unwrap_or(""),
-        ])
-        .map_err(|e| format!("写入数据失败: {}", e))?;
-    }
-
-    wtr.flush().map_err(|e| format!("刷新CSV写入失败: {}", e))?;
-
-    eprintln!("文件写入完成: {}", file_path);
-
-    // 如果有部分失败，返回警告信息
-    if successful_pages < pages {
-        if let Some(error) = last_error {
-            return Err(format!(
-                "部分导出成功: 已保存 {} 页（共 {} 条数据）到文件 {}。\n最后错误: {}",
-                successful_pages, total_results, file_path, error
-            ));
-        }
-    }
-
-    Ok(())
-}
-
-// 导出全部资产 - 真实实现
-pub async fn export_all(
-    query: &str,
-    _pages: u32,
-    page_size: u32,
-    time_range: &str,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    export_path: &str,
-) -> Result<(), String> {
-    // 先获取总数
-    eprintln!("正在获取查询总数...");
-    let first_page = search(query, 1, page_size).await?;
-    let total = first_page["total"].as_u64().unwrap_or(0);
-    let total_pages = ((total as f64) / (page_size as f64)).ceil() as u32;
-
-    eprintln!(
-        "查询总数: {}, 总页数: {}, 每页: {}",
-        total, total_pages, page_size
-    );
-
-    // 限制最大导出页数（避免请求过多）
-    let max_pages = 100;
-    let export_pages = if total_pages > max_pages {
-        eprintln!(
-            "警告: 总页数({})超过限制({}), 将只导出前{}页",
-            total_pages, max_pages, max_pages
-        );
-        max_pages
-    } else {
-        total_pages
-    };
-
-    // 调用 export 函数
-    export(
-        query,
-        export_pages,
-        page_size,
-        time_range,
-        start_date,
-        end_date,
-        export_path,
-    )
-    .await
+// 将 DayDayMap 返回的单条记录映射为资产
+fn to_asset(item: &Value) -> Asset {
+    let port = port_field(&item["port"]);
+    // 智能推断协议：优先使用 API 返回的 protocol，否则根据端口判断
+    let mut protocol = str_field(&item["protocol"]).to_lowercase();
+    if protocol.is_empty() {
+        protocol = if port == Some(443) { "https" } else { "http" }.to_string();
+    }
+
+    Asset {
+        ip: str_field(&item["ip"]),
+        port,
+        protocol,
+        domain: str_field(&item["domain"]),
+        title: str_field(&item["title"]),
+        server: str_field(&item["server"]),
+        geo: Geo {
+            country: str_field(&item["country"]),
+            province: str_field(&item["province"]),
+            city: str_field(&item["city"]),
+            isp: str_field(&item["isp"]),
+        },
+        ..Asset::new("daydaymap", item.clone())
+    }
+    .finalize()
 }
 
 // 验证API密钥 - 真实实现
@@ -350,12 +162,14 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
 
     // 方法1: 尝试用户信息接口
     let user_info_url = "https://www.daydaymap.com/api/v1/user/info";
-    let user_info_response = client
-        .get(user_info_url)
-        .header("api-key", api_key)
-        .header("Content-Type", "application/json")
-        .send()
-        .await;
+    let user_info_response = http::send(
+        "daydaymap",
+        client
+            .get(user_info_url)
+            .header("api-key", api_key)
+            .header("Content-Type", "application/json"),
+    )
+    .await;
 
     // 如果用户信息接口成功，尝试提取额度
     if let Ok(response) = user_info_response {
@@ -368,22 +182,24 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
                         let data = &response_json["data"];
 
                         // 尝试提取额度信息
-                        let quota_info = if let Some(credit) = data["credit"].as_i64() {
-                            format!("剩余积分: {}", credit)
+                        let (quota_info, remaining) = if let Some(credit) = data["credit"].as_i64()
+                        {
+                            (format!("剩余积分: {}", credit), Some(credit.max(0) as u64))
                         } else if let Some(quota) = data["quota"].as_i64() {
-                            format!("剩余配额: {}", quota)
+                            (format!("剩余配额: {}", quota), Some(quota.max(0) as u64))
                         } else if let Some(credit) = data["credit"].as_str() {
-                            format!("剩余积分: {}", credit)
+                            (format!("剩余积分: {}", credit), parse_credits(credit))
                         } else if let Some(quota) = data["quota"].as_str() {
-                            format!("剩余配额: {}", quota)
+                            (format!("剩余配额: {}", quota), parse_credits(quota))
                         } else {
-                            "API密钥有效".to_string()
+                            ("API密钥有效".to_string(), None)
                         };
 
                         return Ok(ApiKeyValidationResult {
                             valid: true,
                             message: Some("API密钥验证成功".to_string()),
                             quota: Some(quota_info),
+                            remaining,
                         });
                     }
                 }
@@ -402,14 +218,15 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
         "page_size": 1
     });
 
-    let response = client
-        .post(search_url)
-        .header("api-key", api_key)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    let response = http::send(
+        "daydaymap",
+        client
+            .post(search_url)
+            .header("api-key", api_key)
+            .header("Content-Type", "application/json")
+            .json(&request_body),
+    )
+    .await?;
 
     let status = response.status();
 
@@ -419,6 +236,7 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
                 valid: false,
                 message: Some("API密钥无效或已过期".to_string()),
                 quota: None,
+                remaining: None,
             });
         }
 
@@ -426,6 +244,7 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
             valid: false,
             message: Some(format!("API返回错误状态码: {}", status)),
             quota: None,
+            remaining: None,
         });
     }
 
@@ -443,6 +262,7 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
             valid: true,
             message: Some("API密钥验证成功".to_string()),
             quota: Some("API密钥有效（该密钥无权限查看额度信息）".to_string()),
+            remaining: None,
         })
     } else {
         let message = response_json["message"]
@@ -455,10 +275,47 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
             valid: false,
             message: Some(message),
             quota: None,
+            remaining: None,
         })
     }
 }
 
+// DayDayMap 数据源
+pub struct DayDayMapSource;
+
+#[async_trait]
+impl AssetSource for DayDayMapSource {
+    fn name(&self) -> &'static str {
+        "daydaymap"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            display_name: "DayDayMap",
+            requires_email: false,
+            max_page_size: 100,
+            pagination: Pagination::PageNumber,
+            cost: CostModel {
+                unit: "积分",
+                billing: Billing::PerResult,
+                credits: 1,
+            },
+        }
+    }
+
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+        search(query, page, page_size).await
+    }
+
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        _email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String> {
+        validate_api_key(api_key).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1656,12 +1513,14 @@ mod tests {
                 valid: false,
                 message: Some("API密钥无效或已过期".to_string()),
                 quota: None,
+                remaining: None,
             }
         } else {
             ApiKeyValidationResult {
                 valid: true,
                 message: Some("Success".to_string()),
                 quota: None,
+                remaining: None,
             }
         };
 
@@ -1702,6 +1561,7 @@ mod tests {
             valid: false,
             message: Some("API密钥无效或已过期".to_string()),
             quota: None,
+            remaining: None,
         };
 
         // Other error codes (e.g., 403, 500) should return different messages
@@ -1709,6 +1569,7 @@ mod tests {
             valid: false,
             message: Some("API返回错误状态码: 403".to_string()),
             quota: None,
+            remaining: None,
         };
 
         // Verify they have different messages
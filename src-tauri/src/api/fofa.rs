@@ -1,10 +1,33 @@
+use super::asset::{number_field, port_field, str_field, Asset, Geo, SearchPage};
+use super::cost::{Billing, CostModel};
+use super::http;
 use super::key_manager;
+use super::source::{AssetSource, Pagination, SourceCapabilities};
 use crate::config;
+use crate::error::{retry_after_secs, ApiError};
 use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
 
-use serde_json::{json, Value};
-use std::path::Path;
+use serde_json::Value;
+
+// 请求的返回字段，顺序与 to_asset 中的解析一致
+const RESULT_FIELDS: [&str; 14] = [
+    "host",
+    "ip",
+    "port",
+    "protocol",
+    "title",
+    "domain",
+    "server",
+    "country_name",
+    "region",
+    "city",
+    "as_number",
+    "as_organization",
+    "os",
+    "icp",
+];
 
 // 使用单个API key进行搜索
 async fn search_with_key(
@@ -13,7 +36,7 @@ async fn search_with_key(
     query: &str,
     page: u32,
     page_size: u32,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let base_url = "https://fofa.info/api/v1/search/all";
 
     // 对查询字符串进行Base64编码
@@ -26,40 +49,36 @@ async fn search_with_key(
         ("qbase64", encoded_query),
         ("page", page.to_string()),
         ("size", page_size.to_string()),
-        (
-            "fields",
-            "host,ip,port,title,country,province,city,server".to_string(),
-        ),
+        ("fields", RESULT_FIELDS.join(",")),
     ];
 
     // 发送请求
-    let client = crate::create_http_client()?;
-    let response = client
-        .get(base_url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    let client = crate::create_http_client().map_err(ApiError::Config)?;
+    let response = http::send("fofa", client.get(base_url).query(&params)).await?;
 
     // 检查响应状态
-    if !response.status().is_success() {
-        return Err(format!("API返回错误状态码: {}", response.status()));
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ApiError::from_status(
+            status.as_u16(),
+            retry_after_secs(response.headers()),
+            format!("API返回错误状态码: {}", status),
+        ));
     }
 
     // 解析响应
     let response_text = response
         .text()
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
-    let response_json: Value =
-        serde_json::from_str(&response_text).map_err(|e| format!("解析JSON失败: {}", e))?;
+        .map_err(|e| ApiError::Network(format!("读取响应失败: {}", e)))?;
+    let response_json: Value = serde_json::from_str(&response_text)
+        .map_err(|e| ApiError::Parse(format!("解析JSON失败: {}", e)))?;
 
     // 检查API返回的错误
     if let Some(error) = response_json["error"].as_bool() {
         if error {
-            return Err(format!(
-                "API返回错误: {}",
-                response_json["errmsg"].as_str().unwrap_or("未知错误")
+            return Err(api_error(
+                response_json["errmsg"].as_str().unwrap_or("未知错误"),
             ));
         }
     }
@@ -67,19 +86,33 @@ async fn search_with_key(
     Ok(response_json)
 }
 
+// FOFA 的错误信息形如 "[-700] Account Invalid"，按方括号中的错误码归类
+fn api_error(errmsg: &str) -> ApiError {
+    let message = format!("API返回错误: {}", errmsg);
+    let code = errmsg
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .map(|(code, _)| code.trim());
+    match code {
+        Some("-700") | Some("-701") | Some("-702") => ApiError::Auth(message),
+        Some("820031") => ApiError::QuotaExhausted(message),
+        _ => ApiError::from_platform_message(message),
+    }
+}
+
 // 搜索资产
-pub async fn search(query: &str, page: u32, page_size: u32) -> Result<Value, String> {
+pub async fn search(query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
     // 获取所有API密钥（包含email）
-    let api_key_pairs = config::get_all_fofa_api_keys()?;
+    let api_key_pairs = config::get_all_api_key_pairs("fofa").map_err(ApiError::Config)?;
 
     if api_key_pairs.is_empty() {
-        return Err("未配置FOFA API密钥".to_string());
+        return Err(ApiError::Config("未配置FOFA API密钥".to_string()));
     }
 
     // 将(key, email)对转换为字符串格式，用于key_manager
     let api_keys: Vec<String> = api_key_pairs
         .iter()
-        .map(|(key, email)| format!("{}:{}", key, email))
+        .map(|(key, email)| FofaSource.rotation_key(key, email))
         .collect();
 
     // Clone data for the closure
@@ -93,7 +126,7 @@ pub async fn search(query: &str, page: u32, page_size: u32) -> Result<Value, Str
             // 分离key和email
             let parts: Vec<&str> = combined_key.split(':').collect();
             if parts.len() != 2 {
-                return Err("API密钥格式错误".to_string());
+                return Err(ApiError::Config("API密钥格式错误".to_string()));
             }
             let api_key = parts[0];
             let email = parts[1];
@@ -105,343 +138,73 @@ pub async fn search(query: &str, page: u32, page_size: u32) -> Result<Value, Str
 
     match result {
         Ok(response_json) => {
-            // 提取结果
-            let total = response_json["size"].as_u64().unwrap_or(0);
             let results = response_json["results"]
                 .as_array()
-                .unwrap_or(&Vec::new())
-                .clone();
-
-            // 格式化结果
-            let fields = [
-                "host",
-                "ip",
-                "port",
-                "web_title",
-                "country",
-                "province",
-                "city",
-                "server",
-            ];
-            let formatted_results = results
-                .iter()
-                .map(|item| {
-                    let empty_vec = Vec::new();
-                    let array = item.as_array().unwrap_or(&empty_vec);
-                    let mut result = json!({});
-
-                    for (i, field) in fields.iter().enumerate() {
-                        if i < array.len() {
-                            result[field] = array[i].clone();
-                        }
-                    }
-
-                    // 构建URL
-                    if let Some(host) = result["host"].as_str() {
-                        result["url"] = Value::String(host.to_string());
-                    }
-
-                    result
-                })
-                .collect::<Vec<Value>>();
-
-            Ok(json!({
-                "total": total,
-                "results": formatted_results
-            }))
+                .map(|rows| rows.iter().map(to_asset).collect())
+                .unwrap_or_default();
+
+            Ok(SearchPage {
+                total: response_json["size"].as_u64().unwrap_or(0),
+                results,
+                ..Default::default()
+            })
         }
         Err(e) => Err(e),
     }
 }
 
-// 导出资产
-pub async fn export(
-    query: &str,
-    pages: u32,
-    page_size: u32,
-    time_range: &str,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    export_path: &str,
-) -> Result<(), String> {
-    // 处理时间范围
-    let mut final_query = query.to_string();
-
-    if time_range != "all" {
-        if time_range != "custom" {
-            let days = time_range.replace("d", "");
-            final_query = format!("{} && before=\"{}d\"", final_query, days);
-        } else if let (Some(start), Some(end)) = (start_date, end_date) {
-            final_query = format!(
-                "{} && before=\"{}\" && after=\"{}\"",
-                final_query, end, start
-            );
-        }
-    }
-
-    // 查询数据
-    let mut all_results = Vec::new();
-    let mut last_successful_page = 0;
-    let max_retries = 3;
-    let retry_delay_secs = 5;
-
-    for page in 1..=pages {
-        eprintln!("正在导出第 {}/{} 页...", page, pages);
-
-        let mut retry_count = 0;
-        let mut page_success = false;
-
-        while retry_count < max_retries && !page_success {
-            match search(&final_query, page, page_size).await {
-                Ok(result) => {
-                    if let Some(results) = result["results"].as_array() {
-                        all_results.extend(results.clone());
-                        eprintln!("第 {} 页成功: 获取 {} 条数据", page, results.len());
-                        last_successful_page = page;
-                        page_success = true;
-                    }
-                }
-                Err(e) => {
-                    // 检查是否是配额耗尽错误
-                    if e.contains("F币")
-                        || e.contains("quota")
-                        || e.contains("所有API Key都无法使用")
-                    {
-                        eprintln!("FOFA: 配额耗尽，停止导出");
-
-                        // 保存部分导出的数据
-                        if !all_results.is_empty() {
-                            let timestamp =
-                                chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-                            let file_path = format!(
-                                "{}/fofa_export_{}_partial_{}of{}_pages.csv",
-                                export_path, timestamp, last_successful_page, pages
-                            );
-
-                            save_to_csv(&file_path, &all_results)?;
-                            eprintln!("已保存部分数据到: {}", file_path);
-                        }
-
-                        return Err(format!(
-                            "第{}页查询失败(配额耗尽): {}。已保存前{}页数据",
-                            page, e, last_successful_page
-                        ));
-                    }
-
-                    // 其他错误，尝试重试
-                    retry_count += 1;
-                    if retry_count < max_retries {
-                        eprintln!(
-                            "第 {} 页失败，{} 秒后重试 ({}/{})...",
-                            page, retry_delay_secs, retry_count, max_retries
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(retry_delay_secs))
-                            .await;
-                    } else {
-                        eprintln!("第 {} 页失败，已达到最大重试次数", page);
-
-                        // 保存部分导出的数据
-                        if !all_results.is_empty() {
-                            let timestamp =
-                                chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-                            let file_path = format!(
-                                "{}/fofa_export_{}_partial_{}of{}_pages.csv",
-                                export_path, timestamp, last_successful_page, pages
-                            );
-
-                            save_to_csv(&file_path, &all_results)?;
-                            eprintln!("已保存部分数据到: {}", file_path);
-                        }
-
-                        return Err(format!(
-                            "第{}页查询失败: {}。已保存前{}页数据",
-                            page, e, last_successful_page
-                        ));
-                    }
-                }
-            }
-        }
-
-        // 避免请求过快，增加延迟到2秒
-        if page < pages {
-            eprintln!("等待 2 秒后继续...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        }
-    }
-
-    // 导出到CSV
-    if all_results.is_empty() {
-        return Err("未找到结果".to_string());
-    }
-
-    // 生成导出文件名
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let file_path = format!("{}/fofa_export_{}.csv", export_path, timestamp);
-
-    save_to_csv(&file_path, &all_results)?;
-    eprintln!("导出完成: {}", file_path);
-
-    Ok(())
-}
-
-// 保存数据到CSV文件
-fn save_to_csv(file_path: &str, results: &[Value]) -> Result<(), String> {
-    // 确保导出目录存在
-    if let Some(export_dir) = Path::new(file_path).parent() {
-        if !export_dir.exists() {
-            std::fs::create_dir_all(export_dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
-        }
-    }
-
-    // 写入CSV文件
-    let mut writer =
-        csv::Writer::from_path(file_path).map_err(|e| format!("创建CSV文件失败: {}", e))?;
-
-    // 获取所有字段
-    let mut fields = Vec::new();
-    if let Some(first) = results.first() {
-        if let Some(obj) = first.as_object() {
-            fields = obj.keys().cloned().collect();
-        }
-    }
-
-    // 写入CSV头
-    writer
-        .write_record(&fields)
-        .map_err(|e| format!("写入CSV头失败: {}", e))?;
-
-    // 写入数据
-    for result in results {
-        let mut record = Vec::new();
-        for field in &fields {
-            if let Some(value) = result.get(field) {
-                if value.is_string() {
-                    record.push(value.as_str().unwrap_or("").to_string());
-                } else {
-                    record.push(value.to_string());
-                }
-            } else {
-                record.push(String::new());
-            }
-        }
-        writer
-            .write_record(&record)
-            .map_err(|e| format!("写入CSV数据失败: {}", e))?;
-    }
-
-    writer
-        .flush()
-        .map_err(|e| format!("保存CSV文件失败: {}", e))?;
-
-    Ok(())
-}
-
-// 导出全部资产
-pub async fn export_all(
-    query: &str,
-    pages: u32,
-    page_size: u32,
-    time_range: &str,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    export_path: &str,
-) -> Result<(), String> {
-    // 处理时间范围
-    let mut final_query = query.to_string();
-
-    if time_range != "all" {
-        if time_range != "custom" {
-            let days = time_range.replace("d", "");
-            final_query = format!("{} && before=\"{}d\"", final_query, days);
-        } else if let (Some(start), Some(end)) = (start_date, end_date) {
-            final_query = format!(
-                "{} && before=\"{}\" && after=\"{}\"",
-                final_query, end, start
-            );
-        }
-    }
-
-    // 先获取总数
-    let initial_result = search(&final_query, 1, 1).await?;
-    let total = initial_result["total"].as_u64().unwrap_or(0) as u32;
-
-    // 计算实际需要的页数
-    let actual_pages = if total > 0 {
-        total.div_ceil(page_size)
-    } else {
-        0
+// 将 FOFA 返回的单行（按 RESULT_FIELDS 排列的数组）映射为资产
+fn to_asset(row: &Value) -> Asset {
+    let field = |name: &str| {
+        RESULT_FIELDS
+            .iter()
+            .position(|f| *f == name)
+            .and_then(|i| row.get(i))
+            .unwrap_or(&Value::Null)
     };
 
-    // 限制最大页数
-    let pages_to_fetch = std::cmp::min(actual_pages, pages);
-
-    // 查询数据
-    let mut all_results = Vec::new();
-
-    for page in 1..=pages_to_fetch {
-        let result = search(&final_query, page, page_size).await?;
-
-        if let Some(results) = result["results"].as_array() {
-            all_results.extend(results.clone());
-        }
-    }
-
-    // 导出到CSV
-    if all_results.is_empty() {
-        return Err("未找到结果".to_string());
-    }
-
-    // 确保导出目录存在
-    let export_dir = Path::new(export_path);
-    if !export_dir.exists() {
-        std::fs::create_dir_all(export_dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
-    }
-
-    // 生成导出文件名
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let export_file = export_dir.join(format!("fofa_all_export_{}.csv", timestamp));
-
-    // 写入CSV文件
-    let mut writer =
-        csv::Writer::from_path(export_file).map_err(|e| format!("创建CSV文件失败: {}", e))?;
+    // host 可能带有协议前缀和端口，如 https://example.com:8443
+    let raw_host = str_field(field("host"));
+    let (scheme, host) = match raw_host.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_lowercase(), rest.to_string()),
+        None => (String::new(), raw_host.clone()),
+    };
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.parse::<u16>().is_ok() => name.to_string(),
+        _ => host,
+    };
 
-    // 获取所有字段
-    let mut fields = Vec::new();
-    if let Some(first) = all_results.first() {
-        if let Some(obj) = first.as_object() {
-            fields = obj.keys().cloned().collect();
-        }
+    let mut protocol = str_field(field("protocol")).to_lowercase();
+    if protocol.is_empty() || (protocol == "http" && scheme == "https") {
+        protocol = scheme;
     }
 
-    // 写入CSV头
-    writer
-        .write_record(&fields)
-        .map_err(|e| format!("写入CSV头失败: {}", e))?;
-
-    // 写入数据
-    for result in all_results {
-        let mut record = Vec::new();
-        for field in &fields {
-            if let Some(value) = result.get(field) {
-                if value.is_string() {
-                    record.push(value.as_str().unwrap_or("").to_string());
-                } else {
-                    record.push(value.to_string());
-                }
-            } else {
-                record.push(String::new());
-            }
-        }
-        writer
-            .write_record(&record)
-            .map_err(|e| format!("写入CSV数据失败: {}", e))?;
+    Asset {
+        ip: str_field(field("ip")),
+        port: port_field(field("port")),
+        protocol,
+        host,
+        domain: str_field(field("domain")),
+        url: if raw_host.contains("://") {
+            raw_host.clone()
+        } else {
+            String::new()
+        },
+        title: str_field(field("title")),
+        server: str_field(field("server")),
+        geo: Geo {
+            country: str_field(field("country_name")),
+            province: str_field(field("region")),
+            city: str_field(field("city")),
+            ..Default::default()
+        },
+        asn: number_field(field("as_number")),
+        as_org: str_field(field("as_organization")),
+        os: str_field(field("os")),
+        icp: str_field(field("icp")),
+        ..Asset::new("fofa", row.clone())
     }
-
-    writer
-        .flush()
-        .map_err(|e| format!("保存CSV文件失败: {}", e))?;
-
-    Ok(())
+    .finalize()
 }
 
 // 验证API密钥
@@ -456,12 +219,7 @@ pub async fn validate_api_key(
 
     // 发送请求
     let client = crate::create_http_client()?;
-    let response = client
-        .get(base_url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    let response = http::send("fofa", client.get(base_url).query(&params)).await?;
 
     // 检查响应状态
     if !response.status().is_success() {
@@ -469,6 +227,7 @@ pub async fn validate_api_key(
             valid: false,
             message: Some(format!("API返回错误状态码: {}", response.status())),
             quota: None,
+            remaining: None,
         });
     }
 
@@ -492,16 +251,141 @@ pub async fn validate_api_key(
                         .to_string(),
                 ),
                 quota: None,
+                remaining: None,
             });
         }
     }
 
     // 提取配额信息
-    let fcoin = response_json["fcoin"].as_i64().unwrap_or(0).to_string();
+    Ok(account_balance(&response_json))
+}
 
-    Ok(ApiKeyValidationResult {
+// 从 /info/my 的响应中取出可用于搜索的额度
+//
+// 搜索先消耗会员每月的 API 数据额度（remain_api_data，按条计），用完后按条扣 F点
+// （fofa_point），两者都按“条”计，与计费模型的单位一致；F币（fcoin）不能直接用于搜索，
+// 只做展示。API 查询次数（remain_api_query）用完时数据额度也无法使用。字段都缺失时额度未知。
+fn account_balance(info: &Value) -> ApiKeyValidationResult {
+    let fofa_point = info["fofa_point"].as_i64();
+    let api_queries = info["remain_api_query"].as_i64();
+    let api_data = info["remain_api_data"]
+        .as_i64()
+        .filter(|_| api_queries != Some(0));
+
+    let remaining = match (api_data, fofa_point) {
+        (None, None) => None,
+        (api_data, fofa_point) => {
+            Some((api_data.unwrap_or(0).max(0) + fofa_point.unwrap_or(0).max(0)) as u64)
+        }
+    };
+    let quota = format!(
+        "API数据: {}, API查询: {}, F点: {}, F币: {}",
+        info["remain_api_data"].as_i64().unwrap_or(0),
+        api_queries.unwrap_or(0),
+        fofa_point.unwrap_or(0),
+        info["fcoin"].as_i64().unwrap_or(0)
+    );
+
+    ApiKeyValidationResult {
         valid: true,
         message: None,
-        quota: Some(format!("F币: {}", fcoin)),
-    })
+        quota: Some(quota),
+        remaining,
+    }
+}
+
+// FOFA 数据源
+pub struct FofaSource;
+
+#[async_trait]
+impl AssetSource for FofaSource {
+    fn name(&self) -> &'static str {
+        "fofa"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            display_name: "FOFA",
+            requires_email: true,
+            max_page_size: 10000,
+            pagination: Pagination::PageNumber,
+            // 每条结果消耗 1 条 API 数据额度，用完后每条扣 1 F点
+            cost: CostModel {
+                unit: "F点",
+                billing: Billing::PerResult,
+                credits: 1,
+            },
+        }
+    }
+
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+        search(query, page, page_size).await
+    }
+
+    fn rotation_key(&self, api_key: &str, email: &str) -> String {
+        format!("{}:{}", api_key, email)
+    }
+
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String> {
+        let email = email.ok_or("FOFA平台需要提供邮箱")?;
+        validate_api_key(api_key, email).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // /info/my 的实际响应（邮箱、用户名已脱敏）
+    fn info_my() -> Value {
+        json!({
+            "error": false,
+            "email": "****@fofa.info",
+            "username": "****",
+            "category": "user",
+            "fcoin": 48,
+            "fofa_point": 120,
+            "remain_free_point": 0,
+            "remain_api_query": 9994,
+            "remain_api_data": 99940,
+            "isvip": true,
+            "vip_level": 2,
+            "is_verified": false,
+            "avatar": "https://nosec.org/missing.jpg",
+            "message": "",
+            "fofacli_ver": "4.0.3",
+            "fofa_server": true
+        })
+    }
+
+    #[test]
+    fn test_account_balance_counts_api_data_and_points() {
+        let result = account_balance(&info_my());
+        assert!(result.valid);
+        // F币不计入可用于搜索的额度
+        assert_eq!(result.remaining, Some(99940 + 120));
+        assert_eq!(
+            result.quota.as_deref(),
+            Some("API数据: 99940, API查询: 9994, F点: 120, F币: 48")
+        );
+    }
+
+    #[test]
+    fn test_account_balance_without_api_queries_or_fields() {
+        // API 查询次数用完时只剩 F点 可用
+        let mut info = info_my();
+        info["remain_api_query"] = json!(0);
+        assert_eq!(account_balance(&info).remaining, Some(120));
+
+        // 只有 F币 的账号额度为 0，而缺少额度字段时额度未知
+        let coins_only = json!({ "error": false, "fcoin": 500, "fofa_point": 0 });
+        assert_eq!(account_balance(&coins_only).remaining, Some(0));
+        let unknown = json!({ "error": false, "fcoin": 500 });
+        assert_eq!(account_balance(&unknown).remaining, None);
+    }
 }
@@ -0,0 +1,372 @@
+use crate::config;
+use crate::error::{retry_after_secs, ApiError};
+use once_cell::sync::Lazy;
+use rand::Rng;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 平台请求频率限制（令牌桶）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    /// 每秒补充的请求数，可以是小数（0.5 即每 2 秒一次）
+    pub requests_per_second: f64,
+    /// 桶容量，即空闲后可连续发出的请求数
+    pub burst: u32,
+}
+
+/// 失败重试策略：指数退避加随机抖动
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// 最多尝试次数（含首次请求）
+    pub max_attempts: u32,
+    /// 第一次重试前的基础等待（毫秒），之后每次翻倍
+    pub base_delay_ms: u64,
+    /// 单次等待上限（毫秒）；平台要求的 Retry-After 超过上限时不再重试
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay_ms: 2000,
+            max_delay_ms: 60_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 读取设置中的重试策略，读取失败时使用默认值
+    pub fn current() -> Self {
+        config::get_settings()
+            .map(|settings| settings.retry_policy)
+            .unwrap_or_default()
+    }
+
+    // 第 attempt 次失败后的退避时间：base * 2^(attempt-1)，不超过上限，
+    // 取其一半加上 [0, 一半] 的随机抖动，避免并发请求同时重试
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay_ms
+            .saturating_mul(1u64 << attempt.saturating_sub(1).min(20))
+            .min(self.max_delay_ms);
+        let half = exp / 2;
+        let jitter = if half > 0 {
+            rand::thread_rng().gen_range(0..=half)
+        } else {
+            0
+        };
+        Duration::from_millis(exp - half + jitter)
+    }
+
+    /// 第 attempt 次失败后应等待多久再重试，`None` 表示不再重试
+    ///
+    /// 平台给出 Retry-After 时按其等待，否则按指数退避。
+    pub fn delay(&self, attempt: u32, error: &ApiError) -> Option<Duration> {
+        if !error.is_retryable() || attempt >= self.max_attempts {
+            return None;
+        }
+        match error.retry_after() {
+            Some(secs) if secs.saturating_mul(1000) > self.max_delay_ms => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+}
+
+/// 各平台默认频率限制，与此前的分页间隔一致
+pub fn default_rate_limits() -> HashMap<String, RateLimit> {
+    [
+        ("hunter", 0.5),
+        ("fofa", 0.5),
+        ("quake", 0.5),
+        ("daydaymap", 0.5),
+        ("shodan", 1.0),
+        ("censys", 1.0),
+        ("zoomeye", 1.0),
+    ]
+    .into_iter()
+    .map(|(platform, requests_per_second)| {
+        (
+            platform.to_string(),
+            RateLimit {
+                requests_per_second,
+                burst: 3,
+            },
+        )
+    })
+    .collect()
+}
+
+// 平台当前生效的频率限制：设置优先，其次平台默认值
+fn rate_limit_for(platform: &str) -> RateLimit {
+    let configured = config::get_settings()
+        .ok()
+        .and_then(|settings| settings.rate_limits.get(platform).copied());
+    configured
+        .or_else(|| default_rate_limits().remove(platform))
+        .unwrap_or(RateLimit {
+            requests_per_second: 1.0,
+            burst: 1,
+        })
+}
+
+/// 令牌桶：令牌可以透支，透支的部分按补充速率折算为等待时间
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    /// 上次补充令牌的时间；被限流暂停时位于未来
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        TokenBucket {
+            limit,
+            tokens: limit.burst.max(1) as f64,
+            updated: now,
+        }
+    }
+
+    fn rate(&self) -> f64 {
+        self.limit.requests_per_second.max(0.01)
+    }
+
+    fn capacity(&self) -> f64 {
+        self.limit.burst.max(1) as f64
+    }
+
+    // 设置修改后沿用已有令牌，只收紧到新容量
+    fn reconfigure(&mut self, limit: RateLimit) {
+        if self.limit != limit {
+            self.limit = limit;
+            self.tokens = self.tokens.min(self.capacity());
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if now > self.updated {
+            let elapsed = now.duration_since(self.updated).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.rate()).min(self.capacity());
+            self.updated = now;
+        }
+    }
+
+    /// 取走一个令牌，返回发出请求前需要等待的时间
+    fn reserve(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= 1.0;
+        let paused = self.updated.saturating_duration_since(now);
+        let deficit = Duration::from_secs_f64((-self.tokens).max(0.0) / self.rate());
+        paused + deficit
+    }
+
+    /// 暂停到 `until` 之前不再发放令牌（平台返回 429 时使用）
+    fn pause_until(&mut self, now: Instant, until: Instant) {
+        self.refill(now);
+        if until > self.updated {
+            self.updated = until;
+            self.tokens = self.tokens.min(1.0);
+        }
+    }
+}
+
+/// 各平台的令牌桶
+static BUCKETS: Lazy<Mutex<HashMap<String, TokenBucket>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// 等待平台令牌桶放行一个请求
+async fn acquire(platform: &str) {
+    let limit = rate_limit_for(platform);
+    let wait = {
+        let mut buckets = BUCKETS.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let bucket = buckets
+            .entry(platform.to_string())
+            .or_insert_with(|| TokenBucket::new(limit, now));
+        bucket.reconfigure(limit);
+        bucket.reserve(now)
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// 暂停平台的请求 `delay` 时长，同一平台的并发请求都会等待
+pub fn throttle(platform: &str, delay: Duration) {
+    let limit = rate_limit_for(platform);
+    let mut buckets = BUCKETS.lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    buckets
+        .entry(platform.to_string())
+        .or_insert_with(|| TokenBucket::new(limit, now))
+        .pause_until(now, now + delay);
+}
+
+/// 经平台限流后发送请求
+///
+/// 平台返回 429 时按 Retry-After（缺省时按退避时间）暂停该平台后续请求；
+/// 响应原样返回，由各平台客户端解析状态码与错误信息。
+pub async fn send(platform: &str, request: RequestBuilder) -> Result<Response, ApiError> {
+    acquire(platform).await;
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ApiError::Network(format!("请求失败: {}", e)))?;
+
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        let delay = retry_after_secs(response.headers())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| RetryPolicy::current().backoff(1));
+        eprintln!("[{}] 请求被限流，暂停 {} 秒", platform, delay.as_secs());
+        throttle(platform, delay);
+    }
+
+    Ok(response)
+}
+
+/// 按设置中的重试策略执行操作，可重试的错误退避后重试
+///
+/// 每次重试前以 (失败次数, 错误, 等待时间) 调用 `on_retry`；被限流时同时暂停该平台的请求。
+pub async fn with_retry<T, F, Fut>(
+    platform: &str,
+    mut on_retry: impl FnMut(u32, &ApiError, Duration),
+    mut operation: F,
+) -> Result<T, ApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, ApiError>>,
+{
+    let policy = RetryPolicy::current();
+    let mut attempt = 1;
+    loop {
+        let error = match operation().await {
+            Ok(result) => return Ok(result),
+            Err(e) => e,
+        };
+        let Some(delay) = policy.delay(attempt, &error) else {
+            return Err(error);
+        };
+        if matches!(error, ApiError::RateLimited { .. }) {
+            throttle(platform, delay);
+        }
+        on_retry(attempt, &error, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(requests_per_second: f64, burst: u32) -> RateLimit {
+        RateLimit {
+            requests_per_second,
+            burst,
+        }
+    }
+
+    #[test]
+    fn test_bucket_allows_burst_then_paces() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(limit(2.0, 2), now);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::ZERO);
+        assert_eq!(bucket.reserve(now), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(now), Duration::from_millis(1000));
+
+        // 空闲足够久后恢复到满桶，但不超过容量
+        let later = now + Duration::from_secs(60);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert!(bucket.reserve(later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_bucket_pause_delays_next_request() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(limit(1.0, 3), now);
+        bucket.pause_until(now, now + Duration::from_secs(10));
+        assert_eq!(bucket.reserve(now), Duration::from_secs(10));
+        assert_eq!(bucket.reserve(now), Duration::from_secs(11));
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay_ms: 1000,
+            max_delay_ms: 5000,
+        };
+        for _ in 0..20 {
+            let first = policy.backoff(1).as_millis();
+            assert!((500..=1000).contains(&first), "{}", first);
+            let third = policy.backoff(3).as_millis();
+            assert!((2000..=4000).contains(&third), "{}", third);
+            let capped = policy.backoff(8).as_millis();
+            assert!((2500..=5000).contains(&capped), "{}", capped);
+        }
+    }
+
+    #[test]
+    fn test_delay_respects_retry_after_and_kind() {
+        let policy = RetryPolicy::default();
+        let limited = |retry_after| ApiError::RateLimited {
+            retry_after,
+            message: String::new(),
+        };
+        assert_eq!(
+            policy.delay(1, &limited(Some(7))),
+            Some(Duration::from_secs(7))
+        );
+        // Retry-After 超过单次等待上限时放弃重试
+        assert_eq!(policy.delay(1, &limited(Some(3600))), None);
+        assert!(policy.delay(1, &ApiError::Network(String::new())).is_some());
+        assert_eq!(policy.delay(3, &ApiError::Network(String::new())), None);
+        assert_eq!(policy.delay(1, &ApiError::Auth(String::new())), None);
+        assert_eq!(
+            policy.delay(1, &ApiError::QuotaExhausted(String::new())),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_retries_retryable_errors() {
+        let mut calls = 0;
+        let mut retries = Vec::new();
+        let result = with_retry(
+            "test-retry",
+            |attempt, _, _| retries.push(attempt),
+            || {
+                calls += 1;
+                let outcome = if calls < 2 {
+                    Err(ApiError::RateLimited {
+                        retry_after: Some(0),
+                        message: String::new(),
+                    })
+                } else {
+                    Ok(calls)
+                };
+                async move { outcome }
+            },
+        )
+        .await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(retries, vec![1]);
+
+        let result: Result<(), _> = with_retry(
+            "test-retry",
+            |_, _, _| panic!("不可重试的错误不应重试"),
+            || async { Err(ApiError::BadQuery("语法错误".to_string())) },
+        )
+        .await;
+        assert_eq!(result.unwrap_err().kind(), "badQuery");
+    }
+}
@@ -1,9 +1,15 @@
+use super::asset::{number_field, port_field, str_field, Asset, Geo, SearchPage};
+use super::cost::{parse_credits, Billing, CostModel};
+use super::http;
 use super::key_manager;
+use super::source::{AssetSource, Pagination, SourceCapabilities};
 use crate::config;
+use crate::error::{retry_after_secs, ApiError};
 use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
 use base64::{engine::general_purpose, Engine as _};
 
-use serde_json::{json, Value};
+use serde_json::Value;
 
 // 使用单个API key进行搜索
 async fn search_with_key(
@@ -14,7 +20,7 @@ async fn search_with_key(
     status_code: Option<&str>,
     start_time: Option<&str>,
     end_time: Option<&str>,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let base_url = "https://hunter.qianxin.com/openApi/search";
 
     // 对查询字符串进行Base64编码
@@ -41,40 +47,58 @@ async fn search_with_key(
     }
 
     // 发送请求
-    let client = crate::create_http_client()?;
-    let response = client
-        .get(base_url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    let client = crate::create_http_client().map_err(ApiError::Config)?;
+    let response = http::send("hunter", client.get(base_url).query(&params)).await?;
 
     // 检查响应状态
-    if !response.status().is_success() {
-        return Err(format!("API返回错误状态码: {}", response.status()));
+    let status = response.status();
+    if !status.is_success() {
+        return Err(ApiError::from_status(
+            status.as_u16(),
+            retry_after_secs(response.headers()),
+            format!("API返回错误状态码: {}", status),
+        ));
     }
 
     // 解析响应
     let response_text = response
         .text()
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
-    let response_json: Value =
-        serde_json::from_str(&response_text).map_err(|e| format!("解析JSON失败: {}", e))?;
+        .map_err(|e| ApiError::Network(format!("读取响应失败: {}", e)))?;
+    let response_json: Value = serde_json::from_str(&response_text)
+        .map_err(|e| ApiError::Parse(format!("解析JSON失败: {}", e)))?;
 
     // 检查API返回的状态码
-    if response_json["code"].as_u64() != Some(200) {
-        return Err(format!(
-            "API返回错误: {}",
-            response_json["message"].as_str().unwrap_or("未知错误")
+    let code = response_json["code"].as_u64().unwrap_or(0);
+    if code != 200 {
+        return Err(api_error(
+            code,
+            format!(
+                "API返回错误: {}",
+                response_json["message"].as_str().unwrap_or("未知错误")
+            ),
         ));
     }
 
     Ok(response_json)
 }
 
+// 将 Hunter 响应中的错误码映射为错误类型，未知错误码按提示文字归类
+fn api_error(code: u64, message: String) -> ApiError {
+    match code {
+        401 | 403 => ApiError::Auth(message),
+        429 => ApiError::RateLimited {
+            retry_after: None,
+            message,
+        },
+        400 => ApiError::BadQuery(message),
+        40204 => ApiError::QuotaExhausted(message),
+        _ => ApiError::from_platform_message(message),
+    }
+}
+
 // 搜索资产 - 支持自动轮询多个API Key
-pub async fn search(query: &str, page: u32, page_size: u32) -> Result<Value, String> {
+pub async fn search(query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
     search_with_options(query, page, page_size, None, None, None).await
 }
 
@@ -86,12 +110,12 @@ pub async fn search_with_options(
     status_code: Option<&str>,
     start_time: Option<&str>,
     end_time: Option<&str>,
-) -> Result<Value, String> {
+) -> Result<SearchPage, ApiError> {
     // 获取所有API密钥
-    let api_keys = config::get_all_hunter_api_keys()?;
+    let api_keys = config::get_all_api_keys("hunter").map_err(ApiError::Config)?;
 
     if api_keys.is_empty() {
-        return Err("未配置Hunter API密钥".to_string());
+        return Err(ApiError::Config("未配置Hunter API密钥".to_string()));
     }
 
     // Clone data for the closure
@@ -124,317 +148,69 @@ pub async fn search_with_options(
 
     match result {
         Ok(response_json) => {
-            // 提取结果
-            let total = response_json["data"]["total"].as_u64().unwrap_or(0);
-            let arr = response_json["data"]["arr"]
+            let data = &response_json["data"];
+            let results = data["arr"]
                 .as_array()
-                .unwrap_or(&Vec::new())
-                .clone();
-
-            // 格式化结果
-            let results = arr
-                .iter()
-                .map(|item| {
-                    // 构建URL，优先使用domain，否则使用IP
-                    let host = if let Some(domain) = item["domain"].as_str() {
-                        if !domain.is_empty() {
-                            domain.to_string()
-                        } else {
-                            item["ip"].as_str().unwrap_or("").to_string()
-                        }
-                    } else {
-                        item["ip"].as_str().unwrap_or("").to_string()
-                    };
-
-                    let protocol = item["protocol"].as_str().unwrap_or("http");
-
-                    // 正确获取端口号，可能是字符串或数字
-                    let port = if let Some(port_str) = item["port"].as_str() {
-                        port_str.to_string()
-                    } else if let Some(port_num) = item["port"].as_u64() {
-                        port_num.to_string()
-                    } else if let Some(port_num) = item["port"].as_i64() {
-                        port_num.to_string()
-                    } else {
-                        "80".to_string()
-                    };
-
-                    let url = format!("{}://{}:{}", protocol, host, port);
-
-                    // 提取组件信息
-                    let components = if let Some(comp_array) = item["component"].as_array() {
-                        comp_array
-                            .iter()
-                            .filter_map(|c| {
-                                let name = c["name"].as_str().unwrap_or("");
-                                let version = c["version"].as_str().unwrap_or("");
-                                if !name.is_empty() {
-                                    if !version.is_empty() {
-                                        Some(format!("{}:{}", name, version))
-                                    } else {
-                                        Some(name.to_string())
-                                    }
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<String>>()
-                            .join(", ")
-                    } else {
-                        String::new()
-                    };
-
-                    json!({
-                        "url": url,
-                        "ip": item["ip"].as_str().unwrap_or(""),
-                        "port": port,
-                        "domain": item["domain"].as_str().unwrap_or(""),
-                        "web_title": item["web_title"].as_str().unwrap_or(""),
-                        "status_code": item["status_code"].as_i64().unwrap_or(0),
-                        "country": item["country"].as_str().unwrap_or(""),
-                        "province": item["province"].as_str().unwrap_or(""),
-                        "city": item["city"].as_str().unwrap_or(""),
-                        "server": item["banner"].as_str().unwrap_or(""),
-                        "protocol": protocol,
-                        "base_protocol": item["base_protocol"].as_str().unwrap_or(""),
-                        "os": item["os"].as_str().unwrap_or(""),
-                        "company": item["company"].as_str().unwrap_or(""),
-                        "number": item["number"].as_str().unwrap_or(""),
-                        "isp": item["isp"].as_str().unwrap_or(""),
-                        "as_org": item["as_org"].as_str().unwrap_or(""),
-                        "component": components,
-                        "updated_at": item["updated_at"].as_str().unwrap_or(""),
-                    })
-                })
-                .collect::<Vec<Value>>();
-
-            Ok(json!({
-                "total": total,
-                "results": results,
-                "consume_quota": response_json["data"]["consume_quota"].as_str().unwrap_or(""),
-                "rest_quota": response_json["data"]["rest_quota"].as_str().unwrap_or(""),
-            }))
+                .map(|arr| arr.iter().map(to_asset).collect())
+                .unwrap_or_default();
+
+            Ok(SearchPage {
+                total: data["total"].as_u64().unwrap_or(0),
+                results,
+                consume_quota: data["consume_quota"].as_str().map(|s| s.to_string()),
+                rest_quota: data["rest_quota"].as_str().map(|s| s.to_string()),
+                next_cursor: None,
+            })
         }
         Err(e) => Err(e),
     }
 }
 
-// 导出资产
-pub async fn export(
-    query: &str,
-    pages: u32,
-    page_size: u32,
-    _time_range: &str,
-    _start_date: Option<String>,
-    _end_date: Option<String>,
-    export_path: &str,
-) -> Result<(), String> {
-    let mut all_results = Vec::new();
-    let mut last_successful_page = 0;
-    let max_retries = 3;
-    let retry_delay_secs = 5;
-
-    // 分页获取所有数据
-    for page in 1..=pages {
-        eprintln!("正在导出第 {}/{} 页...", page, pages);
-
-        let mut retry_count = 0;
-        let mut page_success = false;
-
-        while retry_count < max_retries && !page_success {
-            match search(query, page, page_size).await {
-                Ok(data) => {
-                    if let Some(results) = data["results"].as_array() {
-                        all_results.extend(results.clone());
-                        eprintln!("第 {} 页成功: 获取 {} 条数据", page, results.len());
-                        last_successful_page = page;
-                        page_success = true;
-                    }
-                }
-                Err(e) => {
-                    // 检查是否是配额耗尽错误
-                    if e.contains("积分用完")
-                        || e.contains("次牛")
-                        || e.contains("quota")
-                        || e.contains("所有API Key都无法使用")
-                    {
-                        eprintln!("Hunter: 配额耗尽，停止导出");
-
-                        // 保存部分导出的数据
-                        if !all_results.is_empty() {
-                            let timestamp =
-                                chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-                            let file_path = format!(
-                                "{}/hunter_export_{}_partial_{}of{}_pages.csv",
-                                export_path, timestamp, last_successful_page, pages
-                            );
-
-                            save_to_csv(&file_path, &all_results)?;
-                            eprintln!("已保存部分数据到: {}", file_path);
-                        }
-
-                        return Err(format!(
-                            "第{}页查询失败(配额耗尽): {}。已保存前{}页数据",
-                            page, e, last_successful_page
-                        ));
-                    }
-
-                    // 其他错误，尝试重试
-                    retry_count += 1;
-                    if retry_count < max_retries {
-                        eprintln!(
-                            "第 {} 页失败，{} 秒后重试 ({}/{})...",
-                            page, retry_delay_secs, retry_count, max_retries
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(retry_delay_secs))
-                            .await;
-                    } else {
-                        eprintln!("第 {} 页失败，已达到最大重试次数", page);
-
-                        // 保存部分导出的数据
-                        if !all_results.is_empty() {
-                            let timestamp =
-                                chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-                            let file_path = format!(
-                                "{}/hunter_export_{}_partial_{}of{}_pages.csv",
-                                export_path, timestamp, last_successful_page, pages
-                            );
-
-                            save_to_csv(&file_path, &all_results)?;
-                            eprintln!("已保存部分数据到: {}", file_path);
-                        }
-
-                        return Err(format!(
-                            "第{}页查询失败: {}。已保存前{}页数据",
-                            page, e, last_successful_page
-                        ));
+// 将 Hunter 返回的单条记录映射为资产
+fn to_asset(item: &Value) -> Asset {
+    // 组件信息，形如 name:version
+    let fingerprints = item["component"]
+        .as_array()
+        .map(|components| {
+            components
+                .iter()
+                .filter_map(|c| {
+                    let name = str_field(&c["name"]);
+                    let version = str_field(&c["version"]);
+                    match (name.is_empty(), version.is_empty()) {
+                        (true, _) => None,
+                        (false, true) => Some(name),
+                        (false, false) => Some(format!("{}:{}", name, version)),
                     }
-                }
-            }
-        }
-
-        // 避免请求过快，增加延迟到2秒
-        if page < pages {
-            eprintln!("等待 2 秒后继续...");
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-        }
-    }
-
-    // 生成文件名
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let file_path = format!("{}/hunter_export_{}.csv", export_path, timestamp);
-
-    save_to_csv(&file_path, &all_results)?;
-    eprintln!("导出完成: {}", file_path);
-
-    Ok(())
-}
-
-// 保存数据到CSV文件
-fn save_to_csv(file_path: &str, results: &[Value]) -> Result<(), String> {
-    let mut wtr = csv::Writer::from_path(file_path).map_err(|e| format!("创建文件失败: {}", e))?;
-
-    // 写入CSV头部
-    wtr.write_record([
-        "IP",
-        "端口",
-        "域名",
-        "标题",
-        "状态码",
-        "服务器",
-        "协议",
-        "基础协议",
-        "操作系统",
-        "国家",
-        "省份",
-        "城市",
-        "公司",
-        "备案号",
-        "ISP",
-        "AS组织",
-        "组件",
-        "更新时间",
-        "URL",
-    ])
-    .map_err(|e| format!("写入CSV头部失败: {}", e))?;
-
-    // 写入数据
-    for result in results {
-        wtr.write_record([
-            result["ip"].as_str().unwrap_or(""),
-            &result["port"]
-                .as_str()
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| result["port"].as_i64().unwrap_or(0).to_string()),
-            result["domain"].as_str().unwrap_or(""),
-            result["web_title"].as_str().unwrap_or(""),
-            &result["status_code"].as_i64().unwrap_or(0).to_string(),
-            result["server"].as_str().unwrap_or(""),
-            result["protocol"].as_str().unwrap_or(""),
-            result["base_protocol"].as_str().unwrap_or(""),
-            result["os"].as_str().unwrap_or(""),
-            result["country"].as_str().unwrap_or(""),
-            result["province"].as_str().unwrap_or(""),
-            result["city"].as_str().unwrap_or(""),
-            result["company"].as_str().unwrap_or(""),
-            result["number"].as_str().unwrap_or(""),
-            result["isp"].as_str().unwrap_or(""),
-            result["as_org"].as_str().unwrap_or(""),
-            result["component"].as_str().unwrap_or(""),
-            result["updated_at"].as_str().unwrap_or(""),
-            result["url"].as_str().unwrap_or(""),
-        ])
-        .map_err(|e| format!("写入数据失败: {}", e))?;
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Asset {
+        ip: str_field(&item["ip"]),
+        port: port_field(&item["port"]),
+        protocol: str_field(&item["protocol"]).to_lowercase(),
+        domain: str_field(&item["domain"]),
+        url: str_field(&item["url"]),
+        title: str_field(&item["web_title"]),
+        server: str_field(&item["banner"]),
+        status_code: number_field(&item["status_code"]),
+        geo: Geo {
+            country: str_field(&item["country"]),
+            province: str_field(&item["province"]),
+            city: str_field(&item["city"]),
+            isp: str_field(&item["isp"]),
+        },
+        as_org: str_field(&item["as_org"]),
+        os: str_field(&item["os"]),
+        icp: str_field(&item["number"]),
+        organization: str_field(&item["company"]),
+        fingerprints,
+        last_seen: Some(str_field(&item["updated_at"])).filter(|s| !s.is_empty()),
+        ..Asset::new("hunter", item.clone())
     }
-
-    wtr.flush().map_err(|e| format!("刷新CSV写入失败: {}", e))?;
-    Ok(())
-}
-
-// 导出全部资产
-pub async fn export_all(
-    query: &str,
-    _pages: u32,
-    page_size: u32,
-    time_range: &str,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    export_path: &str,
-) -> Result<(), String> {
-    // 先获取总数
-    eprintln!("正在获取查询总数...");
-    let first_page = search(query, 1, page_size).await?;
-    let total = first_page["total"].as_u64().unwrap_or(0);
-    let total_pages = ((total as f64) / (page_size as f64)).ceil() as u32;
-
-    eprintln!(
-        "查询总数: {}, 总页数: {}, 每页: {}",
-        total, total_pages, page_size
-    );
-
-    // 限制最大导出页数（避免请求过多）
-    let max_pages = 100;
-    let export_pages = if total_pages > max_pages {
-        eprintln!(
-            "警告: 总页数({})超过限制({}), 将只导出前{}页",
-            total_pages, max_pages, max_pages
-        );
-        max_pages
-    } else {
-        total_pages
-    };
-
-    // 导出所有页
-    export(
-        query,
-        export_pages,
-        page_size,
-        time_range,
-        start_date,
-        end_date,
-        export_path,
-    )
-    .await
+    .finalize()
 }
 
 // 验证API密钥
@@ -457,12 +233,7 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
 
     // 发送请求
     let client = crate::create_http_client()?;
-    let response = client
-        .get(base_url)
-        .query(&params)
-        .send()
-        .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+    let response = http::send("hunter", client.get(base_url).query(&params)).await?;
 
     // 检查响应状态
     let status = response.status();
@@ -473,6 +244,7 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
             valid: false,
             message: Some(error_msg),
             quota: None,
+            remaining: None,
         });
     }
 
@@ -493,6 +265,7 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
             valid: true,
             message: Some("API密钥验证成功".to_string()),
             quota: Some(rest_quota.to_string()),
+            remaining: parse_credits(rest_quota),
         })
     } else {
         // API密钥无效
@@ -506,6 +279,43 @@ pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, S
             valid: false,
             message: Some(message),
             quota: None,
+            remaining: None,
         })
     }
 }
+
+// Hunter 数据源
+pub struct HunterSource;
+
+#[async_trait]
+impl AssetSource for HunterSource {
+    fn name(&self) -> &'static str {
+        "hunter"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            display_name: "Hunter",
+            requires_email: false,
+            max_page_size: 100,
+            pagination: Pagination::PageNumber,
+            cost: CostModel {
+                unit: "积分",
+                billing: Billing::PerResult,
+                credits: 1,
+            },
+        }
+    }
+
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+        search(query, page, page_size).await
+    }
+
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        _email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String> {
+        validate_api_key(api_key).await
+    }
+}
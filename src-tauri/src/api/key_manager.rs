@@ -1,11 +1,93 @@
-use chrono::Local;
+use super::registry::all_sources;
+use super::source::AssetSource;
+use crate::config;
+use crate::error::ApiError;
+use chrono::{DateTime, Duration, FixedOffset, Local};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::fs;
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
+/// 后台重新验证的检查间隔；已耗尽的 key 每次检查都会验证
+const REVALIDATE_TICK_SECS: u64 = 3600;
+
+/// 可用 key 的额度超过该时长未刷新时重新验证
+const REFRESH_AFTER_HOURS: i64 = 6;
+
+/// 每个 key 保留的额度采样数
+const MAX_QUOTA_SAMPLES: usize = 24;
+
+/// key 的前 8 个字符，用于在界面和日志中识别 key
+pub fn key_hint(key: &str) -> String {
+    format!("{}...", key.chars().take(8).collect::<String>())
+}
+
+/// 请求实际使用的 key：在密钥池中的索引与 key 前缀
+pub type UsedKey = (usize, String);
+
+// 请求所属的任务：粘性模式按任务固定 key，成功的请求记下所用的 key
+struct TaskScope {
+    id: String,
+    used_key: Mutex<Option<UsedKey>>,
+}
+
+tokio::task_local! {
+    static TASK: TaskScope;
+}
+
+/// key 选择策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RotationStrategy {
+    /// 一直使用当前 key，耗尽后才换下一个
+    #[default]
+    Sequential,
+    /// 每次请求换用下一个可用 key
+    RoundRobin,
+    /// 优先使用最近一次验证剩余额度最多的 key，未验证过的排在最后
+    MostRemaining,
+    /// 优先使用最久未使用的 key，从未使用过的最先
+    LeastRecentlyUsed,
+    /// 按 key 的优先级加权轮询
+    Weighted,
+}
+
+/// 平台的 key 轮询设置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyRotation {
+    pub strategy: RotationStrategy,
+    /// 同一任务固定使用同一个 key，直到它耗尽（分页结果与账号绑定的平台需要）
+    pub sticky: bool,
+    /// 优先使用带有这些标签的 key，它们都不可用时才使用其它 key
+    pub preferred_tags: Vec<String>,
+}
+
+impl KeyRotation {
+    /// 读取设置中平台的轮询设置，未设置时使用默认值
+    pub fn current(platform: &str) -> Self {
+        config::get_settings()
+            .ok()
+            .and_then(|settings| settings.key_rotation.get(platform).cloned())
+            .unwrap_or_default()
+    }
+}
+
+fn default_priority() -> u32 {
+    1
+}
+
+/// 某一时刻的剩余额度
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaSample {
+    pub at: String, // ISO 8601 日期时间
+    pub remaining: u64,
+}
+
 /// API Key 状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyStatus {
@@ -13,6 +95,189 @@ pub struct KeyStatus {
     pub is_exhausted: bool,
     pub exhausted_at: Option<String>, // ISO 8601 日期时间
     pub last_used_at: Option<String>,
+    /// 最近一次验证得到的额度，时间顺序，最多保留 [`MAX_QUOTA_SAMPLES`] 条
+    #[serde(default)]
+    pub quota_samples: Vec<QuotaSample>,
+    #[serde(default)]
+    pub checked_at: Option<String>,
+    #[serde(default)]
+    pub success_count: u64,
+    #[serde(default)]
+    pub failure_count: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_error_at: Option<String>,
+    /// 加权轮询中的权重
+    #[serde(default = "default_priority")]
+    pub priority: u32,
+    /// 自定义标签，如付费账号 / 免费账号
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // 平滑加权轮询的当前权重
+    #[serde(default)]
+    current_weight: i64,
+}
+
+impl KeyStatus {
+    fn new(key: String) -> Self {
+        KeyStatus {
+            key,
+            is_exhausted: false,
+            exhausted_at: None,
+            last_used_at: None,
+            quota_samples: Vec::new(),
+            checked_at: None,
+            success_count: 0,
+            failure_count: 0,
+            last_error: None,
+            last_error_at: None,
+            priority: default_priority(),
+            tags: Vec::new(),
+            current_weight: 0,
+        }
+    }
+
+    fn weight(&self) -> i64 {
+        self.priority.max(1) as i64
+    }
+
+    fn last_used(&self) -> Option<DateTime<FixedOffset>> {
+        self.last_used_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+    }
+
+    fn key_hint(&self) -> String {
+        key_hint(&self.key)
+    }
+
+    fn remaining(&self) -> Option<u64> {
+        self.quota_samples.last().map(|sample| sample.remaining)
+    }
+
+    fn record_error(&mut self, message: &str) {
+        self.last_error = Some(message.to_string());
+        self.last_error_at = Some(Local::now().to_rfc3339());
+    }
+
+    // 是否需要后台重新验证：已耗尽、从未验证或额度信息已过期
+    fn needs_refresh(&self, now: DateTime<Local>) -> bool {
+        self.is_exhausted
+            || self
+                .checked_at
+                .as_deref()
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .is_none_or(|at| {
+                    now.signed_duration_since(at) > Duration::hours(REFRESH_AFTER_HOURS)
+                })
+    }
+}
+
+/// 按额度采样的消耗速度预测耗尽时间
+///
+/// 只使用最近一次额度回升（重置或充值）之后的采样；额度没有下降时无法预测。
+pub fn predict_exhaustion(samples: &[QuotaSample]) -> Option<DateTime<FixedOffset>> {
+    let parsed: Vec<(DateTime<FixedOffset>, u64)> = samples
+        .iter()
+        .filter_map(|sample| {
+            DateTime::parse_from_rfc3339(&sample.at)
+                .ok()
+                .map(|at| (at, sample.remaining))
+        })
+        .collect();
+    let (last_at, last_remaining) = *parsed.last()?;
+    if last_remaining == 0 {
+        return Some(last_at);
+    }
+
+    // 从最新采样往前找，直到额度出现回升
+    let mut first = parsed.len() - 1;
+    while first > 0 && parsed[first - 1].1 >= parsed[first].1 {
+        first -= 1;
+    }
+    let (first_at, first_remaining) = parsed[first];
+    let used = first_remaining
+        .checked_sub(last_remaining)
+        .filter(|used| *used > 0)?;
+    let elapsed = last_at.signed_duration_since(first_at).num_seconds();
+    if elapsed <= 0 {
+        return None;
+    }
+
+    let seconds_left = last_remaining as f64 * elapsed as f64 / used as f64;
+    Some(last_at + Duration::seconds(seconds_left as i64))
+}
+
+/// 单个 key 的健康状况（供前端展示）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyHealth {
+    pub key_hint: String,
+    pub is_exhausted: bool,
+    pub exhausted_at: Option<String>,
+    pub last_used_at: Option<String>,
+    /// 最近一次验证得到的剩余额度
+    pub remaining: Option<u64>,
+    pub checked_at: Option<String>,
+    pub success_count: u64,
+    pub failure_count: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+    /// 按近期消耗速度预测的耗尽时间
+    pub predicted_exhaustion: Option<String>,
+    pub priority: u32,
+    pub tags: Vec<String>,
+}
+
+impl From<&KeyStatus> for KeyHealth {
+    fn from(status: &KeyStatus) -> Self {
+        KeyHealth {
+            key_hint: status.key_hint(),
+            is_exhausted: status.is_exhausted,
+            exhausted_at: status.exhausted_at.clone(),
+            last_used_at: status.last_used_at.clone(),
+            remaining: status.remaining(),
+            checked_at: status.checked_at.clone(),
+            success_count: status.success_count,
+            failure_count: status.failure_count,
+            last_error: status.last_error.clone(),
+            last_error_at: status.last_error_at.clone(),
+            predicted_exhaustion: predict_exhaustion(&status.quota_samples)
+                .map(|at| at.to_rfc3339()),
+            priority: status.priority,
+            tags: status.tags.clone(),
+        }
+    }
+}
+
+/// 平台 key 池的健康状况
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolHealth {
+    pub platform: String,
+    pub total: usize,
+    pub available: usize,
+    pub exhausted: usize,
+    /// 已知剩余额度之和，所有 key 都未验证过时为空
+    pub remaining: Option<u64>,
+    pub keys: Vec<KeyHealth>,
+}
+
+impl PoolHealth {
+    fn from_state(platform: &str, state: &KeyManagerState) -> Self {
+        let keys: Vec<KeyHealth> = state.keys.iter().map(KeyHealth::from).collect();
+        let exhausted = keys.iter().filter(|key| key.is_exhausted).count();
+        let known: Vec<u64> = keys.iter().filter_map(|key| key.remaining).collect();
+        PoolHealth {
+            platform: platform.to_string(),
+            total: keys.len(),
+            available: keys.len() - exhausted,
+            exhausted,
+            remaining: (!known.is_empty()).then(|| known.iter().sum()),
+            keys,
+        }
+    }
 }
 
 /// Key 管理器状态
@@ -21,13 +286,141 @@ pub struct KeyManagerState {
     pub current_index: usize,
     pub keys: Vec<KeyStatus>,
     pub last_reset_date: String, // YYYY-MM-DD
+    /// 粘性模式下各任务固定使用的 key（任务 ID → key）
+    #[serde(default)]
+    pub sticky_keys: HashMap<String, String>,
+}
+
+impl KeyManagerState {
+    /// 按轮询设置选出下一个 key 并移动游标，所有 key 都耗尽时返回 `None`
+    ///
+    /// 粘性模式下 `task` 已固定的 key 未耗尽时继续使用，否则按策略选出新 key 并固定给该任务。
+    pub fn select_key(&mut self, rotation: &KeyRotation, task: Option<&str>) -> Option<usize> {
+        let task = task.filter(|_| rotation.sticky);
+        let pinned = task
+            .and_then(|task| self.sticky_keys.get(task))
+            .and_then(|key| {
+                self.keys
+                    .iter()
+                    .position(|status| &status.key == key && !status.is_exhausted)
+            });
+        if let Some(index) = pinned {
+            self.current_index = index;
+            return Some(index);
+        }
+
+        let candidates = self.candidates(&rotation.preferred_tags);
+        let index = match rotation.strategy {
+            RotationStrategy::Sequential => candidates.first().copied(),
+            RotationStrategy::RoundRobin => candidates
+                .iter()
+                .copied()
+                .find(|&index| index != self.current_index)
+                .or_else(|| candidates.first().copied()),
+            RotationStrategy::MostRemaining => candidates
+                .iter()
+                .copied()
+                .min_by_key(|&index| Reverse(self.keys[index].remaining())),
+            RotationStrategy::LeastRecentlyUsed => candidates
+                .iter()
+                .copied()
+                .min_by_key(|&index| self.keys[index].last_used()),
+            RotationStrategy::Weighted => self.next_weighted(&candidates),
+        }?;
+
+        self.current_index = index;
+        if let Some(task) = task {
+            self.sticky_keys
+                .insert(task.to_string(), self.keys[index].key.clone());
+        }
+        Some(index)
+    }
+
+    // 未耗尽的 key 的索引，从游标开始按顺序排列；带偏好标签的 key 可用时只返回这些
+    fn candidates(&self, preferred_tags: &[String]) -> Vec<usize> {
+        let total = self.keys.len();
+        let available: Vec<usize> = (0..total)
+            .map(|offset| (self.current_index + offset) % total)
+            .filter(|&index| !self.keys[index].is_exhausted)
+            .collect();
+        let preferred: Vec<usize> = available
+            .iter()
+            .copied()
+            .filter(|&index| {
+                self.keys[index]
+                    .tags
+                    .iter()
+                    .any(|tag| preferred_tags.contains(tag))
+            })
+            .collect();
+        if preferred.is_empty() {
+            available
+        } else {
+            preferred
+        }
+    }
+
+    // 平滑加权轮询：每个候选加上自身权重，选当前权重最大的，再减去总权重
+    fn next_weighted(&mut self, candidates: &[usize]) -> Option<usize> {
+        let total: i64 = candidates
+            .iter()
+            .map(|&index| self.keys[index].weight())
+            .sum();
+        for &index in candidates {
+            let weight = self.keys[index].weight();
+            self.keys[index].current_weight += weight;
+        }
+        let chosen = candidates
+            .iter()
+            .copied()
+            .min_by_key(|&index| Reverse(self.keys[index].current_weight))?;
+        self.keys[chosen].current_weight -= total;
+        Some(chosen)
+    }
+
+    /// 标记 key 为已耗尽，游标移到下一个 key
+    pub fn mark_exhausted(&mut self, index: usize) {
+        if let Some(status) = self.keys.get_mut(index) {
+            status.is_exhausted = true;
+            status.exhausted_at = Some(Local::now().to_rfc3339());
+            self.current_index = (index + 1) % self.keys.len();
+        }
+    }
+
+    /// 记录 key 的一次成功使用
+    pub fn record_success(&mut self, index: usize) {
+        if let Some(status) = self.keys.get_mut(index) {
+            status.last_used_at = Some(Local::now().to_rfc3339());
+            status.success_count += 1;
+        }
+    }
+
+    /// 释放任务固定的 key，返回任务是否固定过 key
+    pub fn release_task(&mut self, task: &str) -> bool {
+        self.sticky_keys.remove(task).is_some()
+    }
 }
 
-/// 全局 Key 管理器（支持多平台）
-#[allow(dead_code)]
-pub static KEY_MANAGERS: Lazy<Mutex<HashMap<String, KeyManager>>> =
+/// 进程内的 key 池（平台 → 状态）
+///
+/// 所有读改写都在锁内完成并写回磁盘，并发的导出任务不会互相覆盖耗尽标记与游标。
+static KEY_POOLS: Lazy<Mutex<HashMap<String, KeyManagerState>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// key 状态文件所在目录
+#[cfg(not(test))]
+fn state_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("asset-mapping")
+}
+
+// 测试时写到临时目录，不改动用户的 key 状态
+#[cfg(test)]
+fn state_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("asset-mapping-keys-{}", std::process::id()))
+}
+
 pub struct KeyManager {
     platform: String,
     state_file: PathBuf,
@@ -43,9 +436,7 @@ impl KeyManager {
     }
 
     fn get_state_file_path(platform: &str) -> PathBuf {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("asset-mapping");
+        let config_dir = state_dir();
 
         if !config_dir.exists() {
             fs::create_dir_all(&config_dir).ok();
@@ -64,57 +455,81 @@ impl KeyManager {
         serde_json::from_str(&content).ok()
     }
 
-    /// 保存状态
+    /// 保存状态：写入临时文件后重命名，避免写到一半时崩溃留下损坏的状态文件
     fn save_state(&self, state: &KeyManagerState) -> Result<(), String> {
         let content =
             serde_json::to_string_pretty(state).map_err(|e| format!("序列化状态失败: {}", e))?;
 
-        fs::write(&self.state_file, content).map_err(|e| format!("写入状态文件失败: {}", e))?;
+        let tmp_file = self.state_file.with_extension("json.tmp");
+        fs::write(&tmp_file, content).map_err(|e| format!("写入状态文件失败: {}", e))?;
+        fs::rename(&tmp_file, &self.state_file).map_err(|e| format!("保存状态文件失败: {}", e))?;
 
         Ok(())
     }
 
-    /// 初始化或更新 keys
-    pub fn initialize_keys(&self, api_keys: Vec<String>) -> Result<KeyManagerState, String> {
+    // 在 key 池锁内按配置的 key 列表同步状态后读改写，完成后写回磁盘
+    fn with_state<T>(
+        &self,
+        api_keys: Vec<String>,
+        f: impl FnOnce(&mut KeyManagerState) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut pools = KEY_POOLS.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = pools.remove(&self.platform).or_else(|| self.load_state());
+        let state = self.sync_keys(cached, api_keys);
+        let state = pools.entry(self.platform.clone()).or_insert(state);
+
+        let result = f(state);
+        self.save_state(state)?;
+        result
+    }
+
+    // 在 key 池锁内操作已有状态（不同步 key 列表），状态不存在时返回 `None`
+    fn with_existing_state<T>(&self, f: impl FnOnce(&mut KeyManagerState) -> T) -> Option<T> {
+        let mut pools = KEY_POOLS.lock().unwrap_or_else(|e| e.into_inner());
+        if !pools.contains_key(&self.platform) {
+            pools.insert(self.platform.clone(), self.load_state()?);
+        }
+        pools.get_mut(&self.platform).map(f)
+    }
+
+    // 按配置的 key 列表更新状态，跨天时重置耗尽标记；没有状态时初始化
+    fn sync_keys(&self, state: Option<KeyManagerState>, api_keys: Vec<String>) -> KeyManagerState {
         let today = Local::now().format("%Y-%m-%d").to_string();
 
-        // 尝试加载现有状态
-        if let Some(mut state) = self.load_state() {
-            // 检查是否需要重置（新的一天）
-            if state.last_reset_date != today {
-                eprintln!("[{}] 检测到新的一天，重置所有 key 状态", self.platform);
-                state = self.reset_all_keys(api_keys, today);
-            } else {
-                // 更新 keys 列表（可能有新增或删除）
-                state = self.update_keys(state, api_keys);
-            }
+        let Some(state) = state else {
+            // 首次初始化
+            return self.reset_all_keys(api_keys, today);
+        };
 
-            self.save_state(&state)?;
-            return Ok(state);
+        // 更新 keys 列表（可能有新增或删除）
+        let mut state = self.update_keys(state, api_keys);
+        // 检查是否需要重置（新的一天）
+        if state.last_reset_date != today {
+            eprintln!("[{}] 检测到新的一天，重置所有 key 状态", self.platform);
+            state.current_index = 0;
+            state.last_reset_date = today;
+            for key in &mut state.keys {
+                key.is_exhausted = false;
+                key.exhausted_at = None;
+            }
         }
+        state
+    }
 
-        // 首次初始化
-        let state = self.reset_all_keys(api_keys, today);
-        self.save_state(&state)?;
-        Ok(state)
+    /// 初始化或更新 keys
+    pub fn initialize_keys(&self, api_keys: Vec<String>) -> Result<KeyManagerState, String> {
+        self.with_state(api_keys, |state| Ok(state.clone()))
     }
 
     /// 重置所有 keys
     fn reset_all_keys(&self, api_keys: Vec<String>, date: String) -> KeyManagerState {
-        let keys = api_keys
-            .into_iter()
-            .map(|key| KeyStatus {
-                key,
-                is_exhausted: false,
-                exhausted_at: None,
-                last_used_at: None,
-            })
-            .collect();
+        let keys = api_keys.into_iter().map(KeyStatus::new).collect();
 
         KeyManagerState {
             current_index: 0,
             keys,
             last_reset_date: date,
+            sticky_keys: HashMap::new(),
         }
     }
 
@@ -129,12 +544,7 @@ impl KeyManager {
                 updated_keys.push(existing.clone());
             } else {
                 // 新增的 key
-                updated_keys.push(KeyStatus {
-                    key: new_key,
-                    is_exhausted: false,
-                    exhausted_at: None,
-                    last_used_at: None,
-                });
+                updated_keys.push(KeyStatus::new(new_key));
             }
         }
 
@@ -143,101 +553,159 @@ impl KeyManager {
             state.current_index = 0;
         }
 
+        // 已删除的 key 不再固定给任何任务
+        state
+            .sticky_keys
+            .retain(|_, key| updated_keys.iter().any(|status| &status.key == key));
+
         state.keys = updated_keys;
         state
     }
 
-    /// 获取下一个可用的 key
+    /// 按平台的轮询设置获取下一个可用的 key
     pub fn get_next_available_key(&self, api_keys: Vec<String>) -> Result<(String, usize), String> {
-        let mut state = self.initialize_keys(api_keys)?;
+        let rotation = KeyRotation::current(&self.platform);
+        let task = TASK.try_with(|task| task.id.clone()).ok();
 
-        // 从当前游标开始查找可用的 key
-        let start_index = state.current_index;
-        let total_keys = state.keys.len();
-
-        for offset in 0..total_keys {
-            let index = (start_index + offset) % total_keys;
-            let key_status = &state.keys[index];
+        self.with_state(api_keys, |state| {
+            let Some(index) = state.select_key(&rotation, task.as_deref()) else {
+                return Err(format!("[{}] 所有 API Key 都已额度耗尽", self.platform));
+            };
+            eprintln!(
+                "[{}] 使用 Key {} (索引 {}): {}",
+                self.platform,
+                index + 1,
+                index,
+                state.keys[index].key_hint()
+            );
+            Ok((state.keys[index].key.clone(), index))
+        })
+    }
 
-            if !key_status.is_exhausted {
+    /// 标记 key 为已耗尽
+    pub fn mark_key_exhausted(
+        &self,
+        key_index: usize,
+        api_keys: Vec<String>,
+    ) -> Result<(), String> {
+        self.with_state(api_keys, |state| {
+            if key_index < state.keys.len() {
+                state.mark_exhausted(key_index);
                 eprintln!(
-                    "[{}] 使用 Key {} (索引 {}): {}...",
+                    "[{}] 标记 Key {} 为已耗尽: {}",
                     self.platform,
-                    offset + 1,
-                    index,
-                    &key_status.key[..8.min(key_status.key.len())]
+                    key_index + 1,
+                    state.keys[key_index].key_hint()
                 );
-
-                // 更新游标
-                state.current_index = index;
-                self.save_state(&state)?;
-
-                return Ok((key_status.key.clone(), index));
             }
-        }
+            Ok(())
+        })
+    }
 
-        Err(format!("[{}] 所有 API Key 都已额度耗尽", self.platform))
+    /// 更新 key 的最后使用时间并记一次成功
+    pub fn update_last_used(&self, key_index: usize, api_keys: Vec<String>) -> Result<(), String> {
+        self.with_state(api_keys, |state| {
+            state.record_success(key_index);
+            Ok(())
+        })
     }
 
-    /// 标记 key 为已耗尽
-    pub fn mark_key_exhausted(
+    /// 记录 key 的一次失败
+    pub fn record_failure(
         &self,
         key_index: usize,
         api_keys: Vec<String>,
+        error: &str,
     ) -> Result<(), String> {
-        let mut state = self.initialize_keys(api_keys)?;
-
-        if key_index < state.keys.len() {
-            let now = Local::now().to_rfc3339();
-            state.keys[key_index].is_exhausted = true;
-            state.keys[key_index].exhausted_at = Some(now);
-
-            eprintln!(
-                "[{}] 标记 Key {} 为已耗尽: {}...",
-                self.platform,
-                key_index + 1,
-                &state.keys[key_index].key[..8.min(state.keys[key_index].key.len())]
-            );
-
-            // 移动游标到下一个位置
-            state.current_index = (key_index + 1) % state.keys.len();
-
-            self.save_state(&state)?;
-        }
-
-        Ok(())
+        self.with_state(api_keys, |state| {
+            if let Some(key) = state.keys.get_mut(key_index) {
+                key.failure_count += 1;
+                key.record_error(error);
+            }
+            Ok(())
+        })
     }
 
-    /// 更新 key 的最后使用时间
-    pub fn update_last_used(&self, key_index: usize, api_keys: Vec<String>) -> Result<(), String> {
-        let mut state = self.initialize_keys(api_keys)?;
+    /// 记录验证结果：保存剩余额度，额度恢复的 key 重新启用，额度为 0 的 key 标记为已耗尽
+    ///
+    /// 返回 key 是否从已耗尽恢复为可用。
+    pub fn record_validation(
+        &self,
+        key: &str,
+        api_keys: Vec<String>,
+        result: &crate::ApiKeyValidationResult,
+    ) -> Result<bool, String> {
+        self.with_state(api_keys, |state| {
+            let Some(status) = state.keys.iter_mut().find(|status| status.key == key) else {
+                return Ok(false);
+            };
 
-        if key_index < state.keys.len() {
             let now = Local::now().to_rfc3339();
-            state.keys[key_index].last_used_at = Some(now);
-            self.save_state(&state)?;
-        }
+            status.checked_at = Some(now.clone());
+            if let Some(remaining) = result.remaining {
+                status.quota_samples.push(QuotaSample {
+                    at: now.clone(),
+                    remaining,
+                });
+                let overflow = status.quota_samples.len().saturating_sub(MAX_QUOTA_SAMPLES);
+                status.quota_samples.drain(..overflow);
+            }
+            if !result.valid {
+                status.record_error(result.message.as_deref().unwrap_or("验证失败"));
+            }
 
-        Ok(())
+            let mut restored = false;
+            if result.remaining == Some(0) {
+                if !status.is_exhausted {
+                    status.is_exhausted = true;
+                    status.exhausted_at = Some(now);
+                }
+            } else if result.valid && status.is_exhausted {
+                status.is_exhausted = false;
+                status.exhausted_at = None;
+                restored = true;
+                eprintln!("[{}] Key {} 额度已恢复", self.platform, status.key_hint());
+            }
+            Ok(restored)
+        })
     }
 
-    /// 获取状态摘要
-    #[allow(dead_code)]
-    pub fn get_status_summary(&self, api_keys: Vec<String>) -> Result<String, String> {
-        let state = self.initialize_keys(api_keys)?;
+    /// 设置 key 的加权轮询权重与标签
+    pub fn set_key_options(
+        &self,
+        key_index: usize,
+        api_keys: Vec<String>,
+        priority: u32,
+        tags: Vec<String>,
+    ) -> Result<(), String> {
+        self.with_state(api_keys, |state| {
+            let key = state
+                .keys
+                .get_mut(key_index)
+                .ok_or_else(|| format!("[{}] Key 索引 {} 不存在", self.platform, key_index))?;
+            key.priority = priority.max(1);
+            key.tags = tags;
+            Ok(())
+        })
+    }
 
-        let total = state.keys.len();
-        let exhausted = state.keys.iter().filter(|k| k.is_exhausted).count();
-        let available = total - exhausted;
+    /// 释放任务固定的 key
+    pub fn release_task(&self, task_id: &str) -> Result<(), String> {
+        self.with_existing_state(|state| {
+            if state.release_task(task_id) {
+                self.save_state(state)
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_or(Ok(()))
+    }
 
-        Ok(format!(
-            "[{}] 总计: {} | 可用: {} | 已耗尽: {} | 游标: {}",
-            self.platform,
-            total,
-            available,
-            exhausted,
-            state.current_index + 1
-        ))
+    /// 获取 key 池健康状况
+    pub fn pool_health(&self, api_keys: Vec<String>) -> Result<PoolHealth, String> {
+        self.with_state(api_keys, |state| {
+            Ok(PoolHealth::from_state(&self.platform, state))
+        })
     }
 }
 
@@ -263,11 +731,141 @@ pub fn update_used(platform: &str, key_index: usize, api_keys: Vec<String>) -> R
     manager.update_last_used(key_index, api_keys)
 }
 
-/// 便捷函数：获取状态摘要
-#[allow(dead_code)]
-pub fn get_status(platform: &str, api_keys: Vec<String>) -> Result<String, String> {
+/// 便捷函数：记录 key 的一次失败
+pub fn record_failure(
+    platform: &str,
+    key_index: usize,
+    api_keys: Vec<String>,
+    error: &str,
+) -> Result<(), String> {
     let manager = KeyManager::new(platform);
-    manager.get_status_summary(api_keys)
+    manager.record_failure(key_index, api_keys, error)
+}
+
+// 平台已配置的 key：(轮询标识, API Key, 账号)
+fn configured_keys(source: &dyn AssetSource) -> Result<Vec<(String, String, String)>, String> {
+    let pairs = if source.capabilities().requires_email {
+        config::get_all_api_key_pairs(source.name())?
+    } else {
+        config::get_all_api_keys(source.name())?
+            .into_iter()
+            .map(|key| (key, String::new()))
+            .collect()
+    };
+    Ok(pairs
+        .into_iter()
+        .map(|(key, email)| (source.rotation_key(&key, &email), key, email))
+        .collect())
+}
+
+/// 设置平台第 `key_index` 个 key 的加权轮询权重与标签
+pub fn set_key_options(
+    source: &dyn AssetSource,
+    key_index: usize,
+    priority: u32,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    let rotation_keys = configured_keys(source)?
+        .into_iter()
+        .map(|(rotation_key, _, _)| rotation_key)
+        .collect();
+    KeyManager::new(source.name()).set_key_options(key_index, rotation_keys, priority, tags)
+}
+
+/// 在任务范围内执行 `future`：平台开启粘性模式时，其中的请求固定使用同一个 key
+pub async fn sticky_task<F: Future>(task_id: &str, future: F) -> F::Output {
+    sticky_task_with_key(task_id, future).await.0
+}
+
+/// 同 [`sticky_task`]，并返回其中最后一次成功的请求实际使用的 key；没有发出请求时为 `None`
+pub async fn sticky_task_with_key<F: Future>(
+    task_id: &str,
+    future: F,
+) -> (F::Output, Option<UsedKey>) {
+    let scope = TaskScope {
+        id: task_id.to_string(),
+        used_key: Mutex::new(None),
+    };
+    TASK.scope(scope, async {
+        let output = future.await;
+        let used_key = TASK
+            .try_with(|task| {
+                task.used_key
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .take()
+            })
+            .ok()
+            .flatten();
+        (output, used_key)
+    })
+    .await
+}
+
+/// 任务结束后释放它在平台上固定的 key
+pub fn release_task(platform: &str, task_id: &str) {
+    if let Err(e) = KeyManager::new(platform).release_task(task_id) {
+        eprintln!("[{}] 释放任务 {} 的 key 失败: {}", platform, task_id, e);
+    }
+}
+
+/// 获取平台 key 池健康状况（不发请求）
+pub fn pool_health(source: &dyn AssetSource) -> Result<PoolHealth, String> {
+    let keys = configured_keys(source)?;
+    let rotation_keys = keys
+        .into_iter()
+        .map(|(rotation_key, _, _)| rotation_key)
+        .collect();
+    KeyManager::new(source.name()).pool_health(rotation_keys)
+}
+
+/// 重新验证平台的 key，刷新剩余额度并恢复额度已重置的 key
+///
+/// `all` 为 false 时只验证已耗尽、从未验证或额度信息过期的 key。
+pub async fn revalidate(source: &dyn AssetSource, all: bool) -> Result<PoolHealth, String> {
+    let keys = configured_keys(source)?;
+    let rotation_keys: Vec<String> = keys.iter().map(|(key, _, _)| key.clone()).collect();
+    let manager = KeyManager::new(source.name());
+    let state = manager.initialize_keys(rotation_keys.clone())?;
+    let now = Local::now();
+
+    for (rotation_key, api_key, email) in &keys {
+        let due = state
+            .keys
+            .iter()
+            .find(|status| &status.key == rotation_key)
+            .is_some_and(|status| all || status.needs_refresh(now));
+        if !due {
+            continue;
+        }
+
+        let email = (!email.is_empty()).then_some(email.as_str());
+        let result = match source.validate_api_key(api_key, email).await {
+            Ok(result) => result,
+            Err(e) => {
+                // 请求失败不代表 key 无效，不改动额度与耗尽状态
+                eprintln!("[{}] 验证 key 失败: {}", source.name(), e);
+                continue;
+            }
+        };
+        manager.record_validation(rotation_key, rotation_keys.clone(), &result)?;
+    }
+
+    manager.pool_health(rotation_keys)
+}
+
+/// 启动后台重新验证：定期刷新各平台 key 的额度，额度重置后的 key 自动恢复可用
+pub fn start_revalidation() {
+    tauri::async_runtime::spawn(async {
+        loop {
+            for source in all_sources() {
+                if let Err(e) = revalidate(source, false).await {
+                    eprintln!("[{}] 重新验证 key 失败: {}", source.name(), e);
+                }
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(REVALIDATE_TICK_SECS)).await;
+        }
+    });
 }
 
 /// 便捷函数：使用 key 轮询执行操作
@@ -275,16 +873,16 @@ pub fn get_status(platform: &str, api_keys: Vec<String>) -> Result<String, Strin
 /// 这个函数会自动处理 key 轮询逻辑：
 /// 1. 获取下一个可用的 key
 /// 2. 执行提供的异步操作
-/// 3. 如果操作失败且是配额耗尽错误，标记 key 为已耗尽并尝试下一个
+/// 3. 如果操作失败且是配额耗尽错误（[`ApiError::QuotaExhausted`]），标记 key 为已耗尽并尝试下一个
 /// 4. 如果操作成功，更新 key 的最后使用时间
 pub async fn execute_with_key_rotation<F, Fut, T>(
     platform: &str,
     api_keys: &[String],
     operation: F,
-) -> Result<T, String>
+) -> Result<T, ApiError>
 where
     F: Fn(&str) -> Fut,
-    Fut: std::future::Future<Output = Result<T, String>>,
+    Fut: std::future::Future<Output = Result<T, ApiError>>,
 {
     let api_keys_vec = api_keys.to_vec();
     let max_attempts = api_keys_vec.len();
@@ -295,29 +893,33 @@ where
             Ok(result) => result,
             Err(e) => {
                 if attempt == 0 {
-                    return Err(e);
+                    return Err(ApiError::QuotaExhausted(e));
                 }
                 // 所有 key 都已耗尽
-                return Err(format!("[{}] 所有API Key都无法使用", platform));
+                return Err(ApiError::QuotaExhausted(format!(
+                    "[{}] 所有API Key都无法使用",
+                    platform
+                )));
             }
         };
 
         // 执行操作
         match operation(&api_key).await {
             Ok(result) => {
-                // 成功，更新最后使用时间
+                // 成功，更新最后使用时间，并告知所属任务实际使用的 key
                 update_used(platform, key_index, api_keys_vec.clone()).ok();
+                TASK.try_with(|task| {
+                    *task.used_key.lock().unwrap_or_else(|e| e.into_inner()) =
+                        Some((key_index, key_hint(&api_key)));
+                })
+                .ok();
                 return Ok(result);
             }
             Err(e) => {
-                // 检查是否是配额耗尽错误
-                let is_quota_error = e.contains("积分")
-                    || e.contains("quota")
-                    || e.contains("次牛")
-                    || e.contains("F币")
-                    || e.contains("2004");
+                record_failure(platform, key_index, api_keys_vec.clone(), e.message()).ok();
 
-                if is_quota_error {
+                // 检查是否是配额耗尽错误
+                if e.is_key_exhausted() {
                     eprintln!(
                         "[{}] Key {} 配额耗尽，尝试下一个...",
                         platform,
@@ -333,5 +935,381 @@ where
         }
     }
 
-    Err(format!("[{}] 所有API Key都无法使用", platform))
+    Err(ApiError::QuotaExhausted(format!(
+        "[{}] 所有API Key都无法使用",
+        platform
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(at: &str, remaining: u64) -> QuotaSample {
+        QuotaSample {
+            at: at.to_string(),
+            remaining,
+        }
+    }
+
+    fn state(keys: &[&str]) -> KeyManagerState {
+        KeyManagerState {
+            current_index: 0,
+            keys: keys
+                .iter()
+                .map(|key| KeyStatus::new(key.to_string()))
+                .collect(),
+            last_reset_date: "2024-05-01".to_string(),
+            sticky_keys: HashMap::new(),
+        }
+    }
+
+    fn rotation(strategy: RotationStrategy) -> KeyRotation {
+        KeyRotation {
+            strategy,
+            ..KeyRotation::default()
+        }
+    }
+
+    // 连续选择 n 次，返回选中的 key
+    fn picks(state: &mut KeyManagerState, rotation: &KeyRotation, n: usize) -> Vec<String> {
+        (0..n)
+            .map(|_| {
+                let index = state.select_key(rotation, None).unwrap();
+                state.keys[index].key.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_key_hint_keeps_whole_characters() {
+        assert_eq!(key_hint("abcdefghijkl"), "abcdefgh...");
+        assert_eq!(key_hint("abc"), "abc...");
+        // 多字节字符不能从中间截断
+        assert_eq!(key_hint("密钥密钥密钥密钥密钥"), "密钥密钥密钥密钥...");
+    }
+
+    #[test]
+    fn test_sequential_keeps_key_until_exhausted() {
+        let mut state = state(&["a", "b", "c"]);
+        let sequential = rotation(RotationStrategy::Sequential);
+        assert_eq!(picks(&mut state, &sequential, 2), ["a", "a"]);
+
+        state.mark_exhausted(0);
+        assert_eq!(state.current_index, 1);
+        assert_eq!(picks(&mut state, &sequential, 2), ["b", "b"]);
+
+        state.mark_exhausted(1);
+        state.mark_exhausted(2);
+        assert_eq!(state.select_key(&sequential, None), None);
+    }
+
+    #[test]
+    fn test_round_robin_skips_exhausted_keys() {
+        let mut state = state(&["a", "b", "c"]);
+        let round_robin = rotation(RotationStrategy::RoundRobin);
+        assert_eq!(picks(&mut state, &round_robin, 4), ["b", "c", "a", "b"]);
+
+        state.mark_exhausted(2);
+        assert_eq!(picks(&mut state, &round_robin, 3), ["b", "a", "b"]);
+
+        // 只剩一个可用 key 时继续使用它
+        state.mark_exhausted(1);
+        assert_eq!(picks(&mut state, &round_robin, 2), ["a", "a"]);
+    }
+
+    #[test]
+    fn test_most_remaining_prefers_largest_known_quota() {
+        let mut state = state(&["unknown", "low", "high"]);
+        state.keys[1].quota_samples = vec![sample("2024-05-01T08:00:00+08:00", 10)];
+        state.keys[2].quota_samples = vec![sample("2024-05-01T08:00:00+08:00", 500)];
+        let most_remaining = rotation(RotationStrategy::MostRemaining);
+        assert_eq!(picks(&mut state, &most_remaining, 1), ["high"]);
+
+        state.mark_exhausted(2);
+        assert_eq!(picks(&mut state, &most_remaining, 1), ["low"]);
+        state.mark_exhausted(1);
+        assert_eq!(picks(&mut state, &most_remaining, 1), ["unknown"]);
+    }
+
+    #[test]
+    fn test_least_recently_used_prefers_unused_then_oldest() {
+        let mut state = state(&["recent", "old", "never"]);
+        state.keys[0].last_used_at = Some("2024-05-01T10:00:00+08:00".to_string());
+        state.keys[1].last_used_at = Some("2024-05-01T08:00:00+08:00".to_string());
+        let lru = rotation(RotationStrategy::LeastRecentlyUsed);
+        assert_eq!(picks(&mut state, &lru, 1), ["never"]);
+
+        state.record_success(2);
+        assert_eq!(picks(&mut state, &lru, 1), ["old"]);
+        state.record_success(1);
+        assert_eq!(picks(&mut state, &lru, 1), ["recent"]);
+    }
+
+    #[test]
+    fn test_weighted_follows_priority() {
+        let mut state = state(&["paid", "free"]);
+        state.keys[0].priority = 3;
+        let weighted = rotation(RotationStrategy::Weighted);
+        let picked = picks(&mut state, &weighted, 8);
+        assert_eq!(picked.iter().filter(|key| *key == "paid").count(), 6);
+        assert_eq!(picked.iter().filter(|key| *key == "free").count(), 2);
+        // 平滑加权：低权重的 key 穿插使用，而不是连续用完高权重的
+        assert_eq!(&picked[..4], ["paid", "paid", "free", "paid"]);
+    }
+
+    #[test]
+    fn test_preferred_tags_fall_back_when_exhausted() {
+        let mut state = state(&["free-1", "paid", "free-2"]);
+        state.keys[1].tags = vec!["paid".to_string()];
+        let paid_first = KeyRotation {
+            strategy: RotationStrategy::RoundRobin,
+            preferred_tags: vec!["paid".to_string()],
+            ..KeyRotation::default()
+        };
+        assert_eq!(picks(&mut state, &paid_first, 2), ["paid", "paid"]);
+
+        state.mark_exhausted(1);
+        assert_eq!(picks(&mut state, &paid_first, 2), ["free-1", "free-2"]);
+    }
+
+    #[test]
+    fn test_sticky_task_keeps_its_key() {
+        let mut state = state(&["a", "b", "c"]);
+        let sticky = KeyRotation {
+            strategy: RotationStrategy::RoundRobin,
+            sticky: true,
+            ..KeyRotation::default()
+        };
+        assert_eq!(state.select_key(&sticky, Some("task-1")), Some(1));
+        // 其它任务照常轮询，不影响已固定的 key
+        assert_eq!(state.select_key(&sticky, Some("task-2")), Some(2));
+        assert_eq!(state.select_key(&sticky, None), Some(0));
+        assert_eq!(state.select_key(&sticky, Some("task-1")), Some(1));
+        assert_eq!(state.select_key(&sticky, Some("task-2")), Some(2));
+
+        // 固定的 key 耗尽后重新选择并固定新 key
+        state.mark_exhausted(1);
+        assert_eq!(state.select_key(&sticky, Some("task-1")), Some(0));
+        assert_eq!(state.sticky_keys["task-1"], "a");
+        assert_eq!(state.select_key(&sticky, Some("task-1")), Some(0));
+
+        assert!(state.release_task("task-1"));
+        assert!(!state.release_task("task-1"));
+        assert_eq!(state.sticky_keys.len(), 1);
+
+        // 未开启粘性模式时忽略任务
+        let plain = rotation(RotationStrategy::RoundRobin);
+        assert_eq!(state.select_key(&plain, Some("task-2")), Some(2));
+        assert_eq!(state.select_key(&plain, Some("task-2")), Some(0));
+    }
+
+    fn validation(valid: bool, remaining: Option<u64>) -> crate::ApiKeyValidationResult {
+        crate::ApiKeyValidationResult {
+            valid,
+            message: None,
+            quota: None,
+            remaining,
+        }
+    }
+
+    #[test]
+    fn test_validation_exhausts_only_on_explicit_zero() {
+        let manager = KeyManager::new("validation-test");
+        let keys = vec!["unknown-quota".to_string(), "empty".to_string()];
+
+        // 额度未知的有效 key 保持可用
+        let restored = manager
+            .record_validation("unknown-quota", keys.clone(), &validation(true, None))
+            .unwrap();
+        assert!(!restored);
+        manager
+            .record_validation("empty", keys.clone(), &validation(true, Some(0)))
+            .unwrap();
+        let health = manager.pool_health(keys.clone()).unwrap();
+        assert!(!health.keys[0].is_exhausted);
+        assert_eq!(health.keys[0].remaining, None);
+        assert!(health.keys[1].is_exhausted);
+
+        // 已耗尽的 key 验证有效且额度未知时恢复可用
+        manager.mark_key_exhausted(0, keys.clone()).unwrap();
+        let restored = manager
+            .record_validation("unknown-quota", keys.clone(), &validation(true, None))
+            .unwrap();
+        assert!(restored);
+        assert!(!manager.pool_health(keys).unwrap().keys[0].is_exhausted);
+        fs::remove_file(&manager.state_file).ok();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_concurrent_rotation_keeps_every_update() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        const CALLS: u64 = 64;
+        let platform = "concurrency-test";
+        let keys: Vec<String> = (0..4).map(|i| format!("key-{}", i)).collect();
+        let failures = Arc::new(AtomicU64::new(0));
+
+        // 前两个 key 额度耗尽，其余 key 请求成功
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..CALLS {
+            let keys = keys.clone();
+            let failures = failures.clone();
+            tasks.spawn(async move {
+                execute_with_key_rotation(platform, &keys, |key| {
+                    let exhausted = key == "key-0" || key == "key-1";
+                    let failures = failures.clone();
+                    async move {
+                        tokio::task::yield_now().await;
+                        if exhausted {
+                            failures.fetch_add(1, Ordering::SeqCst);
+                            Err(ApiError::QuotaExhausted("额度不足".to_string()))
+                        } else {
+                            Ok(())
+                        }
+                    }
+                })
+                .await
+            });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            assert!(joined.unwrap().is_ok());
+        }
+
+        let manager = KeyManager::new(platform);
+        let health = manager.pool_health(keys.clone()).unwrap();
+        assert!(health.keys[0].is_exhausted && health.keys[1].is_exhausted);
+        assert_eq!(
+            health.keys.iter().map(|key| key.success_count).sum::<u64>(),
+            CALLS
+        );
+        assert_eq!(
+            health.keys.iter().map(|key| key.failure_count).sum::<u64>(),
+            failures.load(Ordering::SeqCst)
+        );
+
+        // 磁盘上的状态与进程内的 key 池一致
+        let saved = manager.load_state().unwrap();
+        assert_eq!(
+            saved.keys.iter().map(|key| key.success_count).sum::<u64>(),
+            CALLS
+        );
+        fs::remove_file(&manager.state_file).ok();
+    }
+
+    #[tokio::test]
+    async fn test_task_reports_the_key_it_used() {
+        let platform = "used-key-test";
+        let keys = vec!["first-key".to_string(), "second-key".to_string()];
+
+        // 第一个 key 额度耗尽，请求换用第二个 key 后成功
+        let (result, used_key) = sticky_task_with_key(
+            "used-key-task",
+            execute_with_key_rotation(platform, &keys, |key| {
+                let exhausted = key == "first-key";
+                async move {
+                    if exhausted {
+                        Err(ApiError::QuotaExhausted("额度不足".to_string()))
+                    } else {
+                        Ok(())
+                    }
+                }
+            }),
+        )
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(used_key, Some((1, "second-k...".to_string())));
+
+        // 没有发出请求的任务不报告 key
+        let (_, used_key) = sticky_task_with_key("used-key-task", async {}).await;
+        assert_eq!(used_key, None);
+        fs::remove_file(&KeyManager::new(platform).state_file).ok();
+    }
+
+    #[test]
+    fn test_predict_exhaustion_from_recent_usage() {
+        // 2 小时用掉 200，剩余 300 还能用 3 小时
+        let samples = [
+            sample("2024-05-01T08:00:00+08:00", 500),
+            sample("2024-05-01T09:00:00+08:00", 400),
+            sample("2024-05-01T10:00:00+08:00", 300),
+        ];
+        assert_eq!(
+            predict_exhaustion(&samples).unwrap().to_rfc3339(),
+            "2024-05-01T13:00:00+08:00"
+        );
+
+        // 额度回升（重置）之前的采样不参与计算
+        let samples = [
+            sample("2024-05-01T08:00:00+08:00", 10),
+            sample("2024-05-02T00:00:00+08:00", 1000),
+            sample("2024-05-02T01:00:00+08:00", 900),
+        ];
+        assert_eq!(
+            predict_exhaustion(&samples).unwrap().to_rfc3339(),
+            "2024-05-02T10:00:00+08:00"
+        );
+    }
+
+    #[test]
+    fn test_predict_exhaustion_needs_usage() {
+        assert_eq!(predict_exhaustion(&[]), None);
+        assert_eq!(
+            predict_exhaustion(&[sample("2024-05-01T08:00:00+08:00", 100)]),
+            None
+        );
+        let unchanged = [
+            sample("2024-05-01T08:00:00+08:00", 100),
+            sample("2024-05-01T09:00:00+08:00", 100),
+        ];
+        assert_eq!(predict_exhaustion(&unchanged), None);
+        // 额度已为 0 时耗尽时间即最后一次采样时间
+        let empty = [sample("2024-05-01T09:00:00+08:00", 0)];
+        assert_eq!(
+            predict_exhaustion(&empty).unwrap().to_rfc3339(),
+            "2024-05-01T09:00:00+08:00"
+        );
+    }
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = Local::now();
+        let mut status = KeyStatus::new("key".to_string());
+        assert!(status.needs_refresh(now));
+
+        status.checked_at = Some(now.to_rfc3339());
+        assert!(!status.needs_refresh(now));
+        status.is_exhausted = true;
+        assert!(status.needs_refresh(now));
+
+        status.is_exhausted = false;
+        status.checked_at = Some((now - Duration::hours(REFRESH_AFTER_HOURS + 1)).to_rfc3339());
+        assert!(status.needs_refresh(now));
+    }
+
+    #[test]
+    fn test_pool_health_summarises_keys() {
+        let mut exhausted = KeyStatus::new("exhausted-key".to_string());
+        exhausted.is_exhausted = true;
+        exhausted.quota_samples = vec![sample("2024-05-01T08:00:00+08:00", 0)];
+        let mut checked = KeyStatus::new("checked-key".to_string());
+        checked.quota_samples = vec![sample("2024-05-01T08:00:00+08:00", 120)];
+        checked.failure_count = 2;
+        let state = KeyManagerState {
+            current_index: 0,
+            keys: vec![exhausted, checked, KeyStatus::new("new-key".to_string())],
+            last_reset_date: "2024-05-01".to_string(),
+            sticky_keys: HashMap::new(),
+        };
+
+        let health = PoolHealth::from_state("hunter", &state);
+        assert_eq!(health.total, 3);
+        assert_eq!(health.available, 2);
+        assert_eq!(health.exhausted, 1);
+        assert_eq!(health.remaining, Some(120));
+        assert_eq!(health.keys[1].key_hint, "checked-...");
+        assert_eq!(health.keys[1].failure_count, 2);
+        assert_eq!(health.keys[2].remaining, None);
+    }
 }
@@ -0,0 +1,391 @@
+use super::asset::{normalize_protocol, Asset};
+use super::registry::all_sources;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 合并选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeOptions {
+    /// 是否将 host 纳入分组键（同一 ip:port 上的不同站点分开保留）
+    pub by_host: bool,
+}
+
+/// 分组键：ip、端口、协议，可选 host
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MergeKey {
+    /// ip，没有 ip 的资产依次取 host、domain、url
+    ip: String,
+    port: Option<u16>,
+    protocol: String,
+    host: Option<String>,
+}
+
+impl MergeKey {
+    fn of(asset: &Asset, options: MergeOptions) -> Self {
+        // 只有域名的结果没有 ip，不能都归到空 ip 下合并成一条
+        let ip = [&asset.ip, &asset.host, &asset.domain, &asset.url]
+            .into_iter()
+            .map(|value| value.trim())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        MergeKey {
+            ip: ip.to_lowercase(),
+            port: asset.port,
+            protocol: normalize_protocol(&asset.protocol),
+            host: options.by_host.then(|| asset.host.trim().to_lowercase()),
+        }
+    }
+}
+
+/// 合并后的资产
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedAsset {
+    #[serde(flatten)]
+    pub asset: Asset,
+    /// 发现该资产的平台，去重后按注册顺序排列
+    pub engines: Vec<String>,
+}
+
+// 平台在注册表中的位置，未注册的平台排在最后
+fn registry_position(platform: &str) -> usize {
+    all_sources()
+        .position(|source| source.name() == platform)
+        .unwrap_or(usize::MAX)
+}
+
+impl MergedAsset {
+    /// 导出用的记录，platform 列填入全部来源平台
+    pub fn export_record(&self) -> Asset {
+        Asset {
+            platform: self.engines.join(", "),
+            ..self.asset.clone()
+        }
+    }
+}
+
+/// 单个平台的贡献统计
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineContribution {
+    pub platform: String,
+    /// 该平台返回的原始条数
+    pub returned: usize,
+    /// 合并后该平台参与的资产数
+    pub assets: usize,
+    /// 仅由该平台发现的资产数
+    pub unique: usize,
+}
+
+/// 合并统计
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeSummary {
+    pub input_count: usize,
+    pub merged_count: usize,
+    pub engines: Vec<EngineContribution>,
+}
+
+/// 合并结果
+#[derive(Debug, Clone)]
+pub struct MergeReport {
+    pub assets: Vec<MergedAsset>,
+    pub summary: MergeSummary,
+}
+
+// 解析各平台的时间格式（RFC3339、"2024-01-01 12:00:00"、"2024-01-01"）
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.naive_utc());
+    }
+    [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+    ]
+    .iter()
+    .find_map(|fmt| NaiveDateTime::parse_from_str(value, fmt).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+    })
+}
+
+fn timestamp_of(value: &Option<String>) -> Option<NaiveDateTime> {
+    value.as_deref().and_then(parse_timestamp)
+}
+
+// 在两个时间中取较早 / 较晚者，无法解析的时间视为缺失
+fn pick_timestamp(a: Option<String>, b: Option<String>, latest: bool) -> Option<String> {
+    match (timestamp_of(&a), timestamp_of(&b)) {
+        (Some(ta), Some(tb)) => {
+            if (tb > ta) == latest {
+                b
+            } else {
+                a
+            }
+        }
+        (Some(_), None) => a,
+        (None, Some(_)) => b,
+        (None, None) => a.or(b),
+    }
+}
+
+fn fill(target: &mut String, other: &str) {
+    if target.is_empty() && !other.is_empty() {
+        *target = other.to_string();
+    }
+}
+
+// 将 other 中的字段并入 base：base 已有的值保留，缺失的值由 other 补齐
+fn union_into(base: &mut Asset, other: Asset) {
+    fill(&mut base.protocol, &other.protocol);
+    fill(&mut base.host, &other.host);
+    fill(&mut base.domain, &other.domain);
+    fill(&mut base.url, &other.url);
+    fill(&mut base.title, &other.title);
+    fill(&mut base.server, &other.server);
+    fill(&mut base.geo.country, &other.geo.country);
+    fill(&mut base.geo.province, &other.geo.province);
+    fill(&mut base.geo.city, &other.geo.city);
+    fill(&mut base.geo.isp, &other.geo.isp);
+    fill(&mut base.as_org, &other.as_org);
+    fill(&mut base.os, &other.os);
+    fill(&mut base.icp, &other.icp);
+    fill(&mut base.organization, &other.organization);
+    fill(&mut base.certificate, &other.certificate);
+    base.status_code = base.status_code.or(other.status_code);
+    base.asn = base.asn.or(other.asn);
+
+    for fingerprint in other.fingerprints {
+        if !base
+            .fingerprints
+            .iter()
+            .any(|f| f.eq_ignore_ascii_case(&fingerprint))
+        {
+            base.fingerprints.push(fingerprint);
+        }
+    }
+
+    base.first_seen = pick_timestamp(base.first_seen.take(), other.first_seen, false);
+    base.last_seen = pick_timestamp(base.last_seen.take(), other.last_seen, true);
+}
+
+/// 按 (ip, port, protocol[, host]) 合并多个平台的结果
+///
+/// 组内以最新（last_seen 最晚）的记录为基准，其余记录补齐缺失字段；
+/// 输出顺序与每组首次出现的顺序一致。
+pub fn merge_assets(assets: Vec<Asset>, options: MergeOptions) -> MergeReport {
+    let input_count = assets.len();
+
+    // 平台按注册顺序统计，未注册的平台按首次出现顺序排在最后
+    let mut engine_order: Vec<String> = Vec::new();
+    let mut returned: HashMap<String, usize> = HashMap::new();
+
+    let mut index: HashMap<MergeKey, usize> = HashMap::new();
+    let mut groups: Vec<Vec<Asset>> = Vec::new();
+
+    for asset in assets {
+        if !returned.contains_key(&asset.platform) {
+            engine_order.push(asset.platform.clone());
+        }
+        *returned.entry(asset.platform.clone()).or_default() += 1;
+
+        let key = MergeKey::of(&asset, options);
+        match index.get(&key) {
+            Some(&i) => groups[i].push(asset),
+            None => {
+                index.insert(key, groups.len());
+                groups.push(vec![asset]);
+            }
+        }
+    }
+
+    let merged: Vec<MergedAsset> = groups
+        .into_iter()
+        .map(|mut group| {
+            let mut engines: Vec<String> = Vec::new();
+            for asset in &group {
+                if !engines.contains(&asset.platform) {
+                    engines.push(asset.platform.clone());
+                }
+            }
+            engines.sort_by_key(|platform| registry_position(platform));
+
+            // 最新的记录排在最前，稳定排序保证同一时间时保持原顺序
+            group.sort_by_key(|a| std::cmp::Reverse(timestamp_of(&a.last_seen)));
+            let mut records = group.into_iter();
+            let mut asset = records.next().unwrap_or_default();
+            for other in records {
+                union_into(&mut asset, other);
+            }
+
+            MergedAsset { asset, engines }
+        })
+        .collect();
+
+    engine_order.sort_by_key(|platform| registry_position(platform));
+    let engines = engine_order
+        .into_iter()
+        .map(|platform| {
+            let seen = merged.iter().filter(|m| m.engines.contains(&platform));
+            let (assets, unique) = seen.fold((0, 0), |(total, unique), m| {
+                (total + 1, unique + usize::from(m.engines.len() == 1))
+            });
+            EngineContribution {
+                returned: returned[&platform],
+                platform,
+                assets,
+                unique,
+            }
+        })
+        .collect();
+
+    MergeReport {
+        summary: MergeSummary {
+            input_count,
+            merged_count: merged.len(),
+            engines,
+        },
+        assets: merged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn asset(platform: &str, ip: &str, port: u16, protocol: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port: Some(port),
+            protocol: protocol.to_string(),
+            ..Asset::new(platform, Value::Null)
+        }
+    }
+
+    #[test]
+    fn test_merge_groups_same_service_across_engines() {
+        let report = merge_assets(
+            vec![
+                asset("hunter", "1.1.1.1", 443, "https"),
+                asset("fofa", "1.1.1.1", 443, "HTTPS"),
+                asset("quake", "1.1.1.1", 443, "http/ssl"),
+                asset("fofa", "1.1.1.1", 80, "http"),
+            ],
+            MergeOptions::default(),
+        );
+
+        assert_eq!(report.assets.len(), 2);
+        assert_eq!(report.assets[0].engines, vec!["hunter", "fofa", "quake"]);
+        assert_eq!(report.assets[1].engines, vec!["fofa"]);
+        assert_eq!(report.summary.input_count, 4);
+        assert_eq!(report.summary.merged_count, 2);
+        assert_eq!(
+            report.assets[0].export_record().platform,
+            "hunter, fofa, quake"
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_domain_only_results_apart() {
+        let domain = |platform: &str, host: &str| Asset {
+            host: host.to_string(),
+            ..Asset::new(platform, Value::Null)
+        };
+        let report = merge_assets(
+            vec![
+                domain("fofa", "a.example.com"),
+                domain("hunter", "b.example.com"),
+                domain("quake", "A.example.com"),
+            ],
+            MergeOptions::default(),
+        );
+
+        assert_eq!(report.assets.len(), 2);
+        // 平台按注册顺序排列，与出现顺序无关
+        assert_eq!(report.assets[0].engines, vec!["fofa", "quake"]);
+        assert_eq!(report.summary.engines[0].platform, "hunter");
+    }
+
+    #[test]
+    fn test_merge_by_host_keeps_virtual_hosts_apart() {
+        let mut a = asset("hunter", "1.1.1.1", 443, "https");
+        a.host = "a.example.com".to_string();
+        let mut b = asset("fofa", "1.1.1.1", 443, "https");
+        b.host = "b.example.com".to_string();
+
+        let merged = merge_assets(vec![a.clone(), b.clone()], MergeOptions::default());
+        assert_eq!(merged.assets.len(), 1);
+
+        let by_host = merge_assets(vec![a, b], MergeOptions { by_host: true });
+        assert_eq!(by_host.assets.len(), 2);
+    }
+
+    #[test]
+    fn test_merge_unions_fields_and_prefers_freshest() {
+        let mut old = asset("hunter", "1.1.1.1", 80, "http");
+        old.title = "旧标题".to_string();
+        old.server = "nginx".to_string();
+        old.fingerprints = vec!["nginx".to_string()];
+        old.last_seen = Some("2024-01-01 08:00:00".to_string());
+
+        let mut fresh = asset("quake", "1.1.1.1", 80, "http");
+        fresh.title = "新标题".to_string();
+        fresh.geo.country = "中国".to_string();
+        fresh.fingerprints = vec!["NGINX".to_string(), "php".to_string()];
+        fresh.last_seen = Some("2024-03-01T10:00:00Z".to_string());
+
+        let mut undated = asset("fofa", "1.1.1.1", 80, "http");
+        undated.asn = Some(4134);
+
+        let report = merge_assets(vec![old, fresh, undated], MergeOptions::default());
+        let merged = &report.assets[0].asset;
+
+        assert_eq!(merged.title, "新标题");
+        assert_eq!(merged.server, "nginx");
+        assert_eq!(merged.geo.country, "中国");
+        assert_eq!(merged.asn, Some(4134));
+        assert_eq!(merged.fingerprints, vec!["NGINX", "php"]);
+        assert_eq!(merged.last_seen.as_deref(), Some("2024-03-01T10:00:00Z"));
+        assert_eq!(merged.first_seen, None);
+    }
+
+    #[test]
+    fn test_merge_reports_unique_contribution_per_engine() {
+        let report = merge_assets(
+            vec![
+                asset("hunter", "1.1.1.1", 80, "http"),
+                asset("hunter", "2.2.2.2", 80, "http"),
+                asset("fofa", "1.1.1.1", 80, "http"),
+                asset("fofa", "3.3.3.3", 22, "ssh"),
+                asset("fofa", "3.3.3.3", 22, "ssh"),
+            ],
+            MergeOptions::default(),
+        );
+
+        let hunter = &report.summary.engines[0];
+        assert_eq!(
+            hunter,
+            &EngineContribution {
+                platform: "hunter".to_string(),
+                returned: 2,
+                assets: 2,
+                unique: 1,
+            }
+        );
+        let fofa = &report.summary.engines[1];
+        assert_eq!((fofa.returned, fofa.assets, fofa.unique), (3, 2, 1));
+    }
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        assert!(parse_timestamp("2024-01-01 12:00:00").is_some());
+        assert!(parse_timestamp("2024-01-01T12:00:00.123Z").is_some());
+        assert!(parse_timestamp("2024-01-01").is_some());
+        assert!(parse_timestamp("昨天").is_none());
+    }
+}
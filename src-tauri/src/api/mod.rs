@@ -1,464 +1,489 @@
+pub mod asset;
+pub mod censys;
+pub mod cost;
 pub mod daydaymap;
 pub mod fofa;
+pub mod http;
 pub mod hunter;
 pub mod key_manager;
+pub mod merge;
 pub mod quake;
-
-use serde_json::Value;
+pub mod registry;
+pub mod shodan;
+pub mod source;
+pub mod zoomeye;
+
+pub use asset::{Asset, SearchPage};
+pub use merge::{merge_assets, MergeOptions, MergeSummary};
+pub use registry::{all_sources, get_source};
+
+use crate::converter::{QueryConverter, TimeRange};
+use crate::export::cancel::{self, CancelSignal};
+use crate::export::{ExportFile, ExportFormat};
+use serde::Serialize;
+use source::{AssetSource, Pagination};
 use std::path::Path;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// 逐页获取的查询结果
+#[derive(Debug, Default)]
+pub struct FetchedPages {
+    pub assets: Vec<Asset>,
+    /// 平台返回的命中总数
+    pub total: Option<u64>,
+    /// 已消耗的额度（单位见平台计费模型）
+    pub spent: u64,
+    /// 是否因达到额度预算而提前停止
+    pub budget_reached: bool,
+}
 
-// 导出所有平台的资产
-pub async fn export_all_platforms(
+// 逐页获取查询结果，直到取满 `pages` 页、没有更多数据或下一页会超出额度预算 `budget`；
+// 每页取回后以 (页码, 本页条数, 累计结果) 调用 `on_page`，任一页重试后仍失败即返回错误
+pub async fn fetch_pages(
+    source: &dyn AssetSource,
     query: &str,
     pages: u32,
     page_size: u32,
-    time_range: &str,
-    start_date: Option<String>,
-    end_date: Option<String>,
-    export_path: &str,
+    budget: Option<u64>,
+    on_page: impl FnMut(u32, usize, &FetchedPages),
+) -> Result<FetchedPages, String> {
+    let mut fetched = FetchedPages::default();
+    fetch_pages_into(
+        &mut fetched,
+        source,
+        query,
+        pages,
+        page_size,
+        budget,
+        on_page,
+    )
+    .await?;
+    Ok(fetched)
+}
+
+// 同 `fetch_pages`，结果逐页累加到调用方的 `fetched` 中，失败或中途放弃时已取回的页仍然保留
+pub async fn fetch_pages_into(
+    fetched: &mut FetchedPages,
+    source: &dyn AssetSource,
+    query: &str,
+    pages: u32,
+    page_size: u32,
+    budget: Option<u64>,
+    mut on_page: impl FnMut(u32, usize, &FetchedPages),
 ) -> Result<(), String> {
-    // 为每个平台创建适配的查询语句
-    let hunter_query =
-        adapt_query_for_platform(query, "hunter", time_range, &start_date, &end_date)?;
-    let fofa_query = adapt_query_for_platform(query, "fofa", time_range, &start_date, &end_date)?;
-    let quake_query = adapt_query_for_platform(query, "quake", time_range, &start_date, &end_date)?;
-    let daydaymap_query =
-        adapt_query_for_platform(query, "daydaymap", time_range, &start_date, &end_date)?;
-
-    // 并行查询所有平台
-    let mut all_results: Vec<Value> = Vec::new();
-
-    // Hunter查询
-    let hunter_results = match hunter::search(&hunter_query, 1, pages * page_size).await {
-        Ok(results) => {
-            if let Some(results_array) = results["results"].as_array() {
-                results_array
-                    .iter()
-                    .map(|r| {
-                        let mut result = r.clone();
-                        if let Value::Object(obj) = &mut result {
-                            obj.insert("platform".to_string(), Value::String("hunter".to_string()));
-                        }
-                        result
-                    })
-                    .collect()
-            } else {
-                Vec::new()
-            }
-        }
-        Err(_) => Vec::new(),
-    };
-    all_results.extend(hunter_results);
-
-    // FOFA查询
-    let fofa_results = match fofa::search(&fofa_query, 1, pages * page_size).await {
-        Ok(results) => {
-            if let Some(results_array) = results["results"].as_array() {
-                results_array
-                    .iter()
-                    .map(|r| {
-                        let mut result = r.clone();
-                        if let Value::Object(obj) = &mut result {
-                            obj.insert("platform".to_string(), Value::String("fofa".to_string()));
-                        }
-                        result
-                    })
-                    .collect()
-            } else {
-                Vec::new()
-            }
-        }
-        Err(_) => Vec::new(),
-    };
-    all_results.extend(fofa_results);
-
-    // Quake查询
-    let quake_results = match quake::search(&quake_query, 1, pages * page_size).await {
-        Ok(results) => {
-            if let Some(results_array) = results["results"].as_array() {
-                results_array
-                    .iter()
-                    .map(|r| {
-                        let mut result = r.clone();
-                        if let Value::Object(obj) = &mut result {
-                            obj.insert("platform".to_string(), Value::String("quake".to_string()));
-                        }
-                        result
-                    })
-                    .collect()
-            } else {
-                Vec::new()
-            }
-        }
-        Err(_) => Vec::new(),
-    };
-    all_results.extend(quake_results);
-
-    // DayDayMap查询
-    let daydaymap_results = match daydaymap::search(&daydaymap_query, 1, pages * page_size).await {
-        Ok(results) => {
-            if let Some(results_array) = results["results"].as_array() {
-                results_array
-                    .iter()
-                    .map(|r| {
-                        let mut result = r.clone();
-                        if let Value::Object(obj) = &mut result {
-                            obj.insert(
-                                "platform".to_string(),
-                                Value::String("daydaymap".to_string()),
-                            );
-                        }
-                        result
-                    })
-                    .collect()
-            } else {
-                Vec::new()
-            }
+    let capabilities = source.capabilities();
+    let cost = capabilities.cost;
+    let page_size = cost.fit_page_size(page_size.min(capabilities.max_page_size), budget);
+    // 预算连一条都不够时不发请求，部分平台会把 0 条当作默认条数照常计费
+    if page_size == 0 {
+        fetched.budget_reached = true;
+        return Ok(());
+    }
+    let cursor_paging = capabilities.pagination == Pagination::Cursor;
+    let mut cursor: Option<String> = None;
+
+    for page in 1..=pages.max(1) {
+        // 预算是硬上限：下一页可能超出时不再请求
+        if budget.is_some_and(|budget| fetched.spent + cost.page_cost(page_size) > budget) {
+            fetched.budget_reached = true;
+            break;
         }
-        Err(_) => Vec::new(),
-    };
-    all_results.extend(daydaymap_results);
 
-    // 导出结果到CSV
-    if all_results.is_empty() {
-        return Err("未找到任何结果".to_string());
+        let result = http::with_retry(
+            source.name(),
+            |_, e, delay| {
+                eprintln!(
+                    "[{}] 第 {} 页失败，{} 秒后重试: {}",
+                    source.name(),
+                    page,
+                    delay.as_secs(),
+                    e
+                )
+            },
+            || async {
+                if cursor_paging {
+                    source
+                        .search_cursor(query, cursor.as_deref(), page_size)
+                        .await
+                } else {
+                    source.search(query, page, page_size).await
+                }
+            },
+        )
+        .await;
+        let data = result.map_err(|e| format!("获取第 {} 页失败: {}", page, e))?;
+
+        let page_count = data.results.len();
+        fetched.total = Some(data.total);
+        fetched.spent += cost.charged(&data);
+        cursor = data.next_cursor;
+        fetched.assets.extend(data.results);
+        on_page(page, page_count, fetched);
+
+        let exhausted = page_count < page_size as usize
+            || (cursor_paging && cursor.is_none())
+            || fetched.assets.len() as u64 >= data.total;
+        if exhausted {
+            break;
+        }
     }
 
-    // 确保导出目录存在
-    let export_dir = Path::new(export_path);
-    if !export_dir.exists() {
-        std::fs::create_dir_all(export_dir).map_err(|e| e.to_string())?;
-    }
+    Ok(())
+}
 
-    // 生成导出文件名
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let export_file = export_dir.join(format!("all_platforms_export_{}.csv", timestamp));
+/// 导出全部资产时最多获取的页数（每页都会消耗查询额度）
+pub const EXPORT_ALL_MAX_PAGES: u32 = 100;
+
+/// 多平台导出中单个平台的结束状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PlatformStatus {
+    Success,
+    Failed,
+    Cancelled,
+    /// 查询语句无法转换到该平台，未发出请求
+    Skipped,
+}
 
-    // 写入CSV文件
-    let mut writer = csv::Writer::from_path(export_file).map_err(|e| e.to_string())?;
+/// 多平台导出中单个平台的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformOutcome {
+    pub platform: String,
+    pub status: PlatformStatus,
+    /// 转换后的查询语句，跳过的平台为空
+    pub query: Option<String>,
+    /// 取回的资产条数，失败或取消的平台为失败或取消前已取回的条数
+    pub returned: usize,
+    /// 平台返回的命中总数
+    pub total: Option<u64>,
+    pub error: Option<String>,
+}
 
-    // 获取所有可能的字段（用 BTreeSet 保证确定性排序）
-    let mut all_fields = std::collections::BTreeSet::new();
-    for result in &all_results {
-        if let Some(obj) = result.as_object() {
-            for key in obj.keys() {
-                all_fields.insert(key.clone());
-            }
-        }
-    }
+/// 多平台导出中单个平台的进度
+pub enum PlatformProgress<'a> {
+    /// 开始查询，附带转换后的查询语句
+    Started { query: &'a str },
+    /// 取回一页，附带累计结果
+    Page {
+        page: u32,
+        count: usize,
+        fetched: &'a FetchedPages,
+    },
+    /// 该平台已结束
+    Finished(&'a PlatformOutcome),
+}
 
-    // 写入CSV头
-    let fields: Vec<String> = all_fields.into_iter().collect();
-    writer.write_record(&fields).map_err(|e| e.to_string())?;
-
-    // 写入数据
-    for result in all_results {
-        let mut record = Vec::new();
-        for field in &fields {
-            if let Some(value) = result.get(field) {
-                if value.is_string() {
-                    record.push(value.as_str().unwrap_or("").to_string());
-                } else {
-                    record.push(value.to_string());
-                }
-            } else {
-                record.push(String::new());
-            }
-        }
-        writer.write_record(&record).map_err(|e| e.to_string())?;
-    }
+/// 多平台进度回调，参数为 (平台, 进度)，会在各平台的任务中并发调用
+pub type PlatformProgressFn = Arc<dyn Fn(&str, PlatformProgress<'_>) + Send + Sync>;
+
+/// 多平台合并导出的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllPlatformsExport {
+    /// 导出文件路径，所有平台都没有结果时为空
+    pub file_path: Option<String>,
+    pub summary: MergeSummary,
+    /// 各平台的结果，按注册顺序排列
+    pub platforms: Vec<PlatformOutcome>,
+}
 
-    writer.flush().map_err(|e| e.to_string())?;
+// 查询单个平台，直到取完、失败或被取消；失败或取消前已取回的页照常导出
+async fn fetch_platform(
+    source: &'static dyn AssetSource,
+    query: String,
+    pages: u32,
+    page_size: u32,
+    budget: Option<u64>,
+    mut cancel: CancelSignal,
+    progress: PlatformProgressFn,
+) -> (PlatformOutcome, Vec<Asset>) {
+    let platform = source.name();
+    progress(platform, PlatformProgress::Started { query: &query });
+
+    let mut fetched = FetchedPages::default();
+    let fetch = fetch_pages_into(
+        &mut fetched,
+        source,
+        &query,
+        pages,
+        page_size,
+        budget,
+        |page, count, fetched| {
+            progress(
+                platform,
+                PlatformProgress::Page {
+                    page,
+                    count,
+                    fetched,
+                },
+            )
+        },
+    );
+    let result = tokio::select! {
+        result = fetch => Some(result),
+        _ = cancel.cancelled() => None,
+    };
 
-    Ok(())
+    let (status, error) = match result {
+        Some(Ok(())) => (PlatformStatus::Success, None),
+        Some(Err(e)) => (PlatformStatus::Failed, Some(e)),
+        None => (PlatformStatus::Cancelled, None),
+    };
+    let outcome = PlatformOutcome {
+        platform: platform.to_string(),
+        status,
+        query: Some(query),
+        returned: fetched.assets.len(),
+        total: fetched.total,
+        error,
+    };
+    progress(platform, PlatformProgress::Finished(&outcome));
+    (outcome, fetched.assets)
 }
 
-// 为不同平台适配查询语句
-fn adapt_query_for_platform(
+// 并发导出所有平台的资产，同一资产在多个平台出现时合并为一条
+//
+// 每个平台在独立的任务中查询，各自受平台限流控制与额度预算 `budget` 约束，可通过
+// `{task_id}_{平台}` 单独取消；任一平台失败不影响其它平台，失败原因记录在该平台的结果中
+#[allow(clippy::too_many_arguments)]
+pub async fn export_all_platforms(
+    converter: &QueryConverter,
     query: &str,
-    platform: &str,
+    pages: u32,
+    page_size: u32,
     time_range: &str,
-    start_date: &Option<String>,
-    end_date: &Option<String>,
-) -> Result<String, String> {
-    // 解析查询语句中的条件
-    let mut conditions = parse_query_conditions(query, platform)?;
-
-    // 添加时间范围条件
-    if time_range != "all" {
-        match platform {
-            "hunter" => {
-                if time_range != "custom" {
-                    let days = time_range.replace("d", "");
-                    conditions.push(format!("time<=\"{}d\"", days));
-                } else if let (Some(start), Some(end)) = (start_date, end_date) {
-                    conditions.push(format!("time>=\"{}\" && time<=\"{}\"", start, end));
-                }
-            }
-            "fofa" => {
-                if time_range != "custom" {
-                    let days = time_range.replace("d", "");
-                    conditions.push(format!("before=\"{}d\"", days));
-                } else if let (Some(start), Some(end)) = (start_date, end_date) {
-                    conditions.push(format!("before=\"{}\" && after=\"{}\"", end, start));
-                }
-            }
-            "quake" => {
-                if time_range != "custom" {
-                    let days = time_range.replace("d", "");
-                    conditions.push(format!("time: [now-{}d TO now]", days));
-                } else if let (Some(start), Some(end)) = (start_date, end_date) {
-                    conditions.push(format!("time: [\"{start}\" TO \"{end}\"]"));
-                }
+    start_date: Option<String>,
+    end_date: Option<String>,
+    export_path: &str,
+    format: ExportFormat,
+    budget: Option<u64>,
+    task_id: &str,
+    progress: PlatformProgressFn,
+) -> Result<AllPlatformsExport, String> {
+    // 按各平台语法打分，取最可能的源平台
+    let detected = converter.detect_dialect(query);
+    let source_platform = detected
+        .first()
+        .map(|score| score.platform.as_str())
+        .ok_or_else(|| "无法识别查询语句的平台".to_string())?;
+    let time_range = TimeRange::from_options(time_range, &start_date, &end_date);
+
+    // 为每个平台创建适配的查询语句并启动查询任务，无法转换的平台记为跳过
+    let mut outcomes: Vec<Option<PlatformOutcome>> = Vec::new();
+    let mut tasks = JoinSet::new();
+    for (index, source) in all_sources().enumerate() {
+        outcomes.push(None);
+        match adapt_query_for_platform(
+            converter,
+            query,
+            source_platform,
+            source.name(),
+            time_range.as_ref(),
+        ) {
+            Ok(platform_query) => {
+                let sub_task = format!("{}_{}", task_id, source.name());
+                let cancel_signal = cancel::register(&sub_task);
+                let progress = progress.clone();
+                tasks.spawn(async move {
+                    let result = key_manager::sticky_task(
+                        &sub_task,
+                        fetch_platform(
+                            source,
+                            platform_query,
+                            pages,
+                            page_size,
+                            budget,
+                            cancel_signal,
+                            progress,
+                        ),
+                    )
+                    .await;
+                    cancel::finish(&sub_task);
+                    key_manager::release_task(source.name(), &sub_task);
+                    (index, result)
+                });
             }
-            "daydaymap" => {
-                if time_range != "custom" {
-                    let days = time_range.replace("d", "");
-                    conditions.push(format!("time:\"{}d\"", days));
-                } else if let (Some(start), Some(end)) = (start_date, end_date) {
-                    conditions.push(format!("time:[{start} TO {end}]"));
-                }
+            Err(e) => {
+                let outcome = PlatformOutcome {
+                    platform: source.name().to_string(),
+                    status: PlatformStatus::Skipped,
+                    query: None,
+                    returned: 0,
+                    total: None,
+                    error: Some(e),
+                };
+                progress(source.name(), PlatformProgress::Finished(&outcome));
+                outcomes[index] = Some(outcome);
             }
-            _ => return Err("不支持的平台".to_string()),
         }
     }
 
-    // 根据平台组合条件
-    let operator = match platform {
-        "hunter" => " && ",
-        "fofa" => " && ",
-        "quake" => " AND ",
-        "daydaymap" => " AND ",
-        _ => return Err("不支持的平台".to_string()),
-    };
-
-    Ok(conditions.join(operator))
-}
-
-// 解析查询语句中的条件
-fn parse_query_conditions(query: &str, target_platform: &str) -> Result<Vec<String>, String> {
-    // 识别当前查询语句的平台
-    let source_platform = detect_query_platform(query)?;
-
-    // 如果源平台和目标平台相同，直接返回原始查询
-    if source_platform == target_platform {
-        // 分割条件
-        let operator = match source_platform {
-            "hunter" => " && ",
-            "fofa" => " && ",
-            "quake" => " AND ",
-            "daydaymap" => " AND ",
-            _ => return Err("不支持的平台".to_string()),
-        };
-
-        return Ok(query
-            .split(operator)
-            .map(|s| s.trim().to_string())
-            .collect());
+    // 等待所有平台结束，结果已带有来源平台
+    let mut all_results: Vec<Asset> = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, (outcome, assets)) =
+            joined.map_err(|e| format!("平台查询任务异常退出: {}", e))?;
+        all_results.extend(assets);
+        outcomes[index] = Some(outcome);
+    }
+    let platforms: Vec<PlatformOutcome> = outcomes.into_iter().flatten().collect();
+
+    // 按 ip:port:protocol 合并各平台结果
+    let report = merge_assets(all_results, MergeOptions::default());
+    for engine in &report.summary.engines {
+        eprintln!(
+            "{}: 返回 {} 条，涉及 {} 个资产，独有 {} 个",
+            engine.platform, engine.returned, engine.assets, engine.unique
+        );
     }
 
-    // 解析查询条件
-    let mut conditions = Vec::new();
-
-    // 根据源平台的语法分割条件
-    let operator = match source_platform {
-        "hunter" => " && ",
-        "fofa" => " && ",
-        "quake" => " AND ",
-        "daydaymap" => " AND ",
-        _ => return Err("不支持的平台".to_string()),
-    };
-
-    for condition in query.split(operator) {
-        let condition = condition.trim();
+    // 没有任何结果时不生成文件，各平台的结果仍然返回
+    if report.assets.is_empty() {
+        return Ok(AllPlatformsExport {
+            file_path: None,
+            summary: report.summary,
+            platforms,
+        });
+    }
 
-        // 转换条件到目标平台的语法
-        let converted = convert_condition(condition, source_platform, target_platform)?;
-        if !converted.is_empty() {
-            conditions.push(converted);
-        }
+    // 生成导出文件名
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let mut export_file = ExportFile::create(
+        Path::new(export_path).join(format!(
+            "all_platforms_export_{}.{}",
+            timestamp,
+            format.extension()
+        )),
+        format,
+    )?;
+
+    for chunk in report.assets.chunks(1000) {
+        let records: Vec<Asset> = chunk.iter().map(|merged| merged.export_record()).collect();
+        export_file.write_page(&records)?;
     }
+    let file_path = export_file.persist()?;
 
-    Ok(conditions)
+    Ok(AllPlatformsExport {
+        file_path: Some(file_path.to_string_lossy().to_string()),
+        summary: report.summary,
+        platforms,
+    })
 }
 
-// 检测查询语句的平台
-fn detect_query_platform(query: &str) -> Result<&str, String> {
-    if query.contains("domain.suffix=") || query.contains("web.title=") {
-        Ok("hunter")
-    } else if query.contains("domain=") && query.contains("&&") {
-        Ok("fofa")
-    } else if query.contains("domain:") && query.contains("AND") {
-        Ok("quake")
-    } else if query.contains("domain:\"") && query.contains("AND") {
-        Ok("daydaymap")
-    } else {
-        // 默认假设为Hunter语法
-        Ok("hunter")
+// 为不同平台适配查询语句：字段、取值与时间范围均按 config.json 中的规则转换
+pub(crate) fn adapt_query_for_platform(
+    converter: &QueryConverter,
+    query: &str,
+    source_platform: &str,
+    platform: &str,
+    time_range: Option<&TimeRange>,
+) -> Result<String, String> {
+    let conversion = converter
+        .convert(query, source_platform, platform)
+        .map_err(|e| format!("{}平台查询转换失败: {}", platform, e))?;
+    for warning in &conversion.warnings {
+        eprintln!("{}: {} {}", platform, warning.predicate, warning.message);
     }
-}
 
-// 转换条件到目标平台的语法
-fn convert_condition(
-    condition: &str,
-    from_platform: &str,
-    to_platform: &str,
-) -> Result<String, String> {
-    // 解析条件类型
-    if condition.is_empty() {
-        return Ok(String::new());
+    match time_range {
+        Some(time_range) => converter
+            .apply_time_range(&conversion.query, platform, time_range)
+            .map_err(|e| format!("{}平台时间范围转换失败: {}", platform, e)),
+        None => Ok(conversion.query),
     }
+}
 
-    // 域名条件转换
-    if condition.contains("domain") {
-        match (from_platform, to_platform) {
-            ("hunter", "fofa") => {
-                if condition.starts_with("domain.suffix=") {
-                    return Ok(condition.replace("domain.suffix=", "domain="));
-                }
-            }
-            ("hunter", "quake") => {
-                if condition.starts_with("domain.suffix=") {
-                    return Ok(condition.replace("domain.suffix=", "domain: "));
-                }
-            }
-            ("hunter", "daydaymap") => {
-                if condition.starts_with("domain.suffix=") {
-                    return Ok(condition.replace("domain.suffix=", "domain:"));
-                }
-            }
-            ("fofa", "hunter") => {
-                if condition.starts_with("domain=") {
-                    return Ok(condition.replace("domain=", "domain.suffix="));
-                }
-            }
-            ("fofa", "quake") => {
-                if condition.starts_with("domain=") {
-                    return Ok(condition.replace("domain=", "domain: "));
-                }
-            }
-            ("fofa", "daydaymap") => {
-                if condition.starts_with("domain=") {
-                    return Ok(condition.replace("domain=", "domain:"));
-                }
-            }
-            ("quake", "hunter") => {
-                if condition.starts_with("domain:") {
-                    return Ok(condition.replace("domain:", "domain.suffix="));
-                }
-            }
-            ("quake", "fofa") => {
-                if condition.starts_with("domain:") {
-                    return Ok(condition.replace("domain:", "domain="));
-                }
-            }
-            ("quake", "daydaymap") => {
-                if condition.starts_with("domain:") {
-                    // 已经是相似的语法，只需要调整引号
-                    return Ok(condition.replace("domain: ", "domain:"));
-                }
-            }
-            ("daydaymap", "hunter") => {
-                if condition.starts_with("domain:") {
-                    return Ok(condition.replace("domain:", "domain.suffix="));
-                }
-            }
-            ("daydaymap", "fofa") => {
-                if condition.starts_with("domain:") {
-                    return Ok(condition.replace("domain:", "domain="));
-                }
-            }
-            ("daydaymap", "quake") => {
-                if condition.starts_with("domain:") {
-                    return Ok(condition.replace("domain:", "domain: "));
-                }
-            }
-            _ => {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ApiError;
+    use crate::ApiKeyValidationResult;
+    use async_trait::async_trait;
+    use cost::{Billing, CostModel};
+    use serde_json::Value;
+    use source::SourceCapabilities;
+
+    // 第 1 页返回两条资产，之后的页返回不可重试的错误
+    struct FailsAfterFirstPage;
+
+    #[async_trait]
+    impl AssetSource for FailsAfterFirstPage {
+        fn name(&self) -> &'static str {
+            "fails-after-first-page"
         }
-    }
 
-    // IP条件转换
-    if condition.contains("ip") {
-        match (from_platform, to_platform) {
-            ("hunter", "fofa") => {
-                if condition.starts_with("ip=") {
-                    return Ok(condition.to_string());
-                } else if condition.starts_with("ip.province=") {
-                    return Ok(condition.replace("ip.province=", "region="));
-                } else if condition.starts_with("ip.city=") {
-                    return Ok(condition.replace("ip.city=", "city="));
-                }
-            }
-            ("hunter", "quake") => {
-                if condition.starts_with("ip=") {
-                    return Ok(condition.replace("ip=", "ip: "));
-                } else if condition.starts_with("ip.province=") {
-                    return Ok(condition.replace("ip.province=", "province: "));
-                } else if condition.starts_with("ip.city=") {
-                    return Ok(condition.replace("ip.city=", "city: "));
-                }
-            }
-            ("hunter", "daydaymap") => {
-                if condition.starts_with("ip=") {
-                    return Ok(condition.replace("ip=", "ip:"));
-                } else if condition.starts_with("ip.province=") {
-                    return Ok(condition.replace("ip.province=", "province:"));
-                } else if condition.starts_with("ip.city=") {
-                    return Ok(condition.replace("ip.city=", "city:"));
-                }
+        fn capabilities(&self) -> SourceCapabilities {
+            SourceCapabilities {
+                display_name: "Test",
+                requires_email: false,
+                max_page_size: 2,
+                pagination: Pagination::PageNumber,
+                cost: CostModel {
+                    unit: "条",
+                    billing: Billing::PerResult,
+                    credits: 1,
+                },
             }
-            // 其他平台的转换类似...
-            _ => {}
         }
-    }
 
-    // 标题条件转换
-    if condition.contains("title") {
-        match (from_platform, to_platform) {
-            ("hunter", "fofa") => {
-                if condition.starts_with("web.title=") {
-                    return Ok(condition.replace("web.title=", "title="));
-                }
-            }
-            ("hunter", "quake") => {
-                if condition.starts_with("web.title=") {
-                    return Ok(condition.replace("web.title=", "title: "));
-                }
+        async fn search(
+            &self,
+            _query: &str,
+            page: u32,
+            _size: u32,
+        ) -> Result<SearchPage, ApiError> {
+            if page > 1 {
+                return Err(ApiError::BadQuery("查询语法错误".to_string()));
             }
-            ("hunter", "daydaymap") => {
-                if condition.starts_with("web.title=") {
-                    return Ok(condition.replace("web.title=", "title:"));
-                }
-            }
-            // 其他平台的转换类似...
-            _ => {}
+            let asset = |ip: &str| Asset {
+                ip: ip.to_string(),
+                ..Asset::new(self.name(), Value::Null)
+            };
+            Ok(SearchPage {
+                total: 10,
+                results: vec![asset("1.1.1.1"), asset("2.2.2.2")],
+                ..Default::default()
+            })
         }
-    }
 
-    // 端口条件转换
-    if condition.contains("port") {
-        match (from_platform, to_platform) {
-            ("hunter", "fofa") => {
-                if condition.starts_with("port=") {
-                    return Ok(condition.to_string());
-                }
-            }
-            ("hunter", "quake") => {
-                if condition.starts_with("port=") {
-                    let port = condition.replace("port=", "").replace("\"", "");
-                    return Ok(format!("port: {}", port));
-                }
-            }
-            ("hunter", "daydaymap") => {
-                if condition.starts_with("port=") {
-                    return Ok(condition.replace("port=", "port:"));
-                }
-            }
-            // 其他平台的转换类似...
-            _ => {}
+        async fn validate_api_key(
+            &self,
+            _api_key: &str,
+            _email: Option<&str>,
+        ) -> Result<ApiKeyValidationResult, String> {
+            Err("不支持".to_string())
         }
     }
 
-    // 如果无法转换，返回空字符串
-    Ok(String::new())
+    #[tokio::test]
+    async fn test_failed_platform_keeps_fetched_pages() {
+        static SOURCE: FailsAfterFirstPage = FailsAfterFirstPage;
+        let progress: PlatformProgressFn = Arc::new(|_, _| {});
+        let signal = cancel::register("partial-test_fails-after-first-page");
+
+        let (outcome, assets) =
+            fetch_platform(&SOURCE, "x".to_string(), 5, 2, None, signal, progress).await;
+        cancel::finish("partial-test_fails-after-first-page");
+
+        assert_eq!(outcome.status, PlatformStatus::Failed);
+        assert_eq!(outcome.returned, 2);
+        assert_eq!(outcome.total, Some(10));
+        assert!(outcome.error.unwrap().contains("第 2 页"));
+        assert_eq!(assets.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_budget_below_one_result_sends_no_request() {
+        // 每条消耗 1，预算为 0 时连第 1 页都不请求
+        let fetched = fetch_pages(&FailsAfterFirstPage, "x", 5, 2, Some(0), |_, _, _| {
+            panic!("不应请求任何页")
+        })
+        .await
+        .unwrap();
+        assert!(fetched.budget_reached);
+        assert!(fetched.assets.is_empty());
+        assert_eq!(fetched.spent, 0);
+    }
 }
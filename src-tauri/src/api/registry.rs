@@ -0,0 +1,65 @@
+use super::source::AssetSource;
+use super::{censys, daydaymap, fofa, hunter, quake, shodan, zoomeye};
+use once_cell::sync::Lazy;
+
+/// 已注册的数据源（顺序即多平台导出时的查询顺序）
+static SOURCES: Lazy<Vec<Box<dyn AssetSource>>> = Lazy::new(|| {
+    vec![
+        Box::new(hunter::HunterSource),
+        Box::new(fofa::FofaSource),
+        Box::new(quake::QuakeSource),
+        Box::new(daydaymap::DayDayMapSource),
+        Box::new(shodan::ShodanSource),
+        Box::new(zoomeye::ZoomEyeSource),
+        Box::new(censys::CensysSource),
+    ]
+});
+
+/// 按平台名称获取数据源
+pub fn get_source(platform: &str) -> Result<&'static dyn AssetSource, String> {
+    SOURCES
+        .iter()
+        .find(|source| source.name() == platform)
+        .map(|source| source.as_ref())
+        .ok_or_else(|| "不支持的平台".to_string())
+}
+
+/// 获取所有已注册的数据源
+pub fn all_sources() -> impl Iterator<Item = &'static dyn AssetSource> {
+    SOURCES.iter().map(|source| source.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lookup_by_name() {
+        for name in [
+            "hunter",
+            "fofa",
+            "quake",
+            "daydaymap",
+            "shodan",
+            "zoomeye",
+            "censys",
+        ] {
+            let source = get_source(name).expect("platform should be registered");
+            assert_eq!(source.name(), name);
+        }
+    }
+
+    #[test]
+    fn test_registry_unknown_platform() {
+        assert_eq!(get_source("unknown").err().unwrap(), "不支持的平台");
+    }
+
+    #[test]
+    fn test_registry_names_are_unique() {
+        let mut names: Vec<&str> = all_sources().map(|s| s.name()).collect();
+        let total = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), total);
+    }
+}
@@ -0,0 +1,526 @@
+use super::asset::{self, port_field, str_field, Asset, Geo, SearchPage};
+use super::cost::{Billing, CostModel};
+use super::http;
+use super::key_manager;
+use super::source::{AssetSource, Pagination, SourceCapabilities};
+use crate::config;
+use crate::error::{retry_after_secs, ApiError};
+use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const API_BASE: &str = "https://api.shodan.io";
+
+// Shodan 每页固定返回 100 条，无法通过参数调整
+const SHODAN_PAGE_SIZE: u32 = 100;
+
+// 最近取回的 Shodan 页的缓存上限与有效期：每页条数小于 100 时，连续翻页会落在同一个
+// Shodan 页上，缓存后每个 Shodan 页只请求（计费）一次
+const PAGE_CACHE_SIZE: usize = 10;
+const PAGE_CACHE_TTL: Duration = Duration::from_secs(600);
+
+// 已取回的一个 Shodan 页
+struct CachedPage {
+    base_url: String,
+    query: String,
+    page: u32,
+    fetched_at: Instant,
+    total: u64,
+    matches: Vec<Value>,
+}
+
+static PAGE_CACHE: Lazy<Mutex<VecDeque<CachedPage>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+// 从缓存取出未过期的 Shodan 页，顺带清掉过期的页
+fn cached_page(base_url: &str, query: &str, page: u32) -> Option<(u64, Vec<Value>)> {
+    let mut cache = PAGE_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.retain(|cached| cached.fetched_at.elapsed() < PAGE_CACHE_TTL);
+    cache
+        .iter()
+        .find(|cached| cached.base_url == base_url && cached.query == query && cached.page == page)
+        .map(|cached| (cached.total, cached.matches.clone()))
+}
+
+// 缓存新取回的 Shodan 页，超出上限时淘汰最早的页
+fn cache_page(base_url: &str, query: &str, page: u32, total: u64, matches: Vec<Value>) {
+    let mut cache = PAGE_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    cache.retain(|cached| {
+        !(cached.base_url == base_url && cached.query == query && cached.page == page)
+    });
+    cache.push_back(CachedPage {
+        base_url: base_url.to_string(),
+        query: query.to_string(),
+        page,
+        fetched_at: Instant::now(),
+        total,
+        matches,
+    });
+    while cache.len() > PAGE_CACHE_SIZE {
+        cache.pop_front();
+    }
+}
+
+// 解析 Shodan 的错误响应；积分不足时 Shodan 同样返回 401，需按提示文字区分
+fn api_error(status: reqwest::StatusCode, retry_after: Option<u64>, body: &str) -> ApiError {
+    let detail = serde_json::from_str::<Value>(body)
+        .ok()
+        .and_then(|json| json["error"].as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| body.trim().to_string());
+
+    let lower = detail.to_lowercase();
+    if lower.contains("credits") || lower.contains("upgrade your api plan") {
+        ApiError::QuotaExhausted(format!("查询积分不足: {}", detail))
+    } else if status == reqwest::StatusCode::UNAUTHORIZED
+        || status == reqwest::StatusCode::FORBIDDEN
+    {
+        ApiError::Auth(format!("API密钥无效: {}", detail))
+    } else {
+        ApiError::from_status(
+            status.as_u16(),
+            retry_after,
+            format!("API返回错误({}): {}", status, detail),
+        )
+    }
+}
+
+// 使用单个API key请求一页（Shodan 页码）
+async fn search_with_key(
+    base_url: &str,
+    api_key: &str,
+    query: &str,
+    page: u32,
+) -> Result<Value, ApiError> {
+    let url = format!("{}/shodan/host/search", base_url);
+    let params = [
+        ("key", api_key.to_string()),
+        ("query", query.to_string()),
+        ("page", page.to_string()),
+        ("minify", "false".to_string()),
+    ];
+
+    // 发送请求
+    let client = crate::create_http_client().map_err(ApiError::Config)?;
+    let response = http::send("shodan", client.get(&url).query(&params)).await?;
+
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| ApiError::Network(format!("读取响应失败: {}", e)))?;
+
+    // 检查响应状态
+    if !status.is_success() {
+        return Err(api_error(status, retry_after, &response_text));
+    }
+
+    let response_json: Value = serde_json::from_str(&response_text)
+        .map_err(|e| ApiError::Parse(format!("解析JSON失败: {}", e)))?;
+
+    if let Some(error) = response_json["error"].as_str() {
+        return Err(api_error(status, retry_after, error));
+    }
+
+    Ok(response_json)
+}
+
+// 按 page/page_size 取数据，必要时跨越多个 Shodan 页；已取回的 Shodan 页直接用缓存，
+// consume_quota 记录本次实际发出的请求数（即消耗的查询积分）
+async fn search_page_with_key(
+    base_url: &str,
+    api_key: &str,
+    query: &str,
+    page: u32,
+    page_size: u32,
+) -> Result<SearchPage, ApiError> {
+    let offset = page.saturating_sub(1) * page_size;
+    let mut shodan_page = offset / SHODAN_PAGE_SIZE + 1;
+    let mut skip = (offset % SHODAN_PAGE_SIZE) as usize;
+    let mut result = SearchPage::default();
+    let mut requests = 0;
+
+    loop {
+        let (total, matches) = match cached_page(base_url, query, shodan_page) {
+            Some(cached) => cached,
+            None => {
+                let response_json = search_with_key(base_url, api_key, query, shodan_page).await?;
+                requests += 1;
+                let total = response_json["total"].as_u64().unwrap_or(0);
+                let matches = response_json["matches"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                cache_page(base_url, query, shodan_page, total, matches.clone());
+                (total, matches)
+            }
+        };
+        result.total = total;
+        let fetched = matches.len();
+        result
+            .results
+            .extend(matches.iter().skip(skip).map(to_asset));
+        skip = 0;
+
+        let reached_end = fetched < SHODAN_PAGE_SIZE as usize
+            || u64::from(shodan_page * SHODAN_PAGE_SIZE) >= result.total;
+        if result.results.len() >= page_size as usize || reached_end {
+            break;
+        }
+        shodan_page += 1;
+    }
+
+    result.results.truncate(page_size as usize);
+    result.consume_quota = Some(format!("消耗查询积分：{}", requests));
+    Ok(result)
+}
+
+// 搜索资产
+pub async fn search(query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+    // 获取所有API密钥
+    let api_keys = config::get_all_api_keys("shodan").map_err(ApiError::Config)?;
+
+    if api_keys.is_empty() {
+        return Err(ApiError::Config("未配置Shodan API密钥".to_string()));
+    }
+
+    // Clone data for the closure
+    let query = query.to_string();
+
+    // 使用key_manager进行智能轮询
+    key_manager::execute_with_key_rotation("shodan", &api_keys, |api_key| {
+        let query = query.clone();
+        let api_key = api_key.to_string();
+        async move { search_page_with_key(API_BASE, &api_key, &query, page, page_size).await }
+    })
+    .await
+}
+
+// Shodan 的 _shodan.module 形如 https、http-simple-new、ssh，取协议部分
+fn module_protocol(module: &str) -> String {
+    let module = module.to_lowercase();
+    if module.starts_with("https") {
+        "https".to_string()
+    } else if module.starts_with("http") {
+        "http".to_string()
+    } else {
+        module.split('-').next().unwrap_or_default().to_string()
+    }
+}
+
+// 将 Shodan 返回的单条 banner 映射为资产
+fn to_asset(item: &Value) -> Asset {
+    let http = &item["http"];
+    let location = &item["location"];
+    let first = |value: &Value| {
+        value
+            .as_array()
+            .and_then(|values| values.first())
+            .map(str_field)
+            .unwrap_or_default()
+    };
+
+    let mut protocol = module_protocol(&str_field(&item["_shodan"]["module"]));
+    if protocol == "http" && !item["ssl"].is_null() {
+        protocol = "https".to_string();
+    }
+
+    let product = str_field(&item["product"]);
+    let version = str_field(&item["version"]);
+    let mut fingerprints = Vec::new();
+    if !product.is_empty() {
+        fingerprints.push(if version.is_empty() {
+            product
+        } else {
+            format!("{}:{}", product, version)
+        });
+    }
+
+    Asset {
+        ip: str_field(&item["ip_str"]),
+        port: port_field(&item["port"]),
+        protocol,
+        host: first(&item["hostnames"]),
+        domain: first(&item["domains"]),
+        title: str_field(&http["title"]),
+        server: str_field(&http["server"]),
+        status_code: asset::number_field(&http["status"]),
+        geo: Geo {
+            country: str_field(&location["country_name"]),
+            province: str_field(&location["region_code"]),
+            city: str_field(&location["city"]),
+            isp: str_field(&item["isp"]),
+        },
+        asn: asset::number_field(&item["asn"]),
+        as_org: str_field(&item["org"]),
+        os: str_field(&item["os"]),
+        organization: str_field(&item["org"]),
+        certificate: str_field(&item["ssl"]["cert"]["subject"]["CN"]),
+        fingerprints,
+        last_seen: Some(str_field(&item["timestamp"])).filter(|s| !s.is_empty()),
+        ..Asset::new("shodan", item.clone())
+    }
+    .finalize()
+}
+
+// 通过账户信息接口验证API密钥并查询剩余积分
+async fn validate_api_key_at(
+    base_url: &str,
+    api_key: &str,
+) -> Result<ApiKeyValidationResult, String> {
+    let url = format!("{}/api-info", base_url);
+
+    // 发送请求
+    let client = crate::create_http_client()?;
+    let response = http::send("shodan", client.get(&url).query(&[("key", api_key)])).await?;
+
+    let status = response.status();
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| format!("读取响应失败: {}", e))?;
+
+    if !status.is_success() {
+        return Ok(ApiKeyValidationResult {
+            valid: false,
+            message: Some(api_error(status, None, &response_text).to_string()),
+            quota: None,
+            remaining: None,
+        });
+    }
+
+    let response_json: Value =
+        serde_json::from_str(&response_text).map_err(|e| format!("解析JSON失败: {}", e))?;
+
+    // 搜索消耗查询积分；字段缺失时额度未知，不能当作 0
+    let query_credits = response_json["query_credits"].as_i64();
+    let scan_credits = response_json["scan_credits"].as_i64().unwrap_or(0);
+    let plan = response_json["plan"].as_str().unwrap_or("未知");
+
+    Ok(ApiKeyValidationResult {
+        valid: true,
+        message: Some(format!("API密钥验证成功 (套餐: {})", plan)),
+        quota: Some(format!(
+            "查询积分: {}, 扫描积分: {}",
+            query_credits.map_or("未知".to_string(), |credits| credits.to_string()),
+            scan_credits
+        )),
+        remaining: query_credits.map(|credits| credits.max(0) as u64),
+    })
+}
+
+// 验证API密钥
+pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, String> {
+    validate_api_key_at(API_BASE, api_key).await
+}
+
+// Shodan 数据源
+pub struct ShodanSource;
+
+#[async_trait]
+impl AssetSource for ShodanSource {
+    fn name(&self) -> &'static str {
+        "shodan"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            display_name: "Shodan",
+            requires_email: false,
+            max_page_size: SHODAN_PAGE_SIZE,
+            pagination: Pagination::PageNumber,
+            cost: CostModel {
+                unit: "查询积分",
+                billing: Billing::PerRequest,
+                credits: 1,
+            },
+        }
+    }
+
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+        search(query, page, page_size).await
+    }
+
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        _email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String> {
+        validate_api_key(api_key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn banner(ip: &str, port: u16) -> Value {
+        json!({
+            "ip_str": ip,
+            "port": port,
+            "hostnames": ["www.example.com"],
+            "domains": ["example.com"],
+            "org": "Example Org",
+            "isp": "Example ISP",
+            "asn": "AS15169",
+            "product": "nginx",
+            "version": "1.18.0",
+            "timestamp": "2024-05-01T08:00:00.000000",
+            "location": {"country_name": "United States", "region_code": "CA", "city": "Mountain View"},
+            "http": {"title": "Example", "server": "nginx/1.18.0", "status": 200},
+            "ssl": {"cert": {"subject": {"CN": "example.com"}}},
+            "_shodan": {"module": "https"}
+        })
+    }
+
+    fn matches(count: usize) -> Vec<Value> {
+        (0..count)
+            .map(|i| banner(&format!("10.0.{}.{}", i / 256, i % 256), 443))
+            .collect()
+    }
+
+    #[test]
+    fn test_to_asset_maps_banner_fields() {
+        let asset = to_asset(&banner("8.8.8.8", 443));
+        assert_eq!(asset.ip, "8.8.8.8");
+        assert_eq!(asset.port, Some(443));
+        assert_eq!(asset.protocol, "https");
+        assert_eq!(asset.host, "www.example.com");
+        assert_eq!(asset.url, "https://www.example.com");
+        assert_eq!(asset.asn, Some(15169));
+        assert_eq!(asset.status_code, Some(200));
+        assert_eq!(asset.fingerprints, vec!["nginx:1.18.0"]);
+        assert_eq!(asset.geo.country, "United States");
+        assert_eq!(asset.platform, "shodan");
+    }
+
+    #[test]
+    fn test_module_protocol() {
+        assert_eq!(module_protocol("http-simple-new"), "http");
+        assert_eq!(module_protocol("https-simple-new"), "https");
+        assert_eq!(module_protocol("ssh"), "ssh");
+        assert_eq!(module_protocol(""), "");
+    }
+
+    #[tokio::test]
+    async fn test_search_sends_key_query_and_page() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/search"))
+            .and(query_param("key", "test-key"))
+            .and(query_param("query", "port:443"))
+            .and(query_param("page", "1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"total": 2, "matches": matches(2)})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let page = search_page_with_key(&server.uri(), "test-key", "port:443", 1, 100)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].ip, "10.0.0.0");
+    }
+
+    #[tokio::test]
+    async fn test_search_maps_small_pages_onto_shodan_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/search"))
+            .and(query_param("page", "1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"total": 250, "matches": matches(100)})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/search"))
+            .and(query_param("page", "2"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"total": 250, "matches": matches(100)})),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // 第 3 页（每页 40 条）对应偏移 80..120，跨越 Shodan 第 1、2 页
+        let page = search_page_with_key(&server.uri(), "k", "nginx", 3, 40)
+            .await
+            .unwrap();
+        assert_eq!(page.results.len(), 40);
+        assert_eq!(page.results[0].ip, "10.0.0.80");
+        assert_eq!(page.results[20].ip, "10.0.0.0");
+        assert_eq!(page.consume_quota.as_deref(), Some("消耗查询积分：2"));
+
+        // 落在已取回的 Shodan 页上的页不再请求，也不消耗积分
+        for n in [1, 2, 4, 5] {
+            let page = search_page_with_key(&server.uri(), "k", "nginx", n, 40)
+                .await
+                .unwrap();
+            assert_eq!(page.results.len(), 40);
+            assert_eq!(page.consume_quota.as_deref(), Some("消耗查询积分：0"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_reports_exhausted_credits() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/shodan/host/search"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "error": "Insufficient query credits, please upgrade your API plan or wait for the monthly limit to reset"
+            })))
+            .mount(&server)
+            .await;
+
+        let err = search_page_with_key(&server.uri(), "k", "apache", 1, 100)
+            .await
+            .unwrap_err();
+        assert!(err.is_key_exhausted(), "{:?}", err);
+        assert!(err.to_string().contains("积分"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_validate_api_key_reads_credits() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api-info"))
+            .and(query_param("key", "good"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "plan": "dev",
+                "query_credits": 87,
+                "scan_credits": 100
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api-info"))
+            .and(query_param("key", "bad"))
+            .respond_with(
+                ResponseTemplate::new(401).set_body_json(json!({"error": "Invalid API key"})),
+            )
+            .mount(&server)
+            .await;
+
+        let ok = validate_api_key_at(&server.uri(), "good").await.unwrap();
+        assert!(ok.valid);
+        assert_eq!(ok.quota.as_deref(), Some("查询积分: 87, 扫描积分: 100"));
+
+        let bad = validate_api_key_at(&server.uri(), "bad").await.unwrap();
+        assert!(!bad.valid);
+        assert!(bad.message.unwrap().contains("API密钥无效"));
+    }
+}
@@ -0,0 +1,75 @@
+use super::asset::SearchPage;
+use super::cost::CostModel;
+use crate::error::ApiError;
+use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
+use serde::Serialize;
+
+/// 分页方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Pagination {
+    /// 按页码翻页
+    PageNumber,
+    /// 按服务端返回的游标翻页（见 [`AssetSource::search_cursor`]）
+    Cursor,
+}
+
+/// 数据源能力描述
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceCapabilities {
+    /// 界面与提示信息中使用的平台名称
+    pub display_name: &'static str,
+    /// 密钥是否需要配合账号使用（如 FOFA 的邮箱）
+    pub requires_email: bool,
+    /// 单次请求允许的最大条数
+    pub max_page_size: u32,
+    /// 分页方式
+    pub pagination: Pagination,
+    /// 额度计费模型
+    pub cost: CostModel,
+}
+
+/// 资产测绘数据源
+///
+/// 每个平台模块提供一个实现，并在 [`super::registry`] 中注册；
+/// Tauri 命令按平台名称从注册表中取出数据源，不再逐个平台分发。
+#[async_trait]
+pub trait AssetSource: Send + Sync {
+    /// 平台标识（与前端、配置文件中使用的名称一致）
+    fn name(&self) -> &'static str;
+
+    /// 平台能力
+    fn capabilities(&self) -> SourceCapabilities;
+
+    /// 搜索资产，结果已映射为归一化的 [`super::asset::Asset`]
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError>;
+
+    /// 按游标获取一页，`cursor` 为空时从第一页开始，下一页游标见 [`SearchPage::next_cursor`]
+    ///
+    /// 仅 [`Pagination::Cursor`] 数据源需要实现。
+    async fn search_cursor(
+        &self,
+        _query: &str,
+        _cursor: Option<&str>,
+        _page_size: u32,
+    ) -> Result<SearchPage, ApiError> {
+        Err(ApiError::BadQuery(format!(
+            "{}平台不支持游标分页",
+            self.capabilities().display_name
+        )))
+    }
+
+    /// 密钥在轮询状态（[`super::key_manager`]）中的标识，需要账号的平台把账号一并编码进去
+    fn rotation_key(&self, api_key: &str, _email: &str) -> String {
+        api_key.to_string()
+    }
+
+    /// 验证API密钥，`email` 仅对需要账号的平台有效
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String>;
+}
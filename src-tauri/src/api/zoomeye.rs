@@ -0,0 +1,412 @@
+use super::asset::{
+    normalize_protocol, number_field, port_field, str_field, Asset, Geo, SearchPage,
+};
+use super::cost::{Billing, CostModel};
+use super::http;
+use super::key_manager;
+use super::source::{AssetSource, Pagination, SourceCapabilities};
+use crate::config;
+use crate::error::{retry_after_secs, ApiError};
+use crate::ApiKeyValidationResult;
+use async_trait::async_trait;
+use base64::{engine::general_purpose, Engine as _};
+
+use serde_json::{json, Value};
+
+const API_BASE: &str = "https://api.zoomeye.ai";
+
+// ZoomEye 成功响应码
+const CODE_SUCCESS: i64 = 60000;
+
+// 请求的返回字段
+const RESULT_FIELDS: &str = "ip,port,domain,hostname,url,title,os,product,protocol,service,\
+country.name,province.name,city.name,isp.name,organization.name,asn,update_time";
+
+// 解析 ZoomEye 的错误响应，HTTP 状态正常时按返回码的提示文字归类
+fn api_error(status: reqwest::StatusCode, retry_after: Option<u64>, body: &Value) -> ApiError {
+    let detail = body["message"]
+        .as_str()
+        .or_else(|| body["error"].as_str())
+        .unwrap_or("未知错误")
+        .to_string();
+
+    let lower = detail.to_lowercase();
+    if status == reqwest::StatusCode::PAYMENT_REQUIRED
+        || lower.contains("credits")
+        || lower.contains("quota")
+    {
+        ApiError::QuotaExhausted(format!("查询积分不足: {}", detail))
+    } else if status == reqwest::StatusCode::UNAUTHORIZED
+        || status == reqwest::StatusCode::FORBIDDEN
+        || lower.contains("login_required")
+    {
+        ApiError::Auth(format!("API密钥无效: {}", detail))
+    } else if status.is_success() {
+        ApiError::from_platform_message(format!("API返回错误({}): {}", status, detail))
+    } else {
+        ApiError::from_status(
+            status.as_u16(),
+            retry_after,
+            format!("API返回错误({}): {}", status, detail),
+        )
+    }
+}
+
+// 发送 POST 请求并校验 ZoomEye 返回码
+async fn post_with_key(
+    base_url: &str,
+    path: &str,
+    api_key: &str,
+    body: &Value,
+) -> Result<Value, ApiError> {
+    let url = format!("{}{}", base_url, path);
+
+    // 发送请求
+    let client = crate::create_http_client().map_err(ApiError::Config)?;
+    let response = http::send(
+        "zoomeye",
+        client
+            .post(&url)
+            .header("API-KEY", api_key)
+            .header("Content-Type", "application/json")
+            .json(body),
+    )
+    .await?;
+
+    let status = response.status();
+    let retry_after = retry_after_secs(response.headers());
+    let response_text = response
+        .text()
+        .await
+        .map_err(|e| ApiError::Network(format!("读取响应失败: {}", e)))?;
+    let response_json: Value = serde_json::from_str(&response_text)
+        .unwrap_or_else(|_| json!({ "message": response_text.trim() }));
+
+    if !status.is_success() || response_json["code"].as_i64() != Some(CODE_SUCCESS) {
+        return Err(api_error(status, retry_after, &response_json));
+    }
+
+    Ok(response_json)
+}
+
+// 使用单个API key进行搜索
+async fn search_with_key(
+    base_url: &str,
+    api_key: &str,
+    query: &str,
+    page: u32,
+    page_size: u32,
+) -> Result<SearchPage, ApiError> {
+    // 查询语句需要 Base64 编码
+    let request_body = json!({
+        "qbase64": general_purpose::STANDARD.encode(query.as_bytes()),
+        "page": page,
+        "pagesize": page_size,
+        "fields": RESULT_FIELDS,
+    });
+
+    let response_json = post_with_key(base_url, "/v2/search", api_key, &request_body).await?;
+
+    let results = response_json["data"]
+        .as_array()
+        .map(|data| data.iter().map(to_asset).collect())
+        .unwrap_or_default();
+
+    Ok(SearchPage {
+        total: response_json["total"].as_u64().unwrap_or(0),
+        results,
+        ..Default::default()
+    })
+}
+
+// 搜索资产
+pub async fn search(query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+    // 获取所有API密钥
+    let api_keys = config::get_all_api_keys("zoomeye").map_err(ApiError::Config)?;
+
+    if api_keys.is_empty() {
+        return Err(ApiError::Config("未配置ZoomEye API密钥".to_string()));
+    }
+
+    // Clone data for the closure
+    let query = query.to_string();
+
+    // 使用key_manager进行智能轮询
+    key_manager::execute_with_key_rotation("zoomeye", &api_keys, |api_key| {
+        let query = query.clone();
+        let api_key = api_key.to_string();
+        async move { search_with_key(API_BASE, &api_key, &query, page, page_size).await }
+    })
+    .await
+}
+
+// 读取 ZoomEye 的字段，兼容 "country.name" 扁平键与嵌套对象两种返回
+fn field<'a>(item: &'a Value, key: &str) -> &'a Value {
+    match item.get(key) {
+        Some(value) => value,
+        None => key.split('.').fold(item, |value, part| &value[part]),
+    }
+}
+
+// 标题可能是字符串或字符串数组
+fn title_field(value: &Value) -> String {
+    match value {
+        Value::Array(titles) => titles
+            .iter()
+            .map(str_field)
+            .find(|t| !t.is_empty())
+            .unwrap_or_default(),
+        other => str_field(other),
+    }
+}
+
+// 将 ZoomEye 返回的单条记录映射为资产
+fn to_asset(item: &Value) -> Asset {
+    // ZoomEye 的 service 为应用层协议（http/https/ssh），protocol 为传输层
+    let mut protocol = normalize_protocol(&str_field(field(item, "service")));
+    if protocol.is_empty() {
+        protocol = normalize_protocol(&str_field(field(item, "protocol")));
+    }
+
+    let product = str_field(field(item, "product"));
+    let fingerprints = if product.is_empty() {
+        Vec::new()
+    } else {
+        vec![product]
+    };
+
+    Asset {
+        ip: str_field(field(item, "ip")),
+        port: port_field(field(item, "port")),
+        protocol,
+        host: str_field(field(item, "hostname")),
+        domain: str_field(field(item, "domain")),
+        url: str_field(field(item, "url")),
+        title: title_field(field(item, "title")),
+        geo: Geo {
+            country: str_field(field(item, "country.name")),
+            province: str_field(field(item, "province.name")),
+            city: str_field(field(item, "city.name")),
+            isp: str_field(field(item, "isp.name")),
+        },
+        asn: number_field(field(item, "asn")),
+        os: str_field(field(item, "os")),
+        organization: str_field(field(item, "organization.name")),
+        fingerprints,
+        last_seen: Some(str_field(field(item, "update_time"))).filter(|s| !s.is_empty()),
+        ..Asset::new("zoomeye", item.clone())
+    }
+    .finalize()
+}
+
+// 通过用户信息接口验证API密钥并查询剩余积分
+async fn validate_api_key_at(
+    base_url: &str,
+    api_key: &str,
+) -> Result<ApiKeyValidationResult, String> {
+    let response_json = match post_with_key(base_url, "/v2/userinfo", api_key, &json!({})).await {
+        Ok(response_json) => response_json,
+        Err(e) => {
+            return Ok(ApiKeyValidationResult {
+                valid: false,
+                message: Some(e.to_string()),
+                quota: None,
+                remaining: None,
+            })
+        }
+    };
+
+    let data = &response_json["data"];
+    let subscription = &data["subscription"];
+    let points = subscription["points"].as_i64();
+    let zoomeye_points = subscription["zoomeye_points"].as_i64();
+    let plan = subscription["plan"].as_str().unwrap_or("未知");
+
+    // 搜索先扣订阅积分再扣购买的积分，两者都缺失时额度未知
+    let remaining = match (points, zoomeye_points) {
+        (None, None) => None,
+        (points, zoomeye_points) => {
+            Some((points.unwrap_or(0).max(0) + zoomeye_points.unwrap_or(0).max(0)) as u64)
+        }
+    };
+
+    Ok(ApiKeyValidationResult {
+        valid: true,
+        message: Some(format!("API密钥验证成功 (套餐: {})", plan)),
+        quota: Some(format!(
+            "剩余积分: {}, 订阅积分: {}",
+            zoomeye_points.unwrap_or(0),
+            points.unwrap_or(0)
+        )),
+        remaining,
+    })
+}
+
+// 验证API密钥
+pub async fn validate_api_key(api_key: &str) -> Result<ApiKeyValidationResult, String> {
+    validate_api_key_at(API_BASE, api_key).await
+}
+
+// ZoomEye 数据源
+pub struct ZoomEyeSource;
+
+#[async_trait]
+impl AssetSource for ZoomEyeSource {
+    fn name(&self) -> &'static str {
+        "zoomeye"
+    }
+
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities {
+            display_name: "ZoomEye",
+            requires_email: false,
+            max_page_size: 10000,
+            pagination: Pagination::PageNumber,
+            cost: CostModel {
+                unit: "积分",
+                billing: Billing::PerResult,
+                credits: 1,
+            },
+        }
+    }
+
+    async fn search(&self, query: &str, page: u32, page_size: u32) -> Result<SearchPage, ApiError> {
+        search(query, page, page_size).await
+    }
+
+    async fn validate_api_key(
+        &self,
+        api_key: &str,
+        _email: Option<&str>,
+    ) -> Result<ApiKeyValidationResult, String> {
+        validate_api_key(api_key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn record(ip: &str) -> Value {
+        json!({
+            "ip": ip,
+            "port": 8443,
+            "domain": "example.com",
+            "hostname": "www.example.com",
+            "title": ["", "后台登录"],
+            "os": "Linux",
+            "product": "nginx",
+            "protocol": "tcp",
+            "service": "https",
+            "country.name": "中国",
+            "province.name": "北京",
+            "city.name": "北京",
+            "isp.name": "电信",
+            "organization.name": "Example",
+            "asn": 4134,
+            "update_time": "2024-06-01 10:00:00"
+        })
+    }
+
+    #[test]
+    fn test_to_asset_maps_record() {
+        let asset = to_asset(&record("1.2.3.4"));
+        assert_eq!(asset.protocol, "https");
+        assert_eq!(asset.host, "www.example.com");
+        assert_eq!(asset.url, "https://www.example.com:8443");
+        assert_eq!(asset.title, "后台登录");
+        assert_eq!(asset.geo.province, "北京");
+        assert_eq!(asset.asn, Some(4134));
+        assert_eq!(asset.fingerprints, vec!["nginx"]);
+        assert_eq!(asset.platform, "zoomeye");
+    }
+
+    #[test]
+    fn test_field_reads_nested_objects() {
+        let item = json!({"country": {"name": "Japan"}});
+        assert_eq!(str_field(field(&item, "country.name")), "Japan");
+    }
+
+    #[tokio::test]
+    async fn test_search_encodes_query_and_sends_key_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/search"))
+            .and(header("API-KEY", "test-key"))
+            .and(body_partial_json(json!({
+                "qbase64": general_purpose::STANDARD.encode("app=\"nginx\""),
+                "page": 2,
+                "pagesize": 20
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 60000,
+                "total": 41,
+                "data": [record("1.1.1.1"), record("2.2.2.2")]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let page = search_with_key(&server.uri(), "test-key", "app=\"nginx\"", 2, 20)
+            .await
+            .unwrap();
+        assert_eq!(page.total, 41);
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[1].ip, "2.2.2.2");
+    }
+
+    #[tokio::test]
+    async fn test_search_reports_exhausted_credits() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/search"))
+            .respond_with(ResponseTemplate::new(402).set_body_json(json!({
+                "code": 60003,
+                "message": "credits_insufficent"
+            })))
+            .mount(&server)
+            .await;
+
+        let err = search_with_key(&server.uri(), "k", "port=22", 1, 20)
+            .await
+            .unwrap_err();
+        assert!(err.is_key_exhausted(), "{:?}", err);
+        assert!(err.to_string().contains("积分"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_validate_api_key_reads_points() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v2/userinfo"))
+            .and(header("API-KEY", "good"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "code": 60000,
+                "data": {
+                    "username": "tester",
+                    "subscription": {"plan": "basic", "points": 3000, "zoomeye_points": 120}
+                }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v2/userinfo"))
+            .and(header("API-KEY", "bad"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "code": 60001,
+                "message": "login_required"
+            })))
+            .mount(&server)
+            .await;
+
+        let ok = validate_api_key_at(&server.uri(), "good").await.unwrap();
+        assert!(ok.valid);
+        assert_eq!(ok.quota.as_deref(), Some("剩余积分: 120, 订阅积分: 3000"));
+
+        let bad = validate_api_key_at(&server.uri(), "bad").await.unwrap();
+        assert!(!bad.valid);
+        assert!(bad.message.unwrap().contains("API密钥无效"));
+    }
+}
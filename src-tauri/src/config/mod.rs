@@ -1,5 +1,8 @@
+use crate::api::http::{default_rate_limits, RateLimit, RetryPolicy};
+use crate::api::key_manager::KeyRotation;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -8,10 +11,7 @@ pub use platform::*;
 
 // 配置文件路径
 const CONFIG_DIR: &str = "asset-mapping";
-const HUNTER_CONFIG_FILE: &str = "hunter_api.json";
-const FOFA_CONFIG_FILE: &str = "fofa_api.json";
-const QUAKE_CONFIG_FILE: &str = "quake_api.json";
-const DAYDAYMAP_CONFIG_FILE: &str = "daydaymap_api.json";
+const API_CONFIG_FILE_SUFFIX: &str = "_api.json";
 const SETTINGS_FILE: &str = "settings.json";
 
 // 设置结构体（供内部兼容使用，实际序列化使用 crate::Settings）
@@ -38,6 +38,15 @@ pub struct Settings {
     // 请求超时（秒）
     #[serde(default = "crate::default_timeout")]
     pub request_timeout: u32,
+    // 各平台请求频率限制
+    #[serde(default = "crate::api::http::default_rate_limits")]
+    pub rate_limits: HashMap<String, RateLimit>,
+    // 失败重试策略
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    // 各平台 key 轮询策略
+    #[serde(default)]
+    pub key_rotation: HashMap<String, KeyRotation>,
 }
 
 // 获取配置目录
@@ -95,121 +104,14 @@ pub fn decode_key(encoded: &str) -> String {
     }
 }
 
-// 获取Hunter API密钥
-#[allow(dead_code)]
-pub fn get_hunter_api_key() -> Result<String, String> {
-    let api_keys = get_hunter_api_keys_internal()?;
-
-    if api_keys.is_empty() {
-        return Err("未配置Hunter API密钥".to_string());
-    }
-
-    Ok(api_keys[0].clone())
-}
-
-// 获取Hunter API密钥列表
-fn get_hunter_api_keys_internal() -> Result<Vec<String>, String> {
-    let config_file = get_config_dir()?.join(HUNTER_CONFIG_FILE);
-
-    if !config_file.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content =
-        fs::read_to_string(&config_file).map_err(|e| format!("读取配置文件失败: {}", e))?;
-    let config: Value =
-        serde_json::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
-
-    let api_keys = config["api_keys"]
-        .as_array()
-        .ok_or_else(|| "配置文件格式错误".to_string())?
-        .iter()
-        .filter_map(|v| v.as_str())
-        .map(decode_key)
-        .collect();
-
-    Ok(api_keys)
-}
-
-// 获取Hunter API密钥列表（供前端使用）
-pub fn get_hunter_api_keys() -> Result<Value, String> {
-    let api_keys = get_hunter_api_keys_internal()?;
-    Ok(json!({ "api_keys": api_keys }))
-}
-
-// 添加Hunter API密钥
-pub fn add_hunter_api_key(api_key: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(HUNTER_CONFIG_FILE);
-
-    // 读取已有密钥（解码后的明文）
-    let existing_keys = if config_file.exists() {
-        get_hunter_api_keys_internal().unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-
-    // 检查是否已存在
-    if existing_keys.contains(&api_key.to_string()) {
-        return Ok(());
-    }
-
-    // 所有密钥重新编码后保存
-    let mut all_keys = existing_keys;
-    all_keys.push(api_key.to_string());
-    let encoded: Vec<String> = all_keys.iter().map(|k| encode_key(k)).collect();
-
-    let config = json!({
-        "api_keys": encoded
-    });
-
-    let content =
-        serde_json::to_string_pretty(&config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
-
-    Ok(())
-}
-
-// 删除Hunter API密钥
-pub fn delete_hunter_api_key(api_key: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(HUNTER_CONFIG_FILE);
-
-    if !config_file.exists() {
-        return Ok(());
-    }
-
-    // 读取现有密钥（解码后），过滤掉要删除的，重新编码保存
-    let remaining: Vec<String> = get_hunter_api_keys_internal()?
-        .into_iter()
-        .filter(|k| k != api_key)
-        .collect();
-    let encoded: Vec<String> = remaining.iter().map(|k| encode_key(k)).collect();
-
-    let new_config = json!({ "api_keys": encoded });
-
-    let new_content =
-        serde_json::to_string_pretty(&new_config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, new_content).map_err(|e| format!("写入配置文件失败: {}", e))?;
-
-    Ok(())
-}
-
-// 获取FOFA API密钥
-#[allow(dead_code)]
-pub fn get_fofa_api_key() -> Result<(String, String), String> {
-    let (api_keys, emails) = get_fofa_api_keys_internal()?;
-
-    if api_keys.is_empty() || emails.is_empty() {
-        return Err("未配置FOFA API密钥或邮箱".to_string());
-    }
-
-    Ok((api_keys[0].clone(), emails[0].clone()))
+// 获取平台密钥配置文件路径（{platform}_api.json）
+fn get_api_config_file(platform: &str) -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(format!("{}{}", platform, API_CONFIG_FILE_SUFFIX)))
 }
 
-// 获取FOFA API密钥列表
-fn get_fofa_api_keys_internal() -> Result<(Vec<String>, Vec<String>), String> {
-    let config_file = get_config_dir()?.join(FOFA_CONFIG_FILE);
+// 读取平台API密钥及附加账号（如FOFA邮箱），返回解码后的明文
+fn get_api_keys_internal(platform: &str) -> Result<(Vec<String>, Vec<String>), String> {
+    let config_file = get_api_config_file(platform)?;
 
     if !config_file.exists() {
         return Ok((Vec::new(), Vec::new()));
@@ -222,7 +124,7 @@ fn get_fofa_api_keys_internal() -> Result<(Vec<String>, Vec<String>), String> {
 
     let api_keys = config["api_keys"]
         .as_array()
-        .unwrap_or(&Vec::new())
+        .ok_or_else(|| "配置文件格式错误".to_string())?
         .iter()
         .filter_map(|v| v.as_str())
         .map(decode_key)
@@ -239,45 +141,25 @@ fn get_fofa_api_keys_internal() -> Result<(Vec<String>, Vec<String>), String> {
     Ok((api_keys, emails))
 }
 
-// 获取FOFA API密钥列表（供前端使用）
-pub fn get_fofa_api_keys() -> Result<Value, String> {
-    let (api_keys, emails) = get_fofa_api_keys_internal()?;
-    Ok(json!({
-        "api_keys": api_keys,
-        "emails": emails
-    }))
-}
-
-// 添加FOFA API密钥
-pub fn add_fofa_api_key(api_key: &str, email: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(FOFA_CONFIG_FILE);
-
-    // 读取已有密钥（解码后的明文）
-    let (existing_keys, existing_emails) = if config_file.exists() {
-        get_fofa_api_keys_internal().unwrap_or_default()
+// 重新编码并写回平台密钥配置
+fn save_api_keys_internal(
+    platform: &str,
+    api_keys: &[String],
+    emails: &[String],
+) -> Result<(), String> {
+    let config_file = get_api_config_file(platform)?;
+
+    let encoded_keys: Vec<String> = api_keys.iter().map(|k| encode_key(k)).collect();
+    let config = if emails.is_empty() {
+        json!({ "api_keys": encoded_keys })
     } else {
-        (Vec::new(), Vec::new())
+        let encoded_emails: Vec<String> = emails.iter().map(|e| encode_key(e)).collect();
+        json!({
+            "api_keys": encoded_keys,
+            "emails": encoded_emails
+        })
     };
 
-    // 检查是否已存在
-    if existing_keys.contains(&api_key.to_string()) {
-        return Ok(());
-    }
-
-    let mut all_keys = existing_keys;
-    let mut all_emails = existing_emails;
-    all_keys.push(api_key.to_string());
-    all_emails.push(email.to_string());
-
-    let encoded_keys: Vec<String> = all_keys.iter().map(|k| encode_key(k)).collect();
-    let encoded_emails: Vec<String> = all_emails.iter().map(|e| encode_key(e)).collect();
-
-    let config = json!({
-        "api_keys": encoded_keys,
-        "emails": encoded_emails
-    });
-
     let content =
         serde_json::to_string_pretty(&config).map_err(|e| format!("序列化配置失败: {}", e))?;
     fs::write(&config_file, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
@@ -285,160 +167,15 @@ pub fn add_fofa_api_key(api_key: &str, email: &str) -> Result<(), String> {
     Ok(())
 }
 
-// 删除FOFA API密钥
-pub fn delete_fofa_api_key(api_key: &str, email: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(FOFA_CONFIG_FILE);
-
-    if !config_file.exists() {
-        return Ok(());
-    }
-
-    // 读取现有密钥（解码后），过滤掉要删除的
-    let (existing_keys, existing_emails) = get_fofa_api_keys_internal()?;
-    let mut remaining_keys = Vec::new();
-    let mut remaining_emails = Vec::new();
-
-    for (k, e) in existing_keys.iter().zip(existing_emails.iter()) {
-        if k != api_key || e != email {
-            remaining_keys.push(encode_key(k));
-            remaining_emails.push(encode_key(e));
-        }
-    }
-
-    let new_config = json!({
-        "api_keys": remaining_keys,
-        "emails": remaining_emails
-    });
-
-    let new_content =
-        serde_json::to_string_pretty(&new_config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, new_content).map_err(|e| format!("写入配置文件失败: {}", e))?;
-
-    Ok(())
-}
-
-// 获取Quake API密钥
-#[allow(dead_code)]
-pub fn get_quake_api_key() -> Result<String, String> {
-    let api_keys = get_quake_api_keys_internal()?;
-
-    if api_keys.is_empty() {
-        return Err("未配置Quake API密钥".to_string());
-    }
-
-    Ok(api_keys[0].clone())
-}
-
-// 获取Quake API密钥列表
-fn get_quake_api_keys_internal() -> Result<Vec<String>, String> {
-    let config_file = get_config_dir()?.join(QUAKE_CONFIG_FILE);
-
-    if !config_file.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content =
-        fs::read_to_string(&config_file).map_err(|e| format!("读取配置文件失败: {}", e))?;
-    let config: Value =
-        serde_json::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
-
-    let api_keys = config["api_keys"]
-        .as_array()
-        .unwrap_or(&Vec::new())
-        .iter()
-        .filter_map(|v| v.as_str())
-        .map(decode_key)
-        .collect();
-
+// 获取平台所有API密钥（用于轮询）
+pub fn get_all_api_keys(platform: &str) -> Result<Vec<String>, String> {
+    let (api_keys, _) = get_api_keys_internal(platform)?;
     Ok(api_keys)
 }
 
-// 获取Quake API密钥列表（供前端使用）
-pub fn get_quake_api_keys() -> Result<Value, String> {
-    let api_keys = get_quake_api_keys_internal()?;
-    Ok(json!({ "api_keys": api_keys }))
-}
-
-// 添加Quake API密钥
-pub fn add_quake_api_key(api_key: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(QUAKE_CONFIG_FILE);
-
-    let existing_keys = if config_file.exists() {
-        get_quake_api_keys_internal().unwrap_or_default()
-    } else {
-        Vec::new()
-    };
-
-    if existing_keys.contains(&api_key.to_string()) {
-        return Ok(());
-    }
-
-    let mut all_keys = existing_keys;
-    all_keys.push(api_key.to_string());
-    let encoded: Vec<String> = all_keys.iter().map(|k| encode_key(k)).collect();
-
-    let config = json!({
-        "api_keys": encoded
-    });
-
-    let content =
-        serde_json::to_string_pretty(&config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
-
-    Ok(())
-}
-
-// 删除Quake API密钥
-pub fn delete_quake_api_key(api_key: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(QUAKE_CONFIG_FILE);
-
-    if !config_file.exists() {
-        return Ok(());
-    }
-
-    let remaining: Vec<String> = get_quake_api_keys_internal()?
-        .into_iter()
-        .filter(|k| k != api_key)
-        .collect();
-    let encoded: Vec<String> = remaining.iter().map(|k| encode_key(k)).collect();
-
-    let new_config = json!({ "api_keys": encoded });
-
-    let new_content =
-        serde_json::to_string_pretty(&new_config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, new_content).map_err(|e| format!("写入配置文件失败: {}", e))?;
-
-    Ok(())
-}
-
-// 获取DayDayMap API密钥
-#[allow(dead_code)]
-pub fn get_daydaymap_api_key() -> Result<String, String> {
-    let api_keys = get_daydaymap_api_keys_internal()?;
-
-    if api_keys.is_empty() {
-        return Err("未配置DayDayMap API密钥".to_string());
-    }
-
-    Ok(api_keys[0].clone())
-}
-
-// 获取所有DayDayMap API密钥（用于轮询）
-pub fn get_all_daydaymap_api_keys() -> Result<Vec<String>, String> {
-    get_daydaymap_api_keys_internal()
-}
-
-// 获取所有Hunter API密钥（用于轮询）
-pub fn get_all_hunter_api_keys() -> Result<Vec<String>, String> {
-    get_hunter_api_keys_internal()
-}
-
-// 获取所有FOFA API密钥（用于轮询）
-pub fn get_all_fofa_api_keys() -> Result<Vec<(String, String)>, String> {
-    let (api_keys, emails) = get_fofa_api_keys_internal()?;
+// 获取平台所有(API密钥, 邮箱)对（用于需要账号的平台轮询，如FOFA）
+pub fn get_all_api_key_pairs(platform: &str) -> Result<Vec<(String, String)>, String> {
+    let (api_keys, emails) = get_api_keys_internal(platform)?;
 
     // 将API密钥和邮箱配对
     let paired_keys: Vec<(String, String)> = api_keys.into_iter().zip(emails).collect();
@@ -446,93 +183,66 @@ pub fn get_all_fofa_api_keys() -> Result<Vec<(String, String)>, String> {
     Ok(paired_keys)
 }
 
-// 获取所有Quake API密钥（用于轮询）
-pub fn get_all_quake_api_keys() -> Result<Vec<String>, String> {
-    get_quake_api_keys_internal()
-}
-
-// 获取DayDayMap API密钥列表
-fn get_daydaymap_api_keys_internal() -> Result<Vec<String>, String> {
-    let config_file = get_config_dir()?.join(DAYDAYMAP_CONFIG_FILE);
-
-    if !config_file.exists() {
-        return Ok(Vec::new());
+// 获取平台API密钥列表（供前端使用）
+pub fn get_api_keys(platform: &str, with_emails: bool) -> Result<Value, String> {
+    let (api_keys, emails) = get_api_keys_internal(platform)?;
+    if with_emails {
+        Ok(json!({
+            "api_keys": api_keys,
+            "emails": emails
+        }))
+    } else {
+        Ok(json!({ "api_keys": api_keys }))
     }
-
-    let content =
-        fs::read_to_string(&config_file).map_err(|e| format!("读取配置文件失败: {}", e))?;
-    let config: Value =
-        serde_json::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
-
-    let api_keys = config["api_keys"]
-        .as_array()
-        .unwrap_or(&Vec::new())
-        .iter()
-        .filter_map(|v| v.as_str())
-        .map(decode_key)
-        .collect();
-
-    Ok(api_keys)
 }
 
-// 获取DayDayMap API密钥列表（供前端使用）
-pub fn get_daydaymap_api_keys() -> Result<Value, String> {
-    let api_keys = get_daydaymap_api_keys_internal()?;
-    Ok(json!({ "api_keys": api_keys }))
-}
-
-// 添加DayDayMap API密钥
-pub fn add_daydaymap_api_key(api_key: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(DAYDAYMAP_CONFIG_FILE);
-
-    let existing_keys = if config_file.exists() {
-        get_daydaymap_api_keys_internal().unwrap_or_default()
-    } else {
-        Vec::new()
-    };
+// 添加平台API密钥
+pub fn add_api_key(platform: &str, api_key: &str, email: Option<&str>) -> Result<(), String> {
+    // 读取已有密钥（解码后的明文）
+    let (mut all_keys, mut all_emails) = get_api_keys_internal(platform).unwrap_or_default();
 
-    if existing_keys.contains(&api_key.to_string()) {
+    // 检查是否已存在
+    if all_keys.iter().any(|k| k == api_key) {
         return Ok(());
     }
 
-    let mut all_keys = existing_keys;
+    // 所有密钥重新编码后保存
     all_keys.push(api_key.to_string());
-    let encoded: Vec<String> = all_keys.iter().map(|k| encode_key(k)).collect();
-
-    let config = json!({
-        "api_keys": encoded
-    });
-
-    let content =
-        serde_json::to_string_pretty(&config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    if let Some(email) = email {
+        all_emails.push(email.to_string());
+    }
 
-    Ok(())
+    save_api_keys_internal(platform, &all_keys, &all_emails)
 }
 
-// 删除DayDayMap API密钥
-pub fn delete_daydaymap_api_key(api_key: &str) -> Result<(), String> {
-    let config_dir = get_config_dir()?;
-    let config_file = config_dir.join(DAYDAYMAP_CONFIG_FILE);
-
-    if !config_file.exists() {
+// 删除平台API密钥（需要账号的平台同时匹配邮箱）
+pub fn delete_api_key(platform: &str, api_key: &str, email: Option<&str>) -> Result<(), String> {
+    if !get_api_config_file(platform)?.exists() {
         return Ok(());
     }
 
-    let remaining: Vec<String> = get_daydaymap_api_keys_internal()?
-        .into_iter()
-        .filter(|k| k != api_key)
-        .collect();
-    let encoded: Vec<String> = remaining.iter().map(|k| encode_key(k)).collect();
-
-    let new_config = json!({ "api_keys": encoded });
-
-    let new_content =
-        serde_json::to_string_pretty(&new_config).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&config_file, new_content).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    // 读取现有密钥（解码后），过滤掉要删除的，重新编码保存
+    let (existing_keys, existing_emails) = get_api_keys_internal(platform)?;
+
+    let (remaining_keys, remaining_emails) = match email {
+        Some(email) => {
+            let mut remaining_keys = Vec::new();
+            let mut remaining_emails = Vec::new();
+            for (k, e) in existing_keys.iter().zip(existing_emails.iter()) {
+                if k != api_key || e != email {
+                    remaining_keys.push(k.clone());
+                    remaining_emails.push(e.clone());
+                }
+            }
+            (remaining_keys, remaining_emails)
+        }
+        None => (
+            existing_keys.into_iter().filter(|k| k != api_key).collect(),
+            Vec::new(),
+        ),
+    };
 
-    Ok(())
+    save_api_keys_internal(platform, &remaining_keys, &remaining_emails)
 }
 
 // 获取设置
@@ -554,6 +264,9 @@ pub fn get_settings() -> Result<crate::Settings, String> {
             proxy_username: String::new(),
             proxy_password: String::new(),
             request_timeout: 30,
+            rate_limits: default_rate_limits(),
+            retry_policy: RetryPolicy::default(),
+            key_rotation: HashMap::new(),
         });
     }
 
@@ -614,6 +327,21 @@ pub fn get_settings() -> Result<crate::Settings, String> {
                     .as_u64()
                     .or_else(|| value["requestTimeout"].as_u64())
                     .unwrap_or(30) as u32,
+                rate_limits: value
+                    .get("rate_limits")
+                    .or_else(|| value.get("rateLimits"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_else(default_rate_limits),
+                retry_policy: value
+                    .get("retry_policy")
+                    .or_else(|| value.get("retryPolicy"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default(),
+                key_rotation: value
+                    .get("key_rotation")
+                    .or_else(|| value.get("keyRotation"))
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .unwrap_or_default(),
             })
         })
         .map_err(|e| format!("解析配置文件失败: {}", e))?;
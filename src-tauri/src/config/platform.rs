@@ -13,13 +13,66 @@ pub struct Operators {
     pub not_equal: String,
     pub left_paren: String,
     pub right_paren: String,
+    /// Exact match operator (`==`), `None` when the platform only matches fuzzily
+    #[serde(default)]
+    pub exact: Option<String>,
+    /// Whether `[from TO to]` range values are understood
+    #[serde(default)]
+    pub range: bool,
+}
+
+/// How a field matches its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Contains / fuzzy match, the default for every field
+    Fuzzy,
+    /// Matches the value and everything ending with it (sub-domains)
+    Suffix,
+}
+
+/// Platform field with its own match semantics, e.g. Hunter `domain.suffix`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldVariant {
+    /// Shared field key in [`PlatformConfig::fields`]
+    pub key: String,
+    #[serde(rename = "match")]
+    pub match_mode: MatchMode,
+}
+
+/// Query condition restricting results to a time range
+///
+/// `relative` is used for the last N days and may contain `{days}` and
+/// `{since}`; `absolute` is used between two dates and may contain `{start}`
+/// and `{end}`. Dates are written with `date_format`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeRangeRule {
+    pub relative: String,
+    pub absolute: String,
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+}
+
+fn default_date_format() -> String {
+    "%Y-%m-%d".to_string()
 }
 
 /// Platform configuration containing operators and field mappings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlatformConfig {
     pub operators: Operators,
+    /// Shared field key → platform field
     pub fields: HashMap<String, String>,
+    /// Platform field → variant of a shared field with a different match mode
+    #[serde(default)]
+    pub variants: HashMap<String, FieldVariant>,
+    /// Shared field key → (shared value → platform value)
+    ///
+    /// Values without an entry are the same on every platform.
+    #[serde(default)]
+    pub values: HashMap<String, HashMap<String, String>>,
+    #[serde(default)]
+    pub time_range: Option<TimeRangeRule>,
 }
 
 /// Configuration manager for all platforms
@@ -0,0 +1,105 @@
+/// Comparison used by a field predicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// Fuzzy / contains match (`=` or `:`)
+    Equal,
+    /// Exact match (`==`)
+    Exact,
+    /// Negated match (`!=`, `NOT field:value` or `-field:value`)
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+}
+
+impl Comparison {
+    /// Symbol for the ordering comparisons, `None` for the equality family
+    pub fn ordering_symbol(self) -> Option<&'static str> {
+        match self {
+            Comparison::Greater => Some(">"),
+            Comparison::GreaterEqual => Some(">="),
+            Comparison::Less => Some("<"),
+            Comparison::LessEqual => Some("<="),
+            _ => None,
+        }
+    }
+
+    /// Logical negation, `None` when the platform syntax has no direct inverse
+    pub fn negate(self) -> Option<Comparison> {
+        match self {
+            Comparison::Equal => Some(Comparison::NotEqual),
+            Comparison::NotEqual => Some(Comparison::Equal),
+            Comparison::Greater => Some(Comparison::LessEqual),
+            Comparison::GreaterEqual => Some(Comparison::Less),
+            Comparison::Less => Some(Comparison::GreaterEqual),
+            Comparison::LessEqual => Some(Comparison::Greater),
+            Comparison::Exact => None,
+        }
+    }
+}
+
+/// How a value was written in the query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    /// `"..."`, text is stored unescaped
+    Quoted,
+    /// Unquoted word such as `80` or `test.com`
+    Bare,
+    /// `[from TO to]`, text is the content between the brackets
+    Range,
+}
+
+/// Predicate value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Value {
+    pub text: String,
+    pub kind: ValueKind,
+}
+
+impl Value {
+    pub fn quoted(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            kind: ValueKind::Quoted,
+        }
+    }
+
+    pub fn bare(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            kind: ValueKind::Bare,
+        }
+    }
+
+    pub fn range(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            kind: ValueKind::Range,
+        }
+    }
+}
+
+/// `field <comparison> value`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Predicate {
+    /// Field name as written in the platform dialect
+    pub field: String,
+    pub comparison: Comparison,
+    pub value: Value,
+}
+
+/// Query syntax tree
+///
+/// `And` / `Or` are n-ary and never directly contain a node of the same kind;
+/// parentheses written in the query are kept as `Group` so rendering preserves them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Predicate(Predicate),
+    /// Free-text term without a field (e.g. Shodan `nginx`)
+    Term(Value),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Group(Box<Expr>),
+}
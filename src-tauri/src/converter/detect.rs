@@ -0,0 +1,202 @@
+use crate::config::{ConfigManager, PlatformConfig};
+use crate::converter::lexer::{Lexer, Token, TokenKind};
+use crate::converter::validator::{Severity, SyntaxValidator};
+use serde::Serialize;
+
+/// How well a query fits one platform dialect
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DialectScore {
+    pub platform: String,
+    /// Share of the total likelihood, the confidences of a ranking add up to 1
+    pub confidence: f64,
+    /// Raw score the confidence is derived from
+    pub score: f64,
+    /// Number of validator errors for this dialect
+    pub errors: usize,
+}
+
+// Weights of the scoring components
+const OPERATOR_MATCH: f64 = 1.0;
+const OPERATOR_MISMATCH: f64 = -1.5;
+const UNKNOWN_FIELD: f64 = -1.5;
+const VALIDATION_ERROR: f64 = -2.0;
+const VALID_QUERY: f64 = 3.0;
+
+/// Scores a query against every loaded [`PlatformConfig`]
+///
+/// Three signals are combined per platform: how many operators are written in
+/// the platform's style, how many fields belong to its vocabulary (fields few
+/// platforms know weigh more) and whether the validator accepts the query.
+pub struct DialectDetector<'a> {
+    config_manager: &'a ConfigManager,
+}
+
+impl<'a> DialectDetector<'a> {
+    pub fn new(config_manager: &'a ConfigManager) -> Self {
+        Self { config_manager }
+    }
+
+    /// Platforms ranked from most to least likely, ties ordered by name
+    pub fn detect(&self, query: &str) -> Vec<DialectScore> {
+        let (tokens, _) = Lexer::tokenize_recovering(query);
+
+        let mut platforms = self.config_manager.get_supported_platforms();
+        platforms.sort();
+        let configs: Vec<(String, &PlatformConfig)> = platforms
+            .into_iter()
+            .filter_map(|platform| {
+                let config = self.config_manager.get_platform_config(&platform).ok()?;
+                Some((platform, config))
+            })
+            .collect();
+
+        let mut scores: Vec<DialectScore> = configs
+            .iter()
+            .map(|(platform, config)| {
+                let errors = SyntaxValidator::diagnose(query, config, platform)
+                    .iter()
+                    .filter(|diagnostic| diagnostic.severity == Severity::Error)
+                    .count();
+                let validation = if errors == 0 {
+                    VALID_QUERY
+                } else {
+                    VALIDATION_ERROR * errors as f64
+                };
+                let score = Self::operator_score(&tokens, config)
+                    + Self::field_score(&tokens, config, &configs)
+                    + validation;
+                DialectScore {
+                    platform: platform.clone(),
+                    confidence: 0.0,
+                    score,
+                    errors,
+                }
+            })
+            .collect();
+
+        // Softmax turns the scores into confidences
+        let max = scores
+            .iter()
+            .map(|score| score.score)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let total: f64 = scores.iter().map(|score| (score.score - max).exp()).sum();
+        for score in &mut scores {
+            score.confidence = (score.score - max).exp() / total;
+        }
+
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+
+    /// Operators written the way the dialect writes them
+    fn operator_score(tokens: &[Token], config: &PlatformConfig) -> f64 {
+        let operators = &config.operators;
+        let fits = |expected: &str, text: &str| expected.trim().eq_ignore_ascii_case(text);
+        let mut score = 0.0;
+        let mut previous_is_operand = false;
+
+        for token in tokens {
+            let matched = match &token.kind {
+                TokenKind::And => Some(fits(&operators.and, &token.text)),
+                TokenKind::Or => Some(fits(&operators.or, &token.text)),
+                TokenKind::Not => Some(fits(&operators.not_equal, "NOT")),
+                TokenKind::Minus => Some(fits(&operators.not_equal, "-")),
+                TokenKind::Operator(comparison) if comparison.ordering_symbol().is_some() => {
+                    // `port:>80` on `:` dialects, `port>80` on `=` dialects
+                    Some(token.text.starts_with(':') == (operators.equal == ":"))
+                }
+                TokenKind::Operator(_) => Some(match token.text.as_str() {
+                    "!=" => fits(&operators.not_equal, "!="),
+                    "==" => operators.exact.as_deref() == Some("=="),
+                    text => fits(&operators.equal, text),
+                }),
+                // Conditions side by side are Shodan's implicit AND
+                TokenKind::Field(_) | TokenKind::LeftParen if previous_is_operand => {
+                    Some(operators.and.trim().is_empty())
+                }
+                _ => None,
+            };
+            score += match matched {
+                Some(true) => OPERATOR_MATCH,
+                Some(false) => OPERATOR_MISMATCH,
+                None => 0.0,
+            };
+            previous_is_operand = matches!(token.kind, TokenKind::Value(_) | TokenKind::RightParen);
+        }
+
+        score
+    }
+
+    /// Fields of the dialect's vocabulary, weighted by how few platforms share them
+    fn field_score(
+        tokens: &[Token],
+        config: &PlatformConfig,
+        configs: &[(String, &PlatformConfig)],
+    ) -> f64 {
+        let knows = |config: &PlatformConfig, field: &str| {
+            config.fields.values().any(|f| f == field) || config.variants.contains_key(field)
+        };
+
+        tokens
+            .iter()
+            .filter_map(|token| match &token.kind {
+                TokenKind::Field(field) => Some(field.as_str()),
+                _ => None,
+            })
+            .map(|field| {
+                if !knows(config, field) {
+                    return UNKNOWN_FIELD;
+                }
+                let shared_by = configs
+                    .iter()
+                    .filter(|(_, config)| knows(config, field))
+                    .count();
+                1.0 + (configs.len() as f64 / shared_by as f64).ln()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(query: &str) -> Vec<DialectScore> {
+        let config_path = concat!(env!("CARGO_MANIFEST_DIR"), "/config.json");
+        let config_manager = ConfigManager::from_file(config_path).unwrap();
+        DialectDetector::new(&config_manager).detect(query)
+    }
+
+    fn top(query: &str) -> String {
+        detect(query).remove(0).platform
+    }
+
+    #[test]
+    fn test_distinctive_fields_and_operators_win() {
+        assert_eq!(top(r#"web.title="登录" && ip.port="443""#), "hunter");
+        assert_eq!(top(r#"service:"http/ssl" AND title:"admin""#), "quake");
+        assert_eq!(
+            top(r#"http.title:"admin" port:443 -hostname:"a.com""#),
+            "shodan"
+        );
+        assert_eq!(
+            top(r#"services.port: 443 AND dns.names: "a.com""#),
+            "censys"
+        );
+        assert_eq!(top(r#"type="subdomain" && title="x""#), "fofa");
+    }
+
+    #[test]
+    fn test_ranking_has_confidences() {
+        let scores = detect(r#"title="x""#);
+        let total: f64 = scores.iter().map(|score| score.confidence).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(scores.windows(2).all(|pair| pair[0].score >= pair[1].score));
+        // `=` dialects are all plausible, `:` dialects and Hunter are not
+        let rank = |platform: &str| scores.iter().position(|s| s.platform == platform).unwrap();
+        assert!(rank("fofa") < rank("quake"));
+        assert!(rank("zoomeye") < rank("hunter"));
+        assert_eq!(scores[rank("quake")].errors, 1);
+    }
+}
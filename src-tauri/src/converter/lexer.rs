@@ -0,0 +1,366 @@
+use crate::converter::ast::{Comparison, Value};
+use crate::error::{ConversionError, ConversionResult};
+use serde::Serialize;
+
+/// Byte range of a token in the original query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenKind {
+    LeftParen,
+    RightParen,
+    /// `&&` or `AND` (case-insensitive)
+    And,
+    /// `||` or `OR` (case-insensitive)
+    Or,
+    /// `NOT` (case-insensitive)
+    Not,
+    /// Shodan style `-` prefix directly before a field
+    Minus,
+    Field(String),
+    Operator(Comparison),
+    Value(Value),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+    /// Source text of the token
+    pub text: String,
+}
+
+/// What the lexer expects next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Term,
+    Operator,
+    Value,
+}
+
+/// Query tokenizer
+///
+/// Lexing is context sensitive: after a field operator the next token is always
+/// read as a value, so `title="A && B"` or `port:80` never produce operator
+/// tokens from inside the value.
+pub struct Lexer<'a> {
+    query: &'a str,
+    pos: usize,
+    state: State,
+}
+
+// Field operators, longest first
+const OPERATORS: [(&str, Comparison); 8] = [
+    ("!=", Comparison::NotEqual),
+    ("==", Comparison::Exact),
+    (">=", Comparison::GreaterEqual),
+    ("<=", Comparison::LessEqual),
+    ("=", Comparison::Equal),
+    (":", Comparison::Equal),
+    (">", Comparison::Greater),
+    ("<", Comparison::Less),
+];
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+fn is_bare_terminator(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')'
+}
+
+/// Operator at the start of `text`: comparison and byte length
+///
+/// `:` may be followed by an ordering symbol (`port:>1024`).
+fn operator_at(text: &str) -> Option<(Comparison, usize)> {
+    let (op, comparison) = OPERATORS.iter().find(|(op, _)| text.starts_with(op))?;
+    if *op == ":" {
+        let ordering = OPERATORS
+            .iter()
+            .filter(|(_, comparison)| comparison.ordering_symbol().is_some())
+            .find(|(op, _)| text[1..].starts_with(op));
+        if let Some((op, comparison)) = ordering {
+            return Some((*comparison, 1 + op.len()));
+        }
+    }
+    Some((*comparison, op.len()))
+}
+
+fn error(position: usize, message: impl Into<String>) -> ConversionError {
+    ConversionError::ParseError {
+        position,
+        message: message.into(),
+    }
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(query: &'a str) -> Self {
+        Self {
+            query,
+            pos: 0,
+            state: State::Term,
+        }
+    }
+
+    /// Tokenize the whole query
+    pub fn tokenize(query: &str) -> ConversionResult<Vec<Token>> {
+        let mut lexer = Lexer::new(query);
+        let mut tokens = Vec::new();
+        while let Some(token) = lexer.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
+
+    /// Tokenize the whole query, collecting every lexical error instead of stopping at the first
+    ///
+    /// After an error the lexer skips the offending character and carries on;
+    /// an unterminated quote or range swallows the rest of the query.
+    pub fn tokenize_recovering(query: &str) -> (Vec<Token>, Vec<ConversionError>) {
+        let mut lexer = Lexer::new(query);
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            match lexer.next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => break,
+                Err(error) => {
+                    errors.push(error);
+                    if !lexer.recover() {
+                        break;
+                    }
+                }
+            }
+        }
+        (tokens, errors)
+    }
+
+    // Resynchronise after an error, `false` when nothing sensible is left to lex
+    fn recover(&mut self) -> bool {
+        let missing_value = self.state != State::Term;
+        self.state = State::Term;
+        match self.rest().chars().next() {
+            None | Some('"') | Some('[') => false,
+            // A missing value leaves the next token (usually `)`) intact
+            Some(_) if missing_value => true,
+            Some(c) => {
+                self.pos += c.len_utf8();
+                true
+            }
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.query[self.pos..]
+    }
+
+    fn token(&mut self, kind: TokenKind, end: usize) -> Token {
+        let start = self.pos;
+        self.pos = end;
+        Token {
+            kind,
+            span: Span { start, end },
+            text: self.query[start..end].to_string(),
+        }
+    }
+
+    /// Read the next token, `None` at the end of the query
+    pub fn next_token(&mut self) -> ConversionResult<Option<Token>> {
+        // The operator directly follows its field, no whitespace skipping
+        if self.state == State::Operator {
+            let (comparison, len) = operator_at(self.rest())
+                .ok_or_else(|| error(self.pos, "expected a field operator"))?;
+            self.state = State::Value;
+            let end = self.pos + len;
+            return Ok(Some(self.token(TokenKind::Operator(comparison), end)));
+        }
+
+        let trimmed = self.rest().trim_start();
+        self.pos = self.query.len() - trimmed.len();
+        let start = self.pos;
+
+        let Some(c) = trimmed.chars().next() else {
+            if self.state == State::Value {
+                return Err(error(start, "expected a value after the operator"));
+            }
+            return Ok(None);
+        };
+
+        if self.state == State::Value {
+            if c == ')' {
+                return Err(error(start, "expected a value after the operator"));
+            }
+            self.state = State::Term;
+            return self.lex_value().map(Some);
+        }
+
+        let token = match c {
+            '(' => self.token(TokenKind::LeftParen, start + 1),
+            ')' => self.token(TokenKind::RightParen, start + 1),
+            '"' | '[' => self.lex_value()?,
+            _ if trimmed.starts_with("&&") => self.token(TokenKind::And, start + 2),
+            _ if trimmed.starts_with("||") => self.token(TokenKind::Or, start + 2),
+            '-' if trimmed[1..].starts_with(is_ident_char) => {
+                self.token(TokenKind::Minus, start + 1)
+            }
+            c if is_ident_char(c) => self.lex_word(),
+            c => return Err(error(start, format!("unexpected character '{}'", c))),
+        };
+        Ok(Some(token))
+    }
+
+    /// A word is a field (when followed by an operator), a keyword or a free-text term
+    fn lex_word(&mut self) -> Token {
+        let rest = self.rest();
+        let word_len = rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len());
+        let word = &rest[..word_len];
+        let after = &rest[word_len..];
+        let end = self.pos + word_len;
+
+        if operator_at(after).is_some() {
+            self.state = State::Operator;
+            return self.token(TokenKind::Field(word.to_string()), end);
+        }
+
+        if after.chars().next().is_none_or(is_bare_terminator) {
+            let keyword = match word.to_ascii_uppercase().as_str() {
+                "AND" => Some(TokenKind::And),
+                "OR" => Some(TokenKind::Or),
+                "NOT" => Some(TokenKind::Not),
+                _ => None,
+            };
+            if let Some(kind) = keyword {
+                return self.token(kind, end);
+            }
+        }
+
+        self.lex_bare()
+    }
+
+    fn lex_value(&mut self) -> ConversionResult<Token> {
+        match self.rest().chars().next() {
+            Some('"') => self.lex_quoted(),
+            Some('[') => self.lex_range(),
+            _ => Ok(self.lex_bare()),
+        }
+    }
+
+    /// Unquoted value, up to whitespace or a parenthesis
+    fn lex_bare(&mut self) -> Token {
+        let rest = self.rest();
+        let len = rest.find(is_bare_terminator).unwrap_or(rest.len());
+        let value = Value::bare(&rest[..len]);
+        self.token(TokenKind::Value(value), self.pos + len)
+    }
+
+    /// `"..."` with `\"` and `\\` escapes; any other backslash is kept as written
+    fn lex_quoted(&mut self) -> ConversionResult<Token> {
+        let start = self.pos;
+        let mut text = String::new();
+        let mut chars = self.rest().char_indices().skip(1);
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    let end = start + i + 1;
+                    return Ok(self.token(TokenKind::Value(Value::quoted(text)), end));
+                }
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('"' | '\\'))) => text.push(escaped),
+                    Some((_, other)) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => break,
+                },
+                c => text.push(c),
+            }
+        }
+
+        Err(error(start, "unterminated quoted value"))
+    }
+
+    /// `[from TO to]`
+    fn lex_range(&mut self) -> ConversionResult<Token> {
+        let start = self.pos;
+        let close = self
+            .rest()
+            .find(']')
+            .ok_or_else(|| error(start, "unterminated range, expected ']'"))?;
+        let value = Value::range(&self.rest()[1..close]);
+        Ok(self.token(TokenKind::Value(value), start + close + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(query: &str) -> Vec<TokenKind> {
+        Lexer::tokenize(query)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_operators_inside_quotes_stay_in_value() {
+        assert_eq!(
+            kinds(r#"title="A && B" && port="80""#),
+            vec![
+                TokenKind::Field("title".to_string()),
+                TokenKind::Operator(Comparison::Equal),
+                TokenKind::Value(Value::quoted("A && B")),
+                TokenKind::And,
+                TokenKind::Field("port".to_string()),
+                TokenKind::Operator(Comparison::Equal),
+                TokenKind::Value(Value::quoted("80")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_quotes_and_spans() {
+        let tokens = Lexer::tokenize(r#"body="say \"hi\"" OR x"#).unwrap();
+        assert_eq!(
+            tokens[2].kind,
+            TokenKind::Value(Value::quoted(r#"say "hi""#))
+        );
+        assert_eq!(tokens[2].span, Span { start: 5, end: 17 });
+        assert_eq!(tokens[3].kind, TokenKind::Or);
+        assert_eq!(tokens[4].kind, TokenKind::Value(Value::bare("x")));
+    }
+
+    #[test]
+    fn test_colon_dialect_with_ranges_and_prefixes() {
+        assert_eq!(
+            kinds("-port:22 NOT time: [now-30d TO now] port:>=1024"),
+            vec![
+                TokenKind::Minus,
+                TokenKind::Field("port".to_string()),
+                TokenKind::Operator(Comparison::Equal),
+                TokenKind::Value(Value::bare("22")),
+                TokenKind::Not,
+                TokenKind::Field("time".to_string()),
+                TokenKind::Operator(Comparison::Equal),
+                TokenKind::Value(Value::range("now-30d TO now")),
+                TokenKind::Field("port".to_string()),
+                TokenKind::Operator(Comparison::GreaterEqual),
+                TokenKind::Value(Value::bare("1024")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_quote_reports_position() {
+        match Lexer::tokenize(r#"ip="1.1.1.1" && title="abc"#) {
+            Err(ConversionError::ParseError { position, .. }) => assert_eq!(position, 22),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
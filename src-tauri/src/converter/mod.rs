@@ -1,9 +1,16 @@
-pub mod fields;
-pub mod operators;
+pub mod ast;
+pub mod detect;
+pub mod lexer;
+pub mod parser;
 pub mod query;
+pub mod render;
+pub mod time_range;
+pub mod translate;
 pub mod validator;
 
-pub use fields::*;
-pub use operators::*;
+pub use detect::*;
+pub use parser::*;
 pub use query::*;
+pub use render::*;
+pub use time_range::*;
 pub use validator::*;
@@ -0,0 +1,257 @@
+use crate::converter::ast::{Comparison, Expr, Predicate};
+use crate::converter::lexer::{Lexer, Token, TokenKind};
+use crate::error::{ConversionError, ConversionResult};
+
+/// Recursive descent parser for platform queries
+///
+/// The grammar is shared by every supported dialect:
+///
+/// ```text
+/// or      := and (OR and)*
+/// and     := unary ((AND)? unary)*      juxtaposition is an implicit AND
+/// unary   := NOT unary | '-' primary | primary
+/// primary := '(' or ')' | field operator value | value
+/// ```
+///
+/// `NOT field:value` and `-field:value` are folded into a single
+/// [`Comparison::NotEqual`] predicate so that every negation dialect produces
+/// the same tree.
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    query_len: usize,
+}
+
+impl Parser {
+    /// Parse a query into an expression tree
+    pub fn parse(query: &str) -> ConversionResult<Expr> {
+        let mut parser = Parser {
+            tokens: Lexer::tokenize(query)?,
+            pos: 0,
+            query_len: query.len(),
+        };
+
+        if parser.tokens.is_empty() {
+            return Err(parser.error_here("query is empty"));
+        }
+
+        let expr = parser.parse_or()?;
+        if parser.peek().is_some() {
+            return Err(parser.error_here("unexpected token"));
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|token| &token.kind)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error_here(&self, message: &str) -> ConversionError {
+        let (position, message) = match self.tokens.get(self.pos) {
+            Some(token) => (token.span.start, format!("{} '{}'", message, token.text)),
+            None => (self.query_len, format!("{} at end of query", message)),
+        };
+        ConversionError::ParseError { position, message }
+    }
+
+    fn parse_or(&mut self) -> ConversionResult<Expr> {
+        let mut items = vec![self.parse_and()?];
+        while self.peek() == Some(&TokenKind::Or) {
+            self.advance();
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expr::Or(items)
+        })
+    }
+
+    fn parse_and(&mut self) -> ConversionResult<Expr> {
+        let mut items = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(TokenKind::And) => {
+                    self.advance();
+                }
+                // Implicit AND (Shodan separates conditions with spaces)
+                Some(
+                    TokenKind::Not
+                    | TokenKind::Minus
+                    | TokenKind::LeftParen
+                    | TokenKind::Field(_)
+                    | TokenKind::Value(_),
+                ) => {}
+                _ => break,
+            }
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expr::And(items)
+        })
+    }
+
+    fn parse_unary(&mut self) -> ConversionResult<Expr> {
+        match self.peek() {
+            Some(TokenKind::Not) => {
+                self.advance();
+                let operand = self.parse_unary()?;
+                Ok(Self::negate(operand))
+            }
+            Some(TokenKind::Minus) => {
+                self.advance();
+                let operand = self.parse_primary()?;
+                Ok(Self::negate(operand))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    // Fold a prefix negation of a plain predicate into `NotEqual`
+    fn negate(operand: Expr) -> Expr {
+        match operand {
+            Expr::Predicate(Predicate {
+                field,
+                comparison: Comparison::Equal,
+                value,
+            }) => Expr::Predicate(Predicate {
+                field,
+                comparison: Comparison::NotEqual,
+                value,
+            }),
+            other => Expr::Not(Box::new(other)),
+        }
+    }
+
+    fn parse_primary(&mut self) -> ConversionResult<Expr> {
+        match self.peek() {
+            Some(TokenKind::LeftParen) => {
+                self.advance();
+                let inner = self.parse_or()?;
+                if self.peek() != Some(&TokenKind::RightParen) {
+                    return Err(self.error_here("expected ')'"));
+                }
+                self.advance();
+                Ok(Expr::Group(Box::new(inner)))
+            }
+            Some(TokenKind::Field(_)) => {
+                let field = match self.advance().map(|token| token.kind) {
+                    Some(TokenKind::Field(field)) => field,
+                    _ => unreachable!(),
+                };
+                let comparison = match self.advance().map(|token| token.kind) {
+                    Some(TokenKind::Operator(comparison)) => comparison,
+                    _ => return Err(self.error_here("expected a field operator")),
+                };
+                let value = match self.advance().map(|token| token.kind) {
+                    Some(TokenKind::Value(value)) => value,
+                    _ => return Err(self.error_here("expected a value")),
+                };
+                Ok(Expr::Predicate(Predicate {
+                    field,
+                    comparison,
+                    value,
+                }))
+            }
+            Some(TokenKind::Value(_)) => match self.advance().map(|token| token.kind) {
+                Some(TokenKind::Value(value)) => Ok(Expr::Term(value)),
+                _ => unreachable!(),
+            },
+            _ => Err(self.error_here("expected a condition")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::ast::Value;
+
+    fn predicate(field: &str, comparison: Comparison, value: Value) -> Expr {
+        Expr::Predicate(Predicate {
+            field: field.to_string(),
+            comparison,
+            value,
+        })
+    }
+
+    #[test]
+    fn test_quoted_value_with_operators_is_one_predicate() {
+        assert_eq!(
+            Parser::parse(r#"title="A && B" && body="x AND y: z""#).unwrap(),
+            Expr::And(vec![
+                predicate("title", Comparison::Equal, Value::quoted("A && B")),
+                predicate("body", Comparison::Equal, Value::quoted("x AND y: z")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_precedence_and_nested_groups() {
+        let expr = Parser::parse(r#"a="1" || b="2" && ((c="3" || d!="4"))"#).unwrap();
+        assert_eq!(
+            expr,
+            Expr::Or(vec![
+                predicate("a", Comparison::Equal, Value::quoted("1")),
+                Expr::And(vec![
+                    predicate("b", Comparison::Equal, Value::quoted("2")),
+                    Expr::Group(Box::new(Expr::Group(Box::new(Expr::Or(vec![
+                        predicate("c", Comparison::Equal, Value::quoted("3")),
+                        predicate("d", Comparison::NotEqual, Value::quoted("4")),
+                    ]))))),
+                ]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_negation_dialects_share_one_tree() {
+        let expected = Expr::And(vec![
+            predicate("port", Comparison::Equal, Value::bare("80")),
+            predicate("country", Comparison::NotEqual, Value::quoted("CN")),
+        ]);
+        assert_eq!(
+            Parser::parse(r#"port=80 && country!="CN""#).unwrap(),
+            expected
+        );
+        assert_eq!(
+            Parser::parse(r#"port:80 AND NOT country:"CN""#).unwrap(),
+            expected
+        );
+        assert_eq!(Parser::parse(r#"port:80 -country:"CN""#).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_not_group_and_free_text() {
+        assert_eq!(
+            Parser::parse(r#"nginx not (port:80 or port:443)"#).unwrap(),
+            Expr::And(vec![
+                Expr::Term(Value::bare("nginx")),
+                Expr::Not(Box::new(Expr::Group(Box::new(Expr::Or(vec![
+                    predicate("port", Comparison::Equal, Value::bare("80")),
+                    predicate("port", Comparison::Equal, Value::bare("443")),
+                ]))))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_errors_carry_positions() {
+        let position = |query: &str| match Parser::parse(query) {
+            Err(ConversionError::ParseError { position, .. }) => position,
+            other => panic!("unexpected result: {:?}", other),
+        };
+        assert_eq!(position(r#"(ip="1.1.1.1""#), 13);
+        assert_eq!(position(r#"ip="1.1.1.1")"#), 12);
+        assert_eq!(position(r#"ip="1" && "#), 10);
+        assert_eq!(position("   "), 3);
+    }
+}
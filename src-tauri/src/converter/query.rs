@@ -1,6 +1,37 @@
 use crate::config::ConfigManager;
-use crate::converter::{FieldConverter, OperatorConverter, SyntaxValidator};
-use crate::error::ConversionResult;
+use crate::converter::ast::Expr;
+use crate::converter::translate::Translation;
+use crate::converter::{
+    Diagnostic, DialectDetector, DialectScore, Parser, Renderer, SyntaxValidator, TimeRange,
+};
+use crate::error::{ConversionError, ConversionResult};
+use serde::Serialize;
+
+/// How a conversion changed a predicate of the source query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningKind {
+    /// Removed because the target platform has no equivalent field
+    Dropped,
+    /// Kept with weaker semantics than the source query
+    Approximated,
+}
+
+/// A predicate that could not be converted faithfully
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConversionWarning {
+    pub kind: WarningKind,
+    /// Predicate as written in the source dialect
+    pub predicate: String,
+    pub message: String,
+}
+
+/// Converted query and everything that was lost on the way
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Conversion {
+    pub query: String,
+    pub warnings: Vec<ConversionWarning>,
+}
 
 /// Main query converter
 pub struct QueryConverter {
@@ -19,34 +50,110 @@ impl QueryConverter {
         SyntaxValidator::validate_query_syntax(query, from_config, platform_name)
     }
 
+    /// List every syntax problem in the query for the source platform
+    pub fn diagnose(&self, query: &str, platform_name: &str) -> ConversionResult<Vec<Diagnostic>> {
+        let from_config = self.config_manager.get_platform_config(platform_name)?;
+        Ok(SyntaxValidator::diagnose(query, from_config, platform_name))
+    }
+
     /// Convert query from one platform to another
+    ///
+    /// Field names, match variants and values are translated with the rules of
+    /// config.json. Predicates without an equivalent on the target platform are
+    /// dropped and reported in [`Conversion::warnings`] together with approximated ones.
     pub fn convert(
         &self,
         query: &str,
         from_platform: &str,
         to_platform: &str,
-    ) -> ConversionResult<String> {
+    ) -> ConversionResult<Conversion> {
         let from_config = self.config_manager.get_platform_config(from_platform)?;
         let to_config = self.config_manager.get_platform_config(to_platform)?;
 
         // If same platform, return directly
-        if from_platform == to_platform {
-            return Ok(query.to_string());
+        if from_platform == to_platform || query.trim().is_empty() {
+            return Ok(Conversion {
+                query: query.trim().to_string(),
+                warnings: Vec::new(),
+            });
         }
 
-        // Normalize the query first (convert logical operators to uppercase)
-        let mut result = SyntaxValidator::normalize_query(query);
+        let expr = Parser::parse(query)?;
 
-        // Convert not equal operators first (needs to be done before field conversion)
-        result = OperatorConverter::convert_not_equal_operator(&result, from_config, to_config);
+        let mut translation = Translation::new(from_config, to_config, to_platform);
+        let converted = translation.translate(&expr);
+        let warnings = translation.into_warnings();
+
+        match converted {
+            Some(expr) => Ok(Conversion {
+                query: Renderer::render(&expr, to_config),
+                warnings,
+            }),
+            // Nothing of the query survives, an empty query would match everything
+            None => Err(Self::lossy(to_platform, &warnings)),
+        }
+    }
 
-        // Convert field prefixes
-        result = FieldConverter::convert_fields(&result, from_config, to_config);
+    /// Convert query like [`convert`](Self::convert), refusing any lossy conversion
+    pub fn convert_strict(
+        &self,
+        query: &str,
+        from_platform: &str,
+        to_platform: &str,
+    ) -> ConversionResult<Conversion> {
+        let conversion = self.convert(query, from_platform, to_platform)?;
+        if conversion.warnings.is_empty() {
+            Ok(conversion)
+        } else {
+            Err(Self::lossy(to_platform, &conversion.warnings))
+        }
+    }
 
-        // Convert other logical operators
-        result = OperatorConverter::convert_other_operators(&result, from_config, to_config);
+    fn lossy(platform: &str, warnings: &[ConversionWarning]) -> ConversionError {
+        ConversionError::LossyConversion {
+            platform: platform.to_string(),
+            details: warnings
+                .iter()
+                .map(|warning| format!("{}: {}", warning.predicate, warning.message))
+                .collect(),
+        }
+    }
 
-        Ok(result)
+    /// Rank every platform by how well the query fits its dialect
+    pub fn detect_dialect(&self, query: &str) -> Vec<DialectScore> {
+        DialectDetector::new(&self.config_manager).detect(query)
+    }
+
+    /// Restrict a query of the platform to a time range using its `time_range` rule
+    pub fn apply_time_range(
+        &self,
+        query: &str,
+        platform: &str,
+        time_range: &TimeRange,
+    ) -> ConversionResult<String> {
+        let config = self.config_manager.get_platform_config(platform)?;
+        let rule = config.time_range.as_ref().ok_or_else(|| {
+            ConversionError::ConfigurationError(format!(
+                "No time range rule for platform: {}",
+                platform
+            ))
+        })?;
+        let condition = time_range.condition(rule, chrono::Local::now().date_naive());
+
+        let mut items = Vec::new();
+        let queries = [query, condition.as_str()];
+        for part in queries.into_iter().filter(|part| !part.trim().is_empty()) {
+            match Parser::parse(part)? {
+                Expr::And(nested) => items.extend(nested),
+                expr => items.push(expr),
+            }
+        }
+        let expr = if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expr::And(items)
+        };
+        Ok(Renderer::render(&expr, config))
     }
 
     /// Get list of supported platforms
@@ -60,3 +167,173 @@ impl QueryConverter {
         self.config_manager.is_platform_supported(platform)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ConversionError;
+
+    fn converter() -> QueryConverter {
+        let config_path = concat!(env!("CARGO_MANIFEST_DIR"), "/config.json");
+        QueryConverter::new(ConfigManager::from_file(config_path).unwrap())
+    }
+
+    #[test]
+    fn test_convert_keeps_quoted_values_intact() {
+        assert_eq!(
+            converter()
+                .convert(r#"title="A && B" && port="80""#, "fofa", "quake")
+                .unwrap()
+                .query,
+            r#"title:"A && B" AND port:"80""#
+        );
+    }
+
+    #[test]
+    fn test_convert_maps_fields_and_negation() {
+        assert_eq!(
+            converter()
+                .convert(
+                    r#"web.title="登录" && (ip.port="443" || ip.port="8443") && domain!="a.com""#,
+                    "hunter",
+                    "shodan"
+                )
+                .unwrap()
+                .query,
+            r#"http.title:"登录" (port:"443" OR port:"8443") -hostname:"a.com""#
+        );
+        assert_eq!(
+            converter()
+                .convert(r#"http.title:"x y" -hostname:"a.com""#, "shodan", "hunter")
+                .unwrap()
+                .query,
+            r#"web.title="x y" && domain.suffix!="a.com""#
+        );
+    }
+
+    #[test]
+    fn test_convert_reports_parse_errors() {
+        assert!(matches!(
+            converter().convert(r#"title="unterminated"#, "fofa", "quake"),
+            Err(ConversionError::ParseError { position: 6, .. })
+        ));
+    }
+
+    #[test]
+    fn test_convert_drops_unmapped_predicates_with_warnings() {
+        let conversion = converter()
+            .convert(
+                r#"icp="京ICP备" && (title="admin" || icp="沪ICP备") && port="80""#,
+                "fofa",
+                "shodan",
+            )
+            .unwrap();
+        assert_eq!(conversion.query, r#"(http.title:"admin") port:"80""#);
+        assert_eq!(conversion.warnings.len(), 2);
+        assert!(conversion
+            .warnings
+            .iter()
+            .all(|warning| warning.kind == WarningKind::Dropped));
+        assert_eq!(conversion.warnings[0].predicate, r#"icp="京ICP备""#);
+    }
+
+    #[test]
+    fn test_convert_reports_approximated_exact_match() {
+        let conversion = converter()
+            .convert(r#"title=="admin""#, "fofa", "quake")
+            .unwrap();
+        assert_eq!(conversion.query, r#"title:"admin""#);
+        assert_eq!(conversion.warnings[0].kind, WarningKind::Approximated);
+    }
+
+    #[test]
+    fn test_strict_mode_refuses_lossy_conversion() {
+        let converter = converter();
+        assert!(matches!(
+            converter.convert_strict(r#"title="a" && icp="b""#, "fofa", "shodan"),
+            Err(ConversionError::LossyConversion { ref platform, ref details })
+                if platform == "shodan" && details.len() == 1
+        ));
+        assert!(converter
+            .convert_strict(r#"title="a""#, "fofa", "shodan")
+            .is_ok());
+        // Nothing left to search for is an error even without strict mode
+        assert!(matches!(
+            converter.convert(r#"icp="b""#, "fofa", "shodan"),
+            Err(ConversionError::LossyConversion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_convert_applies_value_rules() {
+        let converter = converter();
+        let convert =
+            |query: &str, from: &str, to: &str| converter.convert(query, from, to).unwrap().query;
+
+        // Country codes vs localized names, in both directions
+        assert_eq!(
+            convert(r#"country="中国""#, "hunter", "fofa"),
+            r#"country="CN""#
+        );
+        assert_eq!(
+            convert(r#"country="cn""#, "fofa", "censys"),
+            r#"location.country:"China""#
+        );
+        assert_eq!(
+            convert(r#"protocol="https""#, "fofa", "quake"),
+            r#"service:"http/ssl""#
+        );
+        assert_eq!(
+            convert("is_web=true", "hunter", "fofa"),
+            r#"type="subdomain""#
+        );
+        // Suffix match variants
+        assert_eq!(
+            convert(r#"domain="a.com""#, "fofa", "hunter"),
+            r#"domain.suffix="a.com""#
+        );
+        assert_eq!(
+            convert(r#"domain.suffix="a.com""#, "hunter", "shodan"),
+            r#"hostname:"a.com""#
+        );
+        // Ranges on dialects without range values
+        assert_eq!(
+            convert("port: [80 TO 90]", "quake", "fofa"),
+            "port>=80 && port<=90"
+        );
+        assert_eq!(
+            convert("port: [1024 TO *]", "quake", "hunter"),
+            "ip.port>=1024"
+        );
+    }
+
+    #[test]
+    fn test_convert_warns_about_weaker_match_modes() {
+        let conversion = converter()
+            .convert(r#"domain.suffix="a.com""#, "hunter", "quake")
+            .unwrap();
+        assert_eq!(conversion.query, r#"domain:"a.com""#);
+        assert_eq!(conversion.warnings[0].kind, WarningKind::Approximated);
+    }
+
+    #[test]
+    fn test_apply_time_range() {
+        let converter = converter();
+        let between = TimeRange::Between {
+            start: "2024-01-01".to_string(),
+            end: "2024-01-31".to_string(),
+        };
+        assert_eq!(
+            converter
+                .apply_time_range(r#"title="a" || title="b""#, "fofa", &between)
+                .unwrap(),
+            r#"(title="a" || title="b") && after="2024-01-01" && before="2024-01-31""#
+        );
+        assert_eq!(
+            converter
+                .apply_time_range("", "quake", &TimeRange::LastDays(30))
+                .unwrap(),
+            "time:[now-30d TO now]"
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use crate::config::PlatformConfig;
+use crate::converter::ast::{Comparison, Expr, Predicate, Value, ValueKind};
+
+/// Renders an expression tree in the syntax described by a [`PlatformConfig`]
+pub struct Renderer<'a> {
+    config: &'a PlatformConfig,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(config: &'a PlatformConfig) -> Self {
+        Self { config }
+    }
+
+    /// Render a whole query
+    pub fn render(expr: &Expr, config: &PlatformConfig) -> String {
+        let mut out = String::new();
+        Renderer::new(config).write_expr(expr, &mut out);
+        out
+    }
+
+    /// Whether the dialect can negate an arbitrary sub-expression with `NOT`
+    fn has_prefix_not(&self) -> bool {
+        self.config.operators.not_equal.trim() == "NOT"
+    }
+
+    fn separator(operator: &str) -> String {
+        let operator = operator.trim();
+        if operator.is_empty() {
+            " ".to_string()
+        } else {
+            format!(" {} ", operator)
+        }
+    }
+
+    fn write_expr(&self, expr: &Expr, out: &mut String) {
+        match expr {
+            Expr::Predicate(predicate) => self.write_predicate(predicate, out),
+            Expr::Term(value) => Self::write_value(value, out),
+            Expr::Group(inner) => self.write_group(inner, out),
+            Expr::Not(inner) => {
+                if !self.has_prefix_not() {
+                    // Push the negation into the predicates when the dialect only
+                    // negates single conditions (`!=`, `-field:`)
+                    if let Some(negated) = self.push_not(inner) {
+                        return self.write_expr(&negated, out);
+                    }
+                }
+                out.push_str("NOT ");
+                self.write_operand(inner, out);
+            }
+            Expr::And(items) => {
+                let separator = Self::separator(&self.config.operators.and);
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(&separator);
+                    }
+                    // OR binds looser than AND and needs explicit grouping
+                    if matches!(item, Expr::Or(_)) {
+                        self.write_group(item, out);
+                    } else {
+                        self.write_expr(item, out);
+                    }
+                }
+            }
+            Expr::Or(items) => {
+                let separator = Self::separator(&self.config.operators.or);
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(&separator);
+                    }
+                    self.write_expr(item, out);
+                }
+            }
+        }
+    }
+
+    // Operand of a prefix NOT: anything wider than a single condition is grouped
+    fn write_operand(&self, expr: &Expr, out: &mut String) {
+        if matches!(expr, Expr::And(_) | Expr::Or(_)) {
+            self.write_group(expr, out);
+        } else {
+            self.write_expr(expr, out);
+        }
+    }
+
+    fn write_group(&self, inner: &Expr, out: &mut String) {
+        out.push_str(&self.config.operators.left_paren);
+        self.write_expr(inner, out);
+        out.push_str(&self.config.operators.right_paren);
+    }
+
+    /// Apply De Morgan's laws down to the predicates, `None` if some part cannot be negated
+    fn push_not(&self, expr: &Expr) -> Option<Expr> {
+        match expr {
+            Expr::Predicate(predicate) => {
+                // Dialects without an exact operator render an exact match as a plain match
+                let comparison = match predicate.comparison {
+                    Comparison::Exact if self.config.operators.exact.is_none() => {
+                        Comparison::Equal
+                    }
+                    comparison => comparison,
+                };
+                Some(Expr::Predicate(Predicate {
+                    comparison: comparison.negate()?,
+                    ..predicate.clone()
+                }))
+            }
+            Expr::Term(_) => None,
+            Expr::Not(inner) => Some((**inner).clone()),
+            Expr::Group(inner) => Some(Expr::Group(Box::new(self.push_not(inner)?))),
+            Expr::And(items) => Some(Expr::Or(
+                items
+                    .iter()
+                    .map(|item| self.push_not(item))
+                    .collect::<Option<_>>()?,
+            )),
+            Expr::Or(items) => Some(Expr::And(
+                items
+                    .iter()
+                    .map(|item| self.push_not(item))
+                    .collect::<Option<_>>()?,
+            )),
+        }
+    }
+
+    fn write_predicate(&self, predicate: &Predicate, out: &mut String) {
+        let operators = &self.config.operators;
+        let equal = operators.equal.as_str();
+        let field = predicate.field.as_str();
+
+        match predicate.comparison {
+            Comparison::Equal => {
+                out.push_str(field);
+                out.push_str(equal);
+            }
+            Comparison::Exact => {
+                out.push_str(field);
+                out.push_str(operators.exact.as_deref().unwrap_or(equal));
+            }
+            Comparison::NotEqual => match operators.not_equal.trim() {
+                "NOT" => {
+                    out.push_str("NOT ");
+                    out.push_str(field);
+                    out.push_str(equal);
+                }
+                "-" => {
+                    out.push('-');
+                    out.push_str(field);
+                    out.push_str(equal);
+                }
+                not_equal => {
+                    out.push_str(field);
+                    out.push_str(not_equal);
+                }
+            },
+            ordering => {
+                out.push_str(field);
+                if equal != "=" {
+                    out.push_str(equal);
+                }
+                out.push_str(ordering.ordering_symbol().unwrap_or_default());
+            }
+        }
+
+        Self::write_value(&predicate.value, out);
+    }
+
+    fn write_value(value: &Value, out: &mut String) {
+        match value.kind {
+            ValueKind::Bare => out.push_str(&value.text),
+            ValueKind::Range => {
+                out.push('[');
+                out.push_str(&value.text);
+                out.push(']');
+            }
+            ValueKind::Quoted => {
+                out.push('"');
+                let mut chars = value.text.chars().peekable();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        // A backslash only needs escaping where the lexer would read it as one
+                        '\\' if matches!(chars.peek(), None | Some('"') | Some('\\')) => {
+                            out.push_str("\\\\")
+                        }
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::converter::parser::Parser;
+    use quickcheck::{Arbitrary, Gen, TestResult};
+    use quickcheck_macros::quickcheck;
+    use std::collections::HashMap;
+
+    fn platform_configs() -> HashMap<String, PlatformConfig> {
+        serde_json::from_str(include_str!("../../config.json")).unwrap()
+    }
+
+    fn config(platform: &str) -> PlatformConfig {
+        platform_configs().remove(platform).unwrap()
+    }
+
+    const WORD_CHARS: [char; 8] = ['a', 'x', 'Z', '7', '0', '_', '中', 'é'];
+    const VALUE_CHARS: [char; 14] = [
+        'a', 'Z', '1', '.', '-', '/', '*', ':', '_', '&', '|', '=', '"', '中',
+    ];
+    const FIELDS: [&str; 6] = [
+        "ip",
+        "domain",
+        "web.title",
+        "ssl.cert.serial",
+        "tls_SN",
+        "port",
+    ];
+    const COMPARISONS: [Comparison; 8] = [
+        Comparison::Equal,
+        Comparison::Equal,
+        Comparison::Exact,
+        Comparison::NotEqual,
+        Comparison::Greater,
+        Comparison::GreaterEqual,
+        Comparison::Less,
+        Comparison::LessEqual,
+    ];
+
+    /// Random expression tree shaped like parser output
+    #[derive(Debug, Clone)]
+    struct ArbitraryExpr(Expr);
+
+    fn pick<T: Copy>(g: &mut Gen, items: &[T]) -> T {
+        *g.choose(items).unwrap()
+    }
+
+    fn word(g: &mut Gen, rest: &[char]) -> String {
+        let len = usize::arbitrary(g) % 8;
+        std::iter::once(pick(g, &WORD_CHARS))
+            .chain((0..len).map(|_| pick(g, rest)))
+            .collect()
+    }
+
+    fn arbitrary_value(g: &mut Gen) -> Value {
+        match u8::arbitrary(g) % 4 {
+            0 => Value::bare(word(g, &VALUE_CHARS)),
+            1 => Value::range(format!(
+                "{} TO {}",
+                word(g, &WORD_CHARS),
+                word(g, &WORD_CHARS)
+            )),
+            // Any text, including quotes, backslashes and operators
+            _ => Value::quoted(String::arbitrary(g)),
+        }
+    }
+
+    fn arbitrary_leaf(g: &mut Gen) -> Expr {
+        if u8::arbitrary(g) % 5 == 0 {
+            let text = word(g, &WORD_CHARS);
+            if !["AND", "OR", "NOT"].contains(&text.to_ascii_uppercase().as_str()) {
+                return Expr::Term(Value::bare(text));
+            }
+        }
+        Expr::Predicate(Predicate {
+            field: pick(g, &FIELDS).to_string(),
+            comparison: pick(g, &COMPARISONS),
+            value: arbitrary_value(g),
+        })
+    }
+
+    // `And` never directly holds `And` or `Or`, and `Or` never directly holds `Or`
+    fn arbitrary_expr(g: &mut Gen, depth: usize) -> Expr {
+        if depth == 0 {
+            return arbitrary_leaf(g);
+        }
+        let children = |g: &mut Gen, allow_and: bool| -> Vec<Expr> {
+            let count = 2 + usize::arbitrary(g) % 3;
+            let mut items = Vec::with_capacity(count);
+            while items.len() < count {
+                match arbitrary_expr(g, depth - 1) {
+                    Expr::And(_) if !allow_and => {}
+                    Expr::Or(_) => {}
+                    child => items.push(child),
+                }
+            }
+            items
+        };
+        match u8::arbitrary(g) % 6 {
+            0 => Expr::And(children(g, false)),
+            1 => Expr::Or(children(g, true)),
+            2 => Expr::Group(Box::new(arbitrary_expr(g, depth - 1))),
+            3 => Expr::Not(Box::new(Expr::Group(Box::new(arbitrary_expr(
+                g,
+                depth - 1,
+            ))))),
+            _ => arbitrary_leaf(g),
+        }
+    }
+
+    impl Arbitrary for ArbitraryExpr {
+        fn arbitrary(g: &mut Gen) -> Self {
+            let depth = usize::arbitrary(g) % 4;
+            ArbitraryExpr(arbitrary_expr(g, depth))
+        }
+    }
+
+    // Whether the dialect can express the tree without rewriting it
+    fn representable(expr: &Expr, config: &PlatformConfig) -> bool {
+        match expr {
+            Expr::Predicate(predicate) => {
+                predicate.comparison != Comparison::Exact || config.operators.exact.is_some()
+            }
+            Expr::Term(_) => true,
+            Expr::Not(inner) => {
+                config.operators.not_equal.trim() == "NOT" && representable(inner, config)
+            }
+            Expr::Group(inner) => representable(inner, config),
+            Expr::And(items) | Expr::Or(items) => {
+                items.iter().all(|item| representable(item, config))
+            }
+        }
+    }
+
+    #[quickcheck]
+    fn property_render_then_parse_round_trips(expr: ArbitraryExpr) -> TestResult {
+        let mut checked = 0;
+        for (platform, config) in platform_configs() {
+            if !representable(&expr.0, &config) {
+                continue;
+            }
+            let rendered = Renderer::render(&expr.0, &config);
+            match Parser::parse(&rendered) {
+                Ok(parsed) if parsed == expr.0 => checked += 1,
+                other => {
+                    return TestResult::error(format!(
+                        "{}: {:?} rendered as {:?} parsed as {:?}",
+                        platform, expr.0, rendered, other
+                    ))
+                }
+            }
+        }
+        if checked == 0 {
+            TestResult::discard()
+        } else {
+            TestResult::passed()
+        }
+    }
+
+    #[quickcheck]
+    fn property_rendering_is_stable_across_dialects(expr: ArbitraryExpr) -> TestResult {
+        // Whatever a dialect has to rewrite, a second pass changes nothing
+        for (platform, config) in platform_configs() {
+            let once = Renderer::render(&expr.0, &config);
+            let twice = Parser::parse(&once).map(|parsed| Renderer::render(&parsed, &config));
+            if !matches!(&twice, Ok(twice) if *twice == once) {
+                return TestResult::error(format!(
+                    "{}: {:?} rendered as {:?} then {:?}",
+                    platform, expr.0, once, twice
+                ));
+            }
+        }
+        TestResult::passed()
+    }
+
+    #[test]
+    fn test_render_dialects() {
+        let expr = Parser::parse(r#"title="A && B" && port="80" && country!="CN""#).unwrap();
+        assert_eq!(
+            Renderer::render(&expr, &config("quake")),
+            r#"title:"A && B" AND port:"80" AND NOT country:"CN""#
+        );
+        assert_eq!(
+            Renderer::render(&expr, &config("shodan")),
+            r#"title:"A && B" port:"80" -country:"CN""#
+        );
+    }
+
+    #[test]
+    fn test_not_group_is_pushed_down_for_inequality_dialects() {
+        let expr = Parser::parse(r#"NOT (port:80 AND title:"x") AND ip:"1.1.1.1""#).unwrap();
+        assert_eq!(
+            Renderer::render(&expr, &config("fofa")),
+            r#"(port!=80 || title!="x") && ip="1.1.1.1""#
+        );
+        assert_eq!(
+            Renderer::render(&expr, &config("censys")),
+            r#"NOT (port:80 AND title:"x") AND ip:"1.1.1.1""#
+        );
+    }
+
+    #[test]
+    fn test_quoted_values_escape_only_where_needed() {
+        let expr = Expr::Term(Value::quoted(r#"a\d "q" end\"#));
+        let rendered = Renderer::render(&expr, &config("fofa"));
+        assert_eq!(rendered, r#""a\d \"q\" end\\""#);
+        assert_eq!(Parser::parse(&rendered).unwrap(), expr);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::config::TimeRangeRule;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Time range of an asset search
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeRange {
+    /// Assets seen in the last N days
+    LastDays(i64),
+    /// Assets seen between two `YYYY-MM-DD` dates
+    Between { start: String, end: String },
+}
+
+impl TimeRange {
+    /// Time range from the export options (`30d`, `custom` with both dates), `None` for `all`
+    pub fn from_options(
+        time_range: &str,
+        start_date: &Option<String>,
+        end_date: &Option<String>,
+    ) -> Option<TimeRange> {
+        match time_range {
+            "all" => None,
+            "custom" => match (start_date, end_date) {
+                (Some(start), Some(end)) => Some(TimeRange::Between {
+                    start: start.clone(),
+                    end: end.clone(),
+                }),
+                _ => None,
+            },
+            days => days
+                .trim_end_matches('d')
+                .parse()
+                .ok()
+                .map(TimeRange::LastDays),
+        }
+    }
+
+    /// Query condition for the range written with a platform's rule
+    pub fn condition(&self, rule: &TimeRangeRule, today: NaiveDate) -> String {
+        // Dates that are not `YYYY-MM-DD` are passed through as written
+        let format_date = |date: &str| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map(|d| d.format(&rule.date_format).to_string())
+                .unwrap_or_else(|_| date.to_string())
+        };
+
+        match self {
+            TimeRange::LastDays(days) => {
+                let since = today - chrono::Duration::days(*days);
+                rule.relative
+                    .replace("{days}", &days.to_string())
+                    .replace("{since}", &since.format(&rule.date_format).to_string())
+            }
+            TimeRange::Between { start, end } => rule
+                .absolute
+                .replace("{start}", &format_date(start))
+                .replace("{end}", &format_date(end)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(relative: &str, absolute: &str, date_format: &str) -> TimeRangeRule {
+        TimeRangeRule {
+            relative: relative.to_string(),
+            absolute: absolute.to_string(),
+            date_format: date_format.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_options_and_placeholders() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let shodan = rule(
+            r#"after:"{since}""#,
+            r#"after:"{start}" before:"{end}""#,
+            "%d/%m/%Y",
+        );
+
+        assert_eq!(TimeRange::from_options("all", &None, &None), None);
+        let last_month = TimeRange::from_options("30d", &None, &None).unwrap();
+        assert_eq!(
+            last_month.condition(&shodan, today),
+            r#"after:"01/03/2024""#
+        );
+
+        let between = TimeRange::from_options(
+            "custom",
+            &Some("2024-01-02".to_string()),
+            &Some("2024-02-03".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            between.condition(&shodan, today),
+            r#"after:"02/01/2024" before:"03/02/2024""#
+        );
+
+        let quake = rule("time: [now-{days}d TO now]", "", "%Y-%m-%d");
+        assert_eq!(
+            last_month.condition(&quake, today),
+            "time: [now-30d TO now]"
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::config::{MatchMode, PlatformConfig};
+use crate::converter::ast::{Comparison, Expr, Predicate, Value, ValueKind};
+use crate::converter::{ConversionWarning, Renderer, WarningKind};
+use std::collections::HashMap;
+
+/// Field and value translation of one expression tree between two dialects
+///
+/// Predicates go through the shared keys of config.json: the source field and
+/// value are resolved to their shared form, then written the way the target
+/// platform expects. Anything that cannot be carried over is recorded as a
+/// [`ConversionWarning`].
+pub struct Translation<'a> {
+    from_config: &'a PlatformConfig,
+    to_config: &'a PlatformConfig,
+    to_platform: &'a str,
+    warnings: Vec<ConversionWarning>,
+}
+
+/// Platform value for a shared value, ignoring ASCII case
+fn lookup<'m>(values: &'m HashMap<String, String>, text: &str) -> Option<&'m String> {
+    values.get(text).or_else(|| {
+        values
+            .iter()
+            .filter(|(shared, _)| shared.eq_ignore_ascii_case(text))
+            .min_by_key(|(shared, _)| shared.as_str())
+            .map(|(_, value)| value)
+    })
+}
+
+/// Shared value for a platform value, ignoring ASCII case
+fn reverse_lookup<'m>(values: &'m HashMap<String, String>, text: &str) -> Option<&'m String> {
+    values
+        .iter()
+        .filter(|(_, value)| value.eq_ignore_ascii_case(text))
+        .map(|(shared, _)| shared)
+        .min()
+}
+
+fn describe(mode: MatchMode) -> &'static str {
+    match mode {
+        MatchMode::Fuzzy => "fuzzy",
+        MatchMode::Suffix => "suffix",
+    }
+}
+
+/// Bound of a `[from TO to]` range, `None` for an open `*` bound
+fn range_bound(text: &str) -> Option<Value> {
+    let text = text.trim();
+    if text == "*" {
+        return None;
+    }
+    Some(
+        match text
+            .strip_prefix('"')
+            .and_then(|text| text.strip_suffix('"'))
+        {
+            Some(inner) => Value::quoted(inner),
+            None => Value::bare(text),
+        },
+    )
+}
+
+impl<'a> Translation<'a> {
+    pub fn new(
+        from_config: &'a PlatformConfig,
+        to_config: &'a PlatformConfig,
+        to_platform: &'a str,
+    ) -> Self {
+        Self {
+            from_config,
+            to_config,
+            to_platform,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Warnings collected so far
+    pub fn into_warnings(self) -> Vec<ConversionWarning> {
+        self.warnings
+    }
+
+    /// Translated expression, `None` when every predicate in it was dropped
+    pub fn translate(&mut self, expr: &Expr) -> Option<Expr> {
+        match expr {
+            Expr::Predicate(predicate) => self.translate_predicate(predicate),
+            Expr::Term(value) => Some(Expr::Term(value.clone())),
+            Expr::Not(inner) => Some(Expr::Not(Box::new(self.translate(inner)?))),
+            Expr::Group(inner) => Some(Expr::Group(Box::new(self.translate(inner)?))),
+            Expr::And(items) => {
+                let items = self.translate_items(items, |expr| match expr {
+                    Expr::And(items) => Ok(items),
+                    other => Err(other),
+                });
+                Self::collapse(items, Expr::And)
+            }
+            Expr::Or(items) => {
+                let items = self.translate_items(items, |expr| match expr {
+                    Expr::Or(items) => Ok(items),
+                    other => Err(other),
+                });
+                Self::collapse(items, Expr::Or)
+            }
+        }
+    }
+
+    // A dropped operand may leave a node of the same kind as its parent, which is flattened
+    fn translate_items(
+        &mut self,
+        items: &[Expr],
+        same_kind: impl Fn(Expr) -> Result<Vec<Expr>, Expr>,
+    ) -> Vec<Expr> {
+        let mut translated = Vec::new();
+        for item in items {
+            match self.translate(item).map(&same_kind) {
+                Some(Ok(nested)) => translated.extend(nested),
+                Some(Err(expr)) => translated.push(expr),
+                None => {}
+            }
+        }
+        translated
+    }
+
+    fn collapse(mut items: Vec<Expr>, node: fn(Vec<Expr>) -> Expr) -> Option<Expr> {
+        match items.len() {
+            0 => None,
+            1 => items.pop(),
+            _ => Some(node(items)),
+        }
+    }
+
+    fn translate_predicate(&mut self, predicate: &Predicate) -> Option<Expr> {
+        let Some((key, match_mode)) = self.resolve_field(&predicate.field) else {
+            let message = format!(
+                "field '{}' has no equivalent on {}, the condition was dropped",
+                predicate.field, self.to_platform
+            );
+            self.warn(WarningKind::Dropped, predicate, message);
+            return None;
+        };
+        let (field, target_mode) = self.target_field(key, match_mode)?;
+
+        // Match modes only apply to the equality family
+        if target_mode != match_mode && predicate.comparison.ordering_symbol().is_none() {
+            let message = format!(
+                "{} has no {} match for this field, a {} match is used instead",
+                self.to_platform,
+                describe(match_mode),
+                describe(target_mode)
+            );
+            self.warn(WarningKind::Approximated, predicate, message);
+        }
+
+        if predicate.comparison == Comparison::Exact && self.to_config.operators.exact.is_none() {
+            let message = format!(
+                "{} has no exact match operator, a fuzzy match is used instead",
+                self.to_platform
+            );
+            self.warn(WarningKind::Approximated, predicate, message);
+        }
+
+        let translated = Predicate {
+            field,
+            comparison: predicate.comparison,
+            value: self.translate_value(key, &predicate.value),
+        };
+
+        if translated.value.kind == ValueKind::Range && !self.to_config.operators.range {
+            return self.split_range(predicate, translated);
+        }
+        Some(Expr::Predicate(translated))
+    }
+
+    /// Shared key and match mode of a source field whose key the target platform knows
+    ///
+    /// Several keys may share one platform field (Hunter maps both `domain` and
+    /// `host` to `domain`); a field variant wins, then a key equal to the field
+    /// name, then the keys in alphabetical order.
+    fn resolve_field(&self, field: &str) -> Option<(&'a str, MatchMode)> {
+        let from_config = self.from_config;
+        let variant = from_config
+            .variants
+            .get(field)
+            .map(|variant| (variant.key.as_str(), variant.match_mode));
+
+        let mut keys: Vec<&'a str> = from_config
+            .fields
+            .iter()
+            .filter(|(_, platform_field)| platform_field.as_str() == field)
+            .map(|(key, _)| key.as_str())
+            .collect();
+        keys.sort_by_key(|key| (*key != field, *key));
+
+        variant
+            .into_iter()
+            .chain(keys.into_iter().map(|key| (key, MatchMode::Fuzzy)))
+            .find(|(key, _)| {
+                self.to_config.fields.contains_key(*key)
+                    || self.to_config.variants.values().any(|v| v.key == *key)
+            })
+    }
+
+    /// Target field for a shared key, preferring a field with the requested match mode
+    fn target_field(&self, key: &str, match_mode: MatchMode) -> Option<(String, MatchMode)> {
+        let to_config = self.to_config;
+        let mode_of = |field: &str| {
+            to_config
+                .variants
+                .get(field)
+                .map_or(MatchMode::Fuzzy, |variant| variant.match_mode)
+        };
+
+        let variant = to_config
+            .variants
+            .iter()
+            .filter(|(_, variant)| variant.key == key && variant.match_mode == match_mode)
+            .map(|(field, _)| field)
+            .min();
+        if let Some(field) = variant {
+            return Some((field.clone(), match_mode));
+        }
+        if let Some(field) = to_config.fields.get(key) {
+            return Some((field.clone(), mode_of(field)));
+        }
+        to_config
+            .variants
+            .iter()
+            .filter(|(_, variant)| variant.key == key)
+            .min_by_key(|(field, _)| field.as_str())
+            .map(|(field, variant)| (field.clone(), variant.match_mode))
+    }
+
+    /// Source value → shared value → target value through the `values` tables
+    fn translate_value(&self, key: &str, value: &Value) -> Value {
+        if value.kind == ValueKind::Range {
+            return value.clone();
+        }
+        let shared = self
+            .from_config
+            .values
+            .get(key)
+            .and_then(|values| reverse_lookup(values, &value.text));
+        let target = self
+            .to_config
+            .values
+            .get(key)
+            .and_then(|values| lookup(values, shared.unwrap_or(&value.text)));
+
+        match target.or(shared) {
+            Some(text) => Value::quoted(text.as_str()),
+            None => value.clone(),
+        }
+    }
+
+    /// `field:[from TO to]` as `field>=from AND field<=to` for dialects without ranges
+    fn split_range(&mut self, source: &Predicate, predicate: Predicate) -> Option<Expr> {
+        let text = predicate.value.text.as_str();
+        let bounds = text
+            .find(" TO ")
+            .or_else(|| text.find(" to "))
+            .map(|at| (range_bound(&text[..at]), range_bound(&text[at + 4..])));
+
+        let Some((from, to)) = bounds.filter(|_| predicate.comparison == Comparison::Equal) else {
+            let message = format!(
+                "{} does not support range values, the range is kept as written",
+                self.to_platform
+            );
+            self.warn(WarningKind::Approximated, source, message);
+            return Some(Expr::Predicate(predicate));
+        };
+
+        let mut items: Vec<Expr> = [
+            (Comparison::GreaterEqual, from),
+            (Comparison::LessEqual, to),
+        ]
+        .into_iter()
+        .filter_map(|(comparison, bound)| {
+            Some(Expr::Predicate(Predicate {
+                field: predicate.field.clone(),
+                comparison,
+                value: bound?,
+            }))
+        })
+        .collect();
+
+        if items.is_empty() {
+            let message = "the range is open on both ends, the condition was dropped".to_string();
+            self.warn(WarningKind::Dropped, source, message);
+            return None;
+        }
+        Some(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expr::And(items)
+        })
+    }
+
+    fn warn(&mut self, kind: WarningKind, predicate: &Predicate, message: String) {
+        let predicate = Renderer::render(&Expr::Predicate(predicate.clone()), self.from_config);
+        self.warnings.push(ConversionWarning {
+            kind,
+            predicate,
+            message,
+        });
+    }
+}
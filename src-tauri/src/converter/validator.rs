@@ -1,206 +1,595 @@
 use crate::config::PlatformConfig;
+use crate::converter::ast::Comparison;
+use crate::converter::lexer::{Lexer, Span, Token, TokenKind};
+use crate::converter::parser::Parser;
 use crate::error::{ConversionError, ConversionResult};
-use once_cell::sync::Lazy;
-use regex::Regex;
+use serde::Serialize;
 
-static RE_LOGICAL_AND: Lazy<Regex> = Lazy::new(|| Regex::new(r"\band\b").unwrap());
-static RE_LOGICAL_OR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bor\b").unwrap());
-static RE_LOGICAL_NOT: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bnot\b").unwrap());
-static RE_FIELD_OP: Lazy<Regex> = Lazy::new(|| Regex::new(r"\w+(?:\.\w+)*([=:])").unwrap());
-static RE_NOT_WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bNOT\s+\w+").unwrap());
-static RE_FIELD_NAMES: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\w+(?:\.\w+)*)(?:[:=]|!=)").unwrap());
+/// Diagnostic severity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The platform will reject or misread the query
+    Error,
+    /// Accepted, but not in the platform's canonical form
+    Warning,
+}
+
+/// A single problem found in a query
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    /// Byte range in the query; an empty span marks an insertion point
+    pub span: Span,
+    pub severity: Severity,
+    pub message: String,
+    /// Replacement text for `span` that fixes the problem
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    fn error(span: Span, message: String, suggestion: Option<String>) -> Self {
+        Self {
+            span,
+            severity: Severity::Error,
+            message,
+            suggestion,
+        }
+    }
+
+    fn warning(span: Span, message: String, suggestion: Option<String>) -> Self {
+        Self {
+            span,
+            severity: Severity::Warning,
+            message,
+            suggestion,
+        }
+    }
+}
 
 /// Syntax validator for query statements
 pub struct SyntaxValidator;
 
 impl SyntaxValidator {
-    /// Normalize query by converting logical operators to uppercase
-    pub fn normalize_query(query: &str) -> String {
-        let mut normalized = query.to_string();
-        normalized = RE_LOGICAL_AND.replace_all(&normalized, "AND").to_string();
-        normalized = RE_LOGICAL_OR.replace_all(&normalized, "OR").to_string();
-        normalized = RE_LOGICAL_NOT.replace_all(&normalized, "NOT").to_string();
-        normalized
-    }
-
-    /// Validate query syntax against platform configuration
+    /// Validate query syntax against platform configuration, failing on the first error
     pub fn validate_query_syntax(
         query: &str,
         from_config: &PlatformConfig,
         platform_name: &str,
     ) -> ConversionResult<()> {
-        // First normalize the query
-        let normalized_query = Self::normalize_query(query);
-
-        // Check operator consistency (all fields should use the same operator type)
-        Self::validate_operator_consistency(query, from_config, platform_name)?;
-
-        // Check operator support using normalized query
-        Self::validate_operators(&normalized_query, from_config, platform_name)?;
-
-        // Check field support using original query (fields are case-sensitive)
-        Self::validate_fields(query, from_config, platform_name)?;
-
-        Ok(())
+        let diagnostics = Self::diagnose(query, from_config, platform_name);
+        match diagnostics
+            .into_iter()
+            .find(|diagnostic| diagnostic.severity == Severity::Error)
+        {
+            Some(diagnostic) => Err(ConversionError::SyntaxValidationFailed(format!(
+                "{} (at position {})",
+                diagnostic.message, diagnostic.span.start
+            ))),
+            None => Ok(()),
+        }
     }
 
-    /// Validate operator consistency (all fields should use the same operator type)
-    fn validate_operator_consistency(
+    /// Collect every problem in the query, ordered by position
+    pub fn diagnose(
         query: &str,
         from_config: &PlatformConfig,
         platform_name: &str,
-    ) -> ConversionResult<()> {
-        let mut used_operators = std::collections::HashSet::new();
+    ) -> Vec<Diagnostic> {
+        let platform = platform_name.to_uppercase();
+        let (tokens, lex_errors) = Lexer::tokenize_recovering(query);
 
-        // Extract all field operators from the query
-        for caps in RE_FIELD_OP.captures_iter(query) {
-            if let Some(op_match) = caps.get(1) {
-                used_operators.insert(op_match.as_str());
-            }
-        }
+        let mut diagnostics: Vec<Diagnostic> = lex_errors
+            .into_iter()
+            .map(|error| Self::from_parse_error(error, query))
+            .collect();
+        let structural_errors = !diagnostics.is_empty();
 
-        // Check if multiple different operators are used
-        if used_operators.len() > 1 {
-            let operators_list: Vec<&str> = used_operators.iter().cloned().collect();
-            return Err(ConversionError::SyntaxValidationFailed(
-                format!("Inconsistent field operators in query. Found: '{}'. {} platform expects consistent use of '{}'",
-                    operators_list.join(", "),
-                    platform_name.to_uppercase(),
-                    from_config.operators.equal)
-            ));
-        }
+        let paren_diagnostics = Self::check_parentheses(&tokens, from_config);
+        let structural_errors = structural_errors || !paren_diagnostics.is_empty();
+        diagnostics.extend(paren_diagnostics);
 
-        // Check if the used operator matches the platform's expected operator
-        if let Some(&used_op) = used_operators.iter().next() {
-            if used_op != from_config.operators.equal {
-                return Err(ConversionError::UnsupportedOperator {
-                    platform: platform_name.to_string(),
-                    operator: format!("field{}", used_op),
-                    suggestion: format!("field{}", from_config.operators.equal),
-                });
+        diagnostics.extend(Self::check_boolean_operators(
+            &tokens,
+            from_config,
+            &platform,
+        ));
+        diagnostics.extend(Self::check_field_operators(&tokens, from_config, &platform));
+        diagnostics.extend(Self::check_fields(&tokens, from_config, &platform));
+
+        // Grammar errors are only meaningful once the tokens themselves are sound
+        if !structural_errors && !tokens.is_empty() {
+            if let Err(error) = Parser::parse(query) {
+                diagnostics.push(Self::from_parse_error(error, query));
             }
         }
 
-        Ok(())
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.span.start, diagnostic.span.end));
+        diagnostics
     }
 
-    /// Validate operators used in the query (simplified for normalized input)
-    fn validate_operators(
-        query: &str,
-        from_config: &PlatformConfig,
-        platform_name: &str,
-    ) -> ConversionResult<()> {
-        // Check AND operator
-        if query.contains("AND") && from_config.operators.and != "AND" {
-            return Err(ConversionError::UnsupportedOperator {
-                platform: platform_name.to_string(),
-                operator: "AND".to_string(),
-                suggestion: from_config.operators.and.clone(),
-            });
-        }
+    fn from_parse_error(error: ConversionError, query: &str) -> Diagnostic {
+        let (position, message) = match error {
+            ConversionError::ParseError { position, message } => (position, message),
+            other => (0, other.to_string()),
+        };
+        let suggestion = if message.starts_with("unterminated quoted") {
+            Some(format!("{}\"", &query[position..]))
+        } else if message.starts_with("unterminated range") {
+            Some(format!("{}]", &query[position..]))
+        } else {
+            None
+        };
+        let end = if suggestion.is_some() {
+            query.len()
+        } else {
+            position
+        };
+        Diagnostic::error(
+            Span {
+                start: position,
+                end,
+            },
+            message,
+            suggestion,
+        )
+    }
 
-        // Check OR operator
-        if query.contains("OR") && from_config.operators.or != "OR" {
-            return Err(ConversionError::UnsupportedOperator {
-                platform: platform_name.to_string(),
-                operator: "OR".to_string(),
-                suggestion: from_config.operators.or.clone(),
-            });
-        }
+    /// Unmatched `)` and unclosed `(`
+    fn check_parentheses(tokens: &[Token], config: &PlatformConfig) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut open = Vec::new();
 
-        // Check && operator
-        if query.contains("&&") && from_config.operators.and != "&&" {
-            return Err(ConversionError::UnsupportedOperator {
-                platform: platform_name.to_string(),
-                operator: "&&".to_string(),
-                suggestion: from_config.operators.and.clone(),
-            });
+        for token in tokens {
+            match token.kind {
+                TokenKind::LeftParen => open.push(token.span),
+                TokenKind::RightParen if open.pop().is_none() => {
+                    diagnostics.push(Diagnostic::error(
+                        token.span,
+                        "Unmatched closing parenthesis".to_string(),
+                        Some(String::new()),
+                    ));
+                }
+                _ => {}
+            }
         }
 
-        // Check || operator
-        if query.contains("||") && from_config.operators.or != "||" {
-            return Err(ConversionError::UnsupportedOperator {
-                platform: platform_name.to_string(),
-                operator: "||".to_string(),
-                suggestion: from_config.operators.or.clone(),
-            });
+        for span in open {
+            diagnostics.push(Diagnostic::error(
+                span,
+                format!(
+                    "Unclosed parenthesis, expected a matching '{}'",
+                    config.operators.right_paren
+                ),
+                None,
+            ));
         }
 
-        // Check NOT operator
-        if RE_NOT_WORD.is_match(query) && from_config.operators.not_equal.trim() != "NOT" {
-            return Err(ConversionError::UnsupportedOperator {
-                platform: platform_name.to_string(),
-                operator: "NOT".to_string(),
-                suggestion: from_config.operators.not_equal.clone(),
-            });
+        diagnostics
+    }
+
+    /// AND / OR / NOT spelling, mixed styles and missing operators between conditions
+    fn check_boolean_operators(
+        tokens: &[Token],
+        config: &PlatformConfig,
+        platform: &str,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let expected_and = config.operators.and.trim();
+        let expected_or = config.operators.or.trim();
+        let prefix_not = config.operators.not_equal.trim();
+
+        // Both `&&` and `AND` styles in one query usually means a pasted fragment
+        let symbolic = tokens.iter().any(|t| {
+            matches!(t.kind, TokenKind::And | TokenKind::Or) && t.text.starts_with(['&', '|'])
+        });
+        let worded = tokens.iter().any(|t| {
+            matches!(t.kind, TokenKind::And | TokenKind::Or) && !t.text.starts_with(['&', '|'])
+        });
+        let mixed = symbolic && worded;
+
+        for (i, token) in tokens.iter().enumerate() {
+            let (expected, name) = match token.kind {
+                TokenKind::And => (expected_and, "AND"),
+                TokenKind::Or => (expected_or, "OR"),
+                TokenKind::Not => {
+                    if prefix_not != "NOT" {
+                        diagnostics.push(Diagnostic::error(
+                            token.span,
+                            format!(
+                                "{} platform does not support the 'NOT' operator, negate the condition with '{}' instead",
+                                platform, config.operators.not_equal
+                            ),
+                            None,
+                        ));
+                    } else if token.text != "NOT" {
+                        diagnostics.push(Diagnostic::warning(
+                            token.span,
+                            "Logical operators should be written in upper case".to_string(),
+                            Some("NOT".to_string()),
+                        ));
+                    }
+                    continue;
+                }
+                TokenKind::Minus => {
+                    if prefix_not != "-" {
+                        diagnostics.push(Self::unsupported_negation(token.span, config, platform));
+                    }
+                    continue;
+                }
+                _ => {
+                    if let Some(diagnostic) =
+                        Self::check_missing_operator(tokens, i, config, platform)
+                    {
+                        diagnostics.push(diagnostic);
+                    }
+                    continue;
+                }
+            };
+
+            if token.text == expected {
+                continue;
+            }
+
+            if expected.is_empty() {
+                // Shodan joins conditions with whitespace only
+                if name == "AND" {
+                    diagnostics.push(Diagnostic::error(
+                        token.span,
+                        format!(
+                            "{} platform combines conditions with spaces, not '{}'",
+                            platform, token.text
+                        ),
+                        Some(String::new()),
+                    ));
+                }
+            } else if token.text.eq_ignore_ascii_case(expected) {
+                diagnostics.push(Diagnostic::warning(
+                    token.span,
+                    "Logical operators should be written in upper case".to_string(),
+                    Some(expected.to_string()),
+                ));
+            } else {
+                let message = if mixed {
+                    format!(
+                        "Mixed '&&'/'AND' style operators in one query, {} platform uses '{}'",
+                        platform, expected
+                    )
+                } else {
+                    format!(
+                        "{} platform does not support '{}' operator, please use '{}' instead",
+                        platform, token.text, expected
+                    )
+                };
+                diagnostics.push(Diagnostic::error(
+                    token.span,
+                    message,
+                    Some(expected.to_string()),
+                ));
+            }
         }
 
-        // Check != operator
-        if query.contains("!=") && from_config.operators.not_equal != "!=" {
-            return Err(ConversionError::UnsupportedOperator {
-                platform: platform_name.to_string(),
-                operator: "!=".to_string(),
-                suggestion: from_config.operators.not_equal.clone(),
-            });
+        diagnostics
+    }
+
+    // Two conditions side by side are only an implicit AND on whitespace-joined dialects
+    fn check_missing_operator(
+        tokens: &[Token],
+        i: usize,
+        config: &PlatformConfig,
+        platform: &str,
+    ) -> Option<Diagnostic> {
+        let and = config.operators.and.trim();
+        if and.is_empty() || i == 0 {
+            return None;
         }
+        let ends_condition = matches!(
+            tokens[i - 1].kind,
+            TokenKind::Value(_) | TokenKind::RightParen
+        );
+        let starts_condition = matches!(
+            tokens[i].kind,
+            TokenKind::Field(_) | TokenKind::LeftParen | TokenKind::Value(_)
+        );
+        if !(ends_condition && starts_condition) {
+            return None;
+        }
+        let start = tokens[i].span.start;
+        Some(Diagnostic::error(
+            Span { start, end: start },
+            format!(
+                "Missing logical operator between conditions, {} platform expects '{}'",
+                platform, and
+            ),
+            Some(format!("{} ", and)),
+        ))
+    }
 
-        Ok(())
+    fn unsupported_negation(span: Span, config: &PlatformConfig, platform: &str) -> Diagnostic {
+        Diagnostic::error(
+            span,
+            format!(
+                "{} platform does not support '-' negation, use '{}' instead",
+                platform, config.operators.not_equal
+            ),
+            None,
+        )
     }
 
-    /// Validate fields used in the query
-    fn validate_fields(
-        query: &str,
-        from_config: &PlatformConfig,
-        platform_name: &str,
-    ) -> ConversionResult<()> {
-        let used_field_names = Self::extract_field_names_from_query(query);
+    /// `=` vs `:`, `==` and `!=` support
+    fn check_field_operators(
+        tokens: &[Token],
+        config: &PlatformConfig,
+        platform: &str,
+    ) -> Vec<Diagnostic> {
+        let equal = config.operators.equal.as_str();
+        let not_equal = config.operators.not_equal.trim();
+        let mut diagnostics = Vec::new();
 
-        for field_name in used_field_names {
-            // Check if this field name exists in the platform configuration
-            // First try exact match
-            if from_config.fields.contains_key(&field_name) {
+        for token in tokens {
+            let TokenKind::Operator(comparison) = token.kind else {
                 continue;
+            };
+            let op = token.text.as_str();
+            match comparison {
+                Comparison::Equal if op != equal => diagnostics.push(Diagnostic::error(
+                    token.span,
+                    format!(
+                        "{} platform does not support 'field{}' operator, please use 'field{}' instead",
+                        platform, op, equal
+                    ),
+                    Some(equal.to_string()),
+                )),
+                Comparison::Exact if config.operators.exact.is_none() => diagnostics.push(Diagnostic::warning(
+                    token.span,
+                    format!("{} platform has no exact match operator, '==' is treated as '{}'", platform, equal),
+                    Some(equal.to_string()),
+                )),
+                Comparison::NotEqual if not_equal != "!=" => {
+                    diagnostics.push(Self::unsupported_negation(token.span, config, platform))
+                }
+                // Ordering operators follow the field operator on `:` dialects (`port:>80`)
+                _ if comparison.ordering_symbol().is_some() => {
+                    let symbol = comparison.ordering_symbol().unwrap_or_default();
+                    let expected = if equal == "=" {
+                        symbol.to_string()
+                    } else {
+                        format!("{}{}", equal, symbol)
+                    };
+                    if op != expected {
+                        diagnostics.push(Diagnostic::error(
+                            token.span,
+                            format!(
+                                "{} platform writes this comparison as 'field{}'",
+                                platform, expected
+                            ),
+                            Some(expected),
+                        ));
+                    }
+                }
+                _ => {}
             }
+        }
 
-            // Then try to match by base field name (for compound fields like response.title)
-            let base_field = if field_name.contains('.') {
-                field_name.split('.').next_back().unwrap_or(&field_name)
-            } else {
-                &field_name
+        diagnostics
+    }
+
+    /// Unknown fields, with a did-you-mean from the platform's field list
+    fn check_fields(tokens: &[Token], config: &PlatformConfig, platform: &str) -> Vec<Diagnostic> {
+        let mut known: Vec<&str> = config
+            .fields
+            .values()
+            .chain(config.variants.keys())
+            .map(|f| f.as_str())
+            .collect();
+        known.sort_unstable();
+        known.dedup();
+        // Dotted namespaces of the platform's own fields, e.g. `web` and `cert.subject`
+        let namespaces: Vec<&str> = known
+            .iter()
+            .flat_map(|f| f.match_indices('.').map(move |(i, _)| &f[..i]))
+            .collect();
+
+        let mut diagnostics = Vec::new();
+        for token in tokens {
+            let TokenKind::Field(field) = &token.kind else {
+                continue;
             };
+            if known.contains(&field.as_str()) {
+                continue;
+            }
+            // Sub-fields of a known namespace (e.g. `web.body` next to `web.title`) are accepted
+            if field
+                .rsplit_once('.')
+                .is_some_and(|(namespace, _)| namespaces.contains(&namespace))
+            {
+                continue;
+            }
 
-            // Check if the base field exists in config
-            let is_supported = from_config
+            let suggestion = config
                 .fields
-                .iter()
-                .any(|(config_field, _)| config_field == base_field);
-
-            if !is_supported {
-                return Err(ConversionError::UnsupportedField {
-                    platform: platform_name.to_string(),
-                    field: base_field.to_string(),
-                });
-            }
+                .get(field.as_str())
+                .map(|f| f.as_str())
+                .or_else(|| Self::closest(field, &known));
+            let message = match suggestion {
+                Some(suggestion) => format!(
+                    "{} platform does not support field '{}', did you mean '{}'?",
+                    platform, field, suggestion
+                ),
+                None => format!("{} platform does not support field '{}'", platform, field),
+            };
+            diagnostics.push(Diagnostic::error(
+                token.span,
+                message,
+                suggestion.map(|s| s.to_string()),
+            ));
         }
 
-        Ok(())
+        diagnostics
+    }
+
+    // Closest known field within an edit distance proportional to the name length
+    fn closest<'c>(field: &str, known: &[&'c str]) -> Option<&'c str> {
+        let max_distance = (field.chars().count() / 3).max(2);
+        known
+            .iter()
+            .map(|candidate| (edit_distance(field, candidate), *candidate))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min()
+            .map(|(_, candidate)| candidate)
+    }
+}
+
+/// Levenshtein distance over characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
     }
+    previous[b.len()]
+}
 
-    /// Extract field names from query (without operators)
-    fn extract_field_names_from_query(query: &str) -> Vec<String> {
-        let mut field_names = Vec::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
 
-        // Use regex to find all field names
-        for caps in RE_FIELD_NAMES.captures_iter(query) {
-            if let Some(field_match) = caps.get(1) {
-                let field_str = field_match.as_str();
+    fn config(platform: &str) -> PlatformConfig {
+        let mut configs: HashMap<String, PlatformConfig> =
+            serde_json::from_str(include_str!("../../config.json")).unwrap();
+        configs.remove(platform).unwrap()
+    }
 
-                if !field_names.contains(&field_str.to_string()) {
-                    field_names.push(field_str.to_string());
-                }
-            }
+    fn diagnose(query: &str, platform: &str) -> Vec<Diagnostic> {
+        SyntaxValidator::diagnose(query, &config(platform), platform)
+    }
+
+    fn at(query: &str, text: &str) -> Span {
+        let start = query.find(text).unwrap();
+        Span {
+            start,
+            end: start + text.len(),
         }
+    }
+
+    #[test]
+    fn test_valid_queries_have_no_diagnostics() {
+        assert_eq!(diagnose(r#"title="A && B" && port="80""#, "fofa"), vec![]);
+        assert_eq!(
+            diagnose(r#"web.title="x" || ip.port=443"#, "hunter"),
+            vec![]
+        );
+        assert_eq!(
+            diagnose(r#"title:"登录" AND NOT (port:80 OR port:8080)"#, "quake"),
+            vec![]
+        );
+        assert_eq!(
+            diagnose(r#"http.title:"x" -port:22 nginx"#, "shodan"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_reports_every_problem_with_spans() {
+        let query = r#"(titel="x" && port:"80") AND domian="a.com")"#;
+        let diagnostics = diagnose(query, "fofa");
+        let summary: Vec<(Span, Severity, Option<&str>)> = diagnostics
+            .iter()
+            .map(|d| (d.span, d.severity, d.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (at(query, "titel"), Severity::Error, Some("title")),
+                (at(query, ":"), Severity::Error, Some("=")),
+                (at(query, "AND"), Severity::Error, Some("&&")),
+                (at(query, "domian"), Severity::Error, Some("domain")),
+                (
+                    Span {
+                        start: query.len() - 1,
+                        end: query.len()
+                    },
+                    Severity::Error,
+                    Some("")
+                ),
+            ]
+        );
+        assert!(diagnostics[2].message.contains("Mixed"));
+    }
+
+    #[test]
+    fn test_unterminated_quote_and_unclosed_parenthesis() {
+        let query = r#"(ip="1.1.1.1" && title="abc"#;
+        let diagnostics = diagnose(query, "fofa");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].span, at(query, "("));
+        assert!(diagnostics[0].message.contains("Unclosed parenthesis"));
+        assert_eq!(diagnostics[1].span.start, query.rfind('"').unwrap());
+        assert_eq!(diagnostics[1].suggestion.as_deref(), Some("\"abc\""));
+    }
+
+    #[test]
+    fn test_unknown_namespace_is_an_error() {
+        let query = r#"foo.port="80" && x.title="a""#;
+        let diagnostics = diagnose(query, "fofa");
+        let summary: Vec<(Span, Severity)> =
+            diagnostics.iter().map(|d| (d.span, d.severity)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (at(query, "foo.port"), Severity::Error),
+                (at(query, "x.title"), Severity::Error),
+            ]
+        );
+
+        // Sub-fields of the platform's own namespaces are still accepted
+        assert_eq!(diagnose(r#"web.body="x""#, "hunter"), vec![]);
+        assert_eq!(diagnose(r#"bogus.ip="1.1.1.1""#, "hunter").len(), 1);
+    }
+
+    #[test]
+    fn test_canonical_field_name_suggests_platform_field() {
+        let diagnostics = diagnose(r#"title="x""#, "hunter");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("web.title"));
+    }
+
+    #[test]
+    fn test_style_warnings_and_missing_operator() {
+        let query = r#"port:80 and title:"x" ip:"1.1.1.1""#;
+        let diagnostics = diagnose(query, "quake");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].suggestion.as_deref(), Some("AND"));
+        assert_eq!(diagnostics[1].span, Span { start: 22, end: 22 });
+        assert_eq!(diagnostics[1].suggestion.as_deref(), Some("AND "));
+        // Warnings alone do not fail validation
+        assert!(SyntaxValidator::validate_query_syntax(
+            "port:80 and ip:1",
+            &config("quake"),
+            "quake"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_negation_style_per_platform() {
+        assert_eq!(diagnose(r#"port!="22""#, "quake").len(), 1);
+        assert_eq!(diagnose(r#"NOT port="22""#, "fofa").len(), 1);
+        assert_eq!(diagnose(r#"-port="22""#, "fofa").len(), 1);
+        assert_eq!(diagnose(r#"port!="22""#, "fofa"), vec![]);
+    }
 
-        field_names
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("domian", "domain"), 2);
+        assert_eq!(edit_distance("", "ip"), 2);
+        assert_eq!(edit_distance("标题", "标题"), 0);
     }
 }
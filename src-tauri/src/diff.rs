@@ -0,0 +1,413 @@
+use crate::api::asset::{Asset, CSV_HEADERS};
+use crate::store::{AssetStore, SnapshotInfo};
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// 参与比对的属性（[`CSV_HEADERS`] 中的列名）
+const COMPARED_FIELDS: [&str; 9] = [
+    "protocol",
+    "title",
+    "server",
+    "status_code",
+    "certificate",
+    "fingerprints",
+    "os",
+    "icp",
+    "organization",
+];
+
+/// 差异报告的列
+const DIFF_HEADERS: [&str; 8] = [
+    "变化",
+    "IP",
+    "端口",
+    "Host",
+    "URL",
+    "属性",
+    "变更前",
+    "变更后",
+];
+
+/// 单个属性的变化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// 两次运行都出现、但属性有变化的资产
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetChange {
+    /// 本次运行中的资产
+    pub asset: Asset,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 两次运行都出现的主机上开放端口的变化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PortChange {
+    pub host: String,
+    /// 新开放的端口
+    pub opened: Vec<u16>,
+    /// 不再出现的端口
+    pub closed: Vec<u16>,
+}
+
+/// 同一查询两次运行结果的差异
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetDiff {
+    /// 上一次快照，首次运行时为空（本次作为基线）
+    pub previous: Option<SnapshotInfo>,
+    pub current: SnapshotInfo,
+    pub added: Vec<Asset>,
+    pub removed: Vec<Asset>,
+    pub changed: Vec<AssetChange>,
+    pub ports: Vec<PortChange>,
+}
+
+// 比对用的资产标识：同一主机的同一端口视为同一资产，协议变化记为属性变化
+fn diff_key(asset: &Asset) -> String {
+    format!(
+        "{}|{}|{}",
+        asset.ip.trim(),
+        asset.port.map(|port| port.to_string()).unwrap_or_default(),
+        asset.host.trim().to_lowercase()
+    )
+}
+
+// 端口归属的主机，优先 IP
+fn port_host(asset: &Asset) -> String {
+    if asset.ip.trim().is_empty() {
+        asset.host.trim().to_lowercase()
+    } else {
+        asset.ip.trim().to_string()
+    }
+}
+
+fn field_values(asset: &Asset) -> HashMap<&'static str, String> {
+    CSV_HEADERS.into_iter().zip(asset.csv_record()).collect()
+}
+
+// 按标识去重，保留首次出现的记录与原有顺序
+fn index(assets: &[Asset]) -> (Vec<&Asset>, HashMap<String, &Asset>) {
+    let mut ordered = Vec::new();
+    let mut by_key = HashMap::new();
+    for asset in assets {
+        if let Entry::Vacant(entry) = by_key.entry(diff_key(asset)) {
+            entry.insert(asset);
+            ordered.push(asset);
+        }
+    }
+    (ordered, by_key)
+}
+
+fn ports_by_host(assets: &[&Asset]) -> BTreeMap<String, BTreeSet<u16>> {
+    let mut hosts: BTreeMap<String, BTreeSet<u16>> = BTreeMap::new();
+    for asset in assets {
+        let host = port_host(asset);
+        if !host.is_empty() {
+            hosts.entry(host).or_default().extend(asset.port);
+        }
+    }
+    hosts
+}
+
+impl AssetDiff {
+    /// 比对两次快照；没有上一次快照时本次作为基线，不报告变化
+    pub fn compare(
+        previous: Option<(SnapshotInfo, Vec<Asset>)>,
+        current: SnapshotInfo,
+        current_assets: &[Asset],
+    ) -> Self {
+        let (previous, previous_assets) = match previous {
+            Some((info, assets)) => (Some(info), assets),
+            None => {
+                return AssetDiff {
+                    previous: None,
+                    current,
+                    added: Vec::new(),
+                    removed: Vec::new(),
+                    changed: Vec::new(),
+                    ports: Vec::new(),
+                }
+            }
+        };
+
+        let (before, before_by_key) = index(&previous_assets);
+        let (after, after_by_key) = index(current_assets);
+
+        let mut added = Vec::new();
+        let mut changed = Vec::new();
+        for asset in &after {
+            let Some(old) = before_by_key.get(&diff_key(asset)) else {
+                added.push((*asset).clone());
+                continue;
+            };
+            let old_values = field_values(old);
+            let new_values = field_values(asset);
+            let changes: Vec<FieldChange> = COMPARED_FIELDS
+                .iter()
+                .filter(|field| old_values[*field] != new_values[*field])
+                .map(|field| FieldChange {
+                    field: field.to_string(),
+                    before: old_values[*field].clone(),
+                    after: new_values[*field].clone(),
+                })
+                .collect();
+            if !changes.is_empty() {
+                changed.push(AssetChange {
+                    asset: (*asset).clone(),
+                    changes,
+                });
+            }
+        }
+
+        let removed = before
+            .iter()
+            .filter(|asset| !after_by_key.contains_key(&diff_key(asset)))
+            .map(|asset| (*asset).clone())
+            .collect();
+
+        // 端口变化只看两次都出现的主机，整台主机的增减已体现在新增 / 消失中
+        let before_ports = ports_by_host(&before);
+        let after_ports = ports_by_host(&after);
+        let ports = after_ports
+            .iter()
+            .filter_map(|(host, now)| {
+                let was = before_ports.get(host)?;
+                let opened: Vec<u16> = now.difference(was).copied().collect();
+                let closed: Vec<u16> = was.difference(now).copied().collect();
+                (!opened.is_empty() || !closed.is_empty()).then(|| PortChange {
+                    host: host.clone(),
+                    opened,
+                    closed,
+                })
+            })
+            .collect();
+
+        AssetDiff {
+            previous,
+            current,
+            added,
+            removed,
+            changed,
+            ports,
+        }
+    }
+
+    /// 读取快照及其上一次快照并比对
+    pub fn for_snapshot(store: &AssetStore, snapshot_id: i64) -> Result<Self, String> {
+        let current = store.snapshot(snapshot_id)?;
+        let previous = match store.previous_snapshot(&current)? {
+            Some(info) => {
+                let assets = store.snapshot_assets(info.id)?;
+                Some((info, assets))
+            }
+            None => None,
+        };
+        let assets = store.snapshot_assets(snapshot_id)?;
+        Ok(Self::compare(previous, current, &assets))
+    }
+
+    /// 是否没有任何变化
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.ports.is_empty()
+    }
+
+    /// 一行摘要，用于进度日志
+    pub fn summary(&self) -> String {
+        match &self.previous {
+            None => format!("已建立基线快照，共 {} 条资产", self.current.assets),
+            Some(previous) => format!(
+                "与 {} 的快照相比：新增 {}，消失 {}，属性变化 {}，端口变化的主机 {}",
+                previous.taken_at,
+                self.added.len(),
+                self.removed.len(),
+                self.changed.len(),
+                self.ports.len()
+            ),
+        }
+    }
+
+    /// 差异报告的行，顺序与 [`DIFF_HEADERS`] 一致
+    fn rows(&self) -> Vec<[String; 8]> {
+        fn row(kind: &str, asset: &Asset, field: &str, before: &str, after: &str) -> [String; 8] {
+            [
+                kind.to_string(),
+                asset.ip.clone(),
+                asset.port.map(|port| port.to_string()).unwrap_or_default(),
+                asset.host.clone(),
+                asset.url.clone(),
+                field.to_string(),
+                before.to_string(),
+                after.to_string(),
+            ]
+        }
+
+        let mut rows = Vec::new();
+        for asset in &self.added {
+            rows.push(row("新增", asset, "", "", ""));
+        }
+        for asset in &self.removed {
+            rows.push(row("消失", asset, "", "", ""));
+        }
+        for change in &self.changed {
+            for field in &change.changes {
+                rows.push(row(
+                    "属性变化",
+                    &change.asset,
+                    &field.field,
+                    &field.before,
+                    &field.after,
+                ));
+            }
+        }
+        for change in &self.ports {
+            let ports = [("端口开放", &change.opened), ("端口关闭", &change.closed)];
+            for (kind, list) in ports {
+                for port in list {
+                    rows.push([
+                        kind.to_string(),
+                        change.host.clone(),
+                        port.to_string(),
+                        change.host.clone(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                        String::new(),
+                    ]);
+                }
+            }
+        }
+        rows
+    }
+
+    /// 保存差异报告，`format` 为 csv 或 json
+    pub fn save(&self, path: &str, format: &str) -> Result<(), String> {
+        match format {
+            "csv" => {
+                let mut wtr =
+                    csv::Writer::from_path(path).map_err(|e| format!("创建CSV文件失败: {}", e))?;
+                wtr.write_record(DIFF_HEADERS)
+                    .map_err(|e| format!("写入CSV表头失败: {}", e))?;
+                for row in self.rows() {
+                    wtr.write_record(&row)
+                        .map_err(|e| format!("写入CSV数据失败: {}", e))?;
+                }
+                wtr.flush().map_err(|e| format!("保存CSV文件失败: {}", e))
+            }
+            "json" => {
+                let content = serde_json::to_string_pretty(self)
+                    .map_err(|e| format!("序列化差异报告失败: {}", e))?;
+                std::fs::write(path, content).map_err(|e| format!("保存JSON文件失败: {}", e))
+            }
+            _ => Err(format!("差异报告不支持该格式: {}", format)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn snapshot(id: i64, assets: u64) -> SnapshotInfo {
+        SnapshotInfo {
+            id,
+            platform: "fofa".to_string(),
+            query: "org=\"example\"".to_string(),
+            taken_at: format!("2024-01-0{}T00:00:00.000Z", id),
+            assets,
+        }
+    }
+
+    fn asset(ip: &str, port: u16, title: &str, server: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port: Some(port),
+            protocol: "http".to_string(),
+            title: title.to_string(),
+            server: server.to_string(),
+            ..Asset::new("fofa", Value::Null)
+        }
+        .finalize()
+    }
+
+    fn diff() -> AssetDiff {
+        let before = vec![
+            asset("1.1.1.1", 80, "Home", "nginx"),
+            asset("1.1.1.1", 22, "", ""),
+            asset("2.2.2.2", 443, "Portal", "IIS"),
+        ];
+        let after = vec![
+            asset("1.1.1.1", 80, "Login", "nginx"),
+            asset("1.1.1.1", 8080, "", ""),
+            asset("1.1.1.1", 8080, "", ""),
+            asset("3.3.3.3", 80, "New", "Apache"),
+        ];
+        AssetDiff::compare(Some((snapshot(1, 3), before)), snapshot(2, 4), &after)
+    }
+
+    #[test]
+    fn test_first_run_is_baseline() {
+        let diff = AssetDiff::compare(None, snapshot(1, 1), &[asset("1.1.1.1", 80, "", "")]);
+        assert!(diff.is_empty());
+        assert!(diff.summary().contains("基线"));
+    }
+
+    #[test]
+    fn test_reports_added_removed_and_changed_assets() {
+        let diff = diff();
+        let ips = |assets: &[Asset]| -> Vec<String> {
+            assets
+                .iter()
+                .map(|a| format!("{}:{}", a.ip, a.port.unwrap()))
+                .collect()
+        };
+
+        assert_eq!(ips(&diff.added), ["1.1.1.1:8080", "3.3.3.3:80"]);
+        assert_eq!(ips(&diff.removed), ["1.1.1.1:22", "2.2.2.2:443"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed[0].changes,
+            [FieldChange {
+                field: "title".to_string(),
+                before: "Home".to_string(),
+                after: "Login".to_string(),
+            }]
+        );
+        // 2.2.2.2 与 3.3.3.3 只出现在一次运行中，不计入端口变化
+        assert_eq!(
+            diff.ports,
+            [PortChange {
+                host: "1.1.1.1".to_string(),
+                opened: vec![8080],
+                closed: vec![22],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_csv_report_has_one_row_per_change() {
+        let path =
+            std::env::temp_dir().join(format!("asset-mapping-diff-{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        diff().save(path, "csv").unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "变化,IP,端口,Host,URL,属性,变更前,变更后");
+        // 新增 2、消失 2、属性变化 1、端口开放 1、端口关闭 1
+        assert_eq!(lines.len(), 8);
+        assert!(lines.contains(&"属性变化,1.1.1.1,80,1.1.1.1,http://1.1.1.1,title,Home,Login"));
+        assert!(lines.contains(&"端口关闭,1.1.1.1,22,1.1.1.1,,,,"));
+        assert!(diff().save(path, "xlsx").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+
+/// Errors returned by the asset-mapping platform clients
+///
+/// Each client maps its platform's HTTP statuses and response codes into one of
+/// these kinds, so key rotation, retries and the UI can branch on the kind
+/// instead of on message text. Messages are shown to the user as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    /// The key or account was rejected
+    Auth(String),
+    /// The key has no credits left for the current period
+    QuotaExhausted(String),
+    /// Too many requests; `retry_after` is the wait the platform asked for, in seconds
+    RateLimited {
+        retry_after: Option<u64>,
+        message: String,
+    },
+    /// The platform rejected the query or its parameters
+    BadQuery(String),
+    /// The platform failed on its side: a 5xx, or an error response of no other kind
+    Upstream { status: u16, message: String },
+    /// The request could not be sent or the response could not be read
+    Network(String),
+    /// The response body did not have the expected shape
+    Parse(String),
+    /// Local configuration problem, such as no key configured or an invalid proxy
+    Config(String),
+}
+
+impl ApiError {
+    /// Machine-readable name of the kind, as sent to the frontend
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApiError::Auth(_) => "auth",
+            ApiError::QuotaExhausted(_) => "quotaExhausted",
+            ApiError::RateLimited { .. } => "rateLimited",
+            ApiError::BadQuery(_) => "badQuery",
+            ApiError::Upstream { .. } => "upstream",
+            ApiError::Network(_) => "network",
+            ApiError::Parse(_) => "parse",
+            ApiError::Config(_) => "config",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::Auth(message)
+            | ApiError::QuotaExhausted(message)
+            | ApiError::BadQuery(message)
+            | ApiError::Network(message)
+            | ApiError::Parse(message)
+            | ApiError::Config(message)
+            | ApiError::RateLimited { message, .. }
+            | ApiError::Upstream { message, .. } => message,
+        }
+    }
+
+    /// Map a non-success HTTP status to an error kind
+    pub fn from_status(status: u16, retry_after: Option<u64>, message: impl Into<String>) -> Self {
+        let message = message.into();
+        match status {
+            401 | 403 => ApiError::Auth(message),
+            402 => ApiError::QuotaExhausted(message),
+            429 => ApiError::RateLimited {
+                retry_after,
+                message,
+            },
+            400 | 404 | 422 => ApiError::BadQuery(message),
+            _ => ApiError::Upstream { status, message },
+        }
+    }
+
+    /// Classify an error reported in a platform's response body by its wording
+    ///
+    /// Fallback for platform codes a client does not map explicitly; anything
+    /// unrecognised becomes [`ApiError::Upstream`].
+    pub fn from_platform_message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let lower = message.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| lower.contains(word));
+
+        if has(&[
+            "积分", "额度", "配额", "余额", "f币", "f点", "quota", "credit",
+        ]) {
+            ApiError::QuotaExhausted(message)
+        } else if has(&["频繁", "too many", "rate limit"]) {
+            ApiError::RateLimited {
+                retry_after: None,
+                message,
+            }
+        } else if has(&[
+            "api key",
+            "apikey",
+            "api-key",
+            "密钥",
+            "令牌",
+            "token",
+            "unauthorized",
+            "account invalid",
+            "登录",
+        ]) {
+            ApiError::Auth(message)
+        } else if has(&["语法", "syntax", "参数", "invalid query", "parse"]) {
+            ApiError::BadQuery(message)
+        } else {
+            ApiError::Upstream {
+                status: 200,
+                message,
+            }
+        }
+    }
+
+    /// Whether the key that caused the error should be rotated out
+    pub fn is_key_exhausted(&self) -> bool {
+        matches!(self, ApiError::QuotaExhausted(_))
+    }
+
+    /// Whether sending the same request again later may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ApiError::RateLimited { .. } | ApiError::Upstream { .. } | ApiError::Network(_)
+        )
+    }
+
+    /// Wait requested by the platform before retrying, in seconds
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            ApiError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// Read the `Retry-After` header as a number of seconds
+///
+/// HTTP-date values are not supported and yield `None`.
+pub fn retry_after_secs(headers: &HeaderMap) -> Option<u64> {
+    headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<ApiError> for String {
+    fn from(error: ApiError) -> Self {
+        error.to_string()
+    }
+}
+
+impl Serialize for ApiError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ApiError", 4)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", self.message())?;
+        match self {
+            ApiError::RateLimited { retry_after, .. } => {
+                state.serialize_field("retryAfter", retry_after)?
+            }
+            _ => state.skip_field("retryAfter")?,
+        }
+        match self {
+            ApiError::Upstream { status, .. } => state.serialize_field("status", status)?,
+            _ => state.skip_field("status")?,
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status() {
+        assert_eq!(ApiError::from_status(401, None, "x").kind(), "auth");
+        assert_eq!(
+            ApiError::from_status(402, None, "x").kind(),
+            "quotaExhausted"
+        );
+        assert_eq!(
+            ApiError::from_status(429, Some(30), "x").retry_after(),
+            Some(30)
+        );
+        assert_eq!(ApiError::from_status(422, None, "x").kind(), "badQuery");
+        assert_eq!(
+            ApiError::from_status(503, None, "x"),
+            ApiError::Upstream {
+                status: 503,
+                message: "x".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_platform_message() {
+        assert!(ApiError::from_platform_message("今日积分不足").is_key_exhausted());
+        assert!(ApiError::from_platform_message("[820031] F点余额不足").is_key_exhausted());
+        assert_eq!(
+            ApiError::from_platform_message("[-700] Account Invalid").kind(),
+            "auth"
+        );
+        assert_eq!(
+            ApiError::from_platform_message("请求过于频繁").kind(),
+            "rateLimited"
+        );
+        assert_eq!(
+            ApiError::from_platform_message("查询语法错误").kind(),
+            "badQuery"
+        );
+        assert_eq!(
+            ApiError::from_platform_message("服务繁忙").kind(),
+            "upstream"
+        );
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after_secs(&headers), None);
+        headers.insert(RETRY_AFTER, "12".parse().unwrap());
+        assert_eq!(retry_after_secs(&headers), Some(12));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after_secs(&headers), None);
+    }
+
+    #[test]
+    fn test_serialize_for_frontend() {
+        let error = ApiError::RateLimited {
+            retry_after: Some(5),
+            message: "请求过于频繁".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({"kind": "rateLimited", "message": "请求过于频繁", "retryAfter": 5})
+        );
+        assert_eq!(
+            serde_json::to_value(ApiError::Network("超时".to_string())).unwrap(),
+            serde_json::json!({"kind": "network", "message": "超时"})
+        );
+        assert_eq!(String::from(ApiError::Parse("坏".to_string())), "坏");
+    }
+}
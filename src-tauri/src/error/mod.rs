@@ -1,3 +1,5 @@
+pub mod api;
 pub mod types;
 
+pub use api::*;
 pub use types::*;
@@ -7,13 +7,12 @@ pub enum ConversionError {
     UnsupportedPlatform(String),
     /// Syntax validation failed
     SyntaxValidationFailed(String),
-    /// Field is not supported by the platform
-    UnsupportedField { platform: String, field: String },
-    /// Operator is not supported by the platform
-    UnsupportedOperator {
+    /// Query could not be parsed; `position` is a byte offset into the query
+    ParseError { position: usize, message: String },
+    /// Conversion would drop or weaken conditions of the query
+    LossyConversion {
         platform: String,
-        operator: String,
-        suggestion: String,
+        details: Vec<String>,
     },
     /// Configuration loading failed
     ConfigurationError(String),
@@ -31,25 +30,15 @@ impl fmt::Display for ConversionError {
             ConversionError::SyntaxValidationFailed(msg) => {
                 write!(f, "{}", msg)
             }
-            ConversionError::UnsupportedField { platform, field } => {
-                write!(
-                    f,
-                    "{} platform does not support field '{}'",
-                    platform.to_uppercase(),
-                    field
-                )
+            ConversionError::ParseError { position, message } => {
+                write!(f, "Parse error at position {}: {}", position, message)
             }
-            ConversionError::UnsupportedOperator {
-                platform,
-                operator,
-                suggestion,
-            } => {
+            ConversionError::LossyConversion { platform, details } => {
                 write!(
                     f,
-                    "{} platform does not support '{}' operator, please use '{}' instead",
-                    platform.to_uppercase(),
-                    operator,
-                    suggestion
+                    "Conversion to {} is lossy: {}",
+                    platform,
+                    details.join("; ")
                 )
             }
             ConversionError::ConfigurationError(msg) => {
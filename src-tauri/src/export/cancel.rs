@@ -0,0 +1,95 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// 正在运行、可被取消的导出任务（任务 ID → 取消信号发送端）
+static TASKS: Lazy<Mutex<HashMap<String, watch::Sender<bool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 导出任务的取消信号
+#[derive(Debug, Clone)]
+pub struct CancelSignal(watch::Receiver<bool>);
+
+impl CancelSignal {
+    /// 等到任务被取消；任务结束前未被取消时一直挂起
+    pub async fn cancelled(&mut self) {
+        if self.0.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// 登记一个可取消的任务，同名任务的旧信号被替换
+pub fn register(task_id: &str) -> CancelSignal {
+    let (sender, receiver) = watch::channel(false);
+    TASKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(task_id.to_string(), sender);
+    CancelSignal(receiver)
+}
+
+/// 取消任务及其子任务（ID 为 `{task_id}_{平台}`），返回被取消的任务数
+pub fn cancel(task_id: &str) -> usize {
+    let prefix = format!("{}_", task_id);
+    let tasks = TASKS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut cancelled = 0;
+    for (id, sender) in tasks.iter() {
+        if id == task_id || id.starts_with(&prefix) {
+            sender.send_replace(true);
+            cancelled += 1;
+        }
+    }
+    cancelled
+}
+
+/// 任务结束后移除登记
+pub fn finish(task_id: &str) {
+    TASKS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(task_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    // 信号在短时间内是否报告已取消
+    async fn reports_cancelled(signal: &mut CancelSignal) -> bool {
+        tokio::time::timeout(Duration::from_millis(50), signal.cancelled())
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_cancel_reaches_only_matching_sub_tasks() {
+        let mut hunter = register("cancel-test_hunter");
+        let mut fofa = register("cancel-test_fofa");
+        let mut other = register("cancel-test-2_hunter");
+
+        assert_eq!(cancel("cancel-test"), 2);
+        assert!(reports_cancelled(&mut hunter).await);
+        assert!(reports_cancelled(&mut fofa).await);
+        assert!(!reports_cancelled(&mut other).await);
+
+        for id in [
+            "cancel-test_hunter",
+            "cancel-test_fofa",
+            "cancel-test-2_hunter",
+        ] {
+            finish(id);
+        }
+        assert_eq!(cancel("cancel-test"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_finished_task_never_reports_cancelled() {
+        let mut signal = register("cancel-finish-test");
+        finish("cancel-finish-test");
+        assert_eq!(cancel("cancel-finish-test"), 0);
+        assert!(!reports_cancelled(&mut signal).await);
+    }
+}
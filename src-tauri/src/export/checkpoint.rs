@@ -0,0 +1,412 @@
+use crate::api::key_manager::UsedKey;
+use crate::api::Asset;
+use crate::converter::TimeRange;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const CHECKPOINT_DIR: &str = "exports";
+const CHECKPOINT_SUFFIX: &str = ".checkpoint.json";
+const PARTIAL_SUFFIX: &str = ".partial.jsonl";
+
+/// 导出任务检查点
+///
+/// 每完成一页就把该页资产追加到部分输出文件（每行一条 JSON），再原子地写回检查点。
+/// 程序崩溃、重启或密钥耗尽后，[`ExportCheckpoint::load`] 取回任务，从
+/// `last_page + 1` 继续请求，已消耗额度获取的数据不会丢失。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportCheckpoint {
+    pub task_id: String,
+    pub platform: String,
+    /// 实际请求的查询语句，时间范围已在开始导出时并入
+    pub query: String,
+    /// 导出的时间范围，为空表示全部时间
+    #[serde(default)]
+    pub time_range: Option<TimeRange>,
+    pub pages: u32,
+    pub page_size: u32,
+    /// 最终导出格式（csv / json）
+    pub format: String,
+    /// 已完整写入部分输出文件的最后一页，0 表示尚未开始
+    pub last_page: u32,
+    /// 游标分页平台的下一页游标
+    pub cursor: Option<String>,
+    /// 获取最后一页所用 key 在密钥池中的索引
+    pub key_index: Option<usize>,
+    /// 获取最后一页所用 key 的前缀，便于识别
+    pub key_hint: Option<String>,
+    /// 已获取的资产条数
+    pub fetched: u64,
+    /// 平台返回的命中总数
+    pub total: Option<u64>,
+    /// 额度预算（单位见平台计费模型），下一页会超出时停止获取，为空表示不限
+    #[serde(default)]
+    pub budget: Option<u64>,
+    /// 已消耗的额度
+    #[serde(default)]
+    pub spent: u64,
+    /// 部分输出文件
+    pub partial_file: PathBuf,
+    /// 部分输出文件中已确认写入的字节数，恢复时截断其后未登记的数据
+    pub partial_len: u64,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// 获取检查点目录
+fn get_checkpoint_dir() -> Result<PathBuf, String> {
+    let dir = dirs::config_dir()
+        .ok_or_else(|| "无法获取配置目录".to_string())?
+        .join("asset-mapping")
+        .join(CHECKPOINT_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建检查点目录失败: {}", e))?;
+    Ok(dir)
+}
+
+// 检查点文件路径
+fn checkpoint_file(dir: &Path, task_id: &str) -> Result<PathBuf, String> {
+    // 任务ID来自前端，只允许用作文件名的字符
+    if task_id.is_empty()
+        || !task_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("无效的任务ID: {}", task_id));
+    }
+    Ok(dir.join(format!("{}{}", task_id, CHECKPOINT_SUFFIX)))
+}
+
+impl ExportCheckpoint {
+    /// 为新的导出任务创建检查点
+    pub fn create(
+        task_id: &str,
+        platform: &str,
+        query: &str,
+        pages: u32,
+        page_size: u32,
+        format: &str,
+    ) -> Result<Self, String> {
+        Self::create_in(
+            &get_checkpoint_dir()?,
+            task_id,
+            platform,
+            query,
+            pages,
+            page_size,
+            format,
+        )
+    }
+
+    fn create_in(
+        dir: &Path,
+        task_id: &str,
+        platform: &str,
+        query: &str,
+        pages: u32,
+        page_size: u32,
+        format: &str,
+    ) -> Result<Self, String> {
+        checkpoint_file(dir, task_id)?;
+        let now = Local::now().to_rfc3339();
+        let checkpoint = ExportCheckpoint {
+            task_id: task_id.to_string(),
+            platform: platform.to_string(),
+            query: query.to_string(),
+            time_range: None,
+            pages,
+            page_size,
+            format: format.to_string(),
+            last_page: 0,
+            cursor: None,
+            key_index: None,
+            key_hint: None,
+            fetched: 0,
+            total: None,
+            budget: None,
+            spent: 0,
+            partial_file: dir.join(format!("{}{}", task_id, PARTIAL_SUFFIX)),
+            partial_len: 0,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        fs::write(&checkpoint.partial_file, "")
+            .map_err(|e| format!("创建部分输出文件失败: {}", e))?;
+        checkpoint.save()?;
+        Ok(checkpoint)
+    }
+
+    /// 读取任务的检查点
+    pub fn load(task_id: &str) -> Result<Self, String> {
+        Self::load_from(&get_checkpoint_dir()?, task_id)
+    }
+
+    fn load_from(dir: &Path, task_id: &str) -> Result<Self, String> {
+        let file = checkpoint_file(dir, task_id)?;
+        if !file.exists() {
+            return Err(format!("未找到导出任务 {} 的检查点", task_id));
+        }
+        let content = fs::read_to_string(&file).map_err(|e| format!("读取检查点失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("解析检查点失败: {}", e))
+    }
+
+    /// 列出所有未完成的导出任务，最近更新的在前
+    pub fn list() -> Result<Vec<Self>, String> {
+        Self::list_in(&get_checkpoint_dir()?)
+    }
+
+    fn list_in(dir: &Path) -> Result<Vec<Self>, String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("读取检查点目录失败: {}", e))?;
+        let mut checkpoints: Vec<Self> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let task_id = name.strip_suffix(CHECKPOINT_SUFFIX)?;
+                Self::load_from(dir, task_id).ok()
+            })
+            .collect();
+        checkpoints.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(checkpoints)
+    }
+
+    // 写入临时文件后重命名，避免写到一半时崩溃留下损坏的检查点
+    fn save(&self) -> Result<(), String> {
+        let dir = self
+            .partial_file
+            .parent()
+            .ok_or_else(|| "无效的检查点路径".to_string())?;
+        let file = checkpoint_file(dir, &self.task_id)?;
+        let tmp_file = file.with_extension("json.tmp");
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("序列化检查点失败: {}", e))?;
+        fs::write(&tmp_file, content).map_err(|e| format!("写入检查点失败: {}", e))?;
+        fs::rename(&tmp_file, &file).map_err(|e| format!("保存检查点失败: {}", e))
+    }
+
+    /// 记录导出的时间范围并写回检查点
+    pub fn set_time_range(&mut self, time_range: Option<TimeRange>) -> Result<(), String> {
+        self.time_range = time_range;
+        self.save()
+    }
+
+    /// 设置额度预算并写回检查点
+    pub fn set_budget(&mut self, budget: Option<u64>) -> Result<(), String> {
+        self.budget = budget;
+        self.save()
+    }
+
+    /// 再消耗 `cost` 额度后是否仍在预算内
+    pub fn within_budget(&self, cost: u64) -> bool {
+        self.budget
+            .is_none_or(|budget| self.spent.saturating_add(cost) <= budget)
+    }
+
+    /// 下一页页码
+    pub fn next_page(&self) -> u32 {
+        self.last_page + 1
+    }
+
+    /// 登记完成的一页：先追加资产到部分输出文件，再更新检查点；`used_key` 为获取该页实际使用的 key
+    pub fn record_page(
+        &mut self,
+        page: u32,
+        assets: &[Asset],
+        total: u64,
+        cursor: Option<String>,
+        used_key: Option<UsedKey>,
+    ) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&self.partial_file)
+            .map_err(|e| format!("打开部分输出文件失败: {}", e))?;
+        // 上次崩溃可能留下未登记的半页数据，从已登记的位置继续写
+        file.set_len(self.partial_len)
+            .and_then(|_| file.seek(SeekFrom::Start(self.partial_len)))
+            .map_err(|e| format!("恢复部分输出文件失败: {}", e))?;
+
+        let mut buffer = String::new();
+        for asset in assets {
+            let line =
+                serde_json::to_string(asset).map_err(|e| format!("序列化资产失败: {}", e))?;
+            buffer.push_str(&line);
+            buffer.push('\n');
+        }
+        file.write_all(buffer.as_bytes())
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("写入部分输出文件失败: {}", e))?;
+
+        // 本页未发出请求（如命中缓存）时保留上一次使用的 key
+        if let Some((key_index, key_hint)) = used_key {
+            self.key_index = Some(key_index);
+            self.key_hint = Some(key_hint);
+        }
+        self.last_page = page;
+        self.cursor = cursor;
+        self.fetched += assets.len() as u64;
+        self.total = Some(total);
+        self.partial_len += buffer.len() as u64;
+        self.updated_at = Local::now().to_rfc3339();
+        self.save()
+    }
+
+    /// 按批读取已登记的资产，每批至多 `batch_size` 条，不把整个文件读入内存
+    pub fn read_batches(
+        &self,
+        batch_size: usize,
+        mut f: impl FnMut(&[Asset]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let file = fs::File::open(&self.partial_file)
+            .map_err(|e| format!("读取部分输出文件失败: {}", e))?;
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut read = 0u64;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("读取部分输出文件失败: {}", e))?;
+            read += line.len() as u64 + 1;
+            if read > self.partial_len {
+                break;
+            }
+            let asset = serde_json::from_str(&line).map_err(|e| format!("解析资产失败: {}", e))?;
+            batch.push(asset);
+            if batch.len() >= batch_size {
+                f(&batch)?;
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            f(&batch)?;
+        }
+        Ok(())
+    }
+
+    /// 导出完成后删除检查点与部分输出文件
+    pub fn remove(&self) -> Result<(), String> {
+        let dir = self
+            .partial_file
+            .parent()
+            .ok_or_else(|| "无效的检查点路径".to_string())?;
+        fs::remove_file(checkpoint_file(dir, &self.task_id)?)
+            .map_err(|e| format!("删除检查点失败: {}", e))?;
+        fs::remove_file(&self.partial_file).ok();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "asset-mapping-checkpoint-{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read_ips(checkpoint: &ExportCheckpoint) -> Vec<String> {
+        let mut ips = Vec::new();
+        checkpoint
+            .read_batches(2, |batch| {
+                assert!(batch.len() <= 2);
+                ips.extend(batch.iter().map(|asset| asset.ip.clone()));
+                Ok(())
+            })
+            .unwrap();
+        ips
+    }
+
+    fn asset(ip: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port: Some(443),
+            ..Asset::new("fofa", serde_json::Value::Null)
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_survives_reload_and_discards_unrecorded_data() {
+        let dir = temp_dir("reload");
+        let mut checkpoint =
+            ExportCheckpoint::create_in(&dir, "task-1", "fofa", "title=\"x\"", 5, 10, "csv")
+                .unwrap();
+        checkpoint
+            .record_page(1, &[asset("1.1.1.1"), asset("2.2.2.2")], 42, None, None)
+            .unwrap();
+
+        // 模拟崩溃：第 2 页的数据写了一半，检查点未更新
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&checkpoint.partial_file)
+            .unwrap();
+        file.write_all(b"{\"ip\":\"3.3.3.3\"").unwrap();
+
+        let mut resumed = ExportCheckpoint::load_from(&dir, "task-1").unwrap();
+        assert_eq!(resumed, checkpoint);
+        assert_eq!(resumed.next_page(), 2);
+        assert_eq!(read_ips(&resumed), ["1.1.1.1", "2.2.2.2"]);
+
+        resumed
+            .record_page(2, &[asset("4.4.4.4")], 42, None, None)
+            .unwrap();
+        assert_eq!(read_ips(&resumed), ["1.1.1.1", "2.2.2.2", "4.4.4.4"]);
+        assert_eq!(resumed.fetched, 3);
+
+        assert_eq!(ExportCheckpoint::list_in(&dir).unwrap().len(), 1);
+        resumed.remove().unwrap();
+        assert!(ExportCheckpoint::list_in(&dir).unwrap().is_empty());
+        assert!(!resumed.partial_file.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_budget_survives_reload() {
+        let dir = temp_dir("budget");
+        let mut checkpoint =
+            ExportCheckpoint::create_in(&dir, "task-2", "hunter", "ip=\"1.1.1.1\"", 5, 10, "csv")
+                .unwrap();
+        assert!(checkpoint.within_budget(u64::MAX));
+
+        checkpoint.set_budget(Some(25)).unwrap();
+        checkpoint.spent = 10;
+        checkpoint
+            .record_page(1, &[asset("1.1.1.1")], 42, None, None)
+            .unwrap();
+
+        let resumed = ExportCheckpoint::load_from(&dir, "task-2").unwrap();
+        assert_eq!(resumed.budget, Some(25));
+        assert_eq!(resumed.spent, 10);
+        assert!(resumed.within_budget(15));
+        assert!(!resumed.within_budget(16));
+        resumed.remove().unwrap();
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_time_range_survives_reload() {
+        let dir = temp_dir("time-range");
+        let mut checkpoint =
+            ExportCheckpoint::create_in(&dir, "task-3", "fofa", "title=\"x\"", 5, 10, "csv")
+                .unwrap();
+        assert_eq!(checkpoint.time_range, None);
+
+        checkpoint
+            .set_time_range(Some(TimeRange::LastDays(30)))
+            .unwrap();
+        let resumed = ExportCheckpoint::load_from(&dir, "task-3").unwrap();
+        assert_eq!(resumed.time_range, Some(TimeRange::LastDays(30)));
+        resumed.remove().unwrap();
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_task_id_must_be_a_file_name() {
+        let dir = temp_dir("task-id");
+        assert!(ExportCheckpoint::load_from(&dir, "../settings").is_err());
+        assert!(ExportCheckpoint::create_in(&dir, "", "fofa", "x", 1, 10, "csv").is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,9 @@
+pub mod cancel;
+pub mod checkpoint;
+pub mod report;
+pub mod targets;
+pub mod writer;
+pub mod xlsx;
+
+pub use checkpoint::ExportCheckpoint;
+pub use writer::{ExportFile, ExportFormat};
@@ -0,0 +1,307 @@
+use super::writer::AssetWriter;
+use crate::api::asset::Asset;
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// 每项统计列出的条目数
+const TOP_N: usize = 20;
+
+/// 资产列表的列
+const REPORT_COLUMNS: [&str; 8] = [
+    "IP", "端口", "协议", "Host", "标题", "Server", "国家", "平台",
+];
+
+/// 报告样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportStyle {
+    Markdown,
+    Html,
+}
+
+/// 报告开头的汇总统计
+#[derive(Debug, Default)]
+struct ReportStats {
+    total: u64,
+    ports: HashMap<String, u64>,
+    countries: HashMap<String, u64>,
+    servers: HashMap<String, u64>,
+    platforms: HashMap<String, u64>,
+}
+
+impl ReportStats {
+    fn add(&mut self, asset: &Asset) {
+        fn count(counts: &mut HashMap<String, u64>, value: &str) {
+            if !value.is_empty() {
+                *counts.entry(value.to_string()).or_default() += 1;
+            }
+        }
+        self.total += 1;
+        count(
+            &mut self.ports,
+            &asset.port.map(|port| port.to_string()).unwrap_or_default(),
+        );
+        count(&mut self.countries, &asset.geo.country);
+        count(&mut self.servers, &asset.server);
+        count(&mut self.platforms, &asset.platform);
+    }
+
+    /// 各项统计的标题与数量最多的条目，数量相同时按名称排序
+    fn sections(&self) -> Vec<(&'static str, Vec<(&str, u64)>)> {
+        fn top(counts: &HashMap<String, u64>) -> Vec<(&str, u64)> {
+            let mut entries: Vec<(&str, u64)> = counts
+                .iter()
+                .map(|(value, count)| (value.as_str(), *count))
+                .collect();
+            entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            entries.truncate(TOP_N);
+            entries
+        }
+        vec![
+            ("端口分布", top(&self.ports)),
+            ("国家分布", top(&self.countries)),
+            ("Server 分布", top(&self.servers)),
+            ("平台分布", top(&self.platforms)),
+        ]
+    }
+}
+
+fn report_cells(asset: &Asset) -> [String; 8] {
+    [
+        asset.ip.clone(),
+        asset.port.map(|port| port.to_string()).unwrap_or_default(),
+        asset.protocol.clone(),
+        asset.host.clone(),
+        asset.title.clone(),
+        asset.server.clone(),
+        asset.geo.country.clone(),
+        asset.platform.clone(),
+    ]
+}
+
+// 表格单元格中的竖线需要转义，换行会打断表格
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+        .trim()
+        .to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,\"Segoe UI\",\"Microsoft YaHei\",sans-serif;margin:24px;color:#1f2328}\
+table{border-collapse:collapse;margin-bottom:16px}\
+th,td{border:1px solid #d0d7de;padding:4px 8px;text-align:left;font-size:13px}\
+th{background:#f6f8fa}\
+.stats{display:flex;flex-wrap:wrap;gap:24px}";
+
+/// Markdown / HTML 报告导出器
+///
+/// 汇总统计要写在报告开头，而资产行是逐页到达的：资产行先写入暂存区 `spool`，
+/// 统计只保存各取值的计数，结束时依次写出统计与暂存的资产行。
+pub struct ReportAssetWriter<W: Write, S: Read + Write + Seek> {
+    style: ReportStyle,
+    writer: W,
+    spool: S,
+    stats: ReportStats,
+}
+
+impl<W: Write, S: Read + Write + Seek> ReportAssetWriter<W, S> {
+    pub fn new(style: ReportStyle, writer: W, spool: S) -> Self {
+        Self {
+            style,
+            writer,
+            spool,
+            stats: ReportStats::default(),
+        }
+    }
+
+    fn render_head(&self) -> String {
+        let generated_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
+        let mut head = String::new();
+        match self.style {
+            ReportStyle::Markdown => {
+                head.push_str("# 资产导出报告\n\n");
+                head.push_str(&format!("生成时间：{}  \n", generated_at));
+                head.push_str(&format!("资产总数：{}\n\n", self.stats.total));
+                for (title, entries) in self.stats.sections() {
+                    head.push_str(&format!("## {}\n\n", title));
+                    if entries.is_empty() {
+                        head.push_str("无数据\n\n");
+                        continue;
+                    }
+                    head.push_str("| 取值 | 数量 |\n| --- | ---: |\n");
+                    for (value, count) in entries {
+                        head.push_str(&format!("| {} | {} |\n", escape_markdown(value), count));
+                    }
+                    head.push('\n');
+                }
+                head.push_str("## 资产列表\n\n");
+                head.push_str(&format!("| {} |\n", REPORT_COLUMNS.join(" | ")));
+                head.push_str(&format!("|{}\n", " --- |".repeat(REPORT_COLUMNS.len())));
+            }
+            ReportStyle::Html => {
+                head.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n");
+                head.push_str("<meta charset=\"utf-8\">\n<title>资产导出报告</title>\n");
+                head.push_str(&format!("<style>{}</style>\n", HTML_STYLE));
+                head.push_str("</head>\n<body>\n<h1>资产导出报告</h1>\n");
+                head.push_str(&format!(
+                    "<p>生成时间：{}，资产总数：{}</p>\n<div class=\"stats\">\n",
+                    generated_at, self.stats.total
+                ));
+                for (title, entries) in self.stats.sections() {
+                    head.push_str(&format!("<section>\n<h2>{}</h2>\n", escape_html(title)));
+                    if entries.is_empty() {
+                        head.push_str("<p>无数据</p>\n</section>\n");
+                        continue;
+                    }
+                    head.push_str("<table>\n<tr><th>取值</th><th>数量</th></tr>\n");
+                    for (value, count) in entries {
+                        head.push_str(&format!(
+                            "<tr><td>{}</td><td>{}</td></tr>\n",
+                            escape_html(value),
+                            count
+                        ));
+                    }
+                    head.push_str("</table>\n</section>\n");
+                }
+                head.push_str("</div>\n<h2>资产列表</h2>\n<table>\n<thead><tr>");
+                for column in REPORT_COLUMNS {
+                    head.push_str(&format!("<th>{}</th>", escape_html(column)));
+                }
+                head.push_str("</tr></thead>\n<tbody>\n");
+            }
+        }
+        head
+    }
+
+    fn render_row(&self, asset: &Asset) -> String {
+        let cells = report_cells(asset);
+        match self.style {
+            ReportStyle::Markdown => {
+                let cells: Vec<String> = cells.iter().map(|cell| escape_markdown(cell)).collect();
+                format!("| {} |\n", cells.join(" | "))
+            }
+            ReportStyle::Html => {
+                let mut row = String::from("<tr>");
+                for cell in &cells {
+                    row.push_str(&format!("<td>{}</td>", escape_html(cell)));
+                }
+                row.push_str("</tr>\n");
+                row
+            }
+        }
+    }
+}
+
+impl<W: Write, S: Read + Write + Seek> AssetWriter for ReportAssetWriter<W, S> {
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        let mut buffer = String::new();
+        for asset in assets {
+            self.stats.add(asset);
+            buffer.push_str(&self.render_row(asset));
+        }
+        self.spool
+            .write_all(buffer.as_bytes())
+            .map_err(|e| format!("写入报告失败: {}", e))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        let head = self.render_head();
+        let tail = match self.style {
+            ReportStyle::Markdown => "",
+            ReportStyle::Html => "</tbody>\n</table>\n</body>\n</html>\n",
+        };
+        self.writer
+            .write_all(head.as_bytes())
+            .and_then(|_| self.spool.flush())
+            .and_then(|_| self.spool.seek(SeekFrom::Start(0)))
+            .and_then(|_| io::copy(&mut self.spool, &mut self.writer))
+            .and_then(|_| self.writer.write_all(tail.as_bytes()))
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("写入报告失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::io::Cursor;
+
+    fn asset(ip: &str, port: u16, server: &str, title: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port: Some(port),
+            server: server.to_string(),
+            title: title.to_string(),
+            ..Asset::new("fofa", Value::Null)
+        }
+    }
+
+    fn render(style: ReportStyle, pages: &[&[Asset]]) -> String {
+        let mut output = Vec::new();
+        {
+            let mut writer = ReportAssetWriter::new(style, &mut output, Cursor::new(Vec::new()));
+            for page in pages {
+                writer.write_page(page).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_markdown_report_summarizes_before_rows() {
+        let report = render(
+            ReportStyle::Markdown,
+            &[
+                &[asset("1.1.1.1", 443, "nginx", "a|b")],
+                &[
+                    asset("2.2.2.2", 443, "nginx", "登录\n页"),
+                    asset("3.3.3.3", 80, "", ""),
+                ],
+            ],
+        );
+
+        assert!(report.contains("资产总数：3\n"));
+        assert!(report
+            .contains("## 端口分布\n\n| 取值 | 数量 |\n| --- | ---: |\n| 443 | 2 |\n| 80 | 1 |\n"));
+        assert!(report.contains("## 国家分布\n\n无数据\n"));
+        assert!(report.contains("| 1.1.1.1 | 443 |  |  | a\\|b | nginx |  | fofa |\n"));
+        assert!(report.contains("| 登录 页 |"));
+        // 统计在资产列表之前
+        assert!(report.find("Server 分布").unwrap() < report.find("1.1.1.1").unwrap());
+    }
+
+    #[test]
+    fn test_html_report_is_escaped_and_closed() {
+        let report = render(
+            ReportStyle::Html,
+            &[&[asset(
+                "1.1.1.1",
+                8080,
+                "Apache",
+                "<script>alert(1)</script>",
+            )]],
+        );
+
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.ends_with("</tbody>\n</table>\n</body>\n</html>\n"));
+        assert!(report.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!report.contains("<script>"));
+        assert!(report.contains("<tr><td>Apache</td><td>1</td></tr>"));
+    }
+}
@@ -0,0 +1,239 @@
+use super::writer::AssetWriter;
+use crate::api::asset::Asset;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+use std::net::IpAddr;
+
+/// masscan 配置中的默认发包速率
+const MASSCAN_RATE: u32 = 1000;
+
+/// 目标列表样式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetStyle {
+    /// 去重的 `ip:port`，每行一个
+    HostPort,
+    /// 去重的 http / https URL，供 httpx、nuclei 使用
+    Url,
+    /// `nmap -iL` 主机列表，行尾注释为该主机的端口
+    Nmap,
+    /// `masscan -c` 配置文件
+    Masscan,
+}
+
+// 主机排序：IP 按数值，其余（域名）按字母排在 IP 之后
+fn host_order(host: &str) -> (bool, Option<IpAddr>, &str) {
+    let ip = host.parse().ok();
+    (ip.is_none(), ip, host)
+}
+
+fn join_ports(ports: &BTreeSet<u16>) -> String {
+    ports
+        .iter()
+        .map(|port| port.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 安全工具可直接使用的目标列表导出器
+///
+/// `ip:port` 与 URL 列表边读边写，只保留去重用的集合；nmap 与 masscan 需要按主机
+/// 汇总端口，结束时统一写出。
+pub struct TargetAssetWriter<W: Write> {
+    style: TargetStyle,
+    writer: W,
+    seen: HashSet<String>,
+    /// 主机 → 端口
+    ports: BTreeMap<String, BTreeSet<u16>>,
+}
+
+impl<W: Write> TargetAssetWriter<W> {
+    pub fn new(style: TargetStyle, writer: W) -> Self {
+        Self {
+            style,
+            writer,
+            seen: HashSet::new(),
+            ports: BTreeMap::new(),
+        }
+    }
+
+    // 资产的扫描目标主机，优先 IP
+    fn host(asset: &Asset) -> Option<&str> {
+        [asset.ip.trim(), asset.host.trim()]
+            .into_iter()
+            .find(|host| !host.is_empty())
+    }
+
+    fn line(&self, asset: &Asset) -> Option<String> {
+        match self.style {
+            TargetStyle::HostPort => {
+                let host = Self::host(asset)?;
+                let port = asset.port?;
+                Some(match host.parse::<IpAddr>() {
+                    Ok(IpAddr::V6(ip)) => format!("[{}]:{}", ip, port),
+                    _ => format!("{}:{}", host, port),
+                })
+            }
+            TargetStyle::Url => {
+                let url = asset.url.trim();
+                (url.starts_with("http://") || url.starts_with("https://")).then(|| url.to_string())
+            }
+            TargetStyle::Nmap | TargetStyle::Masscan => None,
+        }
+    }
+
+    fn render_nmap(&self) -> String {
+        let mut hosts: Vec<(&String, &BTreeSet<u16>)> = self.ports.iter().collect();
+        hosts.sort_by(|a, b| host_order(a.0).cmp(&host_order(b.0)));
+        let all_ports: BTreeSet<u16> = self.ports.values().flatten().copied().collect();
+
+        let mut output = String::from("# nmap -iL <本文件>");
+        if !all_ports.is_empty() {
+            output.push_str(&format!(" -p {}", join_ports(&all_ports)));
+        }
+        output.push('\n');
+        for (host, ports) in hosts {
+            if ports.is_empty() {
+                output.push_str(&format!("{}\n", host));
+            } else {
+                output.push_str(&format!("{} # {}\n", host, join_ports(ports)));
+            }
+        }
+        output
+    }
+
+    fn render_masscan(&self) -> String {
+        // masscan 只接受 IP
+        let mut ips: Vec<IpAddr> = self.ports.keys().filter_map(|h| h.parse().ok()).collect();
+        ips.sort();
+        let ports: BTreeSet<u16> = ips
+            .iter()
+            .flat_map(|ip| &self.ports[&ip.to_string()])
+            .copied()
+            .collect();
+
+        let mut output = String::from("# masscan -c <本文件>\n");
+        output.push_str(&format!("rate = {}\n", MASSCAN_RATE));
+        output.push_str("output-format = list\n");
+        output.push_str("output-filename = masscan.txt\n");
+        if !ports.is_empty() {
+            output.push_str(&format!("ports = {}\n", join_ports(&ports)));
+        }
+        for ip in ips {
+            output.push_str(&format!("range = {}\n", ip));
+        }
+        output
+    }
+}
+
+impl<W: Write> AssetWriter for TargetAssetWriter<W> {
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        let mut buffer = String::new();
+        for asset in assets {
+            if matches!(self.style, TargetStyle::Nmap | TargetStyle::Masscan) {
+                if let Some(host) = Self::host(asset) {
+                    // 统一 IP 写法，避免同一地址因大小写或前导零重复
+                    let host = host
+                        .parse::<IpAddr>()
+                        .map(|ip| ip.to_string())
+                        .unwrap_or_else(|_| host.to_lowercase());
+                    let ports = self.ports.entry(host).or_default();
+                    ports.extend(asset.port);
+                }
+                continue;
+            }
+            if let Some(line) = self.line(asset) {
+                if self.seen.insert(line.clone()) {
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+                }
+            }
+        }
+        self.writer
+            .write_all(buffer.as_bytes())
+            .map_err(|e| format!("写入目标列表失败: {}", e))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        let tail = match self.style {
+            TargetStyle::Nmap => self.render_nmap(),
+            TargetStyle::Masscan => self.render_masscan(),
+            TargetStyle::HostPort | TargetStyle::Url => String::new(),
+        };
+        self.writer
+            .write_all(tail.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("写入目标列表失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn asset(ip: &str, port: Option<u16>, protocol: &str, domain: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port,
+            protocol: protocol.to_string(),
+            domain: domain.to_string(),
+            ..Asset::new("fofa", Value::Null)
+        }
+        .finalize()
+    }
+
+    fn assets() -> Vec<Asset> {
+        vec![
+            asset("10.0.0.2", Some(443), "https", "a.com"),
+            asset("10.0.0.2", Some(443), "https", "b.com"),
+            asset("10.0.0.10", Some(22), "ssh", ""),
+            asset("10.0.0.2", Some(80), "http", ""),
+            asset("2001:db8::1", Some(8080), "http", ""),
+            asset("", None, "", "c.com"),
+        ]
+    }
+
+    fn render(style: TargetStyle) -> String {
+        let mut output = Vec::new();
+        {
+            let mut writer = TargetAssetWriter::new(style, &mut output);
+            let assets = assets();
+            writer.write_page(&assets[..3]).unwrap();
+            writer.write_page(&assets[3..]).unwrap();
+            writer.finish().unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_host_port_and_url_lists_are_deduplicated() {
+        assert_eq!(
+            render(TargetStyle::HostPort),
+            "10.0.0.2:443\n10.0.0.10:22\n10.0.0.2:80\n[2001:db8::1]:8080\n"
+        );
+        assert_eq!(
+            render(TargetStyle::Url),
+            "https://a.com\nhttps://b.com\nhttp://10.0.0.2\nhttp://[2001:db8::1]:8080\nhttp://c.com\n"
+        );
+    }
+
+    #[test]
+    fn test_nmap_list_maps_ports_per_host() {
+        assert_eq!(
+            render(TargetStyle::Nmap),
+            "# nmap -iL <本文件> -p 22,80,443,8080\n\
+             10.0.0.2 # 80,443\n\
+             10.0.0.10 # 22\n\
+             2001:db8::1 # 8080\n\
+             c.com\n"
+        );
+    }
+
+    #[test]
+    fn test_masscan_config_only_lists_ips() {
+        let config = render(TargetStyle::Masscan);
+        assert!(config.contains("ports = 22,80,443,8080\n"));
+        assert!(config.ends_with("range = 10.0.0.2\nrange = 10.0.0.10\nrange = 2001:db8::1\n"));
+        assert!(!config.contains("c.com"));
+    }
+}
@@ -0,0 +1,411 @@
+use super::report::{ReportAssetWriter, ReportStyle};
+use super::targets::{TargetAssetWriter, TargetStyle};
+use super::xlsx::XlsxAssetWriter;
+use crate::api::asset::{Asset, CSV_HEADERS};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    /// 每行一条 JSON，便于 jq、ELK 等按行处理
+    Ndjson,
+    /// 每个平台一个工作表
+    Xlsx,
+    /// 带汇总统计的 Markdown 报告
+    Markdown,
+    /// 带汇总统计、可独立打开的 HTML 报告
+    Html,
+    /// 去重的 `ip:port` 列表
+    HostPort,
+    /// httpx / nuclei 使用的 URL 列表
+    UrlList,
+    /// `nmap -iL` 主机列表与各主机端口
+    Nmap,
+    /// masscan 配置文件
+    Masscan,
+}
+
+impl ExportFormat {
+    /// 按前端传入的格式名称解析
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            "xlsx" => Ok(ExportFormat::Xlsx),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "html" => Ok(ExportFormat::Html),
+            "ipport" => Ok(ExportFormat::HostPort),
+            "urls" => Ok(ExportFormat::UrlList),
+            "nmap" => Ok(ExportFormat::Nmap),
+            "masscan" => Ok(ExportFormat::Masscan),
+            other => Err(format!("不支持的导出格式: {}", other)),
+        }
+    }
+
+    /// 格式名称，[`ExportFormat::from_name`] 可解析回来
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Markdown => "markdown",
+            ExportFormat::Html => "html",
+            ExportFormat::HostPort => "ipport",
+            ExportFormat::UrlList => "urls",
+            ExportFormat::Nmap => "nmap",
+            ExportFormat::Masscan => "masscan",
+        }
+    }
+
+    /// 文件扩展名，目标列表带上用途以便区分
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::HostPort => "ipport.txt",
+            ExportFormat::UrlList => "urls.txt",
+            ExportFormat::Nmap => "nmap.txt",
+            ExportFormat::Masscan => "masscan.conf",
+        }
+    }
+}
+
+/// 逐页写入资产的导出器
+///
+/// 每到一页就写出一页，内存占用与导出总条数无关。列固定为归一化的
+/// [`CSV_HEADERS`]，各平台返回字段不一致时不会因首行缺列而丢数据。
+pub trait AssetWriter {
+    /// 写入一页资产
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String>;
+
+    /// 写入结尾并刷新缓冲，之后不应再写入
+    fn finish(&mut self) -> Result<(), String>;
+}
+
+/// CSV 导出器，创建时即写入表头
+pub struct CsvAssetWriter<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> CsvAssetWriter<W> {
+    pub fn new(inner: W) -> Result<Self, String> {
+        let mut writer = csv::Writer::from_writer(inner);
+        writer
+            .write_record(CSV_HEADERS)
+            .map_err(|e| format!("写入CSV头失败: {}", e))?;
+        Ok(Self { writer })
+    }
+}
+
+impl<W: Write> AssetWriter for CsvAssetWriter<W> {
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        for asset in assets {
+            self.writer
+                .write_record(asset.csv_record())
+                .map_err(|e| format!("写入数据失败: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("保存CSV失败: {}", e))
+    }
+}
+
+/// JSON 导出器，输出与一次性序列化整个数组的格式一致
+pub struct JsonAssetWriter<W: Write> {
+    writer: W,
+    empty: bool,
+}
+
+impl<W: Write> JsonAssetWriter<W> {
+    pub fn new(mut inner: W) -> Result<Self, String> {
+        inner
+            .write_all(b"[")
+            .map_err(|e| format!("写入JSON文件失败: {}", e))?;
+        Ok(Self {
+            writer: inner,
+            empty: true,
+        })
+    }
+}
+
+impl<W: Write> AssetWriter for JsonAssetWriter<W> {
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        let mut buffer = String::new();
+        for asset in assets {
+            let json = serde_json::to_string_pretty(asset)
+                .map_err(|e| format!("序列化JSON失败: {}", e))?;
+            buffer.push_str(if self.empty { "\n" } else { ",\n" });
+            self.empty = false;
+            // 字符串中的换行已被转义，按行缩进不会破坏内容
+            for (i, line) in json.lines().enumerate() {
+                if i > 0 {
+                    buffer.push('\n');
+                }
+                buffer.push_str("  ");
+                buffer.push_str(line);
+            }
+        }
+        self.writer
+            .write_all(buffer.as_bytes())
+            .map_err(|e| format!("写入JSON文件失败: {}", e))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        let end: &[u8] = if self.empty { b"]" } else { b"\n]" };
+        self.writer
+            .write_all(end)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("写入JSON文件失败: {}", e))
+    }
+}
+
+/// NDJSON 导出器，每条资产一行
+pub struct NdjsonAssetWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonAssetWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { writer: inner }
+    }
+}
+
+impl<W: Write> AssetWriter for NdjsonAssetWriter<W> {
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        let mut buffer = String::new();
+        for asset in assets {
+            let line =
+                serde_json::to_string(asset).map_err(|e| format!("序列化JSON失败: {}", e))?;
+            buffer.push_str(&line);
+            buffer.push('\n');
+        }
+        self.writer
+            .write_all(buffer.as_bytes())
+            .map_err(|e| format!("写入NDJSON文件失败: {}", e))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer
+            .flush()
+            .map_err(|e| format!("写入NDJSON文件失败: {}", e))
+    }
+}
+
+/// 正在写入的导出文件
+///
+/// 数据先写入同目录下的 `.part` 临时文件，[`ExportFile::persist`] 时才改名为
+/// 目标文件，未完成的导出不会被误当成完整结果；未保存就被丢弃时删除临时文件。
+pub struct ExportFile {
+    writer: Option<Box<dyn AssetWriter + Send>>,
+    path: PathBuf,
+    temp_path: PathBuf,
+    /// 报告格式暂存资产行的临时文件
+    spool_path: Option<PathBuf>,
+    rows: u64,
+}
+
+impl ExportFile {
+    /// 为目标路径创建导出文件
+    pub fn create(path: impl AsRef<Path>, format: ExportFormat) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+            }
+        }
+
+        let mut temp_name = path
+            .file_name()
+            .ok_or_else(|| "无效的文件路径".to_string())?
+            .to_os_string();
+        temp_name.push(".part");
+        let temp_path = path.with_file_name(temp_name);
+
+        let mut spool_path = None;
+        let writer: Box<dyn AssetWriter + Send> = match format {
+            // 工作簿在结束时一次保存
+            ExportFormat::Xlsx => Box::new(XlsxAssetWriter::new(temp_path.clone())),
+            format => {
+                let file =
+                    File::create(&temp_path).map_err(|e| format!("创建导出文件失败: {}", e))?;
+                let file = BufWriter::new(file);
+                match format {
+                    ExportFormat::Json => Box::new(JsonAssetWriter::new(file)?),
+                    ExportFormat::Ndjson => Box::new(NdjsonAssetWriter::new(file)),
+                    ExportFormat::Markdown | ExportFormat::Html => {
+                        let style = if format == ExportFormat::Html {
+                            ReportStyle::Html
+                        } else {
+                            ReportStyle::Markdown
+                        };
+                        let path = temp_path.with_extension("rows.part");
+                        let spool = File::options()
+                            .read(true)
+                            .write(true)
+                            .create(true)
+                            .truncate(true)
+                            .open(&path)
+                            .map_err(|e| format!("创建导出文件失败: {}", e))?;
+                        spool_path = Some(path);
+                        Box::new(ReportAssetWriter::new(style, file, spool))
+                    }
+                    ExportFormat::HostPort => {
+                        Box::new(TargetAssetWriter::new(TargetStyle::HostPort, file))
+                    }
+                    ExportFormat::UrlList => {
+                        Box::new(TargetAssetWriter::new(TargetStyle::Url, file))
+                    }
+                    ExportFormat::Nmap => Box::new(TargetAssetWriter::new(TargetStyle::Nmap, file)),
+                    ExportFormat::Masscan => {
+                        Box::new(TargetAssetWriter::new(TargetStyle::Masscan, file))
+                    }
+                    _ => Box::new(CsvAssetWriter::new(file)?),
+                }
+            }
+        };
+
+        Ok(Self {
+            writer: Some(writer),
+            path,
+            temp_path,
+            spool_path,
+            rows: 0,
+        })
+    }
+
+    /// 追加一页资产
+    pub fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| "导出文件已关闭".to_string())?;
+        writer.write_page(assets)?;
+        self.rows += assets.len() as u64;
+        Ok(())
+    }
+
+    /// 已写入的条数
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// 完成写入并保存到创建时的路径
+    pub fn persist(self) -> Result<PathBuf, String> {
+        let path = self.path.clone();
+        self.persist_as(path)
+    }
+
+    /// 完成写入并保存到指定路径（如部分导出使用另外的文件名）
+    pub fn persist_as(mut self, path: impl AsRef<Path>) -> Result<PathBuf, String> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        let path = path.as_ref().to_path_buf();
+        fs::rename(&self.temp_path, &path).map_err(|e| format!("保存导出文件失败: {}", e))?;
+        Ok(path)
+    }
+}
+
+impl Drop for ExportFile {
+    fn drop(&mut self) {
+        // 先关闭文件，再删除未保存（或保存失败）的临时文件
+        self.writer.take();
+        fs::remove_file(&self.temp_path).ok();
+        if let Some(spool_path) = &self.spool_path {
+            fs::remove_file(spool_path).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn asset(ip: &str, title: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port: Some(80),
+            title: title.to_string(),
+            ..Asset::new("hunter", Value::Null)
+        }
+    }
+
+    #[test]
+    fn test_json_stream_matches_whole_array() {
+        let assets = [asset("1.1.1.1", "a\nb"), asset("2.2.2.2", "c")];
+        for pages in [vec![], vec![&assets[..1], &assets[1..]], vec![&assets[..]]] {
+            let mut output = Vec::new();
+            let mut written = Vec::new();
+            {
+                let mut writer = JsonAssetWriter::new(&mut output).unwrap();
+                for page in pages {
+                    writer.write_page(page).unwrap();
+                    written.extend_from_slice(page);
+                }
+                writer.finish().unwrap();
+            }
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                serde_json::to_string_pretty(&written).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_csv_rows_keep_fixed_columns() {
+        let mut output = Vec::new();
+        {
+            let mut writer = CsvAssetWriter::new(&mut output).unwrap();
+            writer.write_page(&[asset("1.1.1.1", "")]).unwrap();
+            // 后一页多出字段也落在固定的列中
+            let mut extra = asset("2.2.2.2", "login");
+            extra.icp = "京ICP备1号".to_string();
+            writer.write_page(&[extra]).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        assert_eq!(reader.headers().unwrap().len(), CSV_HEADERS.len());
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[1][16], "京ICP备1号");
+        assert!(rows.iter().all(|row| row.len() == CSV_HEADERS.len()));
+    }
+
+    #[test]
+    fn test_export_file_is_visible_only_after_persist() {
+        let dir = std::env::temp_dir().join(format!("asset-mapping-writer-{}", std::process::id()));
+        let path = dir.join("hunter_export.csv");
+
+        let mut file = ExportFile::create(&path, ExportFormat::Csv).unwrap();
+        file.write_page(&[asset("1.1.1.1", "")]).unwrap();
+        assert!(!path.exists());
+        drop(file);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let mut file = ExportFile::create(&path, ExportFormat::Csv).unwrap();
+        file.write_page(&[asset("1.1.1.1", ""), asset("2.2.2.2", "")])
+            .unwrap();
+        assert_eq!(file.rows(), 2);
+        let partial = dir.join("hunter_export_partial.csv");
+        assert_eq!(file.persist_as(&partial).unwrap(), partial);
+        assert_eq!(fs::read_to_string(&partial).unwrap().lines().count(), 3);
+        assert!(!path.exists());
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,172 @@
+use super::writer::AssetWriter;
+use crate::api::asset::{Asset, CSV_HEADERS};
+use rust_xlsxwriter::{Format, Workbook};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Excel 单元格允许的最大字符数
+const MAX_CELL_CHARS: usize = 32767;
+
+/// 按数字写入的列
+const NUMBER_COLUMNS: [&str; 3] = ["port", "status_code", "asn"];
+
+/// XLSX 导出器，每个来源平台一个工作表
+///
+/// 工作表使用常量内存模式，写完的行即刷到磁盘上的临时文件，结束时打包成
+/// 工作簿，导出大量数据时内存占用保持平稳。
+pub struct XlsxAssetWriter {
+    workbook: Workbook,
+    path: PathBuf,
+    header_format: Format,
+    /// 平台 → (工作表索引, 下一行)
+    sheets: HashMap<String, (usize, u32)>,
+}
+
+// 工作表名称最长 31 个字符，且不能包含 []:*?/\
+fn sheet_name(platform: &str) -> String {
+    let name: String = platform
+        .chars()
+        .map(|c| match c {
+            '[' | ']' | ':' | '*' | '?' | '/' | '\\' => '_',
+            c => c,
+        })
+        .take(31)
+        .collect();
+    let name = name.trim_matches('\'').trim();
+    if name.is_empty() {
+        "assets".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+impl XlsxAssetWriter {
+    /// 工作簿在 [`AssetWriter::finish`] 时保存到 `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            workbook: Workbook::new(),
+            path,
+            header_format: Format::new().set_bold(),
+            sheets: HashMap::new(),
+        }
+    }
+
+    // 取平台对应的工作表，首次出现时创建并写入表头
+    fn sheet_for(&mut self, platform: &str) -> Result<(usize, u32), String> {
+        if let Some(sheet) = self.sheets.get(platform) {
+            return Ok(*sheet);
+        }
+
+        // 不同平台名清理后可能重名，加序号区分
+        let base = sheet_name(platform);
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self
+            .sheets
+            .keys()
+            .any(|existing| sheet_name(existing).eq_ignore_ascii_case(&name))
+        {
+            let tag = format!("_{}", suffix);
+            name = format!(
+                "{}{}",
+                base.chars().take(31 - tag.len()).collect::<String>(),
+                tag
+            );
+            suffix += 1;
+        }
+
+        let worksheet = self.workbook.add_worksheet_with_constant_memory();
+        worksheet
+            .set_name(&name)
+            .and_then(|sheet| sheet.write_row_with_format(0, 0, CSV_HEADERS, &self.header_format))
+            .and_then(|sheet| sheet.set_freeze_panes(1, 0))
+            .map_err(|e| format!("创建工作表失败: {}", e))?;
+
+        let sheet = (self.sheets.len(), 1);
+        self.sheets.insert(platform.to_string(), sheet);
+        Ok(sheet)
+    }
+}
+
+impl AssetWriter for XlsxAssetWriter {
+    fn write_page(&mut self, assets: &[Asset]) -> Result<(), String> {
+        for asset in assets {
+            let (index, row) = self.sheet_for(&asset.platform)?;
+            let worksheet = self
+                .workbook
+                .worksheet_from_index(index)
+                .map_err(|e| format!("写入XLSX失败: {}", e))?;
+
+            for (col, (header, value)) in CSV_HEADERS.iter().zip(asset.csv_record()).enumerate() {
+                let col = col as u16;
+                let number = NUMBER_COLUMNS
+                    .contains(header)
+                    .then(|| value.parse::<f64>().ok())
+                    .flatten();
+                let result = match number {
+                    Some(number) => worksheet.write_number(row, col, number).map(|_| ()),
+                    None if value.is_empty() => Ok(()),
+                    None if value.chars().count() > MAX_CELL_CHARS => {
+                        let value: String = value.chars().take(MAX_CELL_CHARS).collect();
+                        worksheet.write_string(row, col, value).map(|_| ())
+                    }
+                    None => worksheet.write_string(row, col, value).map(|_| ()),
+                };
+                result.map_err(|e| format!("写入XLSX失败: {}", e))?;
+            }
+
+            if let Some(sheet) = self.sheets.get_mut(&asset.platform) {
+                sheet.1 += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // 没有数据时也保留一个只有表头的工作表
+        if self.sheets.is_empty() {
+            self.sheet_for("")?;
+        }
+        self.workbook
+            .save(&self.path)
+            .map_err(|e| format!("保存XLSX失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheet_names_are_valid() {
+        assert_eq!(sheet_name("fofa"), "fofa");
+        assert_eq!(sheet_name("hunter/quake"), "hunter_quake");
+        assert_eq!(sheet_name(""), "assets");
+        assert_eq!(sheet_name(&"x".repeat(40)).len(), 31);
+    }
+
+    #[test]
+    fn test_one_sheet_per_platform() {
+        let path =
+            std::env::temp_dir().join(format!("asset-mapping-xlsx-{}.xlsx", std::process::id()));
+        let asset = |platform: &str, ip: &str| Asset {
+            ip: ip.to_string(),
+            port: Some(443),
+            ..Asset::new(platform, serde_json::Value::Null)
+        };
+
+        let mut writer = XlsxAssetWriter::new(path.clone());
+        writer
+            .write_page(&[asset("fofa", "1.1.1.1"), asset("hunter", "2.2.2.2")])
+            .unwrap();
+        writer.write_page(&[asset("fofa", "3.3.3.3")]).unwrap();
+        assert_eq!(writer.sheets["fofa"], (0, 3));
+        assert_eq!(writer.sheets["hunter"], (1, 2));
+        writer.finish().unwrap();
+
+        // XLSX 是 zip 包
+        let content = std::fs::read(&path).unwrap();
+        assert!(content.starts_with(b"PK"));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -12,6 +12,9 @@ pub struct QueryHistory {
     pub timestamp: String,
     pub success: bool,
     pub error_message: Option<String>,
+    /// 该次运行保存的结果快照（见 [`crate::store::AssetStore::save_snapshot`]），用于比对变化
+    #[serde(default)]
+    pub snapshot_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -74,6 +77,34 @@ pub fn add_history(
     results_count: u64,
     success: bool,
     error_message: Option<String>,
+) -> Result<(), String> {
+    push_history(platform, query, results_count, success, error_message, None)
+}
+
+// 添加带结果快照的历史记录
+pub fn add_snapshot_history(
+    platform: String,
+    query: String,
+    results_count: u64,
+    snapshot_id: i64,
+) -> Result<(), String> {
+    push_history(
+        platform,
+        query,
+        results_count,
+        true,
+        None,
+        Some(snapshot_id),
+    )
+}
+
+fn push_history(
+    platform: String,
+    query: String,
+    results_count: u64,
+    success: bool,
+    error_message: Option<String>,
+    snapshot_id: Option<i64>,
 ) -> Result<(), String> {
     let mut store = load_history()?;
 
@@ -88,6 +119,7 @@ pub fn add_history(
         timestamp: now.to_rfc3339(),
         success,
         error_message,
+        snapshot_id,
     };
 
     // 添加到列表开头（最新的在前面）
@@ -109,6 +141,16 @@ pub fn get_all_history() -> Result<Vec<QueryHistory>, String> {
     Ok(store.records)
 }
 
+// 按ID获取历史记录
+pub fn get_history(id: &str) -> Result<QueryHistory, String> {
+    let store = load_history()?;
+    store
+        .records
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or_else(|| format!("历史记录不存在: {}", id))
+}
+
 // 根据平台筛选历史记录
 pub fn get_history_by_platform(platform: &str) -> Result<Vec<QueryHistory>, String> {
     let store = load_history()?;
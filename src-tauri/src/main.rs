@@ -7,28 +7,20 @@
 mod api;
 mod config;
 mod converter;
+mod diff;
 mod error;
+mod export;
 mod history;
 mod pocs;
+mod schedule;
+mod store;
 mod utils;
 
-/// 平台分发宏——统一处理 4 个平台的 match 分发
-macro_rules! dispatch_platform {
-    ($platform:expr, $method:ident, $($arg:expr),* $(,)?) => {
-        match $platform {
-            "hunter" => api::hunter::$method($($arg),*).await,
-            "fofa" => api::fofa::$method($($arg),*).await,
-            "quake" => api::quake::$method($($arg),*).await,
-            "daydaymap" => api::daydaymap::$method($($arg),*).await,
-            _ => Err("不支持的平台".to_string()),
-        }
-    };
-}
-
 use config::ConfigManager;
 use converter::QueryConverter;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::Emitter;
@@ -74,6 +66,15 @@ struct Settings {
     // 请求超时（秒）
     #[serde(default = "default_timeout")]
     request_timeout: u32,
+    // 各平台请求频率限制
+    #[serde(default = "api::http::default_rate_limits")]
+    rate_limits: HashMap<String, api::http::RateLimit>,
+    // 失败重试策略
+    #[serde(default)]
+    retry_policy: api::http::RetryPolicy,
+    // 各平台 key 轮询策略
+    #[serde(default)]
+    key_rotation: HashMap<String, api::key_manager::KeyRotation>,
 }
 
 fn default_timeout() -> u32 {
@@ -117,7 +118,11 @@ pub fn create_http_client() -> Result<reqwest::Client, String> {
 pub struct ApiKeyValidationResult {
     pub valid: bool,
     pub message: Option<String>,
+    /// 额度说明，仅用于展示
     pub quota: Option<String>,
+    /// 剩余额度数值（单位见平台计费模型），平台未返回时为空
+    #[serde(default)]
+    pub remaining: Option<u64>,
 }
 
 // 进度事件结构体
@@ -134,6 +139,9 @@ pub struct ProgressEvent {
     pub total_pages: Option<u32>,
     pub total_results: Option<u64>,
     pub fetched_results: Option<u64>,
+    /// 多平台并发任务中事件所属的平台
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
 }
 
 // 扫描配置结构体
@@ -168,20 +176,23 @@ async fn search_assets(
     query: String,
     page: u32,
     page_size: u32,
-) -> Result<serde_json::Value, String> {
+) -> Result<api::SearchPage, error::ApiError> {
     #[cfg(debug_assertions)]
     eprintln!(
         "[DEBUG] search_assets: platform={}, query={}, page={}, page_size={}",
         platform, query, page, page_size
     );
 
-    let result = dispatch_platform!(platform.as_str(), search, &query, page, page_size);
+    let result = match api::get_source(&platform) {
+        Ok(source) => source.search(&query, page, page_size).await,
+        Err(e) => Err(error::ApiError::Config(e)),
+    };
 
     // 保存历史记录
     match &result {
         Ok(data) => {
             // 提取结果数量
-            let results_count = data["total"].as_u64().unwrap_or(0);
+            let results_count = data.total;
 
             // 保存成功的查询记录
             if let Err(e) =
@@ -189,15 +200,20 @@ async fn search_assets(
             {
                 eprintln!("保存历史记录失败: {}", e);
             }
+
+            // 同步到本地资产库
+            if let Err(e) = store::sync_assets(&platform, &query, &data.results) {
+                eprintln!("同步本地资产库失败: {}", e);
+            }
         }
-        Err(error_msg) => {
+        Err(error) => {
             // 保存失败的查询记录
             if let Err(e) = history::add_history(
                 platform.clone(),
                 query.clone(),
                 0,
                 false,
-                Some(error_msg.clone()),
+                Some(error.to_string()),
             ) {
                 eprintln!("保存历史记录失败: {}", e);
             }
@@ -207,7 +223,7 @@ async fn search_assets(
     result
 }
 
-// 导出当前查询结果（带进度事件）
+// 导出当前查询结果（带进度事件），每页完成后写入检查点，可通过 resume_export 继续
 #[tauri::command]
 #[allow(clippy::too_many_arguments)]
 async fn export_results_with_progress(
@@ -217,41 +233,225 @@ async fn export_results_with_progress(
     query: String,
     pages: u32,
     page_size: u32,
-    _time_range: String,
-    _start_date: Option<String>,
-    _end_date: Option<String>,
+    time_range: String,
+    start_date: Option<String>,
+    end_date: Option<String>,
     format: Option<String>,
+    budget: Option<u64>,
 ) -> Result<String, String> {
-    let export_path = config::get_export_path()?;
+    start_checkpointed_export(
+        &window,
+        task_id,
+        platform,
+        query,
+        pages,
+        page_size,
+        converter::TimeRange::from_options(&time_range, &start_date, &end_date),
+        format,
+        budget,
+    )
+    .await
+}
+
+// 按 config.json 中平台的时间范围规则，把时间范围并入查询语句
+fn restrict_time_range(
+    window: &tauri::WebviewWindow,
+    platform: &str,
+    query: &str,
+    time_range: &converter::TimeRange,
+) -> Result<String, String> {
+    let config_path = get_config_path(window.app_handle())?;
+    let config_manager =
+        ConfigManager::from_file(&config_path).map_err(|e| format!("加载配置文件失败: {}", e))?;
+    QueryConverter::new(config_manager)
+        .apply_time_range(query, platform, time_range)
+        .map_err(|e| format!("{}平台时间范围转换失败: {}", platform, e))
+}
+
+// 创建检查点并开始导出，没有更多数据时提前结束；时间范围只在这里并入查询语句一次，
+// 继续导出时沿用检查点中的查询语句
+#[allow(clippy::too_many_arguments)]
+async fn start_checkpointed_export(
+    window: &tauri::WebviewWindow,
+    task_id: String,
+    platform: String,
+    query: String,
+    pages: u32,
+    page_size: u32,
+    time_range: Option<converter::TimeRange>,
+    format: Option<String>,
+    budget: Option<u64>,
+) -> Result<String, String> {
+    let source = api::get_source(&platform)?;
+    let capabilities = source.capabilities();
+    // 按条计费且预算不足一页时缩小每页条数
+    let page_size = capabilities
+        .cost
+        .fit_page_size(page_size.min(capabilities.max_page_size), budget);
+    if page_size == 0 {
+        return Err(format!(
+            "额度预算不足以获取一条数据（每条消耗 {} {}）",
+            capabilities.cost.credits, capabilities.cost.unit
+        ));
+    }
+    let export_format = export::ExportFormat::from_name(format.as_deref().unwrap_or("csv"))?;
+    let query = match &time_range {
+        Some(range) => restrict_time_range(window, &platform, &query, range)?,
+        None => query,
+    };
+
+    let mut checkpoint = export::ExportCheckpoint::create(
+        &task_id,
+        &platform,
+        &query,
+        pages,
+        page_size,
+        export_format.name(),
+    )?;
+    if budget.is_some() {
+        checkpoint.set_budget(budget)?;
+    }
+    if time_range.is_some() {
+        checkpoint.set_time_range(time_range)?;
+    }
 
     // 发送开始事件
     emit_progress(
-        &window,
+        window,
         &ProgressEvent {
             task_id: task_id.clone(),
             percent: 0.0,
             status: "running".to_string(),
             status_text: format!("正在准备导出 [{}] ...", platform),
-            log_message: Some(format!(
-                "开始导出: 平台={}, 页数={}, 每页={}",
-                platform, pages, page_size
-            )),
+            log_message: Some(match budget {
+                Some(budget) => format!(
+                    "开始导出: 平台={}, 页数={}, 每页={}, 额度预算={} {}",
+                    platform, pages, page_size, budget, capabilities.cost.unit
+                ),
+                None => format!(
+                    "开始导出: 平台={}, 页数={}, 每页={}",
+                    platform, pages, page_size
+                ),
+            }),
             log_type: Some("info".to_string()),
             current_page: Some(0),
             total_pages: Some(pages),
             total_results: None,
             fetched_results: Some(0),
+            platform: None,
         },
     );
 
-    let mut all_results = Vec::new();
-    let max_retries = 3;
-    let retry_delay_secs = 5;
+    run_checkpointed_export(window, checkpoint).await
+}
+
+// 从检查点继续未完成的导出任务
+#[tauri::command]
+async fn resume_export(window: tauri::WebviewWindow, task_id: String) -> Result<String, String> {
+    let checkpoint = export::ExportCheckpoint::load(&task_id)?;
 
-    for page in 1..=pages {
+    emit_progress(
+        &window,
+        &ProgressEvent {
+            task_id: task_id.clone(),
+            percent: (checkpoint.last_page as f64 / checkpoint.pages as f64) * 100.0,
+            status: "running".to_string(),
+            status_text: format!("正在继续导出 [{}] ...", checkpoint.platform),
+            log_message: Some(format!(
+                "从第 {} 页继续导出，已获取 {} 条数据",
+                checkpoint.next_page(),
+                checkpoint.fetched
+            )),
+            log_type: Some("info".to_string()),
+            current_page: Some(checkpoint.last_page),
+            total_pages: Some(checkpoint.pages),
+            total_results: checkpoint.total,
+            fetched_results: Some(checkpoint.fetched),
+            platform: None,
+        },
+    );
+
+    run_checkpointed_export(&window, checkpoint).await
+}
+
+// 列出未完成（可继续）的导出任务
+#[tauri::command]
+fn list_export_checkpoints() -> Result<Vec<export::ExportCheckpoint>, String> {
+    export::ExportCheckpoint::list()
+}
+
+// 按检查点逐页导出，导出期间可通过 cancel_export 取消
+async fn run_checkpointed_export(
+    window: &tauri::WebviewWindow,
+    checkpoint: export::ExportCheckpoint,
+) -> Result<String, String> {
+    let task_id = checkpoint.task_id.clone();
+    let cancel_signal = export::cancel::register(&task_id);
+    let result = export_checkpoint_pages(window, checkpoint, cancel_signal).await;
+    export::cancel::finish(&task_id);
+    result
+}
+
+// 逐页导出，每页完成后先落盘再继续；全部完成后生成导出文件并删除检查点。
+// 中途失败或取消时导出已取得的数据并保留检查点；一页都没取到时删除检查点并释放固定的 key
+async fn export_checkpoint_pages(
+    window: &tauri::WebviewWindow,
+    mut checkpoint: export::ExportCheckpoint,
+    mut cancel_signal: export::cancel::CancelSignal,
+) -> Result<String, String> {
+    let export_path = config::get_export_path()?;
+    let source = api::get_source(&checkpoint.platform)?;
+    let task_id = checkpoint.task_id.clone();
+    let platform = checkpoint.platform.clone();
+    let query = checkpoint.query.clone();
+    let pages = checkpoint.pages;
+    let page_size = checkpoint.page_size;
+
+    // 游标分页的平台按上一页返回的游标继续请求
+    let cursor_paging = source.capabilities().pagination == api::source::Pagination::Cursor;
+    let cost = source.capabilities().cost;
+    // 游标平台上一页已是最后一页时无需继续
+    let last_page = if cursor_paging && checkpoint.last_page > 0 && checkpoint.cursor.is_none() {
+        checkpoint.last_page
+    } else {
+        pages
+    };
+    let mut failed = false;
+    let mut cancelled = false;
+    let mut budget_reached = false;
+
+    for page in checkpoint.next_page()..=last_page {
         let pct = ((page - 1) as f64 / pages as f64) * 100.0;
+
+        // 预算是硬上限：下一页可能超出时停止获取
+        if !checkpoint.within_budget(cost.page_cost(page_size)) {
+            emit_progress(
+                window,
+                &ProgressEvent {
+                    task_id: task_id.clone(),
+                    percent: pct,
+                    status: "running".to_string(),
+                    status_text: "已达到额度预算，停止获取".to_string(),
+                    log_message: Some(format!(
+                        "⚠ 已消耗 {} {}，再获取第 {} 页将超出预算 {}，停止获取",
+                        checkpoint.spent,
+                        cost.unit,
+                        page,
+                        checkpoint.budget.unwrap_or_default()
+                    )),
+                    log_type: Some("warning".to_string()),
+                    current_page: Some(page),
+                    total_pages: Some(pages),
+                    total_results: None,
+                    fetched_results: Some(checkpoint.fetched),
+                    platform: None,
+                },
+            );
+            budget_reached = true;
+            break;
+        }
         emit_progress(
-            &window,
+            window,
             &ProgressEvent {
                 task_id: task_id.clone(),
                 percent: pct,
@@ -262,246 +462,435 @@ async fn export_results_with_progress(
                 current_page: Some(page),
                 total_pages: Some(pages),
                 total_results: None,
-                fetched_results: Some(all_results.len() as u64),
+                fetched_results: Some(checkpoint.fetched),
+                platform: None,
             },
         );
 
-        let mut retry_count = 0;
-        let mut page_success = false;
-
-        while retry_count < max_retries && !page_success {
-            let result = match platform.as_str() {
-                "hunter" => api::hunter::search(&query, page, page_size).await,
-                "fofa" => api::fofa::search(&query, page, page_size).await,
-                "quake" => api::quake::search(&query, page, page_size).await,
-                "daydaymap" => api::daydaymap::search(&query, page, page_size).await,
-                _ => Err("不支持的平台".to_string()),
-            };
-
-            match result {
-                Ok(data) => {
-                    if let Some(results) = data["results"].as_array() {
-                        all_results.extend(results.clone());
-                        emit_progress(
-                            &window,
-                            &ProgressEvent {
-                                task_id: task_id.clone(),
-                                percent: (page as f64 / pages as f64) * 100.0,
-                                status: "running".to_string(),
-                                status_text: format!(
-                                    "第 {}/{} 页完成，已获取 {} 条数据",
-                                    page,
-                                    pages,
-                                    all_results.len()
-                                ),
-                                log_message: Some(format!(
-                                    "✓ 第 {} 页成功: {} 条",
-                                    page,
-                                    results.len()
-                                )),
-                                log_type: Some("success".to_string()),
-                                current_page: Some(page),
-                                total_pages: Some(pages),
-                                total_results: data["total"].as_u64(),
-                                fetched_results: Some(all_results.len() as u64),
-                            },
-                        );
-                        page_success = true;
-                    }
-                }
-                Err(e) => {
-                    retry_count += 1;
-                    if retry_count < max_retries {
-                        emit_progress(
-                            &window,
-                            &ProgressEvent {
-                                task_id: task_id.clone(),
-                                percent: pct,
-                                status: "running".to_string(),
-                                status_text: format!(
-                                    "第 {} 页失败，正在重试 ({}/{})...",
-                                    page, retry_count, max_retries
-                                ),
-                                log_message: Some(format!(
-                                    "⚠ 第 {} 页失败: {}，重试中...",
-                                    page, e
-                                )),
-                                log_type: Some("warning".to_string()),
-                                current_page: Some(page),
-                                total_pages: Some(pages),
-                                total_results: None,
-                                fetched_results: Some(all_results.len() as u64),
-                            },
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_secs(retry_delay_secs))
-                            .await;
+        let cursor = checkpoint.cursor.clone();
+        let fetched = checkpoint.fetched;
+        let request = api::http::with_retry(
+            &platform,
+            |attempt, e, delay| {
+                emit_progress(
+                    window,
+                    &ProgressEvent {
+                        task_id: task_id.clone(),
+                        percent: pct,
+                        status: "running".to_string(),
+                        status_text: format!(
+                            "第 {} 页失败，{} 秒后重试 (第 {} 次)...",
+                            page,
+                            delay.as_secs(),
+                            attempt
+                        ),
+                        log_message: Some(format!("⚠ 第 {} 页失败: {}，重试中...", page, e)),
+                        log_type: Some("warning".to_string()),
+                        current_page: Some(page),
+                        total_pages: Some(pages),
+                        total_results: None,
+                        fetched_results: Some(fetched),
+                        platform: None,
+                    },
+                )
+            },
+            || async {
+                // 开启粘性模式的平台整个导出任务使用同一个 key，并取回本页实际使用的 key
+                let (result, used_key) = api::key_manager::sticky_task_with_key(&task_id, async {
+                    if cursor_paging {
+                        source
+                            .search_cursor(&query, cursor.as_deref(), page_size)
+                            .await
                     } else {
-                        emit_progress(
-                            &window,
-                            &ProgressEvent {
-                                task_id: task_id.clone(),
-                                percent: pct,
-                                status: "error".to_string(),
-                                status_text: format!("第 {} 页失败，已达最大重试次数", page),
-                                log_message: Some(format!("✗ 第 {} 页最终失败: {}", page, e)),
-                                log_type: Some("error".to_string()),
-                                current_page: Some(page),
-                                total_pages: Some(pages),
-                                total_results: None,
-                                fetched_results: Some(all_results.len() as u64),
-                            },
-                        );
-                        // 如果有部分数据，仍然保存
-                        if !all_results.is_empty() {
-                            break;
-                        }
-                        return Err(format!("导出失败: {}", e));
+                        source.search(&query, page, page_size).await
                     }
+                })
+                .await;
+                result.map(|data| (data, used_key))
+            },
+        );
+        // 取消时放弃正在请求的页，检查点停在上一页
+        let result = tokio::select! {
+            biased;
+            _ = cancel_signal.cancelled() => None,
+            result = request => Some(result),
+        };
+        let Some(result) = result else {
+            emit_progress(
+                window,
+                &ProgressEvent {
+                    task_id: task_id.clone(),
+                    percent: pct,
+                    status: "running".to_string(),
+                    status_text: "导出已取消".to_string(),
+                    log_message: Some(format!("⚠ 已取消，第 {} 页未获取", page)),
+                    log_type: Some("warning".to_string()),
+                    current_page: Some(page),
+                    total_pages: Some(pages),
+                    total_results: None,
+                    fetched_results: Some(checkpoint.fetched),
+                    platform: None,
+                },
+            );
+            cancelled = true;
+            break;
+        };
+
+        match result {
+            Ok((data, used_key)) => {
+                let page_count = data.results.len();
+                // 本页不足一页或已取满命中总数，说明没有更多数据
+                let exhausted = page_count < page_size as usize
+                    || checkpoint.fetched + page_count as u64 >= data.total;
+                checkpoint.spent += cost.charged(&data);
+                checkpoint.record_page(
+                    page,
+                    &data.results,
+                    data.total,
+                    data.next_cursor,
+                    used_key,
+                )?;
+                if let Err(e) = store::sync_assets(&platform, &query, &data.results) {
+                    eprintln!("同步本地资产库失败: {}", e);
+                }
+                emit_progress(
+                    window,
+                    &ProgressEvent {
+                        task_id: task_id.clone(),
+                        percent: (page as f64 / pages as f64) * 100.0,
+                        status: "running".to_string(),
+                        status_text: format!(
+                            "第 {}/{} 页完成，已获取 {} 条数据",
+                            page, pages, checkpoint.fetched
+                        ),
+                        log_message: Some(format!("✓ 第 {} 页成功: {} 条", page, page_count)),
+                        log_type: Some("success".to_string()),
+                        current_page: Some(page),
+                        total_pages: Some(pages),
+                        total_results: Some(data.total),
+                        fetched_results: Some(checkpoint.fetched),
+                        platform: None,
+                    },
+                );
+                if exhausted {
+                    break;
+                }
+            }
+            Err(e) => {
+                // 已取得部分数据时导出并保留检查点，之后可从失败的页继续
+                let resumable = checkpoint.fetched > 0;
+                emit_progress(
+                    window,
+                    &ProgressEvent {
+                        task_id: task_id.clone(),
+                        percent: pct,
+                        status: "error".to_string(),
+                        status_text: if e.is_retryable() {
+                            format!("第 {} 页失败，已达最大重试次数", page)
+                        } else {
+                            format!("第 {} 页失败，错误不可重试", page)
+                        },
+                        log_message: Some(if resumable {
+                            format!(
+                                "✗ 第 {} 页最终失败: {}（已保存检查点，可继续导出）",
+                                page, e
+                            )
+                        } else {
+                            format!("✗ 第 {} 页最终失败: {}", page, e)
+                        }),
+                        log_type: Some("error".to_string()),
+                        current_page: Some(page),
+                        total_pages: Some(pages),
+                        total_results: None,
+                        fetched_results: Some(checkpoint.fetched),
+                        platform: None,
+                    },
+                );
+                if !resumable {
+                    checkpoint.remove()?;
+                    api::key_manager::release_task(&platform, &task_id);
+                    return Err(format!("导出失败: {}", e));
                 }
+                failed = true;
+                break;
             }
         }
 
-        // 页间延迟
-        if page < pages && page_success {
-            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        // 没有下一页游标，说明已取完全部结果
+        if cursor_paging && checkpoint.cursor.is_none() {
+            break;
         }
     }
 
-    if all_results.is_empty() {
+    if checkpoint.fetched == 0 {
         emit_progress(
-            &window,
+            window,
             &ProgressEvent {
                 task_id: task_id.clone(),
                 percent: 100.0,
-                status: "error".to_string(),
-                status_text: "未获取到任何数据".to_string(),
-                log_message: Some("✗ 导出失败: 无数据".to_string()),
-                log_type: Some("error".to_string()),
+                status: if cancelled { "cancelled" } else { "error" }.to_string(),
+                status_text: if cancelled {
+                    "导出已取消，未获取到任何数据"
+                } else {
+                    "未获取到任何数据"
+                }
+                .to_string(),
+                log_message: Some(if cancelled {
+                    "⚠ 导出已取消".to_string()
+                } else {
+                    "✗ 导出失败: 无数据".to_string()
+                }),
+                log_type: Some(if cancelled { "warning" } else { "error" }.to_string()),
                 current_page: None,
                 total_pages: Some(pages),
                 total_results: Some(0),
                 fetched_results: Some(0),
+                platform: None,
             },
         );
-        return Err("未获取到任何数据".to_string());
+        checkpoint.remove()?;
+        api::key_manager::release_task(&platform, &task_id);
+        return Err(if cancelled {
+            "导出已取消".to_string()
+        } else {
+            "未获取到任何数据".to_string()
+        });
     }
 
     // 根据导出格式保存文件
-    let export_format = format.unwrap_or_else(|| "csv".to_string());
+    let export_format = export::ExportFormat::from_name(&checkpoint.format)?;
+    let format_name = export_format.name().to_uppercase();
     emit_progress(
-        &window,
+        window,
         &ProgressEvent {
             task_id: task_id.clone(),
             percent: 95.0,
             status: "running".to_string(),
-            status_text: format!("正在保存{}文件...", export_format.to_uppercase()),
+            status_text: format!("正在保存{}文件...", format_name),
             log_message: Some(format!(
                 "正在写入 {} 条数据到{}...",
-                all_results.len(),
-                export_format.to_uppercase()
+                checkpoint.fetched, format_name
             )),
             log_type: Some("info".to_string()),
             current_page: None,
             total_pages: Some(pages),
-            total_results: Some(all_results.len() as u64),
-            fetched_results: Some(all_results.len() as u64),
+            total_results: Some(checkpoint.fetched),
+            fetched_results: Some(checkpoint.fetched),
+            platform: None,
         },
     );
 
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
     let file_path = format!(
         "{}/{}_export_{}.{}",
-        export_path, platform, timestamp, export_format
+        export_path,
+        platform,
+        timestamp,
+        export_format.extension()
     );
 
-    match export_format.as_str() {
-        "json" => save_results_to_json(&file_path, &all_results)?,
-        _ => save_results_to_csv(&file_path, &all_results)?,
+    // 从部分输出文件按批转写，不把全部结果读入内存
+    let mut export_file = export::ExportFile::create(&file_path, export_format)?;
+    checkpoint.read_batches(1000, |batch| export_file.write_page(batch))?;
+    let exported = export_file.rows();
+    export_file.persist()?;
+
+    // 中途失败或取消时保留检查点，之后可从未完成的页继续
+    if !failed && !cancelled {
+        checkpoint.remove()?;
+        api::key_manager::release_task(&platform, &task_id);
     }
 
     emit_progress(
-        &window,
+        window,
         &ProgressEvent {
             task_id: task_id.clone(),
             percent: 100.0,
-            status: "success".to_string(),
-            status_text: format!("导出完成！共 {} 条数据", all_results.len()),
+            status: if cancelled { "cancelled" } else { "success" }.to_string(),
+            status_text: if cancelled {
+                format!("导出已取消，已导出 {} 条数据，可继续导出", exported)
+            } else if failed {
+                format!("已导出 {} 条数据，任务未完成，可继续导出", exported)
+            } else if budget_reached {
+                format!(
+                    "已达到额度预算，导出 {} 条数据（消耗 {} {}）",
+                    exported, checkpoint.spent, cost.unit
+                )
+            } else {
+                format!("导出完成！共 {} 条数据", exported)
+            },
             log_message: Some(format!("✓ 文件已保存: {}", file_path)),
             log_type: Some("success".to_string()),
             current_page: Some(pages),
             total_pages: Some(pages),
-            total_results: Some(all_results.len() as u64),
-            fetched_results: Some(all_results.len() as u64),
+            total_results: Some(exported),
+            fetched_results: Some(exported),
+            platform: None,
         },
     );
 
     Ok(file_path)
 }
 
-// 通用CSV保存函数
-fn save_results_to_csv(file_path: &str, results: &[serde_json::Value]) -> Result<(), String> {
-    let export_dir = std::path::Path::new(file_path)
-        .parent()
-        .ok_or_else(|| "无效的文件路径".to_string())?;
-    if !export_dir.exists() {
-        std::fs::create_dir_all(export_dir).map_err(|e| format!("创建目录失败: {}", e))?;
-    }
+// 重新运行历史查询，保存结果快照并与上一次快照比对变化
+#[tauri::command]
+async fn run_query_diff(
+    window: tauri::WebviewWindow,
+    task_id: String,
+    history_id: String,
+    pages: u32,
+    page_size: u32,
+) -> Result<diff::AssetDiff, String> {
+    let record = history::get_history(&history_id)?;
+    let platform = record.platform;
+    let query = record.query;
+    let source = api::get_source(&platform)?;
+    let page_size = page_size.min(source.capabilities().max_page_size);
+    let pages = pages.max(1);
 
-    let mut wtr =
-        csv::Writer::from_path(file_path).map_err(|e| format!("创建CSV文件失败: {}", e))?;
+    emit_progress(
+        &window,
+        &ProgressEvent {
+            task_id: task_id.clone(),
+            percent: 0.0,
+            status: "running".to_string(),
+            status_text: format!("正在重新运行 [{}] 查询...", platform),
+            log_message: Some(format!(
+                "开始比对: 查询={}, 页数={}, 每页={}",
+                query, pages, page_size
+            )),
+            log_type: Some("info".to_string()),
+            current_page: Some(0),
+            total_pages: Some(pages),
+            total_results: None,
+            fetched_results: Some(0),
+            platform: None,
+        },
+    );
 
-    // 收集所有字段
-    let mut fields = Vec::new();
-    if let Some(first) = results.first() {
-        if let Some(obj) = first.as_object() {
-            fields = obj.keys().cloned().collect();
+    let fetched = api::fetch_pages(
+        source,
+        &query,
+        pages,
+        page_size,
+        None,
+        |page, page_count, fetched| {
+            emit_progress(
+                &window,
+                &ProgressEvent {
+                    task_id: task_id.clone(),
+                    percent: (page as f64 / pages as f64) * 90.0,
+                    status: "running".to_string(),
+                    status_text: format!(
+                        "第 {}/{} 页完成，已获取 {} 条数据",
+                        page,
+                        pages,
+                        fetched.assets.len()
+                    ),
+                    log_message: Some(format!("✓ 第 {} 页成功: {} 条", page, page_count)),
+                    log_type: Some("success".to_string()),
+                    current_page: Some(page),
+                    total_pages: Some(pages),
+                    total_results: fetched.total,
+                    fetched_results: Some(fetched.assets.len() as u64),
+                    platform: None,
+                },
+            );
+        },
+    )
+    .await;
+    // 只有完整的结果才能比对，中途失败时不保存快照，避免把未取到的资产记为消失
+    let (assets, total) = match fetched {
+        Ok(fetched) => (fetched.assets, fetched.total),
+        Err(e) => {
+            emit_progress(
+                &window,
+                &ProgressEvent {
+                    task_id: task_id.clone(),
+                    percent: 100.0,
+                    status: "error".to_string(),
+                    status_text: "查询失败，已停止比对".to_string(),
+                    log_message: Some(format!("✗ {}", e)),
+                    log_type: Some("error".to_string()),
+                    current_page: None,
+                    total_pages: Some(pages),
+                    total_results: None,
+                    fetched_results: None,
+                    platform: None,
+                },
+            );
+            return Err(e);
         }
+    };
+
+    let mut asset_store = store::AssetStore::open()?;
+    asset_store.sync(&platform, &query, &assets)?;
+    let snapshot = asset_store.save_snapshot(&platform, &query, &assets)?;
+    let result = diff::AssetDiff::for_snapshot(&asset_store, snapshot.id)?;
+
+    if let Err(e) = history::add_snapshot_history(
+        platform.clone(),
+        query.clone(),
+        total.unwrap_or(assets.len() as u64),
+        snapshot.id,
+    ) {
+        eprintln!("保存历史记录失败: {}", e);
     }
 
-    wtr.write_record(&fields)
-        .map_err(|e| format!("写入CSV头失败: {}", e))?;
+    emit_progress(
+        &window,
+        &ProgressEvent {
+            task_id: task_id.clone(),
+            percent: 100.0,
+            status: "success".to_string(),
+            status_text: if result.is_empty() && result.previous.is_some() {
+                "比对完成，没有发现变化".to_string()
+            } else {
+                "比对完成".to_string()
+            },
+            log_message: Some(result.summary()),
+            log_type: Some("success".to_string()),
+            current_page: None,
+            total_pages: Some(pages),
+            total_results: total,
+            fetched_results: Some(assets.len() as u64),
+            platform: None,
+        },
+    );
 
-    for result in results {
-        let record: Vec<String> = fields
-            .iter()
-            .map(|f| {
-                if let Some(v) = result.get(f) {
-                    if v.is_string() {
-                        v.as_str().unwrap_or("").to_string()
-                    } else {
-                        v.to_string()
-                    }
-                } else {
-                    String::new()
-                }
-            })
-            .collect();
-        wtr.write_record(&record)
-            .map_err(|e| format!("写入数据失败: {}", e))?;
-    }
+    Ok(result)
+}
 
-    wtr.flush().map_err(|e| format!("保存CSV失败: {}", e))?;
-    Ok(())
+// 查看某次快照与上一次快照的差异
+#[tauri::command]
+fn get_query_diff(snapshot_id: i64) -> Result<diff::AssetDiff, String> {
+    diff::AssetDiff::for_snapshot(&store::AssetStore::open()?, snapshot_id)
 }
 
-// 将结果保存为 JSON 文件
-fn save_results_to_json(file_path: &str, results: &[serde_json::Value]) -> Result<(), String> {
-    let export_dir = std::path::Path::new(file_path)
-        .parent()
-        .ok_or_else(|| "无效的文件路径".to_string())?;
-    if !export_dir.exists() {
-        std::fs::create_dir_all(export_dir).map_err(|e| format!("创建目录失败: {}", e))?;
+// 导出某次快照与上一次快照的差异报告
+#[tauri::command]
+fn export_query_diff(snapshot_id: i64, format: Option<String>) -> Result<String, String> {
+    let result = diff::AssetDiff::for_snapshot(&store::AssetStore::open()?, snapshot_id)?;
+    if result.previous.is_none() {
+        return Err("该快照是首次运行的基线，没有可导出的变化".to_string());
     }
 
-    let json_content =
-        serde_json::to_string_pretty(results).map_err(|e| format!("序列化JSON失败: {}", e))?;
-    std::fs::write(file_path, json_content).map_err(|e| format!("写入JSON文件失败: {}", e))?;
-    Ok(())
+    let format = format.unwrap_or_else(|| "csv".to_string()).to_lowercase();
+    let export_path = config::get_export_path()?;
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let file_path = format!(
+        "{}/{}_diff_{}.{}",
+        export_path, result.current.platform, timestamp, format
+    );
+    result.save(&file_path, &format)?;
+    Ok(file_path)
 }
 
-// 导出当前查询结果
+// 导出当前查询结果，与 export_results_with_progress 一样按检查点逐页导出，返回文件路径
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn export_results(
+    window: tauri::WebviewWindow,
     platform: String,
     query: String,
     pages: u32,
@@ -509,25 +898,28 @@ async fn export_results(
     time_range: String,
     start_date: Option<String>,
     end_date: Option<String>,
-) -> Result<(), String> {
-    let export_path = config::get_export_path()?;
-
-    dispatch_platform!(
-        platform.as_str(),
-        export,
-        &query,
+    format: Option<String>,
+) -> Result<String, String> {
+    let task_id = format!("export_{}", chrono::Utc::now().timestamp_millis());
+    start_checkpointed_export(
+        &window,
+        task_id,
+        platform,
+        query,
         pages,
         page_size,
-        &time_range,
-        start_date,
-        end_date,
-        &export_path,
+        converter::TimeRange::from_options(&time_range, &start_date, &end_date),
+        format,
+        None,
     )
+    .await
 }
 
-// 导出平台全部资产
+// 导出平台全部资产：按检查点逐页导出直到取完，至多 pages 页且不超过 EXPORT_ALL_MAX_PAGES 页
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn export_platform_all(
+    window: tauri::WebviewWindow,
     platform: String,
     query: String,
     pages: u32,
@@ -535,35 +927,135 @@ async fn export_platform_all(
     time_range: String,
     start_date: Option<String>,
     end_date: Option<String>,
-) -> Result<(), String> {
-    let export_path = config::get_export_path()?;
-
-    dispatch_platform!(
-        platform.as_str(),
-        export_all,
-        &query,
-        pages,
+    format: Option<String>,
+) -> Result<String, String> {
+    let task_id = format!("export_all_{}", chrono::Utc::now().timestamp_millis());
+    start_checkpointed_export(
+        &window,
+        task_id,
+        platform,
+        query,
+        pages.min(api::EXPORT_ALL_MAX_PAGES),
         page_size,
-        &time_range,
-        start_date,
-        end_date,
-        &export_path,
+        converter::TimeRange::from_options(&time_range, &start_date, &end_date),
+        format,
+        None,
     )
+    .await
+}
+
+// 把单个平台的进度转换为进度事件，任务 ID 为 `{task_id}_{平台}`
+fn platform_progress_event(
+    task_id: &str,
+    platform: &str,
+    pages: u32,
+    progress: api::PlatformProgress<'_>,
+) -> ProgressEvent {
+    let event = ProgressEvent {
+        task_id: format!("{}_{}", task_id, platform),
+        status: "running".to_string(),
+        total_pages: Some(pages),
+        platform: Some(platform.to_string()),
+        ..Default::default()
+    };
+    match progress {
+        api::PlatformProgress::Started { query } => ProgressEvent {
+            status_text: format!("正在查询 {} ...", platform),
+            log_message: Some(format!("[{}] 开始查询: {}", platform, query)),
+            log_type: Some("info".to_string()),
+            current_page: Some(0),
+            ..event
+        },
+        api::PlatformProgress::Page {
+            page,
+            count,
+            fetched,
+        } => ProgressEvent {
+            percent: (page as f64 / pages as f64) * 100.0,
+            status_text: format!(
+                "{} 第 {}/{} 页完成，已获取 {} 条数据",
+                platform,
+                page,
+                pages,
+                fetched.assets.len()
+            ),
+            log_message: Some(format!("[{}] ✓ 第 {} 页成功: {} 条", platform, page, count)),
+            log_type: Some("success".to_string()),
+            current_page: Some(page),
+            total_results: fetched.total,
+            fetched_results: Some(fetched.assets.len() as u64),
+            ..event
+        },
+        api::PlatformProgress::Finished(outcome) => {
+            let (status, log_type, message) = match outcome.status {
+                api::PlatformStatus::Success => (
+                    "success",
+                    "success",
+                    format!("完成，共获取 {} 条", outcome.returned),
+                ),
+                api::PlatformStatus::Failed => (
+                    "error",
+                    "error",
+                    format!("失败: {}", outcome.error.as_deref().unwrap_or("未知错误")),
+                ),
+                api::PlatformStatus::Cancelled => ("cancelled", "warning", "已取消".to_string()),
+                api::PlatformStatus::Skipped => (
+                    "error",
+                    "warning",
+                    format!("已跳过: {}", outcome.error.as_deref().unwrap_or("未知原因")),
+                ),
+            };
+            ProgressEvent {
+                percent: 100.0,
+                status: status.to_string(),
+                status_text: format!("{} {}", platform, message),
+                log_message: Some(format!("[{}] {}", platform, message)),
+                log_type: Some(log_type.to_string()),
+                total_results: outcome.total,
+                fetched_results: Some(outcome.returned as u64),
+                ..event
+            }
+        }
+    }
 }
 
-// 导出所有平台资产
+// 并发导出所有平台资产并合并，每个平台单独推送进度、可单独取消
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn export_all_platforms(
+    app_handle: AppHandle,
+    window: tauri::WebviewWindow,
+    task_id: String,
     query: String,
     pages: u32,
     page_size: u32,
     time_range: String,
     start_date: Option<String>,
     end_date: Option<String>,
-) -> Result<(), String> {
+    format: Option<String>,
+    budget: Option<u64>,
+) -> Result<api::AllPlatformsExport, String> {
     let export_path = config::get_export_path()?;
+    let format = export::ExportFormat::from_name(format.as_deref().unwrap_or("csv"))?;
 
-    api::export_all_platforms(
+    let config_path = get_config_path(&app_handle)?;
+    let config_manager =
+        ConfigManager::from_file(&config_path).map_err(|e| format!("加载配置文件失败: {}", e))?;
+    let converter = QueryConverter::new(config_manager);
+
+    let progress: api::PlatformProgressFn = {
+        let window = window.clone();
+        let task_id = task_id.clone();
+        std::sync::Arc::new(move |platform: &str, progress: api::PlatformProgress<'_>| {
+            emit_progress(
+                &window,
+                &platform_progress_event(&task_id, platform, pages, progress),
+            )
+        })
+    };
+
+    let result = api::export_all_platforms(
+        &converter,
         &query,
         pages,
         page_size,
@@ -571,20 +1063,70 @@ async fn export_all_platforms(
         start_date,
         end_date,
         &export_path,
+        format,
+        budget,
+        &task_id,
+        progress,
     )
-    .await
+    .await;
+
+    let (status, status_text, log_type) = match &result {
+        Ok(export) => {
+            let succeeded = export
+                .platforms
+                .iter()
+                .filter(|outcome| outcome.status == api::PlatformStatus::Success)
+                .count();
+            let text = match &export.file_path {
+                Some(path) => format!(
+                    "{}/{} 个平台成功，合并为 {} 条资产: {}",
+                    succeeded,
+                    export.platforms.len(),
+                    export.summary.merged_count,
+                    path
+                ),
+                None => format!(
+                    "{}/{} 个平台成功，未找到任何结果",
+                    succeeded,
+                    export.platforms.len()
+                ),
+            };
+            let log_type = if succeeded == export.platforms.len() {
+                "success"
+            } else {
+                "warning"
+            };
+            ("success", text, log_type)
+        }
+        Err(e) => ("error", format!("导出失败: {}", e), "error"),
+    };
+    emit_progress(
+        &window,
+        &ProgressEvent {
+            task_id,
+            percent: 100.0,
+            status: status.to_string(),
+            status_text: status_text.clone(),
+            log_message: Some(status_text),
+            log_type: Some(log_type.to_string()),
+            ..Default::default()
+        },
+    );
+
+    result
+}
+
+// 取消导出任务，多平台任务可按 `{task_id}_{平台}` 只取消单个平台
+#[tauri::command]
+fn cancel_export(task_id: String) -> Result<usize, String> {
+    Ok(export::cancel::cancel(&task_id))
 }
 
 // 获取API密钥
 #[tauri::command]
 fn get_api_keys(platform: String) -> Result<serde_json::Value, String> {
-    match platform.as_str() {
-        "hunter" => config::get_hunter_api_keys(),
-        "fofa" => config::get_fofa_api_keys(),
-        "quake" => config::get_quake_api_keys(),
-        "daydaymap" => config::get_daydaymap_api_keys(),
-        _ => Err("不支持的平台".to_string()),
-    }
+    let source = api::get_source(&platform)?;
+    config::get_api_keys(source.name(), source.capabilities().requires_email)
 }
 
 // 添加API密钥
@@ -594,31 +1136,31 @@ async fn add_api_key(
     api_key: String,
     email: Option<String>,
 ) -> Result<(), String> {
-    match platform.as_str() {
-        "hunter" => config::add_hunter_api_key(&api_key),
-        "fofa" => {
-            let email = email.ok_or("FOFA平台需要提供邮箱")?;
-            config::add_fofa_api_key(&api_key, &email)
-        }
-        "quake" => config::add_quake_api_key(&api_key),
-        "daydaymap" => config::add_daydaymap_api_key(&api_key),
-        _ => Err("不支持的平台".to_string()),
-    }
+    let source = api::get_source(&platform)?;
+    let email = required_email(source, email)?;
+    config::add_api_key(source.name(), &api_key, email.as_deref())
 }
 
 // 删除API密钥
 #[tauri::command]
 fn delete_api_key(platform: String, api_key: String, email: Option<String>) -> Result<(), String> {
-    match platform.as_str() {
-        "hunter" => config::delete_hunter_api_key(&api_key),
-        "fofa" => {
-            let email = email.ok_or("FOFA平台需要提供邮箱")?;
-            config::delete_fofa_api_key(&api_key, &email)
-        }
-        "quake" => config::delete_quake_api_key(&api_key),
-        "daydaymap" => config::delete_daydaymap_api_key(&api_key),
-        _ => Err("不支持的平台".to_string()),
+    let source = api::get_source(&platform)?;
+    let email = required_email(source, email)?;
+    config::delete_api_key(source.name(), &api_key, email.as_deref())
+}
+
+// 需要账号的平台（如FOFA）必须提供邮箱，其他平台忽略邮箱
+fn required_email(
+    source: &dyn api::source::AssetSource,
+    email: Option<String>,
+) -> Result<Option<String>, String> {
+    let capabilities = source.capabilities();
+    if !capabilities.requires_email {
+        return Ok(None);
     }
+    email
+        .map(Some)
+        .ok_or_else(|| format!("{}平台需要提供邮箱", capabilities.display_name))
 }
 
 // 验证API密钥
@@ -628,16 +1170,80 @@ async fn validate_api_key(
     api_key: String,
     email: Option<String>,
 ) -> Result<ApiKeyValidationResult, String> {
-    match platform.as_str() {
-        "hunter" => api::hunter::validate_api_key(&api_key).await,
-        "fofa" => {
-            let email = email.ok_or("FOFA平台需要提供邮箱")?;
-            api::fofa::validate_api_key(&api_key, &email).await
-        }
-        "quake" => api::quake::validate_api_key(&api_key).await,
-        "daydaymap" => api::daydaymap::validate_api_key(&api_key).await,
-        _ => Err("不支持的平台".to_string()),
+    let source = api::get_source(&platform)?;
+    let email = required_email(source, email)?;
+    source.validate_api_key(&api_key, email.as_deref()).await
+}
+
+// 预估导出消耗的额度，并与该平台各密钥的剩余额度比较
+#[tauri::command]
+async fn estimate_export_cost(
+    platform: String,
+    pages: u32,
+    page_size: u32,
+) -> Result<api::cost::CostEstimate, String> {
+    let source = api::get_source(&platform)?;
+    let keys = if source.capabilities().requires_email {
+        config::get_all_api_key_pairs(&platform)?
+    } else {
+        config::get_all_api_keys(&platform)?
+            .into_iter()
+            .map(|key| (key, String::new()))
+            .collect()
+    };
+    if keys.is_empty() {
+        return Err(format!(
+            "请先配置{}的API密钥",
+            source.capabilities().display_name
+        ));
     }
+    api::cost::estimate(source, pages, page_size, &keys).await
+}
+
+// 获取各平台 key 池健康状况（读取本地状态，不发请求）
+#[tauri::command]
+fn get_key_pool_health() -> Result<Vec<api::key_manager::PoolHealth>, String> {
+    api::all_sources()
+        .map(api::key_manager::pool_health)
+        .collect()
+}
+
+// 立即重新验证 key 池并刷新额度，`platform` 为空时刷新所有平台
+#[tauri::command]
+async fn refresh_key_pool(
+    platform: Option<String>,
+) -> Result<Vec<api::key_manager::PoolHealth>, String> {
+    let sources: Vec<&'static dyn api::source::AssetSource> = match platform {
+        Some(platform) => vec![api::get_source(&platform)?],
+        None => api::all_sources().collect(),
+    };
+    let mut pools = Vec::new();
+    for source in sources {
+        pools.push(api::key_manager::revalidate(source, true).await?);
+    }
+    Ok(pools)
+}
+
+// 设置 key 在加权轮询中的权重与标签，`index` 为 key 在平台配置中的位置
+#[tauri::command]
+fn set_key_options(
+    platform: String,
+    index: usize,
+    priority: u32,
+    tags: Vec<String>,
+) -> Result<(), String> {
+    api::key_manager::set_key_options(api::get_source(&platform)?, index, priority, tags)
+}
+
+// 获取各平台能力
+#[tauri::command]
+fn get_platform_capabilities() -> Vec<PlatformCapabilities> {
+    api::all_sources()
+        .map(|source| PlatformCapabilities {
+            platform: source.name().to_string(),
+            capabilities: source.capabilities(),
+        })
+        .collect()
 }
 
 // 获取设置
@@ -669,11 +1275,42 @@ async fn select_directory(app_handle: AppHandle) -> Result<String, String> {
     }
 }
 
+// 平台能力结构体
+#[derive(Serialize)]
+struct PlatformCapabilities {
+    platform: String,
+    #[serde(flatten)]
+    capabilities: api::source::SourceCapabilities,
+}
+
 // 转换结果结构体
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize)]
 struct ConversionResult {
     platform: String,
     query: String,
+    // 被丢弃或近似转换的条件，非空表示该平台的结果不完整
+    warnings: Vec<converter::ConversionWarning>,
+    // 转换失败原因（严格模式下有损转换也会失败）
+    error: Option<String>,
+}
+
+impl ConversionResult {
+    fn new(platform: &str, conversion: Result<converter::Conversion, String>) -> Self {
+        match conversion {
+            Ok(conversion) => ConversionResult {
+                platform: platform.to_string(),
+                query: conversion.query,
+                warnings: conversion.warnings,
+                error: None,
+            },
+            Err(e) => ConversionResult {
+                platform: platform.to_string(),
+                query: String::new(),
+                warnings: Vec::new(),
+                error: Some(e),
+            },
+        }
+    }
 }
 
 // 获取支持的平台列表
@@ -688,6 +1325,22 @@ fn get_supported_platforms(app_handle: AppHandle) -> Result<Vec<String>, String>
     Ok(converter.get_supported_platforms())
 }
 
+// 按模式执行转换：严格模式下拒绝有损转换
+fn run_conversion(
+    converter: &QueryConverter,
+    query: &str,
+    from_platform: &str,
+    to_platform: &str,
+    strict: bool,
+) -> Result<converter::Conversion, String> {
+    let conversion = if strict {
+        converter.convert_strict(query, from_platform, to_platform)
+    } else {
+        converter.convert(query, from_platform, to_platform)
+    };
+    conversion.map_err(|e| format!("{}", e))
+}
+
 // 转换查询语句
 #[tauri::command]
 fn convert_query(
@@ -695,7 +1348,8 @@ fn convert_query(
     query: String,
     from_platform: String,
     to_platform: String,
-) -> Result<String, String> {
+    strict: Option<bool>,
+) -> Result<ConversionResult, String> {
     let config_path = get_config_path(&app_handle)?;
 
     let config_manager =
@@ -709,17 +1363,23 @@ fn convert_query(
         .map_err(|e| format!("{}", e))?;
 
     // 执行转换
-    converter
-        .convert(&query, &from_platform, &to_platform)
-        .map_err(|e| format!("{}", e))
+    let conversion = run_conversion(
+        &converter,
+        &query,
+        &from_platform,
+        &to_platform,
+        strict.unwrap_or(false),
+    )?;
+    Ok(ConversionResult::new(&to_platform, Ok(conversion)))
 }
 
-// 转换查询语句到所有平台
+// 转换查询语句到所有平台，单个平台失败不影响其他平台
 #[tauri::command]
 fn convert_query_to_all(
     app_handle: AppHandle,
     query: String,
     from_platform: String,
+    strict: Option<bool>,
 ) -> Result<Vec<ConversionResult>, String> {
     let config_path = get_config_path(&app_handle)?;
 
@@ -733,35 +1393,42 @@ fn convert_query_to_all(
         .validate_query_syntax(&query, &from_platform)
         .map_err(|e| format!("{}", e))?;
 
-    let supported_platforms = converter.get_supported_platforms();
-    let mut results = Vec::new();
-
-    for platform in supported_platforms {
-        if platform != from_platform {
-            match converter.convert(&query, &from_platform, &platform) {
-                Ok(converted_query) => {
-                    results.push(ConversionResult {
-                        platform: platform.clone(),
-                        query: converted_query,
-                    });
-                }
-                Err(e) => {
-                    return Err(format!("转换到 {} 平台失败: {}", platform, e));
-                }
-            }
-        }
-    }
+    let strict = strict.unwrap_or(false);
+    let results = converter
+        .get_supported_platforms()
+        .into_iter()
+        .filter(|platform| *platform != from_platform)
+        .map(|platform| {
+            let conversion = run_conversion(&converter, &query, &from_platform, &platform, strict);
+            ConversionResult::new(&platform, conversion)
+        })
+        .collect();
 
     Ok(results)
 }
 
-// 验证查询语法
+// 识别查询语句所属平台，按可能性从高到低返回各平台得分
+#[tauri::command]
+fn detect_query_dialect(
+    app_handle: AppHandle,
+    query: String,
+) -> Result<Vec<converter::DialectScore>, String> {
+    let config_path = get_config_path(&app_handle)?;
+
+    let config_manager =
+        ConfigManager::from_file(&config_path).map_err(|e| format!("加载配置文件失败: {}", e))?;
+
+    let converter = QueryConverter::new(config_manager);
+    Ok(converter.detect_dialect(&query))
+}
+
+// 验证查询语法，返回全部诊断信息（为空表示语法正确）
 #[tauri::command]
 fn validate_query_syntax(
     app_handle: AppHandle,
     query: String,
     platform: String,
-) -> Result<bool, String> {
+) -> Result<Vec<converter::Diagnostic>, String> {
     let config_path = get_config_path(&app_handle)?;
 
     let config_manager =
@@ -769,10 +1436,9 @@ fn validate_query_syntax(
 
     let converter = QueryConverter::new(config_manager);
 
-    match converter.validate_query_syntax(&query, &platform) {
-        Ok(_) => Ok(true),
-        Err(e) => Err(format!("{}", e)),
-    }
+    converter
+        .diagnose(&query, &platform)
+        .map_err(|e| format!("{}", e))
 }
 
 // 添加查询历史记录
@@ -823,6 +1489,52 @@ fn export_query_history(export_path: String) -> Result<String, String> {
     history::export_history_to_csv(&export_path)
 }
 
+// 获取所有定时查询任务
+#[tauri::command]
+fn list_scheduled_queries() -> Result<Vec<schedule::ScheduledQuery>, String> {
+    schedule::list_jobs()
+}
+
+// 新建或更新定时查询任务
+#[tauri::command]
+fn save_scheduled_query(job: schedule::ScheduledQuery) -> Result<schedule::ScheduledQuery, String> {
+    schedule::save_job(job)
+}
+
+// 删除定时查询任务
+#[tauri::command]
+fn delete_scheduled_query(id: String) -> Result<(), String> {
+    schedule::delete_job(&id)
+}
+
+// 立即运行定时查询任务
+#[tauri::command]
+fn run_scheduled_query_now(id: String) -> Result<(), String> {
+    schedule::run_job_now(&id)
+}
+
+// 分页查询本地资产库
+#[tauri::command]
+fn query_local_assets(
+    filter: Option<store::LocalAssetFilter>,
+    page: u32,
+    page_size: u32,
+) -> Result<store::LocalAssetPage, String> {
+    store::AssetStore::open()?.query(&filter.unwrap_or_default(), page, page_size)
+}
+
+// 列出本地资产库中保存过的查询
+#[tauri::command]
+fn list_local_asset_queries() -> Result<Vec<store::LocalQuerySummary>, String> {
+    store::AssetStore::open()?.list_queries()
+}
+
+// 删除本地资产库中某个查询的资产
+#[tauri::command]
+fn delete_local_asset_query(platform: String, query: String) -> Result<u64, String> {
+    store::AssetStore::open()?.delete_query(&platform, &query)
+}
+
 // 漏洞扫描
 #[tauri::command]
 async fn run_vulnerability_scan(
@@ -850,6 +1562,7 @@ async fn run_vulnerability_scan(
                 total_pages: None,
                 total_results: None,
                 fetched_results: None,
+                platform: None,
             },
         );
 
@@ -888,6 +1601,7 @@ async fn run_vulnerability_scan(
                     total_pages: None,
                     total_results: None,
                     fetched_results: None,
+                    platform: None,
                 },
             );
 
@@ -1041,6 +1755,7 @@ async fn run_vulnerability_scan(
                 total_pages: None,
                 total_results: None,
                 fetched_results: None,
+                platform: None,
             },
         );
     });
@@ -1282,17 +1997,18 @@ fn export_all_api_keys() -> Result<serde_json::Value, String> {
     use serde_json::json;
     let mut result = json!({});
 
-    if let Ok(keys) = config::get_all_fofa_api_keys() {
-        result["fofa"] = json!(keys.into_iter().map(|(email, key)| json!({"email": email, "key": key})).collect::<Vec<_>>());
-    }
-    if let Ok(keys) = config::get_all_hunter_api_keys() {
-        result["hunter"] = json!(keys);
-    }
-    if let Ok(keys) = config::get_all_quake_api_keys() {
-        result["quake"] = json!(keys);
-    }
-    if let Ok(keys) = config::get_all_daydaymap_api_keys() {
-        result["daydaymap"] = json!(keys);
+    for source in api::all_sources() {
+        let platform = source.name();
+        if source.capabilities().requires_email {
+            if let Ok(pairs) = config::get_all_api_key_pairs(platform) {
+                result[platform] = json!(pairs
+                    .into_iter()
+                    .map(|(key, email)| json!({"email": email, "key": key}))
+                    .collect::<Vec<_>>());
+            }
+        } else if let Ok(keys) = config::get_all_api_keys(platform) {
+            result[platform] = json!(keys);
+        }
     }
 
     Ok(result)
@@ -1300,34 +2016,22 @@ fn export_all_api_keys() -> Result<serde_json::Value, String> {
 
 #[tauri::command]
 fn import_all_api_keys(data: serde_json::Value) -> Result<(), String> {
-    if let Some(fofa_keys) = data.get("fofa").and_then(|v| v.as_array()) {
-        for item in fofa_keys {
-            if let (Some(email), Some(key)) = (item.get("email").and_then(|v| v.as_str()), item.get("key").and_then(|v| v.as_str())) {
-                let _ = config::add_fofa_api_key(key, email);
-            }
-        }
-    }
-
-    if let Some(hunter_keys) = data.get("hunter").and_then(|v| v.as_array()) {
-        for key in hunter_keys {
-            if let Some(key_str) = key.as_str() {
-                let _ = config::add_hunter_api_key(key_str);
-            }
-        }
-    }
-
-    if let Some(quake_keys) = data.get("quake").and_then(|v| v.as_array()) {
-        for key in quake_keys {
-            if let Some(key_str) = key.as_str() {
-                let _ = config::add_quake_api_key(key_str);
-            }
-        }
-    }
-
-    if let Some(daydaymap_keys) = data.get("daydaymap").and_then(|v| v.as_array()) {
-        for key in daydaymap_keys {
-            if let Some(key_str) = key.as_str() {
-                let _ = config::add_daydaymap_api_key(key_str);
+    for source in api::all_sources() {
+        let platform = source.name();
+        let Some(items) = data.get(platform).and_then(|v| v.as_array()) else {
+            continue;
+        };
+
+        for item in items {
+            if source.capabilities().requires_email {
+                if let (Some(email), Some(key)) = (
+                    item.get("email").and_then(|v| v.as_str()),
+                    item.get("key").and_then(|v| v.as_str()),
+                ) {
+                    let _ = config::add_api_key(platform, key, Some(email));
+                }
+            } else if let Some(key_str) = item.as_str() {
+                let _ = config::add_api_key(platform, key_str, None);
             }
         }
     }
@@ -1341,10 +2045,26 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            // 启动定时查询调度器，查询转换规则加载失败时按原查询语句运行
+            let converter = get_config_path(app.handle())
+                .and_then(|path| {
+                    ConfigManager::from_file(&path).map_err(|e| format!("加载配置文件失败: {}", e))
+                })
+                .map(QueryConverter::new)
+                .map_err(|e| eprintln!("{}", e))
+                .ok();
+            schedule::start(app.handle().clone(), converter);
+            // 定期重新验证 key，额度重置后自动恢复可用
+            api::key_manager::start_revalidation();
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             search_assets,
             export_results,
             export_results_with_progress,
+            resume_export,
+            list_export_checkpoints,
             export_platform_all,
             export_all_platforms,
             export_all_api_keys,
@@ -1353,6 +2073,11 @@ fn main() {
             add_api_key,
             delete_api_key,
             validate_api_key,
+            estimate_export_cost,
+            get_key_pool_health,
+            refresh_key_pool,
+            set_key_options,
+            get_platform_capabilities,
             get_settings,
             save_settings,
             select_directory,
@@ -1360,6 +2085,7 @@ fn main() {
             convert_query,
             convert_query_to_all,
             validate_query_syntax,
+            detect_query_dialect,
             add_query_history,
             get_query_history,
             get_history_by_platform,
@@ -1367,8 +2093,19 @@ fn main() {
             delete_query_history,
             clear_all_history,
             export_query_history,
+            run_query_diff,
+            get_query_diff,
+            export_query_diff,
+            list_scheduled_queries,
+            save_scheduled_query,
+            delete_scheduled_query,
+            run_scheduled_query_now,
+            query_local_assets,
+            list_local_asset_queries,
+            delete_local_asset_query,
             run_vulnerability_scan,
             cancel_vulnerability_scan,
+            cancel_export,
             test_proxy,
             send_raw_http,
             get_scan_history,
@@ -0,0 +1,540 @@
+use crate::api;
+use crate::converter::QueryConverter;
+use crate::export::{ExportFile, ExportFormat};
+use crate::{config, history, store};
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+const SCHEDULE_FILE: &str = "scheduled_queries.json";
+
+/// 检查到期任务的间隔
+const TICK_SECS: u64 = 30;
+
+/// 发现新资产时发往前端的事件
+const NEW_ASSETS_EVENT: &str = "scheduled-query-new-assets";
+
+// 任务文件的读改写在该锁内完成，避免调度器与前端命令互相覆盖
+static SCHEDULE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// 任务变更或手动触发时唤醒调度器，不必等到下一次检查
+static WAKE: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// 运行周期
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ScheduleRule {
+    /// 每天的固定时间（本地时间，HH:MM）
+    Daily { time: String },
+    /// 每隔若干小时
+    Interval { hours: u32 },
+}
+
+impl ScheduleRule {
+    fn daily_time(time: &str) -> Result<NaiveTime, String> {
+        NaiveTime::parse_from_str(time.trim(), "%H:%M")
+            .map_err(|_| format!("无效的运行时间: {}，应为 HH:MM", time))
+    }
+
+    /// `after` 之后的下一次运行时间
+    pub fn next_after(&self, after: DateTime<Local>) -> Result<DateTime<Local>, String> {
+        match self {
+            ScheduleRule::Daily { time } => {
+                let time = Self::daily_time(time)?;
+                // 夏令时跳过的时刻不存在，顺延到下一天
+                (0..=2)
+                    .filter_map(|days| {
+                        let date = after.date_naive() + Duration::days(days);
+                        Local.from_local_datetime(&date.and_time(time)).earliest()
+                    })
+                    .find(|candidate| *candidate > after)
+                    .ok_or_else(|| "无法计算下一次运行时间".to_string())
+            }
+            ScheduleRule::Interval { hours } => {
+                if *hours == 0 {
+                    return Err("运行间隔至少为 1 小时".to_string());
+                }
+                Ok(after + Duration::hours(i64::from(*hours)))
+            }
+        }
+    }
+}
+
+/// 任务的运行状态，随任务一起保存，重启后继续按 `next_run` 调度
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScheduleState {
+    pub next_run: Option<String>,
+    pub last_run: Option<String>,
+    pub last_success: Option<bool>,
+    pub last_error: Option<String>,
+    /// 上一次运行新增的资产数
+    pub last_new_assets: u64,
+    pub run_count: u64,
+}
+
+/// 定时查询任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledQuery {
+    /// 新建任务时留空，保存时生成
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub query: String,
+    /// 运行的平台，查询语句按识别出的语法转换到各平台
+    pub platforms: Vec<String>,
+    /// 每个平台最多获取的页数
+    pub pages: u32,
+    pub page_size: u32,
+    /// 每次运行每个平台的额度预算（按平台计费单位计），下一页会超出时停止获取，为空表示不限
+    #[serde(default)]
+    pub credit_budget: Option<u64>,
+    /// 同时导出结果的格式，为空表示只写入历史记录与本地资产库
+    #[serde(default)]
+    pub export_format: Option<String>,
+    pub schedule: ScheduleRule,
+    pub enabled: bool,
+    #[serde(default)]
+    pub state: ScheduleState,
+}
+
+impl ScheduledQuery {
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("任务名称不能为空".to_string());
+        }
+        if self.query.trim().is_empty() {
+            return Err("查询语句不能为空".to_string());
+        }
+        if self.platforms.is_empty() {
+            return Err("至少选择一个平台".to_string());
+        }
+        for platform in &self.platforms {
+            api::get_source(platform)?;
+        }
+        if self.pages == 0 || self.page_size == 0 {
+            return Err("页数与每页条数必须大于 0".to_string());
+        }
+        if self.credit_budget == Some(0) {
+            return Err("额度预算必须大于 0".to_string());
+        }
+        if let Some(format) = &self.export_format {
+            ExportFormat::from_name(format)?;
+        }
+        self.schedule.next_after(Local::now()).map(|_| ())
+    }
+
+    // 是否已到运行时间，未计算过下一次运行时间的任务视为到期
+    fn is_due(&self, now: DateTime<Local>) -> bool {
+        self.enabled
+            && match &self.state.next_run {
+                Some(next_run) => DateTime::parse_from_rfc3339(next_run)
+                    .map(|next_run| next_run <= now)
+                    .unwrap_or(true),
+                None => true,
+            }
+    }
+}
+
+/// 发现新资产的通知
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewAssetsEvent {
+    pub job_id: String,
+    pub job_name: String,
+    pub platform: String,
+    pub query: String,
+    pub new_assets: u64,
+    pub total_assets: u64,
+    /// 本次运行的快照，可用于查看与上一次的差异；达到额度预算、结果不完整时没有快照
+    pub snapshot_id: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScheduleStore {
+    jobs: Vec<ScheduledQuery>,
+}
+
+// 任务文件与 query_history.json 位于同一目录
+fn schedule_file_path() -> Result<PathBuf, String> {
+    let app_dir = dirs::config_dir()
+        .ok_or_else(|| "无法获取配置目录".to_string())?
+        .join("asset-mapping");
+    fs::create_dir_all(&app_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    Ok(app_dir.join(SCHEDULE_FILE))
+}
+
+fn load_jobs(path: &Path) -> Result<ScheduleStore, String> {
+    if !path.exists() {
+        return Ok(ScheduleStore::default());
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("读取定时任务失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析定时任务失败: {}", e))
+}
+
+fn save_jobs(path: &Path, store: &ScheduleStore) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(store).map_err(|e| format!("序列化定时任务失败: {}", e))?;
+    // 先写临时文件再替换，写到一半退出也不会损坏任务文件
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, content).map_err(|e| format!("保存定时任务失败: {}", e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("保存定时任务失败: {}", e))
+}
+
+// 在锁内读取、修改并保存任务文件
+fn update_jobs<T>(
+    update: impl FnOnce(&mut Vec<ScheduledQuery>) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = SCHEDULE_LOCK
+        .lock()
+        .map_err(|_| "定时任务锁已损坏".to_string())?;
+    let path = schedule_file_path()?;
+    let mut store = load_jobs(&path)?;
+    let result = update(&mut store.jobs)?;
+    save_jobs(&path, &store)?;
+    Ok(result)
+}
+
+// 获取所有定时任务
+pub fn list_jobs() -> Result<Vec<ScheduledQuery>, String> {
+    let _guard = SCHEDULE_LOCK
+        .lock()
+        .map_err(|_| "定时任务锁已损坏".to_string())?;
+    Ok(load_jobs(&schedule_file_path()?)?.jobs)
+}
+
+// 新建或更新定时任务，保留已有的运行状态
+pub fn save_job(mut job: ScheduledQuery) -> Result<ScheduledQuery, String> {
+    job.validate()?;
+    let now = Local::now();
+
+    let saved = update_jobs(|jobs| {
+        let existing = jobs
+            .iter()
+            .position(|j| !job.id.is_empty() && j.id == job.id);
+        match existing {
+            Some(index) => {
+                let previous = &jobs[index];
+                job.state = previous.state.clone();
+                // 周期变化或重新启用时重新计算下一次运行时间
+                if previous.schedule != job.schedule || (!previous.enabled && job.enabled) {
+                    job.state.next_run = Some(job.schedule.next_after(now)?.to_rfc3339());
+                }
+                jobs[index] = job.clone();
+            }
+            None => {
+                job.id = format!("schedule_{}", now.timestamp_millis());
+                job.state = ScheduleState {
+                    next_run: Some(job.schedule.next_after(now)?.to_rfc3339()),
+                    ..Default::default()
+                };
+                jobs.push(job.clone());
+            }
+        }
+        Ok(job)
+    })?;
+
+    WAKE.notify_one();
+    Ok(saved)
+}
+
+// 删除定时任务
+pub fn delete_job(id: &str) -> Result<(), String> {
+    update_jobs(|jobs| {
+        jobs.retain(|job| job.id != id);
+        Ok(())
+    })
+}
+
+// 立即运行定时任务，运行结束后按周期继续调度
+pub fn run_job_now(id: &str) -> Result<(), String> {
+    update_jobs(|jobs| {
+        let job = jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| format!("定时任务不存在: {}", id))?;
+        if !job.enabled {
+            return Err("任务已停用，请先启用".to_string());
+        }
+        job.state.next_run = Some(Local::now().to_rfc3339());
+        Ok(())
+    })?;
+    WAKE.notify_one();
+    Ok(())
+}
+
+/// 在后台启动调度器
+///
+/// 调度器定期检查到期任务并依次运行；应用关闭期间错过的运行在启动后补跑一次。
+/// `converter` 用于把查询语句转换到任务中的各平台，不可用时原样使用查询语句。
+pub fn start(app: AppHandle, converter: Option<QueryConverter>) {
+    let converter = Arc::new(converter);
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due_jobs(&app, &converter).await {
+                eprintln!("定时任务调度失败: {}", e);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(TICK_SECS)) => {}
+                _ = WAKE.notified() => {}
+            }
+        }
+    });
+}
+
+async fn run_due_jobs(app: &AppHandle, converter: &Option<QueryConverter>) -> Result<(), String> {
+    let now = Local::now();
+    let due: Vec<ScheduledQuery> = list_jobs()?
+        .into_iter()
+        .filter(|job| job.is_due(now))
+        .collect();
+
+    for job in due {
+        let outcome = run_job(app, converter, &job).await;
+        let finished = Local::now();
+
+        // 运行期间任务可能已被修改或删除，只回写运行状态
+        update_jobs(|jobs| {
+            if let Some(saved) = jobs.iter_mut().find(|saved| saved.id == job.id) {
+                let state = &mut saved.state;
+                state.last_run = Some(finished.to_rfc3339());
+                state.run_count += 1;
+                match &outcome {
+                    Ok(new_assets) => {
+                        state.last_success = Some(true);
+                        state.last_error = None;
+                        state.last_new_assets = *new_assets;
+                    }
+                    Err(e) => {
+                        state.last_success = Some(false);
+                        state.last_error = Some(e.clone());
+                        state.last_new_assets = 0;
+                    }
+                }
+                state.next_run = Some(saved.schedule.next_after(finished)?.to_rfc3339());
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(())
+}
+
+// 把任务的查询语句转换为平台语法，识别不出语法或转换失败时原样使用
+fn platform_query(converter: &Option<QueryConverter>, query: &str, platform: &str) -> String {
+    let Some(converter) = converter else {
+        return query.to_string();
+    };
+    let detected = converter.detect_dialect(query);
+    let Some(source_platform) = detected.first().map(|score| score.platform.as_str()) else {
+        return query.to_string();
+    };
+    if source_platform == platform {
+        return query.to_string();
+    }
+    api::adapt_query_for_platform(converter, query, source_platform, platform, None).unwrap_or_else(
+        |e| {
+            eprintln!("{}", e);
+            query.to_string()
+        },
+    )
+}
+
+// 依次在各平台运行任务，返回新增资产总数；部分平台失败时其余平台照常运行
+async fn run_job(
+    app: &AppHandle,
+    converter: &Option<QueryConverter>,
+    job: &ScheduledQuery,
+) -> Result<u64, String> {
+    let mut new_assets = 0;
+    let mut errors = Vec::new();
+
+    for platform in &job.platforms {
+        let query = platform_query(converter, &job.query, platform);
+        let source = match api::get_source(platform) {
+            Ok(source) => source,
+            Err(e) => {
+                errors.push(format!("{}: {}", platform, e));
+                continue;
+            }
+        };
+
+        let fetched = api::fetch_pages(
+            source,
+            &query,
+            job.pages,
+            job.page_size,
+            job.credit_budget,
+            |_, _, _| {},
+        )
+        .await;
+        let fetched = match fetched {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                if let Err(e) =
+                    history::add_history(platform.clone(), query.clone(), 0, false, Some(e.clone()))
+                {
+                    eprintln!("保存历史记录失败: {}", e);
+                }
+                errors.push(format!("{}: {}", platform, e));
+                continue;
+            }
+        };
+        if fetched.budget_reached && fetched.assets.is_empty() {
+            errors.push(format!("{}: 额度预算不足以获取一页数据", platform));
+            continue;
+        }
+
+        match record_results(app, job, platform, &query, &fetched) {
+            Ok(inserted) => new_assets += inserted,
+            Err(e) => errors.push(format!("{}: {}", platform, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(new_assets)
+    } else {
+        Err(errors.join("；"))
+    }
+}
+
+// 写入本地资产库与历史记录，按需导出，有新资产时通知前端；返回新增资产数
+fn record_results(
+    app: &AppHandle,
+    job: &ScheduledQuery,
+    platform: &str,
+    query: &str,
+    fetched: &api::FetchedPages,
+) -> Result<u64, String> {
+    let mut asset_store = store::AssetStore::open()?;
+    let summary = asset_store.sync(platform, query, &fetched.assets)?;
+    let total = fetched.total.unwrap_or(fetched.assets.len() as u64);
+    // 达到额度预算时只取到部分页，不保存快照，避免下一次比对把未获取的资产记为消失
+    let snapshot_id = if fetched.budget_reached {
+        history::add_history(platform.to_string(), query.to_string(), total, true, None)?;
+        None
+    } else {
+        let snapshot = asset_store.save_snapshot(platform, query, &fetched.assets)?;
+        history::add_snapshot_history(platform.to_string(), query.to_string(), total, snapshot.id)?;
+        Some(snapshot.id)
+    };
+
+    if let Some(format) = &job.export_format {
+        let format = ExportFormat::from_name(format)?;
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
+        let file_path = format!(
+            "{}/{}_schedule_{}_{}.{}",
+            config::get_export_path()?,
+            platform,
+            job.id,
+            timestamp,
+            format.extension()
+        );
+        let mut export_file = ExportFile::create(&file_path, format)?;
+        export_file.write_page(&fetched.assets)?;
+        export_file.persist()?;
+    }
+
+    if summary.inserted > 0 {
+        let event = NewAssetsEvent {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            platform: platform.to_string(),
+            query: query.to_string(),
+            new_assets: summary.inserted,
+            total_assets: fetched.assets.len() as u64,
+            snapshot_id,
+        };
+        match app.get_webview_window("main") {
+            Some(window) => {
+                let _ = window.emit(NEW_ASSETS_EVENT, event);
+            }
+            None => {
+                let _ = app.emit(NEW_ASSETS_EVENT, event);
+            }
+        }
+    }
+
+    Ok(summary.inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn local(date: &str) -> DateTime<Local> {
+        let naive = chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap();
+        Local.from_local_datetime(&naive).earliest().unwrap()
+    }
+
+    #[test]
+    fn test_daily_rule_runs_at_the_next_occurrence() {
+        let rule = ScheduleRule::Daily {
+            time: "02:00".to_string(),
+        };
+        let next = rule.next_after(local("2024-03-01 01:30")).unwrap();
+        assert_eq!(next, local("2024-03-01 02:00"));
+        let next = rule.next_after(local("2024-03-01 02:00")).unwrap();
+        assert_eq!(next.date_naive(), local("2024-03-02 00:00").date_naive());
+        assert_eq!((next.hour(), next.minute()), (2, 0));
+
+        assert!(ScheduleRule::Daily {
+            time: "25:00".to_string()
+        }
+        .next_after(Local::now())
+        .is_err());
+    }
+
+    #[test]
+    fn test_interval_rule() {
+        let rule = ScheduleRule::Interval { hours: 6 };
+        let after = local("2024-03-01 22:00");
+        assert_eq!(rule.next_after(after).unwrap(), after + Duration::hours(6));
+        assert!(ScheduleRule::Interval { hours: 0 }
+            .next_after(after)
+            .is_err());
+    }
+
+    #[test]
+    fn test_due_jobs_survive_restart_state() {
+        let now = Local::now();
+        let mut job = ScheduledQuery {
+            id: "schedule_1".to_string(),
+            name: "weekly".to_string(),
+            query: "domain=\"example.com\"".to_string(),
+            platforms: vec!["fofa".to_string()],
+            pages: 1,
+            page_size: 100,
+            credit_budget: None,
+            export_format: None,
+            schedule: ScheduleRule::Interval { hours: 6 },
+            enabled: true,
+            state: ScheduleState::default(),
+        };
+        assert!(job.is_due(now));
+
+        job.state.next_run = Some((now + Duration::hours(1)).to_rfc3339());
+        let saved: ScheduledQuery =
+            serde_json::from_str(&serde_json::to_string(&job).unwrap()).unwrap();
+        assert!(!saved.is_due(now));
+        // 停机期间错过的运行在启动后补跑
+        assert!(saved.is_due(now + Duration::hours(2)));
+
+        job.enabled = false;
+        assert!(!job.is_due(now + Duration::hours(2)));
+
+        // 额度预算为 0 的任务一页都取不到
+        assert!(job.validate().is_ok());
+        job.credit_budget = Some(0);
+        assert!(job.validate().is_err());
+        job.credit_budget = Some(1);
+        assert!(job.validate().is_ok());
+    }
+}
@@ -0,0 +1,729 @@
+use crate::api::Asset;
+use chrono::{SecondsFormat, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const DATABASE_FILE: &str = "assets.db";
+
+/// 单页最多返回的条数
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// 每个查询保留的快照数，超出时删除最早的
+const MAX_SNAPSHOTS_PER_QUERY: i64 = 20;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS assets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    platform TEXT NOT NULL,
+    query TEXT NOT NULL,
+    asset_key TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    port INTEGER,
+    protocol TEXT NOT NULL,
+    host TEXT NOT NULL,
+    title TEXT NOT NULL,
+    server TEXT NOT NULL,
+    country TEXT NOT NULL,
+    data TEXT NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    UNIQUE (platform, query, asset_key)
+);
+CREATE INDEX IF NOT EXISTS idx_assets_last_seen ON assets (last_seen);
+CREATE INDEX IF NOT EXISTS idx_assets_ip ON assets (ip);
+CREATE TABLE IF NOT EXISTS snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    platform TEXT NOT NULL,
+    query TEXT NOT NULL,
+    taken_at TEXT NOT NULL,
+    assets INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_snapshots_query ON snapshots (platform, query);
+CREATE TABLE IF NOT EXISTS snapshot_assets (
+    snapshot_id INTEGER NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_snapshot_assets ON snapshot_assets (snapshot_id);
+";
+
+/// 本地库中的一条资产
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredAsset {
+    pub id: i64,
+    pub platform: String,
+    pub query: String,
+    /// 首次同步到本地库的时间
+    pub first_seen: String,
+    /// 最近一次查询仍命中的时间
+    pub last_seen: String,
+    pub asset: Asset,
+}
+
+/// 本地资产分页结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalAssetPage {
+    pub total: u64,
+    pub results: Vec<StoredAsset>,
+}
+
+/// 本地资产筛选条件，未填写的条件不参与筛选
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LocalAssetFilter {
+    pub platform: Option<String>,
+    pub query: Option<String>,
+    /// 在 IP、Host、标题、Server 中匹配（不区分大小写）
+    pub keyword: Option<String>,
+    pub port: Option<u16>,
+    pub country: Option<String>,
+    /// 只返回该时间（RFC 3339）之后仍被查询命中的资产
+    pub seen_since: Option<String>,
+}
+
+/// 一次同步的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SyncSummary {
+    /// 新增的资产
+    pub inserted: u64,
+    /// 已存在、更新了最近命中时间的资产
+    pub updated: u64,
+}
+
+/// 本地库中按查询汇总的记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalQuerySummary {
+    pub platform: String,
+    pub query: String,
+    pub assets: u64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// 某次查询运行的结果快照
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub platform: String,
+    pub query: String,
+    pub taken_at: String,
+    /// 快照中的资产条数
+    pub assets: u64,
+}
+
+// 资产在同一平台、同一查询下的唯一标识
+fn asset_key(asset: &Asset) -> String {
+    let host = if asset.ip.is_empty() && asset.host.is_empty() {
+        asset.url.trim()
+    } else {
+        asset.host.trim()
+    };
+    format!(
+        "{}|{}|{}|{}",
+        asset.ip.trim(),
+        asset.port.map(|port| port.to_string()).unwrap_or_default(),
+        asset.protocol.trim().to_lowercase(),
+        host.to_lowercase()
+    )
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// 本地资产库（SQLite）
+///
+/// 每次搜索或导出取回的资产按 平台 + 查询 + 资产标识 去重保存：首次出现时写入并记录
+/// `first_seen`，之后再次命中只刷新 `last_seen` 与资产内容，离线时可按条件分页查询。
+pub struct AssetStore {
+    conn: Connection,
+}
+
+impl AssetStore {
+    /// 打开配置目录下的资产库，不存在时创建
+    pub fn open() -> Result<Self, String> {
+        let dir = dirs::config_dir()
+            .ok_or_else(|| "无法获取配置目录".to_string())?
+            .join("asset-mapping");
+        fs::create_dir_all(&dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+        Self::open_at(&dir.join(DATABASE_FILE))
+    }
+
+    fn open_at(path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(path).map_err(|e| format!("打开本地资产库失败: {}", e))?;
+        // 搜索与导出可能同时写入，WAL 模式下读写互不阻塞
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .and_then(|_| conn.busy_timeout(std::time::Duration::from_secs(5)))
+            .map_err(|e| format!("打开本地资产库失败: {}", e))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    fn open_in_memory() -> Result<Self, String> {
+        let conn =
+            Connection::open_in_memory().map_err(|e| format!("打开本地资产库失败: {}", e))?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("初始化本地资产库失败: {}", e))?;
+        Ok(Self { conn })
+    }
+
+    /// 同步一批查询结果：新资产写入，已有资产更新最近命中时间
+    pub fn sync(
+        &mut self,
+        platform: &str,
+        query: &str,
+        assets: &[Asset],
+    ) -> Result<SyncSummary, String> {
+        self.sync_at(platform, query, assets, &now())
+    }
+
+    fn sync_at(
+        &mut self,
+        platform: &str,
+        query: &str,
+        assets: &[Asset],
+        seen_at: &str,
+    ) -> Result<SyncSummary, String> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|e| format!("写入本地资产库失败: {}", e))?;
+        let mut summary = SyncSummary::default();
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO assets (platform, query, asset_key, ip, port, protocol, \
+                     host, title, server, country, data, first_seen, last_seen) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?12)",
+                )
+                .map_err(|e| format!("写入本地资产库失败: {}", e))?;
+            let mut update = tx
+                .prepare(
+                    "UPDATE assets SET ip = ?4, port = ?5, protocol = ?6, host = ?7, title = ?8, \
+                     server = ?9, country = ?10, data = ?11, last_seen = ?12 \
+                     WHERE platform = ?1 AND query = ?2 AND asset_key = ?3",
+                )
+                .map_err(|e| format!("写入本地资产库失败: {}", e))?;
+
+            for asset in assets {
+                let data =
+                    serde_json::to_string(asset).map_err(|e| format!("序列化资产失败: {}", e))?;
+                let values = params![
+                    platform,
+                    query,
+                    asset_key(asset),
+                    asset.ip,
+                    asset.port,
+                    asset.protocol,
+                    asset.host,
+                    asset.title,
+                    asset.server,
+                    asset.geo.country,
+                    data,
+                    seen_at,
+                ];
+                let inserted = insert
+                    .execute(values)
+                    .map_err(|e| format!("写入本地资产库失败: {}", e))?;
+                if inserted > 0 {
+                    summary.inserted += 1;
+                } else {
+                    update
+                        .execute(values)
+                        .map_err(|e| format!("写入本地资产库失败: {}", e))?;
+                    summary.updated += 1;
+                }
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("写入本地资产库失败: {}", e))?;
+        Ok(summary)
+    }
+
+    /// 按条件分页查询，`page` 从 1 开始，按最近命中时间倒序
+    pub fn query(
+        &self,
+        filter: &LocalAssetFilter,
+        page: u32,
+        page_size: u32,
+    ) -> Result<LocalAssetPage, String> {
+        let mut conditions: Vec<&str> = Vec::new();
+        let mut values: Vec<SqlValue> = Vec::new();
+        let text = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        if let Some(platform) = text(&filter.platform) {
+            conditions.push("platform = ?");
+            values.push(platform.into());
+        }
+        if let Some(query) = text(&filter.query) {
+            conditions.push("query = ?");
+            values.push(query.into());
+        }
+        if let Some(keyword) = text(&filter.keyword) {
+            conditions.push(
+                "(instr(lower(ip), lower(?)) > 0 OR instr(lower(host), lower(?)) > 0 \
+                 OR instr(lower(title), lower(?)) > 0 OR instr(lower(server), lower(?)) > 0)",
+            );
+            values.extend(std::iter::repeat_n(SqlValue::from(keyword), 4));
+        }
+        if let Some(port) = filter.port {
+            conditions.push("port = ?");
+            values.push(i64::from(port).into());
+        }
+        if let Some(country) = text(&filter.country) {
+            conditions.push("country = ?");
+            values.push(country.into());
+        }
+        if let Some(seen_since) = text(&filter.seen_since) {
+            conditions.push("last_seen >= ?");
+            values.push(seen_since.into());
+        }
+
+        let clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+
+        let total: u64 = self
+            .conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM assets{}", clause),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("查询本地资产库失败: {}", e))?;
+
+        let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        let offset = u64::from(page.max(1) - 1) * u64::from(page_size);
+        let sql = format!(
+            "SELECT id, platform, query, first_seen, last_seen, data FROM assets{} \
+             ORDER BY last_seen DESC, id DESC LIMIT {} OFFSET {}",
+            clause, page_size, offset
+        );
+        let mut statement = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| format!("查询本地资产库失败: {}", e))?;
+        let rows = statement
+            .query_map(params_from_iter(values.iter()), |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })
+            .map_err(|e| format!("查询本地资产库失败: {}", e))?;
+
+        let mut results = Vec::new();
+        for row in rows {
+            let (id, platform, query, first_seen, last_seen, data) =
+                row.map_err(|e| format!("查询本地资产库失败: {}", e))?;
+            let asset: Asset =
+                serde_json::from_str(&data).map_err(|e| format!("解析本地资产失败: {}", e))?;
+            results.push(StoredAsset {
+                id,
+                platform,
+                query,
+                first_seen,
+                last_seen,
+                asset,
+            });
+        }
+
+        Ok(LocalAssetPage { total, results })
+    }
+
+    /// 按平台与查询汇总本地库，最近命中的在前
+    pub fn list_queries(&self) -> Result<Vec<LocalQuerySummary>, String> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT platform, query, COUNT(*), MIN(first_seen), MAX(last_seen) FROM assets \
+                 GROUP BY platform, query ORDER BY MAX(last_seen) DESC",
+            )
+            .map_err(|e| format!("查询本地资产库失败: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(LocalQuerySummary {
+                    platform: row.get(0)?,
+                    query: row.get(1)?,
+                    assets: row.get(2)?,
+                    first_seen: row.get(3)?,
+                    last_seen: row.get(4)?,
+                })
+            })
+            .map_err(|e| format!("查询本地资产库失败: {}", e))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("查询本地资产库失败: {}", e))
+    }
+
+    /// 删除某个查询保存的全部资产，返回删除条数
+    pub fn delete_query(&self, platform: &str, query: &str) -> Result<u64, String> {
+        self.conn
+            .execute(
+                "DELETE FROM assets WHERE platform = ?1 AND query = ?2",
+                params![platform, query],
+            )
+            .map(|deleted| deleted as u64)
+            .map_err(|e| format!("删除本地资产失败: {}", e))
+    }
+
+    /// 保存一次查询运行的完整结果，供之后与下一次运行比对
+    pub fn save_snapshot(
+        &mut self,
+        platform: &str,
+        query: &str,
+        assets: &[Asset],
+    ) -> Result<SnapshotInfo, String> {
+        self.save_snapshot_at(platform, query, assets, &now())
+    }
+
+    fn save_snapshot_at(
+        &mut self,
+        platform: &str,
+        query: &str,
+        assets: &[Asset],
+        taken_at: &str,
+    ) -> Result<SnapshotInfo, String> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|e| format!("保存快照失败: {}", e))?;
+        tx.execute(
+            "INSERT INTO snapshots (platform, query, taken_at, assets) VALUES (?1, ?2, ?3, ?4)",
+            params![platform, query, taken_at, assets.len() as u64],
+        )
+        .map_err(|e| format!("保存快照失败: {}", e))?;
+        let id = tx.last_insert_rowid();
+        {
+            let mut insert = tx
+                .prepare("INSERT INTO snapshot_assets (snapshot_id, data) VALUES (?1, ?2)")
+                .map_err(|e| format!("保存快照失败: {}", e))?;
+            for asset in assets {
+                // 比对只用归一化字段，不保存平台原始返回
+                let asset = Asset {
+                    raw: serde_json::Value::Null,
+                    ..asset.clone()
+                };
+                let data =
+                    serde_json::to_string(&asset).map_err(|e| format!("序列化资产失败: {}", e))?;
+                insert
+                    .execute(params![id, data])
+                    .map_err(|e| format!("保存快照失败: {}", e))?;
+            }
+        }
+
+        // 只保留最近的若干个快照
+        tx.execute(
+            "DELETE FROM snapshot_assets WHERE snapshot_id IN (SELECT id FROM snapshots \
+             WHERE platform = ?1 AND query = ?2 ORDER BY id DESC LIMIT -1 OFFSET ?3)",
+            params![platform, query, MAX_SNAPSHOTS_PER_QUERY],
+        )
+        .and_then(|_| {
+            tx.execute(
+                "DELETE FROM snapshots WHERE id IN (SELECT id FROM snapshots \
+                 WHERE platform = ?1 AND query = ?2 ORDER BY id DESC LIMIT -1 OFFSET ?3)",
+                params![platform, query, MAX_SNAPSHOTS_PER_QUERY],
+            )
+        })
+        .map_err(|e| format!("清理旧快照失败: {}", e))?;
+
+        tx.commit().map_err(|e| format!("保存快照失败: {}", e))?;
+        Ok(SnapshotInfo {
+            id,
+            platform: platform.to_string(),
+            query: query.to_string(),
+            taken_at: taken_at.to_string(),
+            assets: assets.len() as u64,
+        })
+    }
+
+    /// 按 ID 读取快照信息
+    pub fn snapshot(&self, id: i64) -> Result<SnapshotInfo, String> {
+        self.conn
+            .query_row(
+                "SELECT id, platform, query, taken_at, assets FROM snapshots WHERE id = ?1",
+                params![id],
+                snapshot_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("读取快照失败: {}", e))?
+            .ok_or_else(|| format!("快照不存在: {}", id))
+    }
+
+    /// 同一查询在 `snapshot` 之前的最近一次快照
+    pub fn previous_snapshot(
+        &self,
+        snapshot: &SnapshotInfo,
+    ) -> Result<Option<SnapshotInfo>, String> {
+        self.conn
+            .query_row(
+                "SELECT id, platform, query, taken_at, assets FROM snapshots \
+                 WHERE platform = ?1 AND query = ?2 AND id < ?3 ORDER BY id DESC LIMIT 1",
+                params![snapshot.platform, snapshot.query, snapshot.id],
+                snapshot_from_row,
+            )
+            .optional()
+            .map_err(|e| format!("读取快照失败: {}", e))
+    }
+
+    /// 读取快照中的全部资产
+    pub fn snapshot_assets(&self, id: i64) -> Result<Vec<Asset>, String> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT data FROM snapshot_assets WHERE snapshot_id = ?1 ORDER BY rowid")
+            .map_err(|e| format!("读取快照失败: {}", e))?;
+        let rows = statement
+            .query_map(params![id], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("读取快照失败: {}", e))?;
+        let mut assets = Vec::new();
+        for row in rows {
+            let data = row.map_err(|e| format!("读取快照失败: {}", e))?;
+            assets
+                .push(serde_json::from_str(&data).map_err(|e| format!("解析快照资产失败: {}", e))?);
+        }
+        Ok(assets)
+    }
+}
+
+fn snapshot_from_row(row: &rusqlite::Row) -> rusqlite::Result<SnapshotInfo> {
+    Ok(SnapshotInfo {
+        id: row.get(0)?,
+        platform: row.get(1)?,
+        query: row.get(2)?,
+        taken_at: row.get(3)?,
+        assets: row.get(4)?,
+    })
+}
+
+/// 把一批查询结果同步到本地资产库
+pub fn sync_assets(platform: &str, query: &str, assets: &[Asset]) -> Result<SyncSummary, String> {
+    if assets.is_empty() {
+        return Ok(SyncSummary::default());
+    }
+    AssetStore::open()?.sync(platform, query, assets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn asset(ip: &str, port: u16, title: &str) -> Asset {
+        Asset {
+            ip: ip.to_string(),
+            port: Some(port),
+            protocol: "http".to_string(),
+            title: title.to_string(),
+            ..Asset::new("fofa", Value::Null)
+        }
+        .finalize()
+    }
+
+    #[test]
+    fn test_resync_only_adds_new_assets() {
+        let mut store = AssetStore::open_in_memory().unwrap();
+        let first = store
+            .sync_at(
+                "fofa",
+                "port=80",
+                &[asset("1.1.1.1", 80, "a"), asset("2.2.2.2", 80, "b")],
+                "2024-01-01T00:00:00.000Z",
+            )
+            .unwrap();
+        assert_eq!(
+            first,
+            SyncSummary {
+                inserted: 2,
+                updated: 0
+            }
+        );
+
+        let second = store
+            .sync_at(
+                "fofa",
+                "port=80",
+                &[asset("2.2.2.2", 80, "b2"), asset("3.3.3.3", 80, "c")],
+                "2024-01-08T00:00:00.000Z",
+            )
+            .unwrap();
+        assert_eq!(
+            second,
+            SyncSummary {
+                inserted: 1,
+                updated: 1
+            }
+        );
+
+        let page = store.query(&LocalAssetFilter::default(), 1, 10).unwrap();
+        assert_eq!(page.total, 3);
+        let updated = page
+            .results
+            .iter()
+            .find(|stored| stored.asset.ip == "2.2.2.2")
+            .unwrap();
+        assert_eq!(updated.first_seen, "2024-01-01T00:00:00.000Z");
+        assert_eq!(updated.last_seen, "2024-01-08T00:00:00.000Z");
+        assert_eq!(updated.asset.title, "b2");
+
+        // 不同查询各自保存
+        let other = store
+            .sync_at(
+                "fofa",
+                "port=443",
+                &[asset("1.1.1.1", 80, "a")],
+                "2024-01-08T00:00:00.000Z",
+            )
+            .unwrap();
+        assert_eq!(other.inserted, 1);
+    }
+
+    #[test]
+    fn test_query_filters_and_paginates() {
+        let mut store = AssetStore::open_in_memory().unwrap();
+        store
+            .sync_at(
+                "fofa",
+                "title=login",
+                &[
+                    asset("1.1.1.1", 80, "Login"),
+                    asset("2.2.2.2", 8080, "Admin"),
+                ],
+                "2024-01-01T00:00:00.000Z",
+            )
+            .unwrap();
+        store
+            .sync_at(
+                "hunter",
+                "web.title=login",
+                &[asset("3.3.3.3", 80, "login page")],
+                "2024-01-02T00:00:00.000Z",
+            )
+            .unwrap();
+
+        let keyword = LocalAssetFilter {
+            keyword: Some("LOGIN".to_string()),
+            ..Default::default()
+        };
+        let page = store.query(&keyword, 1, 10).unwrap();
+        assert_eq!(page.total, 2);
+        // 最近命中的在前
+        assert_eq!(page.results[0].asset.ip, "3.3.3.3");
+        assert_eq!(page.results[0].platform, "hunter");
+
+        let filter = LocalAssetFilter {
+            platform: Some("fofa".to_string()),
+            port: Some(8080),
+            ..Default::default()
+        };
+        assert_eq!(
+            store.query(&filter, 1, 10).unwrap().results[0].asset.ip,
+            "2.2.2.2"
+        );
+
+        let since = LocalAssetFilter {
+            seen_since: Some("2024-01-02T00:00:00.000Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(store.query(&since, 1, 10).unwrap().total, 1);
+
+        let all = LocalAssetFilter::default();
+        let second_page = store.query(&all, 2, 2).unwrap();
+        assert_eq!(second_page.total, 3);
+        assert_eq!(second_page.results.len(), 1);
+        assert_eq!(second_page.results[0].asset.ip, "1.1.1.1");
+    }
+
+    #[test]
+    fn test_list_and_delete_queries() {
+        let mut store = AssetStore::open_in_memory().unwrap();
+        store
+            .sync_at(
+                "fofa",
+                "port=80",
+                &[asset("1.1.1.1", 80, "")],
+                "2024-01-01T00:00:00.000Z",
+            )
+            .unwrap();
+        store
+            .sync_at(
+                "fofa",
+                "port=22",
+                &[asset("1.1.1.1", 22, ""), asset("2.2.2.2", 22, "")],
+                "2024-01-02T00:00:00.000Z",
+            )
+            .unwrap();
+
+        let queries = store.list_queries().unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(queries[0].query, "port=22");
+        assert_eq!(queries[0].assets, 2);
+
+        assert_eq!(store.delete_query("fofa", "port=22").unwrap(), 2);
+        assert_eq!(store.list_queries().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_snapshots_are_ordered_and_pruned() {
+        let mut store = AssetStore::open_in_memory().unwrap();
+        let first = store
+            .save_snapshot_at(
+                "fofa",
+                "port=80",
+                &[asset("1.1.1.1", 80, "")],
+                "2024-01-01T00:00:00.000Z",
+            )
+            .unwrap();
+        let second = store
+            .save_snapshot_at(
+                "fofa",
+                "port=80",
+                &[asset("1.1.1.1", 80, ""), asset("2.2.2.2", 80, "")],
+                "2024-01-08T00:00:00.000Z",
+            )
+            .unwrap();
+        store
+            .save_snapshot_at("fofa", "port=22", &[], "2024-01-09T00:00:00.000Z")
+            .unwrap();
+
+        assert_eq!(store.snapshot(second.id).unwrap(), second);
+        assert_eq!(
+            store.previous_snapshot(&second).unwrap(),
+            Some(first.clone())
+        );
+        assert_eq!(store.previous_snapshot(&first).unwrap(), None);
+        assert_eq!(store.snapshot_assets(second.id).unwrap().len(), 2);
+
+        for _ in 0..MAX_SNAPSHOTS_PER_QUERY {
+            store
+                .save_snapshot_at(
+                    "fofa",
+                    "port=80",
+                    &[asset("3.3.3.3", 80, "")],
+                    "2024-02-01T00:00:00.000Z",
+                )
+                .unwrap();
+        }
+        assert!(store.snapshot(first.id).is_err());
+        assert!(store.snapshot_assets(second.id).unwrap().is_empty());
+    }
+}
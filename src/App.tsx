@@ -1,5 +1,6 @@
 import React, { Suspense, lazy, startTransition, useCallback, useEffect, useMemo, useRef, useState } from 'react';
-import { theme, ConfigProvider, Tooltip, Spin, Space } from 'antd';
+import { theme, ConfigProvider, Tooltip, Spin, Space, notification } from 'antd';
+import { listen } from '@tauri-apps/api/event';
 import { useTheme } from './hooks/useTheme';
 import { endPerf, isPerfEnabled, PerfToken, scheduleIdle, startPerf } from './utils/perf';
 import {
@@ -8,6 +9,8 @@ import {
   SettingOutlined,
   ExportOutlined,
   HistoryOutlined,
+  HddOutlined,
+  FieldTimeOutlined,
   SwapOutlined,
   SendOutlined,
   DatabaseOutlined,
@@ -28,6 +31,8 @@ type ViewKey =
   | 'api-keys'
   | 'export'
   | 'history'
+  | 'local-assets'
+  | 'scheduled-queries'
   | 'settings';
 
 type ViewModule = { default: React.ComponentType<any> };
@@ -41,6 +46,8 @@ const viewLoaders: Record<ViewKey, () => Promise<ViewModule>> = {
   'api-keys': () => import('./components/ApiKeyManagement'),
   'export': () => import('./components/ExportData'),
   'history': () => import('./components/HistoryRecords'),
+  'local-assets': () => import('./components/LocalAssets'),
+  'scheduled-queries': () => import('./components/ScheduledQueries'),
   'settings': () => import('./components/Settings'),
 };
 
@@ -76,6 +83,8 @@ const ApiKeyManagement = lazy(() => loadView('api-keys'));
 const Settings = lazy(() => loadView('settings'));
 const ExportData = lazy(() => loadView('export'));
 const HistoryRecords = lazy(() => loadView('history'));
+const LocalAssets = lazy(() => loadView('local-assets'));
+const ScheduledQueries = lazy(() => loadView('scheduled-queries'));
 const QueryConverter = lazy(() => loadView('query-converter'));
 const Resender = lazy(() => loadView('resender'));
 const PocManager = lazy(() => loadView('poc-manager'));
@@ -97,6 +106,8 @@ const navItems: NavItem[] = [
   { key: 'api-keys', icon: <KeyOutlined />, label: 'API密钥' },
   { key: 'export', icon: <ExportOutlined />, label: '数据导出' },
   { key: 'history', icon: <HistoryOutlined />, label: '历史记录' },
+  { key: 'local-assets', icon: <HddOutlined />, label: '本地资产库' },
+  { key: 'scheduled-queries', icon: <FieldTimeOutlined />, label: '定时任务' },
   { key: 'settings', icon: <SettingOutlined />, label: '设置' },
 ];
 
@@ -108,7 +119,9 @@ const likelyNextViews: Record<ViewKey, ViewKey[]> = {
   'query-converter': ['asset-query', 'export'],
   'api-keys': ['settings', 'asset-query'],
   'export': ['history', 'query-converter'],
-  'history': ['export', 'asset-query'],
+  'history': ['local-assets', 'export'],
+  'local-assets': ['history', 'asset-query'],
+  'scheduled-queries': ['history', 'local-assets'],
   'settings': ['api-keys'],
 };
 
@@ -126,6 +139,8 @@ const App: React.FC<AppProps> = ({ bootstrapPerfToken = null }) => {
     'api-keys': false,
     'export': false,
     'history': false,
+    'local-assets': false,
+    'scheduled-queries': false,
     'settings': false,
   });
   const activationPerfRef = useRef<Partial<Record<ViewKey, PerfToken>>>({});
@@ -152,6 +167,26 @@ const App: React.FC<AppProps> = ({ bootstrapPerfToken = null }) => {
     });
   };
 
+  // 定时任务发现新资产时弹出通知
+  useEffect(() => {
+    const unlisten = listen<{ jobName: string; platform: string; newAssets: number; totalAssets: number; snapshotId: number | null }>(
+      'scheduled-query-new-assets',
+      (event) => {
+        const { jobName, platform, newAssets, totalAssets, snapshotId } = event.payload;
+        // 达到额度预算的运行结果不完整，没有快照可比对
+        const hint = snapshotId != null ? '可在历史记录中查看变化' : '已达到额度预算，结果不完整';
+        notification.info({
+          message: `定时任务「${jobName}」发现新资产`,
+          description: `${platform.toUpperCase()} 新增 ${newAssets} 条（本次共 ${totalAssets} 条），${hint}`,
+          duration: 0,
+        });
+      }
+    );
+    return () => {
+      unlisten.then((dispose) => dispose());
+    };
+  }, []);
+
   // Predictive preload on idle
   useEffect(() => {
     const dispose = scheduleIdle(() => {
@@ -284,6 +319,8 @@ const App: React.FC<AppProps> = ({ bootstrapPerfToken = null }) => {
               {renderLazyView('export', <ExportData />)}
               {renderLazyView('query-converter', <QueryConverter />)}
               {renderLazyView('history', <HistoryRecords active={selectedKey === 'history'} />)}
+              {renderLazyView('local-assets', <LocalAssets active={selectedKey === 'local-assets'} />)}
+              {renderLazyView('scheduled-queries', <ScheduledQueries active={selectedKey === 'scheduled-queries'} />)}
               {renderLazyView('settings', <Settings />)}
             </div>
           </div>
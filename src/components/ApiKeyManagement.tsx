@@ -2,6 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { Card, Tabs, Input, Button, Table, Form, Modal, message, Popconfirm, Tag, Space } from 'antd';
 import { PlusOutlined, DeleteOutlined, CheckCircleOutlined, ExclamationCircleOutlined, DownloadOutlined, UploadOutlined } from '@ant-design/icons';
 import { invoke } from '@tauri-apps/api/core';
+import KeyPoolHealth from './KeyPoolHealth';
 
 interface ApiKey {
   key: string;
@@ -10,6 +11,12 @@ interface ApiKey {
   quota?: string;
 }
 
+// 密钥需要配合账号使用的平台（账号保存在 email 字段中）
+const ACCOUNT_FIELDS: Record<string, { label: string; placeholder: string; isEmail: boolean }> = {
+  fofa: { label: '邮箱', placeholder: '请输入FOFA账号邮箱', isEmail: true },
+  censys: { label: 'API ID', placeholder: '请输入Censys API ID（密钥处填写 API Secret）', isEmail: false },
+};
+
 const ApiKeyManagement: React.FC = () => {
   const [platform, setPlatform] = useState<string>('hunter');
   const [apiKeys, setApiKeys] = useState<ApiKey[]>([]);
@@ -17,6 +24,7 @@ const ApiKeyManagement: React.FC = () => {
   const [isModalVisible, setIsModalVisible] = useState<boolean>(false);
   const [isBatchMode, setIsBatchMode] = useState<boolean>(false);
   const [form] = Form.useForm();
+  const accountField = ACCOUNT_FIELDS[platform];
 
   // 加载API密钥
   useEffect(() => {
@@ -31,8 +39,8 @@ const ApiKeyManagement: React.FC = () => {
 
       let keys: ApiKey[] = [];
 
-      if (platform === 'fofa' && data.emails) {
-        // FOFA平台需要同时处理API密钥和邮箱
+      if (accountField && data.emails) {
+        // FOFA、Censys 等平台需要同时处理API密钥和账号
         keys = data.api_keys.map((key, index) => ({
           key,
           email: data.emails?.[index] || '',
@@ -77,7 +85,7 @@ const ApiKeyManagement: React.FC = () => {
 
         for (const key of keys) {
           try {
-            if (platform === 'fofa' && values.email) {
+            if (accountField && values.email) {
               await invoke('add_api_key', {
                 platform,
                 apiKey: key,
@@ -111,7 +119,7 @@ const ApiKeyManagement: React.FC = () => {
       }
 
       // 单个添加模式
-      if (platform === 'fofa' && values.email) {
+      if (accountField && values.email) {
         await invoke('add_api_key', {
           platform,
           apiKey: values.apiKey,
@@ -137,7 +145,7 @@ const ApiKeyManagement: React.FC = () => {
   // 删除API密钥
   const deleteApiKey = async (apiKey: string, email?: string) => {
     try {
-      if (platform === 'fofa' && email) {
+      if (accountField && email) {
         await invoke('delete_api_key', {
           platform,
           apiKey: apiKey,
@@ -163,7 +171,7 @@ const ApiKeyManagement: React.FC = () => {
     try {
       let result;
 
-      if (platform === 'fofa' && email) {
+      if (accountField && email) {
         result = await invoke('validate_api_key', {
           platform,
           apiKey: apiKey,
@@ -293,12 +301,12 @@ const ApiKeyManagement: React.FC = () => {
       }
     ];
 
-    // FOFA平台需要额外显示邮箱列
-    if (platform === 'fofa') {
+    // 需要账号的平台额外显示账号列
+    if (accountField) {
       return [
         ...baseColumns.slice(0, 1),
         {
-          title: '邮箱',
+          title: accountField.label,
           dataIndex: 'email',
           key: 'email',
         },
@@ -357,7 +365,10 @@ const ApiKeyManagement: React.FC = () => {
     { key: 'hunter', label: 'Hunter' },
     { key: 'fofa', label: 'FOFA' },
     { key: 'quake', label: 'Quake' },
-    { key: 'daydaymap', label: 'DayDayMap' }
+    { key: 'daydaymap', label: 'DayDayMap' },
+    { key: 'shodan', label: 'Shodan' },
+    { key: 'zoomeye', label: 'ZoomEye' },
+    { key: 'censys', label: 'Censys' }
   ];
 
   return (
@@ -412,6 +423,8 @@ const ApiKeyManagement: React.FC = () => {
         pagination={false}
       />
 
+      <KeyPoolHealth platform={platform} />
+
       <Modal
         title={
           <Space>
@@ -462,17 +475,17 @@ const ApiKeyManagement: React.FC = () => {
                 />
               </Form.Item>
 
-              {platform === 'fofa' && (
+              {accountField && (
                 <Form.Item
                   name="email"
-                  label="邮箱"
+                  label={accountField.label}
                   rules={[
-                    { required: true, message: '请输入FOFA邮箱' },
-                    { type: 'email', message: '请输入有效的邮箱地址' }
+                    { required: true, message: `请输入${accountField.label}` },
+                    ...(accountField.isEmail ? [{ type: 'email' as const, message: '请输入有效的邮箱地址' }] : [])
                   ]}
-                  extra="批量添加时，所有密钥将使用同一个邮箱"
+                  extra={`批量添加时，所有密钥将使用同一个${accountField.label}`}
                 >
-                  <Input placeholder="请输入FOFA账号邮箱" />
+                  <Input placeholder={accountField.placeholder} />
                 </Form.Item>
               )}
             </>
@@ -487,16 +500,16 @@ const ApiKeyManagement: React.FC = () => {
                 <Input placeholder={`请输入${platform.toUpperCase()} API密钥`} />
               </Form.Item>
 
-              {platform === 'fofa' && (
+              {accountField && (
                 <Form.Item
                   name="email"
-                  label="邮箱"
+                  label={accountField.label}
                   rules={[
-                    { required: true, message: '请输入FOFA邮箱' },
-                    { type: 'email', message: '请输入有效的邮箱地址' }
+                    { required: true, message: `请输入${accountField.label}` },
+                    ...(accountField.isEmail ? [{ type: 'email' as const, message: '请输入有效的邮箱地址' }] : [])
                   ]}
                 >
-                  <Input placeholder="请输入FOFA账号邮箱" />
+                  <Input placeholder={accountField.placeholder} />
                 </Form.Item>
               )}
             </>
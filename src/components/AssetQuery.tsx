@@ -7,12 +7,13 @@ import { fingerprints, Fingerprint } from '../data/fingerprints';
 import { useExportProgress } from '../hooks/useExportProgress';
 import { endPerf, startPerf } from '../utils/perf';
 import { normalizeSmartPunctuation } from '../utils/textInput';
+import { formatApiError } from '../utils/apiError';
 
 interface AssetResult {
   url: string;
   ip: string;
-  port: string;
-  web_title?: string;
+  port?: number;
+  title?: string;
   country?: string;
   province?: string;
   city?: string;
@@ -20,12 +21,15 @@ interface AssetResult {
   source?: string;
 }
 
-const ALL_PLATFORMS = ['hunter', 'fofa', 'quake', 'daydaymap'] as const;
+const ALL_PLATFORMS = ['hunter', 'fofa', 'quake', 'daydaymap', 'shodan', 'zoomeye', 'censys'] as const;
 const EMPTY_PLATFORM_TOTALS: Record<string, number> = {
   hunter: 0,
   fofa: 0,
   quake: 0,
   daydaymap: 0,
+  shodan: 0,
+  zoomeye: 0,
+  censys: 0,
 };
 
 const AssetQuery: React.FC = () => {
@@ -158,6 +162,42 @@ const AssetQuery: React.FC = () => {
       { label: 'cert:"baidu"', description: '搜索证书' },
       { label: 'banner:"nginx"', description: '搜索Banner' },
     ],
+    shodan: [
+      { label: 'hostname:"test.com"', description: '搜索主机名' },
+      { label: 'net:"1.1.1.0/24"', description: '搜索IP段（CIDR）' },
+      { label: 'http.title:"login"', description: '搜索网页标题' },
+      { label: 'product:"nginx"', description: '搜索产品' },
+      { label: 'port:"3389"', description: '搜索端口' },
+      { label: 'country:"US"', description: '搜索国家' },
+      { label: 'city:"Tokyo"', description: '搜索城市' },
+      { label: 'http.html:"login"', description: '搜索网页内容' },
+      { label: 'ssl:"example.com"', description: '搜索证书' },
+    ],
+    zoomeye: [
+      { label: 'domain="test.com"', description: '搜索域名' },
+      { label: 'ip="1.1.1.1"', description: '搜索IP' },
+      { label: 'title="登录"', description: '搜索网页标题' },
+      { label: 'app="nginx"', description: '搜索应用组件' },
+      { label: 'service="https"', description: '搜索服务协议' },
+      { label: 'port=3389', description: '搜索端口' },
+      { label: 'country="CN"', description: '搜索国家' },
+      { label: 'subdivisions="beijing"', description: '搜索省份' },
+      { label: 'city="beijing"', description: '搜索城市' },
+      { label: 'ssl="example.com"', description: '搜索证书' },
+      { label: 'after="2024-01-01"', description: '搜索更新时间之后' },
+    ],
+    censys: [
+      { label: 'dns.names: "test.com"', description: '搜索域名' },
+      { label: 'ip: "1.1.1.0/24"', description: '搜索IP段（CIDR）' },
+      { label: 'services.http.response.html_title: "login"', description: '搜索网页标题' },
+      { label: 'services.software.product: "nginx"', description: '搜索产品' },
+      { label: 'services.port: 3389', description: '搜索端口' },
+      { label: 'services.service_name: "SSH"', description: '搜索服务协议' },
+      { label: 'location.country: "China"', description: '搜索国家' },
+      { label: 'location.province: "Beijing"', description: '搜索省份' },
+      { label: 'location.city: "Beijing"', description: '搜索城市' },
+      { label: 'last_updated_at: [2024-01-01 TO *]', description: '搜索更新时间之后' },
+    ],
   };
 
   // 查询占位符
@@ -166,6 +206,9 @@ const AssetQuery: React.FC = () => {
     fofa: '例如: domain="test.com" && country="CN"',
     quake: '例如: domain: test.com AND country: "China"',
     daydaymap: '例如: ip:"1.1.1.0/24" 或 domain:"test.com" (注意：使用冒号和引号)',
+    shodan: '例如: hostname:"test.com" country:"US" (条件之间使用空格)',
+    zoomeye: '例如: domain="test.com" && app="nginx"',
+    censys: '例如: dns.names: "test.com" AND services.port: 443',
   };
 
   const platformMeta = {
@@ -189,6 +232,21 @@ const AssetQuery: React.FC = () => {
       description: '对地理与资产分布信息更直观，适合快速筛查。',
       accent: '#13c2c2',
     },
+    shodan: {
+      label: 'Shodan',
+      description: '覆盖境外资产，适合海外目标的服务与组件排查。',
+      accent: '#cf1322',
+    },
+    zoomeye: {
+      label: 'ZoomEye',
+      description: '组件与服务识别细致，适合按应用指纹定位目标。',
+      accent: '#2f54eb',
+    },
+    censys: {
+      label: 'Censys',
+      description: '按主机聚合服务与证书信息，适合证书和服务画像分析。',
+      accent: '#d4380d',
+    },
   };
   const currentPlatformMeta = platformMeta[platform as keyof typeof platformMeta];
   const modeLabel = aggregatedSearch ? '全平台聚合' : `${currentPlatformMeta.label} 单平台`;
@@ -205,7 +263,10 @@ const AssetQuery: React.FC = () => {
           fofa: '#1677ff',
           hunter: '#fa8c16',
           quake: '#722ed1',
-          daydaymap: '#13c2c2'
+          daydaymap: '#13c2c2',
+          shodan: '#cf1322',
+          zoomeye: '#2f54eb',
+          censys: '#d4380d'
         };
         return <Tag color={colors[platform] || 'blue'}>{platform.toUpperCase()}</Tag>;
       }
@@ -240,8 +301,8 @@ const AssetQuery: React.FC = () => {
     },
     {
       title: '标题',
-      dataIndex: 'web_title',
-      key: 'web_title',
+      dataIndex: 'title',
+      key: 'title',
       width: 220,
       ellipsis: true,
     },
@@ -425,7 +486,7 @@ const AssetQuery: React.FC = () => {
     }
 
     // 否则追加到当前查询
-    const connector = platform === 'quake' || platform === 'daydaymap' ? ' AND ' : ' && ';
+    const connector = platform === 'shodan' ? ' ' : platform === 'quake' || platform === 'daydaymap' || platform === 'censys' ? ' AND ' : ' && ';
     const newQuery = query + connector + normalizedValue;
     setQuery(newQuery);
     queryRef.current = newQuery;
@@ -509,7 +570,7 @@ const AssetQuery: React.FC = () => {
             });
             return { platform: p, data: res };
           } catch (e) {
-            console.error(`平台 ${p} 搜索失败:`, e);
+            console.error(`平台 ${p} 搜索失败:`, formatApiError(e));
             return { platform: p, data: { results: [], total: 0 } };
           }
         });
@@ -558,7 +619,7 @@ const AssetQuery: React.FC = () => {
       });
     } catch (error: any) {
       console.error('查询出错:', error);
-      const errMsg = typeof error === 'string' ? error : (error?.message || '未知错误');
+      const errMsg = formatApiError(error);
       message.error(`查询出错: ${errMsg}`);
 
       setResults([]);
@@ -603,25 +664,30 @@ const AssetQuery: React.FC = () => {
         case 'fofa': locationQuery += `region="${province}"`; break;
         case 'quake': locationQuery += `province: "${province}"`; break;
         case 'daydaymap': locationQuery += `province:"${province}"`; break;
+        case 'zoomeye': locationQuery += `subdivisions="${province}"`; break;
+        case 'censys': locationQuery += `location.province: "${province}"`; break;
       }
     }
 
     if (city) {
       if (locationQuery) {
-        locationQuery += (platform === 'quake' || platform === 'daydaymap') ? ' AND ' : ' && ';
+        locationQuery += (platform === 'quake' || platform === 'daydaymap' || platform === 'censys') ? ' AND ' : ' && ';
       }
       switch (platform) {
         case 'hunter': locationQuery += `ip.city="${city}"`; break;
         case 'fofa': locationQuery += `city="${city}"`; break;
         case 'quake': locationQuery += `city: "${city}"`; break;
         case 'daydaymap': locationQuery += `city:"${city}"`; break;
+        case 'shodan': locationQuery += `city:"${city}"`; break;
+        case 'zoomeye': locationQuery += `city="${city}"`; break;
+        case 'censys': locationQuery += `location.city: "${city}"`; break;
       }
     }
 
     if (locationQuery) {
       let newQuery = '';
       if (appendToQuery && queryRef.current) {
-        const connector = (platform === 'quake' || platform === 'daydaymap') ? ' AND ' : ' && ';
+        const connector = (platform === 'quake' || platform === 'daydaymap' || platform === 'censys') ? ' AND ' : ' && ';
         newQuery = `${queryRef.current}${connector}${locationQuery}`;
       } else {
         newQuery = locationQuery;
@@ -774,7 +840,10 @@ const AssetQuery: React.FC = () => {
     { key: 'hunter', label: 'Hunter' },
     { key: 'fofa', label: 'FOFA' },
     { key: 'quake', label: 'Quake' },
-    { key: 'daydaymap', label: 'DayDayMap' }
+    { key: 'daydaymap', label: 'DayDayMap' },
+    { key: 'shodan', label: 'Shodan' },
+    { key: 'zoomeye', label: 'ZoomEye' },
+    { key: 'censys', label: 'Censys' }
   ];
 
   // 创建页码选项
@@ -1,10 +1,11 @@
-import React, { useState } from 'react';
-import { Card, Tabs, Input, Button, Select, DatePicker, Radio, Form, message, Space, Divider, Alert, InputNumber } from 'antd';
-import { DownloadOutlined } from '@ant-design/icons';
+import React, { useCallback, useEffect, useState } from 'react';
+import { Card, Tabs, Input, Button, Select, DatePicker, Radio, Form, message, Space, Divider, Alert, InputNumber, List, Tag, Typography } from 'antd';
+import { CalculatorOutlined, DownloadOutlined, RedoOutlined } from '@ant-design/icons';
 import { invoke } from '@tauri-apps/api/core';
 import type { RadioChangeEvent } from 'antd';
 import dayjs from 'dayjs';
 import ProgressModal from './ProgressModal';
+import type { ProgressStatus } from './ProgressModal';
 import { endPerf, startPerf } from '../utils/perf';
 import { normalizeSmartPunctuation } from '../utils/textInput';
 
@@ -13,6 +14,115 @@ const { TextArea } = Input;
 
 import { useExportProgress } from '../hooks/useExportProgress';
 
+// 导出格式
+type ExportFormat =
+  | 'csv' | 'json' | 'ndjson' | 'xlsx' | 'markdown' | 'html'
+  | 'ipport' | 'urls' | 'nmap' | 'masscan';
+
+// 导出格式选项：数据文件与可直接交给扫描工具的目标列表
+const exportFormatOptions = [
+  {
+    label: '数据文件',
+    options: [
+      { value: 'csv', label: 'CSV' },
+      { value: 'json', label: 'JSON' },
+      { value: 'ndjson', label: 'NDJSON' },
+      { value: 'xlsx', label: 'XLSX（按平台分表）' },
+      { value: 'markdown', label: 'Markdown 报告' },
+      { value: 'html', label: 'HTML 报告' },
+    ],
+  },
+  {
+    label: '扫描目标',
+    options: [
+      { value: 'ipport', label: 'ip:port 列表' },
+      { value: 'urls', label: 'URL 列表（httpx / nuclei）' },
+      { value: 'nmap', label: 'nmap -iL 主机列表' },
+      { value: 'masscan', label: 'masscan 配置' },
+    ],
+  },
+];
+
+// 未完成的导出任务检查点
+interface ExportCheckpoint {
+  taskId: string;
+  platform: string;
+  query: string;
+  pages: number;
+  pageSize: number;
+  format: string;
+  lastPage: number;
+  fetched: number;
+  total: number | null;
+  keyHint: string | null;
+  budget: number | null;
+  spent: number;
+  updatedAt: string;
+}
+
+// 单个密钥的剩余额度
+interface KeyBalance {
+  keyHint: string;
+  valid: boolean;
+  remaining: number | null;
+  message: string | null;
+}
+
+// 导出前的额度预估
+interface CostEstimate {
+  platform: string;
+  unit: string;
+  billing: 'perResult' | 'perRequest';
+  estimated: number;
+  available: number | null;
+  sufficient: boolean | null;
+  keys: KeyBalance[];
+}
+
+// 多平台导出中单个平台的结果（对应后端 PlatformOutcome）
+interface PlatformOutcome {
+  platform: string;
+  status: 'success' | 'failed' | 'cancelled' | 'skipped';
+  query: string | null;
+  returned: number;
+  total: number | null;
+  error: string | null;
+}
+
+// 单个平台对合并结果的贡献
+interface EngineContribution {
+  platform: string;
+  returned: number;
+  assets: number;
+  unique: number;
+}
+
+// 多平台合并导出的结果（对应后端 AllPlatformsExport）
+interface AllPlatformsExport {
+  filePath: string | null;
+  summary: {
+    inputCount: number;
+    mergedCount: number;
+    engines: EngineContribution[];
+  };
+  platforms: PlatformOutcome[];
+}
+
+const outcomeTag: Record<PlatformOutcome['status'], { color: string; label: string }> = {
+  success: { color: 'success', label: '成功' },
+  failed: { color: 'error', label: '失败' },
+  cancelled: { color: 'warning', label: '已取消' },
+  skipped: { color: 'default', label: '已跳过' },
+};
+
+const runningTag: Record<ProgressStatus, { color: string; label: string }> = {
+  idle: { color: 'default', label: '等待中' },
+  running: { color: 'processing', label: '查询中' },
+  success: { color: 'success', label: '成功' },
+  error: { color: 'error', label: '失败' },
+  cancelled: { color: 'warning', label: '已取消' },
+};
+
 const ExportData: React.FC = () => {
   const [platform, setPlatform] = useState<string>('hunter');
   const [query, setQuery] = useState<string>('');
@@ -22,17 +132,85 @@ const ExportData: React.FC = () => {
   const [dateRange, setDateRange] = useState<[dayjs.Dayjs | null, dayjs.Dayjs | null]>([null, null]);
   const [loading, setLoading] = useState<boolean>(false);
   const [exportType, setExportType] = useState<'current' | 'platform' | 'all'>('current');
-  const [exportFormat, setExportFormat] = useState<'csv' | 'json'>('csv');
+  const [exportFormat, setExportFormat] = useState<ExportFormat>('csv');
+  const [budget, setBudget] = useState<number | null>(null);
+  const [estimate, setEstimate] = useState<CostEstimate | null>(null);
+  const [estimating, setEstimating] = useState<boolean>(false);
+
+  const [checkpoints, setCheckpoints] = useState<ExportCheckpoint[]>([]);
+  const [resumingTaskId, setResumingTaskId] = useState<string | null>(null);
+
+  // 正在运行的导出任务，用于取消
+  const [runningTaskId, setRunningTaskId] = useState<string | null>(null);
+  const [allExport, setAllExport] = useState<AllPlatformsExport | null>(null);
 
   // 导出进度 hook
   const exportProgress = useExportProgress();
 
+  // 加载未完成的导出任务
+  const loadCheckpoints = useCallback(async () => {
+    try {
+      setCheckpoints(await invoke<ExportCheckpoint[]>('list_export_checkpoints'));
+    } catch (error) {
+      console.error('加载未完成的导出任务失败:', error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadCheckpoints();
+  }, [loadCheckpoints]);
+
+  // 导出范围变化后旧的预估不再准确
+  useEffect(() => {
+    setEstimate(null);
+  }, [platform, pages, pageSize, exportType]);
+
+  // 预估本次导出消耗的额度，并查询各密钥的剩余额度
+  const handleEstimate = async () => {
+    setEstimating(true);
+    try {
+      setEstimate(await invoke<CostEstimate>('estimate_export_cost', { platform, pages, pageSize }));
+    } catch (error) {
+      message.error(`额度预估失败: ${error}`);
+    } finally {
+      setEstimating(false);
+    }
+  };
+
+  // 从检查点继续导出
+  const handleResume = async (checkpoint: ExportCheckpoint) => {
+    exportProgress.startTask(
+      checkpoint.taskId,
+      `继续导出: 平台=${checkpoint.platform}, 从第 ${checkpoint.lastPage + 1} 页开始`,
+    );
+    setResumingTaskId(checkpoint.taskId);
+    setRunningTaskId(checkpoint.taskId);
+    try {
+      const filePath = await invoke<string>('resume_export', { taskId: checkpoint.taskId });
+      message.success(`导出成功: ${filePath}`);
+    } catch (error: any) {
+      const errMsg = typeof error === 'string' ? error : (error?.message || '未知错误');
+      message.error(`继续导出失败: ${errMsg}`);
+      exportProgress.setStatus('error');
+      exportProgress.setStatusText(`继续导出失败: ${errMsg}`);
+      exportProgress.addLog(`✗ 继续导出失败: ${errMsg}`, 'error');
+      exportProgress.finishTask();
+    } finally {
+      setResumingTaskId(null);
+      setRunningTaskId(null);
+      loadCheckpoints();
+    }
+  };
+
   // 平台查询语法提示
   const placeholders = {
     hunter: '例如: domain.suffix="test.com" && ip.province="北京市"',
     fofa: '例如: domain="test.com" && country="CN"',
     quake: '例如: domain: test.com AND country: "China"',
     daydaymap: '例如: domain:"test.com" AND region:"北京"',
+    shodan: '例如: hostname:"test.com" country:"US"',
+    zoomeye: '例如: domain="test.com" && after="2024-01-01"',
+    censys: '例如: dns.names: "test.com" AND last_updated_at: [2024-01-01 TO *]',
   };
 
   // 处理平台切换
@@ -81,6 +259,7 @@ const ExportData: React.FC = () => {
       pageSize,
       timeRange,
       format: exportFormat,
+      budget: budget ?? undefined,
     };
 
     // 如果是自定义时间范围，添加开始和结束日期
@@ -91,6 +270,8 @@ const ExportData: React.FC = () => {
 
     // 重置并打开进度弹窗
     exportProgress.startTask(taskId, `开始导出: 类型=${exportType}, 平台=${platform}, 页数=${pages}`);
+    setRunningTaskId(taskId);
+    setAllExport(null);
     const exportPerfToken = startPerf('asset-export', {
       source: 'export-center',
       exportType,
@@ -113,53 +294,14 @@ const ExportData: React.FC = () => {
           status: 'success',
         });
       } else {
-        // "all" 类型：按顺序调用各平台导出，确保进度弹窗一致
-        exportProgress.setStatusText('正在准备导出所有平台...');
-        const platforms = ['hunter', 'fofa', 'quake', 'daydaymap'];
-        // 因为没有指定源平台，我们假设当前选中的标签页就是源平台
-        const sourcePlatform = platform;
-
-        for (let i = 0; i < platforms.length; i++) {
-          const targetPlatform = platforms[i];
-          let convertedQuery = normalizedQuery;
-
-          // 尝试将语法适应目标平台
-          if (sourcePlatform !== targetPlatform) {
-            try {
-              const convertRes = await invoke<{ platform: string, query: string }[]>('convert_query_to_all', {
-                query: normalizedQuery,
-                fromPlatform: sourcePlatform,
-              });
-              const target = convertRes.find(r => r.platform === targetPlatform);
-              if (target) {
-                convertedQuery = target.query;
-              }
-            } catch (e) {
-              exportProgress.addLog(`⚠ ${targetPlatform} 查询语法转换提示: ${e}`, 'warning');
-              // 继续往下走，也许语法本就兼容
-            }
-          }
-
-          exportProgress.setStatusText(`正在导出 ${targetPlatform} 平台数据 (${i + 1}/${platforms.length})...`);
-          try {
-            const filePath = await invoke<string>('export_results_with_progress', {
-              ...params,
-              platform: targetPlatform,
-              query: convertedQuery,
-              taskId: `${taskId}_${targetPlatform}`,
-            });
-            exportProgress.addLog(`✓ ${targetPlatform} 导出成功: ${filePath}`, 'success');
-          } catch (err: any) {
-            exportProgress.addLog(`✗ ${targetPlatform} 导出失败: ${err}`, 'error');
-          }
+        // "all" 类型：后端识别查询语法、并发查询各平台并合并重复资产
+        const result = await invoke<AllPlatformsExport>('export_all_platforms', params);
+        setAllExport(result);
+        if (result.filePath) {
+          message.success(`导出成功: ${result.filePath}`);
+        } else {
+          message.warning('所有平台均未返回结果');
         }
-
-        exportProgress.setPercent(100);
-        exportProgress.setStatus('success');
-        exportProgress.setStatusText('所有平台导出任务结束！');
-        exportProgress.addLog('✓ 全部平台导出完毕', 'success');
-        exportProgress.finishTask();
-        message.success('导出成功，结果请查看日志路径');
         endPerf(exportPerfToken, {
           source: 'export-center',
           exportType,
@@ -170,10 +312,15 @@ const ExportData: React.FC = () => {
     } catch (error: any) {
       console.error('导出出错:', error);
       const errMsg = typeof error === 'string' ? error : (error?.message || '未知错误');
-      message.error(`导出失败: ${errMsg}`);
-      exportProgress.setStatus('error');
-      exportProgress.setStatusText(`导出失败: ${errMsg}`);
-      exportProgress.addLog(`✗ 导出失败: ${errMsg}`, 'error');
+      if (errMsg === '导出已取消') {
+        // 取消状态已由进度事件推送
+        message.info(errMsg);
+      } else {
+        message.error(`导出失败: ${errMsg}`);
+        exportProgress.setStatus('error');
+        exportProgress.setStatusText(`导出失败: ${errMsg}`);
+        exportProgress.addLog(`✗ 导出失败: ${errMsg}`, 'error');
+      }
       exportProgress.finishTask();
       endPerf(exportPerfToken, {
         source: 'export-center',
@@ -184,15 +331,86 @@ const ExportData: React.FC = () => {
       });
     } finally {
       setLoading(false);
+      setRunningTaskId(null);
+      loadCheckpoints();
+    }
+  };
+
+  // 取消正在运行的导出；多平台导出时传入平台只取消该平台
+  const handleCancel = async (targetPlatform?: string) => {
+    if (!runningTaskId) {
+      return;
+    }
+    const taskId = targetPlatform ? `${runningTaskId}_${targetPlatform}` : runningTaskId;
+    try {
+      await invoke<number>('cancel_export', { taskId });
+      exportProgress.addLog(targetPlatform ? `正在取消 ${targetPlatform} ...` : '正在取消导出...', 'warning');
+    } catch (error) {
+      message.error(`取消失败: ${error}`);
     }
   };
 
+  // 多平台导出：运行中显示各平台状态，结束后显示各平台结果与合并统计
+  const renderAllPlatforms = () => {
+    if (allExport) {
+      const { summary } = allExport;
+      return (
+        <Space direction="vertical" size={4} style={{ width: '100%', marginBottom: 12 }}>
+          {allExport.platforms.map((outcome) => (
+            <Space key={outcome.platform} wrap>
+              <Tag color="blue">{outcome.platform}</Tag>
+              <Tag color={outcomeTag[outcome.status].color}>{outcomeTag[outcome.status].label}</Tag>
+              {outcome.status !== 'skipped' && (
+                <span>
+                  获取 {outcome.returned}{outcome.total != null ? `/${outcome.total}` : ''} 条
+                </span>
+              )}
+              {outcome.error && <Typography.Text type="secondary">{outcome.error}</Typography.Text>}
+            </Space>
+          ))}
+          <Divider style={{ margin: '8px 0' }} />
+          <span>
+            合并前 {summary.inputCount} 条，合并后 {summary.mergedCount} 条资产
+          </span>
+          {summary.engines.map((engine) => (
+            <span key={engine.platform} style={{ color: 'var(--text-secondary)', fontSize: 12 }}>
+              {engine.platform}: 返回 {engine.returned} 条，涉及 {engine.assets} 个资产，独有 {engine.unique} 个
+            </span>
+          ))}
+        </Space>
+      );
+    }
+
+    const running = Object.entries(exportProgress.platforms);
+    if (!runningTaskId || running.length === 0) {
+      return null;
+    }
+    return (
+      <Space direction="vertical" size={4} style={{ width: '100%', marginBottom: 12 }}>
+        {running.map(([name, status]) => (
+          <Space key={name}>
+            <Tag color="blue">{name}</Tag>
+            <Tag color={runningTag[status].color}>{runningTag[status].label}</Tag>
+            {status === 'running' && (
+              <Button type="link" size="small" danger onClick={() => handleCancel(name)}>
+                取消
+              </Button>
+            )}
+          </Space>
+        ))}
+      </Space>
+    );
+  };
+
   // 创建平台选项卡
   const tabItems = [
     { key: 'hunter', label: 'Hunter' },
     { key: 'fofa', label: 'FOFA' },
     { key: 'quake', label: 'Quake' },
-    { key: 'daydaymap', label: 'DayDayMap' }
+    { key: 'daydaymap', label: 'DayDayMap' },
+    { key: 'shodan', label: 'Shodan' },
+    { key: 'zoomeye', label: 'ZoomEye' },
+    { key: 'censys', label: 'Censys' }
   ];
 
   // 创建每页条数选项
@@ -258,18 +476,28 @@ const ExportData: React.FC = () => {
               支持自定义 1-100 页
             </span>
 
+            <Space>
+              <span>额度预算:</span>
+              <InputNumber
+                value={budget}
+                min={1}
+                placeholder="不限"
+                onChange={(value) => setBudget(value)}
+                style={{ width: 120 }}
+              />
+              <span style={{ color: 'var(--text-secondary)', fontSize: 12 }}>
+                按平台计费单位计，下一页会超出预算时停止获取{exportType === 'all' ? '（每个平台分别计算）' : ''}
+              </span>
+            </Space>
+
             <Space>
               <span>导出格式:</span>
-              <Radio.Group
+              <Select
                 value={exportFormat}
-                onChange={(e) => setExportFormat(e.target.value)}
-                optionType="button"
-                buttonStyle="solid"
-                size="small"
-              >
-                <Radio.Button value="csv">CSV</Radio.Button>
-                <Radio.Button value="json">JSON</Radio.Button>
-              </Radio.Group>
+                onChange={(value) => setExportFormat(value)}
+                style={{ width: 240 }}
+                options={exportFormatOptions}
+              />
             </Space>
           </Space>
         </Form.Item>
@@ -299,34 +527,121 @@ const ExportData: React.FC = () => {
         {exportType === 'all' && (
           <Alert
             message="注意"
-            description="导出全部平台资产时，会自动根据不同平台调整查询语法。如果某些平台不支持特定语法，将自动跳过相关条件。"
+            description="导出全部平台资产时，会自动识别查询语法并转换到各平台后并发查询，无法转换的平台将被跳过；各平台的结果按 IP、端口与协议合并去重后写入同一个文件。"
             type="info"
             showIcon
             style={{ marginBottom: 16 }}
           />
         )}
 
+        {estimate && (
+          <Alert
+            type={estimate.sufficient === false ? 'warning' : 'info'}
+            showIcon
+            style={{ marginBottom: 16 }}
+            message={
+              `预计消耗 ${estimate.estimated.toLocaleString()} ${estimate.unit}` +
+              (estimate.billing === 'perRequest' ? '（按请求次数计费）' : '（按返回条数计费）') +
+              (estimate.available != null
+                ? `，可用 ${estimate.available.toLocaleString()} ${estimate.unit}`
+                : '，部分密钥未返回剩余额度')
+            }
+            description={
+              <Space direction="vertical" size={0}>
+                {estimate.sufficient === false && <span>剩余额度不足，导出会在额度耗尽时中断，建议设置额度预算</span>}
+                {estimate.keys.map((key) => (
+                  <span key={key.keyHint}>
+                    <Tag color={key.valid ? 'green' : 'red'}>{key.keyHint}</Tag>
+                    {key.valid
+                      ? (key.remaining != null ? `剩余 ${key.remaining.toLocaleString()} ${estimate.unit}` : '未返回剩余额度')
+                      : (key.message || '密钥无效')}
+                  </span>
+                ))}
+              </Space>
+            }
+          />
+        )}
+
         <Form.Item>
-          <Button
-            type="primary"
-            icon={<DownloadOutlined />}
-            onClick={handleExport}
-            loading={loading}
-            size="large"
-          >
-            开始导出
-          </Button>
+          <Space>
+            <Button
+              type="primary"
+              icon={<DownloadOutlined />}
+              onClick={handleExport}
+              loading={loading}
+              size="large"
+            >
+              开始导出
+            </Button>
+            {exportType !== 'all' && (
+              <Button
+                icon={<CalculatorOutlined />}
+                onClick={handleEstimate}
+                loading={estimating}
+                size="large"
+              >
+                预估消耗
+              </Button>
+            )}
+          </Space>
         </Form.Item>
       </Form>
 
+      {checkpoints.length > 0 && (
+        <>
+          <Divider />
+          <Card title="未完成的导出" size="small" className="glass-effect" bordered={false}>
+            <List
+              dataSource={checkpoints}
+              renderItem={(checkpoint) => (
+                <List.Item
+                  actions={[
+                    <Button
+                      key="resume"
+                      type="link"
+                      icon={<RedoOutlined />}
+                      loading={resumingTaskId === checkpoint.taskId}
+                      disabled={loading || (resumingTaskId !== null && resumingTaskId !== checkpoint.taskId)}
+                      onClick={() => handleResume(checkpoint)}
+                    >
+                      继续导出
+                    </Button>,
+                  ]}
+                >
+                  <List.Item.Meta
+                    title={
+                      <Space>
+                        <Tag color="blue">{checkpoint.platform}</Tag>
+                        <span style={{ fontFamily: 'monospace' }}>{checkpoint.query}</span>
+                      </Space>
+                    }
+                    description={
+                      `已完成 ${checkpoint.lastPage}/${checkpoint.pages} 页，已获取 ${checkpoint.fetched}` +
+                      (checkpoint.total != null ? `/${checkpoint.total}` : '') +
+                      ` 条` +
+                      (checkpoint.keyHint ? `，密钥 ${checkpoint.keyHint}` : '') +
+                      (checkpoint.budget != null ? `，额度 ${checkpoint.spent}/${checkpoint.budget}` : '') +
+                      `，更新于 ${dayjs(checkpoint.updatedAt).format('YYYY-MM-DD HH:mm:ss')}`
+                    }
+                  />
+                </List.Item>
+              )}
+            />
+          </Card>
+        </>
+      )}
+
       <Divider />
 
       <Card title="导出说明" size="small" className="glass-effect" bordered={false}>
         <ul>
           <li><strong>导出当前查询结果</strong>：仅导出当前查询条件下的资产数据</li>
           <li><strong>导出本平台全部资产</strong>：自动计算总页数，导出当前平台下符合条件的所有资产</li>
-          <li><strong>导出全部平台资产</strong>：将查询语句适配到所有平台，并导出所有平台的资产</li>
+          <li><strong>导出全部平台资产</strong>：将查询语句适配到所有平台并发查询，合并重复资产后导出到一个文件，可单独取消某个平台</li>
           <li><strong>时间范围</strong>：限制导出资产的时间范围，不同平台的时间语法会自动适配</li>
+          <li><strong>额度预算</strong>：导出前可预估消耗并查看各密钥剩余额度；设置预算后，下一页会超出预算时停止获取并导出已取得的数据</li>
+          <li><strong>导出格式</strong>：XLSX 按平台分工作表；NDJSON 每行一条记录，便于 jq / ELK 处理；Markdown 与 HTML 报告附带端口、国家、Server 分布统计</li>
+          <li><strong>扫描目标</strong>：去重后的 ip:port、URL 列表，nmap -iL 主机列表（行尾注释为端口）和 masscan 配置，可直接交给扫描工具</li>
         </ul>
       </Card>
 
@@ -339,6 +654,8 @@ const ExportData: React.FC = () => {
         statusText={exportProgress.statusText}
         logs={exportProgress.logs}
         summary={exportProgress.summary}
+        extra={exportType === 'all' ? renderAllPlatforms() : null}
+        onCancel={runningTaskId ? () => handleCancel() : undefined}
         onClose={() => exportProgress.setModalOpen(false)}
       />
     </Card>
@@ -13,6 +13,7 @@ import {
   message,
   Tooltip,
   Modal,
+  Tabs,
   Typography
 } from 'antd';
 import {
@@ -23,7 +24,8 @@ import {
   ReloadOutlined,
   ClearOutlined,
   EyeOutlined,
-  CopyOutlined
+  CopyOutlined,
+  DiffOutlined
 } from '@ant-design/icons';
 import { invoke } from '@tauri-apps/api/core';
 import dayjs from 'dayjs';
@@ -41,6 +43,33 @@ interface QueryHistory {
   timestamp: string;
   success: boolean;
   error_message?: string;
+  snapshot_id?: number;
+}
+
+interface DiffAsset {
+  ip: string;
+  port?: number;
+  host: string;
+  url: string;
+  title: string;
+  server: string;
+}
+
+interface SnapshotInfo {
+  id: number;
+  platform: string;
+  query: string;
+  takenAt: string;
+  assets: number;
+}
+
+interface AssetDiff {
+  previous?: SnapshotInfo;
+  current: SnapshotInfo;
+  added: DiffAsset[];
+  removed: DiffAsset[];
+  changed: { asset: DiffAsset; changes: { field: string; before: string; after: string }[] }[];
+  ports: { host: string; opened: number[]; closed: number[] }[];
 }
 
 interface HistoryRecordsProps {
@@ -64,6 +93,10 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
   const [exportPageMode, setExportPageMode] = useState<'recommended' | 'exact' | 'manual'>('recommended');
   const [exportFormat, setExportFormat] = useState<'csv' | 'json'>('csv');
   const [exportSubmitting, setExportSubmitting] = useState<boolean>(false);
+  // 设置弹窗用于导出资产或比对变化
+  const [configMode, setConfigMode] = useState<'export' | 'diff'>('export');
+  const [diffResult, setDiffResult] = useState<AssetDiff | null>(null);
+  const [diffModalVisible, setDiffModalVisible] = useState<boolean>(false);
   const exportProgress = useExportProgress();
 
   useEffect(() => {
@@ -167,8 +200,9 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
     };
   };
 
-  const openExportConfig = (record: QueryHistory) => {
+  const openExportConfig = (record: QueryHistory, mode: 'export' | 'diff' = 'export') => {
     const defaultPageSize = 100;
+    setConfigMode(mode);
     const { exactPages, recommendedPages } = calculateExportPlan(record.results_count, defaultPageSize);
     setExportTargetRecord(record);
     setExportPageSize(defaultPageSize);
@@ -245,6 +279,63 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
     }
   };
 
+  // 重新运行查询并与上一次快照比对
+  const diffRecordAssets = async (record: QueryHistory, pages: number, pageSize: number) => {
+    try {
+      const taskId = `diff_${record.platform}_${Date.now()}`;
+      const pagesToFetch = Math.max(1, Math.min(100, pages));
+
+      exportProgress.startTask(
+        taskId,
+        `开始比对变化: 平台=${record.platform}, 页数=${pagesToFetch}`
+      );
+
+      const result = await invoke<AssetDiff>('run_query_diff', {
+        taskId,
+        historyId: record.id,
+        pages: pagesToFetch,
+        pageSize,
+      });
+      exportProgress.setModalOpen(false);
+      setDiffResult(result);
+      setDiffModalVisible(true);
+      loadHistory();
+    } catch (error) {
+      console.error('比对变化失败:', error);
+      const errMsg = typeof error === 'string' ? error : '未知错误';
+      message.error({ content: `比对失败: ${errMsg}`, duration: 3 });
+      exportProgress.setStatus('error');
+      exportProgress.setStatusText(`比对失败: ${errMsg}`);
+      exportProgress.addLog(`比对失败: ${errMsg}`, 'error');
+      exportProgress.finishTask();
+    }
+  };
+
+  // 查看历史记录保存的快照与上一次快照的差异
+  const viewRecordDiff = async (record: QueryHistory) => {
+    if (record.snapshot_id == null) return;
+    try {
+      const result = await invoke<AssetDiff>('get_query_diff', { snapshotId: record.snapshot_id });
+      setDiffResult(result);
+      setDiffModalVisible(true);
+    } catch (error) {
+      message.error(`加载变化失败: ${error}`);
+    }
+  };
+
+  const exportDiff = async (format: 'csv' | 'json') => {
+    if (!diffResult) return;
+    try {
+      const filePath = await invoke<string>('export_query_diff', {
+        snapshotId: diffResult.current.id,
+        format,
+      });
+      message.success(`导出成功: ${filePath}`);
+    } catch (error) {
+      message.error(`导出失败: ${error}`);
+    }
+  };
+
   const confirmExportRecordAssets = async () => {
     if (!exportTargetRecord) {
       return;
@@ -253,7 +344,11 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
     setExportSubmitting(true);
     setExportConfigVisible(false);
     try {
-      await exportRecordAssets(exportTargetRecord, exportPages, exportPageSize);
+      if (configMode === 'diff') {
+        await diffRecordAssets(exportTargetRecord, exportPages, exportPageSize);
+      } else {
+        await exportRecordAssets(exportTargetRecord, exportPages, exportPageSize);
+      }
     } finally {
       setExportSubmitting(false);
     }
@@ -301,7 +396,10 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
     hunter: 'orange',
     fofa: 'blue',
     quake: 'purple',
-    daydaymap: 'cyan'
+    daydaymap: 'cyan',
+    shodan: 'red',
+    zoomeye: 'geekblue',
+    censys: 'volcano'
   };
 
   // 表格列定义
@@ -369,7 +467,7 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
     {
       title: '操作',
       key: 'action',
-      width: 220,
+      width: 280,
       render: (_: any, record: QueryHistory) => (
         <Space size="small">
           <Tooltip title="查看详情">
@@ -397,6 +495,17 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
               disabled={!record.success || record.results_count === 0}
             />
           </Tooltip>
+          <Tooltip title={record.snapshot_id != null ? '查看本次变化' : '重新运行并比对变化'}>
+            <Button
+              type="link"
+              size="small"
+              icon={<DiffOutlined />}
+              onClick={() =>
+                record.snapshot_id != null ? viewRecordDiff(record) : openExportConfig(record, 'diff')
+              }
+              disabled={!record.success}
+            />
+          </Tooltip>
           <Popconfirm
             title="确定要删除这条记录吗？"
             onConfirm={() => deleteRecord(record.id)}
@@ -417,6 +526,19 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
     },
   ];
 
+  // 新增 / 消失资产的表格列
+  const diffAssetColumns = [
+    {
+      title: '资产',
+      key: 'asset',
+      render: (_: any, asset: DiffAsset) =>
+        `${asset.ip || asset.host}${asset.port ? `:${asset.port}` : ''}`,
+    },
+    { title: 'Host', dataIndex: 'host', key: 'host', ellipsis: true },
+    { title: '标题', dataIndex: 'title', key: 'title', ellipsis: true },
+    { title: 'Server', dataIndex: 'server', key: 'server', width: 140, ellipsis: true },
+  ];
+
   const estimatedExportCount = exportTargetRecord
     ? Math.min(exportTargetRecord.results_count, exportPages * exportPageSize)
     : 0;
@@ -484,6 +606,9 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
                 { label: 'FOFA', value: 'fofa' },
                 { label: 'Quake', value: 'quake' },
                 { label: 'DayDayMap', value: 'daydaymap' },
+                { label: 'Shodan', value: 'shodan' },
+                { label: 'ZoomEye', value: 'zoomeye' },
+                { label: 'Censys', value: 'censys' },
               ]}
             />
             <Input
@@ -552,12 +677,12 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
       </Modal>
 
       <Modal
-        title="导出资产设置"
+        title={configMode === 'diff' ? '比对变化设置' : '导出资产设置'}
         open={exportConfigVisible}
         onCancel={() => !exportSubmitting && setExportConfigVisible(false)}
         onOk={confirmExportRecordAssets}
         confirmLoading={exportSubmitting}
-        okText="开始导出"
+        okText={configMode === 'diff' ? '开始比对' : '开始导出'}
         cancelText="取消"
       >
         {exportTargetRecord && (
@@ -597,7 +722,16 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
               </div>
             </div>
 
-            <div>
+            {configMode === 'diff' && (
+              <Alert
+                type="warning"
+                showIcon
+                message="重新运行会消耗额度，结果保存为快照后与上一次快照比对"
+                description="请与上一次使用相同的页数和每页条数，否则未取到的资产会被记为消失。"
+              />
+            )}
+
+            {configMode === 'export' && <div>
               <Text strong>导出格式</Text>
               <div style={{ marginTop: 8 }}>
                 <Select
@@ -610,7 +744,7 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
                   ]}
                 />
               </div>
-            </div>
+            </div>}
 
             <div>
               <Text strong>导出页数</Text>
@@ -675,11 +809,128 @@ const HistoryRecords: React.FC<HistoryRecordsProps> = ({ active = true }) => {
         )}
       </Modal>
 
+      {/* 变化比对结果 */}
+      <Modal
+        title="资产变化"
+        open={diffModalVisible}
+        onCancel={() => setDiffModalVisible(false)}
+        width={900}
+        footer={[
+          <Button key="csv" icon={<ExportOutlined />} onClick={() => exportDiff('csv')} disabled={!diffResult?.previous}>
+            导出 CSV
+          </Button>,
+          <Button key="json" icon={<ExportOutlined />} onClick={() => exportDiff('json')} disabled={!diffResult?.previous}>
+            导出 JSON
+          </Button>,
+          <Button key="close" type="primary" onClick={() => setDiffModalVisible(false)}>
+            关闭
+          </Button>,
+        ]}
+      >
+        {diffResult && (
+          <Space direction="vertical" size={16} style={{ width: '100%' }}>
+            <Alert
+              type={diffResult.previous ? 'info' : 'success'}
+              showIcon
+              message={
+                diffResult.previous
+                  ? `${dayjs(diffResult.previous.takenAt).format('YYYY-MM-DD HH:mm')}（${diffResult.previous.assets} 条） → ${dayjs(diffResult.current.takenAt).format('YYYY-MM-DD HH:mm')}（${diffResult.current.assets} 条）`
+                  : `已建立基线快照，共 ${diffResult.current.assets} 条资产，下次运行时将与本次比对`
+              }
+            />
+            {diffResult.previous && (
+              <Tabs
+                items={[
+                  {
+                    key: 'added',
+                    label: `新增 (${diffResult.added.length})`,
+                    children: (
+                      <Table
+                        size="small"
+                        rowKey={(asset) => `${asset.ip}:${asset.port}:${asset.host}`}
+                        dataSource={diffResult.added}
+                        columns={diffAssetColumns}
+                        pagination={{ pageSize: 10 }}
+                      />
+                    ),
+                  },
+                  {
+                    key: 'removed',
+                    label: `消失 (${diffResult.removed.length})`,
+                    children: (
+                      <Table
+                        size="small"
+                        rowKey={(asset) => `${asset.ip}:${asset.port}:${asset.host}`}
+                        dataSource={diffResult.removed}
+                        columns={diffAssetColumns}
+                        pagination={{ pageSize: 10 }}
+                      />
+                    ),
+                  },
+                  {
+                    key: 'changed',
+                    label: `属性变化 (${diffResult.changed.length})`,
+                    children: (
+                      <Table
+                        size="small"
+                        rowKey={(row) => `${row.asset.ip}:${row.asset.port}:${row.asset.host}:${row.field}`}
+                        dataSource={diffResult.changed.flatMap((change) =>
+                          change.changes.map((field) => ({ asset: change.asset, ...field }))
+                        )}
+                        columns={[
+                          {
+                            title: '资产',
+                            key: 'asset',
+                            render: (_: any, row: { asset: DiffAsset }) =>
+                              `${row.asset.ip || row.asset.host}${row.asset.port ? `:${row.asset.port}` : ''}`,
+                          },
+                          { title: '属性', dataIndex: 'field', key: 'field', width: 120 },
+                          { title: '变更前', dataIndex: 'before', key: 'before', ellipsis: true },
+                          { title: '变更后', dataIndex: 'after', key: 'after', ellipsis: true },
+                        ]}
+                        pagination={{ pageSize: 10 }}
+                      />
+                    ),
+                  },
+                  {
+                    key: 'ports',
+                    label: `端口变化 (${diffResult.ports.length})`,
+                    children: (
+                      <Table
+                        size="small"
+                        rowKey="host"
+                        dataSource={diffResult.ports}
+                        columns={[
+                          { title: '主机', dataIndex: 'host', key: 'host' },
+                          {
+                            title: '新开放',
+                            dataIndex: 'opened',
+                            key: 'opened',
+                            render: (ports: number[]) => ports.map((port) => <Tag color="green" key={port}>{port}</Tag>),
+                          },
+                          {
+                            title: '已关闭',
+                            dataIndex: 'closed',
+                            key: 'closed',
+                            render: (ports: number[]) => ports.map((port) => <Tag color="red" key={port}>{port}</Tag>),
+                          },
+                        ]}
+                        pagination={{ pageSize: 10 }}
+                      />
+                    ),
+                  },
+                ]}
+              />
+            )}
+          </Space>
+        )}
+      </Modal>
+
       {/* 导出进度弹窗 */}
       <ProgressModal
         open={exportProgress.modalOpen}
         onClose={() => exportProgress.setModalOpen(false)}
-        title="导出资产"
+        title={configMode === 'diff' ? '比对变化' : '导出资产'}
         status={exportProgress.status}
         percent={exportProgress.percent}
         statusText={exportProgress.statusText}
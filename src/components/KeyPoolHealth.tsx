@@ -0,0 +1,181 @@
+import React, { useState, useEffect, useCallback } from 'react';
+import { Card, Table, Tag, Button, Statistic, Row, Col, Tooltip, InputNumber, Select, message } from 'antd';
+import { ReloadOutlined } from '@ant-design/icons';
+import { invoke } from '@tauri-apps/api/core';
+
+// 单个 key 的健康状况（对应后端 KeyHealth）
+interface KeyHealth {
+  keyHint: string;
+  isExhausted: boolean;
+  exhaustedAt?: string;
+  lastUsedAt?: string;
+  remaining?: number;
+  checkedAt?: string;
+  successCount: number;
+  failureCount: number;
+  lastError?: string;
+  lastErrorAt?: string;
+  predictedExhaustion?: string;
+  priority: number;
+  tags: string[];
+}
+
+// 平台 key 池健康状况（对应后端 PoolHealth）
+interface PoolHealth {
+  platform: string;
+  total: number;
+  available: number;
+  exhausted: number;
+  remaining?: number;
+  keys: KeyHealth[];
+}
+
+const formatTime = (value?: string) => (value ? new Date(value).toLocaleString() : '-');
+
+const KeyPoolHealth: React.FC<{ platform: string }> = ({ platform }) => {
+  const [pool, setPool] = useState<PoolHealth | null>(null);
+  const [loading, setLoading] = useState(false);
+  const [refreshing, setRefreshing] = useState(false);
+
+  const loadHealth = useCallback(async () => {
+    setLoading(true);
+    try {
+      const pools = await invoke<PoolHealth[]>('get_key_pool_health');
+      setPool(pools.find(p => p.platform === platform) ?? null);
+    } catch (error) {
+      console.error('获取密钥池状态出错:', error);
+    } finally {
+      setLoading(false);
+    }
+  }, [platform]);
+
+  useEffect(() => {
+    loadHealth();
+  }, [loadHealth]);
+
+  // 立即重新验证该平台全部 key 并刷新额度
+  const refreshPool = async () => {
+    setRefreshing(true);
+    try {
+      const pools = await invoke<PoolHealth[]>('refresh_key_pool', { platform });
+      setPool(pools[0] ?? null);
+      message.success('额度已刷新');
+    } catch (error) {
+      message.error(`刷新额度失败: ${error}`);
+    } finally {
+      setRefreshing(false);
+    }
+  };
+
+  // 修改 key 的轮询权重与标签，index 为 key 在平台配置中的位置
+  const saveKeyOptions = async (index: number, priority: number, tags: string[]) => {
+    try {
+      await invoke('set_key_options', { platform, index, priority, tags });
+      await loadHealth();
+    } catch (error) {
+      message.error(`保存失败: ${error}`);
+    }
+  };
+
+  const columns = [
+    { title: 'Key', dataIndex: 'keyHint', key: 'keyHint' },
+    {
+      title: '状态',
+      key: 'status',
+      render: (_: unknown, record: KeyHealth) => record.isExhausted
+        ? <Tooltip title={`耗尽于 ${formatTime(record.exhaustedAt)}`}><Tag color="error">已耗尽</Tag></Tooltip>
+        : <Tag color="success">可用</Tag>,
+    },
+    {
+      title: '剩余额度',
+      key: 'remaining',
+      render: (_: unknown, record: KeyHealth) => (
+        <Tooltip title={`验证于 ${formatTime(record.checkedAt)}`}>
+          {record.remaining ?? '未知'}
+        </Tooltip>
+      ),
+    },
+    {
+      title: '成功 / 失败',
+      key: 'counts',
+      render: (_: unknown, record: KeyHealth) => `${record.successCount} / ${record.failureCount}`,
+    },
+    {
+      title: '最近错误',
+      key: 'lastError',
+      ellipsis: true,
+      render: (_: unknown, record: KeyHealth) => record.lastError
+        ? <Tooltip title={formatTime(record.lastErrorAt)}>{record.lastError}</Tooltip>
+        : '-',
+    },
+    {
+      title: '预计耗尽',
+      key: 'predictedExhaustion',
+      render: (_: unknown, record: KeyHealth) => formatTime(record.predictedExhaustion),
+    },
+    {
+      title: '最后使用',
+      key: 'lastUsedAt',
+      render: (_: unknown, record: KeyHealth) => formatTime(record.lastUsedAt),
+    },
+    {
+      title: '权重',
+      key: 'priority',
+      render: (_: unknown, record: KeyHealth, index: number) => (
+        <InputNumber
+          min={1}
+          max={100}
+          precision={0}
+          size="small"
+          value={record.priority}
+          onChange={value => value && saveKeyOptions(index, value, record.tags)}
+        />
+      ),
+    },
+    {
+      title: '标签',
+      key: 'tags',
+      render: (_: unknown, record: KeyHealth, index: number) => (
+        <Select
+          mode="tags"
+          size="small"
+          style={{ minWidth: 120 }}
+          value={record.tags}
+          onChange={(tags: string[]) => saveKeyOptions(index, record.priority, tags)}
+        />
+      ),
+    },
+  ];
+
+  return (
+    <Card
+      title="密钥池状态"
+      size="small"
+      className="glass-effect"
+      bordered={false}
+      style={{ marginTop: 16 }}
+      extra={
+        <Button icon={<ReloadOutlined />} onClick={refreshPool} loading={refreshing}>
+          刷新额度
+        </Button>
+      }
+    >
+      <Row gutter={16} style={{ marginBottom: 16 }}>
+        <Col span={6}><Statistic title="总数" value={pool?.total ?? 0} /></Col>
+        <Col span={6}><Statistic title="可用" value={pool?.available ?? 0} /></Col>
+        <Col span={6}><Statistic title="已耗尽" value={pool?.exhausted ?? 0} /></Col>
+        <Col span={6}><Statistic title="剩余额度合计" value={pool?.remaining ?? '未知'} /></Col>
+      </Row>
+      <Table
+        columns={columns}
+        dataSource={pool?.keys ?? []}
+        rowKey="keyHint"
+        loading={loading}
+        pagination={false}
+        size="small"
+      />
+    </Card>
+  );
+};
+
+export default KeyPoolHealth;
@@ -0,0 +1,316 @@
+import React, { useState, useEffect, useCallback } from 'react';
+import {
+  Card,
+  Table,
+  Button,
+  Input,
+  InputNumber,
+  Select,
+  Space,
+  Tag,
+  Popconfirm,
+  message,
+  Tooltip,
+  Typography
+} from 'antd';
+import {
+  HddOutlined,
+  SearchOutlined,
+  ReloadOutlined,
+  DeleteOutlined
+} from '@ant-design/icons';
+import { invoke } from '@tauri-apps/api/core';
+import dayjs from 'dayjs';
+
+const { Title, Text } = Typography;
+
+interface LocalAsset {
+  ip: string;
+  port?: number;
+  protocol: string;
+  host: string;
+  url: string;
+  title: string;
+  server: string;
+  country: string;
+}
+
+interface StoredAsset {
+  id: number;
+  platform: string;
+  query: string;
+  firstSeen: string;
+  lastSeen: string;
+  asset: LocalAsset;
+}
+
+interface LocalAssetPage {
+  total: number;
+  results: StoredAsset[];
+}
+
+interface LocalQuerySummary {
+  platform: string;
+  query: string;
+  assets: number;
+  firstSeen: string;
+  lastSeen: string;
+}
+
+interface LocalAssetsProps {
+  active?: boolean;
+}
+
+const platformColors: Record<string, string> = {
+  hunter: 'orange',
+  fofa: 'blue',
+  quake: 'purple',
+  daydaymap: 'cyan',
+  shodan: 'red',
+  zoomeye: 'geekblue',
+  censys: 'volcano'
+};
+
+const formatTime = (value: string) => dayjs(value).format('YYYY-MM-DD HH:mm:ss');
+
+// 查询选项的值，平台与查询语句之间用换行分隔
+const queryKey = (item: { platform: string; query: string }) => `${item.platform}\n${item.query}`;
+
+const LocalAssets: React.FC<LocalAssetsProps> = ({ active = true }) => {
+  const [queries, setQueries] = useState<LocalQuerySummary[]>([]);
+  const [selectedQuery, setSelectedQuery] = useState<string>('all');
+  const [keyword, setKeyword] = useState<string>('');
+  const [port, setPort] = useState<number | null>(null);
+  const [data, setData] = useState<LocalAssetPage>({ total: 0, results: [] });
+  const [page, setPage] = useState<number>(1);
+  const [pageSize, setPageSize] = useState<number>(20);
+  const [loading, setLoading] = useState<boolean>(false);
+
+  // 加载本地库中保存过的查询
+  const loadQueries = useCallback(async () => {
+    try {
+      const result = await invoke<LocalQuerySummary[]>('list_local_asset_queries');
+      setQueries(result);
+    } catch (error) {
+      message.error(`加载本地查询失败: ${error}`);
+    }
+  }, []);
+
+  // 按筛选条件分页加载资产
+  const loadAssets = useCallback(async (targetPage: number, targetPageSize: number) => {
+    setLoading(true);
+    try {
+      const selected = queries.find((item) => queryKey(item) === selectedQuery);
+      const result = await invoke<LocalAssetPage>('query_local_assets', {
+        filter: {
+          platform: selected?.platform,
+          query: selected?.query,
+          keyword: keyword.trim() || undefined,
+          port: port ?? undefined,
+        },
+        page: targetPage,
+        pageSize: targetPageSize,
+      });
+      setData(result);
+      setPage(targetPage);
+      setPageSize(targetPageSize);
+    } catch (error) {
+      message.error(`查询本地资产失败: ${error}`);
+    } finally {
+      setLoading(false);
+    }
+  }, [queries, selectedQuery, keyword, port]);
+
+  useEffect(() => {
+    if (active) {
+      loadQueries();
+    }
+  }, [active, loadQueries]);
+
+  useEffect(() => {
+    if (active) {
+      loadAssets(1, pageSize);
+    }
+    // 筛选条件变化时回到第一页
+  }, [active, selectedQuery, port, queries]);
+
+  const deleteSelectedQuery = async () => {
+    const selected = queries.find((item) => queryKey(item) === selectedQuery);
+    if (!selected) return;
+    try {
+      const deleted = await invoke<number>('delete_local_asset_query', {
+        platform: selected.platform,
+        query: selected.query,
+      });
+      message.success(`已删除 ${deleted} 条本地资产`);
+      setSelectedQuery('all');
+      loadQueries();
+    } catch (error) {
+      message.error(`删除失败: ${error}`);
+    }
+  };
+
+  const columns = [
+    {
+      title: '资产',
+      key: 'asset',
+      render: (_: any, record: StoredAsset) => (
+        <Space direction="vertical" size={0}>
+          <Text copyable={{ text: record.asset.url || record.asset.host }}>
+            {record.asset.ip || record.asset.host}
+            {record.asset.port ? `:${record.asset.port}` : ''}
+          </Text>
+          {record.asset.host && record.asset.host !== record.asset.ip && (
+            <Text type="secondary" style={{ fontSize: 12 }}>{record.asset.host}</Text>
+          )}
+        </Space>
+      ),
+    },
+    {
+      title: '协议',
+      key: 'protocol',
+      width: 90,
+      render: (_: any, record: StoredAsset) => record.asset.protocol || '-',
+    },
+    {
+      title: '标题',
+      key: 'title',
+      ellipsis: { showTitle: false },
+      render: (_: any, record: StoredAsset) => (
+        <Tooltip title={record.asset.title} placement="topLeft">
+          <Text>{record.asset.title || '-'}</Text>
+        </Tooltip>
+      ),
+    },
+    {
+      title: 'Server',
+      key: 'server',
+      width: 140,
+      ellipsis: true,
+      render: (_: any, record: StoredAsset) => record.asset.server || '-',
+    },
+    {
+      title: '平台',
+      dataIndex: 'platform',
+      key: 'platform',
+      width: 100,
+      render: (platform: string) => (
+        <Tag color={platformColors[platform.toLowerCase()] || 'default'}>
+          {platform.toUpperCase()}
+        </Tag>
+      ),
+    },
+    {
+      title: '首次发现',
+      dataIndex: 'firstSeen',
+      key: 'firstSeen',
+      width: 170,
+      render: formatTime,
+    },
+    {
+      title: '最近命中',
+      dataIndex: 'lastSeen',
+      key: 'lastSeen',
+      width: 170,
+      render: formatTime,
+    },
+  ];
+
+  return (
+    <div className="local-assets">
+      <Card
+        className="glass-effect"
+        bordered={false}
+        title={
+          <Space>
+            <HddOutlined />
+            <Title level={4} style={{ margin: 0 }}>本地资产库</Title>
+          </Space>
+        }
+        extra={
+          <Space>
+            <Button
+              icon={<ReloadOutlined />}
+              onClick={() => { loadQueries(); loadAssets(page, pageSize); }}
+              loading={loading}
+            >
+              刷新
+            </Button>
+            <Popconfirm
+              title="确定要删除该查询保存的全部资产吗？"
+              onConfirm={deleteSelectedQuery}
+              okText="确定"
+              cancelText="取消"
+              disabled={selectedQuery === 'all'}
+            >
+              <Button icon={<DeleteOutlined />} danger disabled={selectedQuery === 'all'}>
+                删除查询
+              </Button>
+            </Popconfirm>
+          </Space>
+        }
+      >
+        {/* 筛选栏 */}
+        <Space style={{ marginBottom: 16, width: '100%', justifyContent: 'space-between' }}>
+          <Space wrap>
+            <Select
+              style={{ width: 320 }}
+              value={selectedQuery}
+              onChange={setSelectedQuery}
+              showSearch
+              optionFilterProp="label"
+              options={[
+                { label: '全部查询', value: 'all' },
+                ...queries.map((item) => ({
+                  label: `[${item.platform.toUpperCase()}] ${item.query}（${item.assets}）`,
+                  value: queryKey(item),
+                })),
+              ]}
+            />
+            <Input
+              placeholder="IP、Host、标题或 Server..."
+              prefix={<SearchOutlined />}
+              allowClear
+              style={{ width: 260 }}
+              value={keyword}
+              onChange={(e) => setKeyword(e.target.value)}
+              onPressEnter={() => loadAssets(1, pageSize)}
+            />
+            <InputNumber
+              placeholder="端口"
+              min={1}
+              max={65535}
+              value={port}
+              onChange={(value) => setPort(value)}
+            />
+            <Button type="primary" icon={<SearchOutlined />} onClick={() => loadAssets(1, pageSize)}>
+              查询
+            </Button>
+          </Space>
+          <Text type="secondary">
+            共 {data.total.toLocaleString()} 条资产
+          </Text>
+        </Space>
+
+        <Table
+          columns={columns}
+          dataSource={data.results}
+          rowKey="id"
+          loading={loading}
+          pagination={{
+            current: page,
+            pageSize,
+            total: data.total,
+            showSizeChanger: true,
+            pageSizeOptions: ['20', '50', '100', '200'],
+            showQuickJumper: true,
+            showTotal: (total) => `共 ${total} 条资产`,
+            onChange: (nextPage, nextPageSize) => loadAssets(nextPage, nextPageSize),
+          }}
+        />
+      </Card>
+    </div>
+  );
+};
+
+export default LocalAssets;
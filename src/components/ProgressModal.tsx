@@ -28,6 +28,8 @@ export interface ProgressModalProps {
   onClose: () => void;
   /** 额外的摘要信息，如总数、已完成数等 */
   summary?: { label: string; value: string | number }[];
+  /** 显示在状态文字与日志之间的附加内容，如多平台任务的各平台状态 */
+  extra?: React.ReactNode;
 }
 
 const statusIcon: Record<ProgressStatus, React.ReactNode> = {
@@ -71,6 +73,7 @@ const ProgressModal: React.FC<ProgressModalProps> = ({
   onCancel,
   onClose,
   summary,
+  extra,
 }) => {
   const isFinished = status === 'success' || status === 'error' || status === 'cancelled';
   const progressStatus = status === 'error' ? 'exception' : status === 'success' ? 'success' : 'active';
@@ -136,6 +139,8 @@ const ProgressModal: React.FC<ProgressModalProps> = ({
         {statusText}
       </Paragraph>
 
+      {extra}
+
       {/* 日志区域 */}
       {logs.length > 0 && (
         <div className="progress-log-container">
@@ -12,7 +12,8 @@ import {
   Col,
   Divider,
   Tooltip,
-  Tag
+  Tag,
+  Switch
 } from 'antd';
 import {
   SwapOutlined,
@@ -20,7 +21,8 @@ import {
   CheckCircleOutlined,
   CloseCircleOutlined,
   ReloadOutlined,
-  InfoCircleOutlined
+  InfoCircleOutlined,
+  WarningOutlined
 } from '@ant-design/icons';
 import { invoke } from '@tauri-apps/api/core';
 import { normalizeSmartPunctuation } from '../utils/textInput';
@@ -29,11 +31,42 @@ const { TextArea } = Input;
 const { Title, Text, Paragraph } = Typography;
 const { Option } = Select;
 
+// 有损转换提示：dropped 表示条件被丢弃，approximated 表示条件被近似转换
+interface ConversionWarning {
+  kind: 'dropped' | 'approximated';
+  predicate: string;
+  message: string;
+}
+
 interface ConversionResult {
   platform: string;
   query: string;
+  warnings: ConversionWarning[];
+  error?: string | null;
 }
 
+// 语法诊断（span 为查询语句中的字节偏移）
+interface Diagnostic {
+  span: { start: number; end: number };
+  severity: 'error' | 'warning';
+  message: string;
+  suggestion?: string | null;
+}
+
+// 平台识别得分（confidence 为 0~1 的可能性）
+interface DialectScore {
+  platform: string;
+  confidence: number;
+  score: number;
+  errors: number;
+}
+
+// 将字节偏移转换为字符偏移，便于截取原文
+const byteToCharOffset = (text: string, byteOffset: number) => {
+  const bytes = new TextEncoder().encode(text).slice(0, byteOffset);
+  return new TextDecoder().decode(bytes).length;
+};
+
 const QueryConverter: React.FC = () => {
   const [query, setQuery] = useState('');
   const [fromPlatform, setFromPlatform] = useState<string>('fofa');
@@ -43,15 +76,21 @@ const QueryConverter: React.FC = () => {
   const [validating, setValidating] = useState(false);
   const [converting, setConverting] = useState(false);
   const [conversionResults, setConversionResults] = useState<ConversionResult[]>([]);
-  const [validationResult, setValidationResult] = useState<{ valid: boolean; error?: string } | null>(null);
+  const [validationResult, setValidationResult] = useState<{ valid: boolean; error?: string; diagnostics?: Diagnostic[] } | null>(null);
   const [conversionMode, setConversionMode] = useState<'single' | 'all'>('all');
+  const [strictMode, setStrictMode] = useState(false);
+  const [detecting, setDetecting] = useState(false);
+  const [dialectScores, setDialectScores] = useState<DialectScore[]>([]);
 
   // 平台展示名称映射
   const platformNames: Record<string, string> = {
     fofa: 'FOFA',
     quake: 'QUAKE',
     hunter: 'Hunter',
-    daydaymap: 'DayDayMap'
+    daydaymap: 'DayDayMap',
+    shodan: 'Shodan',
+    zoomeye: 'ZoomEye',
+    censys: 'Censys'
   };
 
   // 平台颜色映射
@@ -59,7 +98,10 @@ const QueryConverter: React.FC = () => {
     fofa: 'blue',
     quake: 'purple',
     hunter: 'orange',
-    daydaymap: 'cyan'
+    daydaymap: 'cyan',
+    shodan: 'red',
+    zoomeye: 'geekblue',
+    censys: 'volcano'
   };
 
   // 加载支持的平台列表
@@ -82,6 +124,32 @@ const QueryConverter: React.FC = () => {
     }
   };
 
+  // 识别查询语句所属平台，并将最可能的平台设为源平台
+  const handleDetect = async () => {
+    const normalizedQuery = normalizeSmartPunctuation(query).trim();
+    if (!normalizedQuery) {
+      message.warning('请输入查询语句');
+      return;
+    }
+
+    try {
+      setDetecting(true);
+      const scores = await invoke<DialectScore[]>('detect_query_dialect', { query: normalizedQuery });
+      setDialectScores(scores);
+      if (scores.length > 0) {
+        const best = scores[0];
+        setFromPlatform(best.platform);
+        setValidationResult(null);
+        setConversionResults([]);
+        message.success(`识别为 ${platformNames[best.platform] || best.platform}（可能性 ${(best.confidence * 100).toFixed(0)}%）`);
+      }
+    } catch (error) {
+      message.error(`识别平台失败: ${error}`);
+    } finally {
+      setDetecting(false);
+    }
+  };
+
   // 验证查询语法
   const handleValidate = async () => {
     const normalizedQuery = normalizeSmartPunctuation(query).trim();
@@ -92,15 +160,20 @@ const QueryConverter: React.FC = () => {
 
     try {
       setValidating(true);
-      await invoke('validate_query_syntax', {
+      const diagnostics = await invoke<Diagnostic[]>('validate_query_syntax', {
         query: normalizedQuery,
         platform: fromPlatform
       });
       if (normalizedQuery !== query) {
         setQuery(normalizedQuery);
       }
-      setValidationResult({ valid: true });
-      message.success('查询语法验证通过！');
+      const errorCount = diagnostics.filter(d => d.severity === 'error').length;
+      setValidationResult({ valid: errorCount === 0, diagnostics });
+      if (errorCount === 0) {
+        message.success(diagnostics.length ? `查询语法验证通过，${diagnostics.length} 条建议` : '查询语法验证通过！');
+      } else {
+        message.error(`语法验证失败: 发现 ${errorCount} 个错误`);
+      }
     } catch (error) {
       setValidationResult({ valid: false, error: String(error) });
       message.error(`语法验证失败: ${error}`);
@@ -130,19 +203,31 @@ const QueryConverter: React.FC = () => {
         // 转换到所有平台
         const results = await invoke<ConversionResult[]>('convert_query_to_all', {
           query: normalizedQuery,
-          fromPlatform: fromPlatform
+          fromPlatform: fromPlatform,
+          strict: strictMode
         });
         setConversionResults(results);
-        message.success(`成功转换到 ${results.length} 个平台！`);
+        const failed = results.filter(r => r.error).length;
+        const lossy = results.filter(r => !r.error && r.warnings.length > 0).length;
+        if (failed > 0 || lossy > 0) {
+          message.warning(`转换完成：${failed} 个平台失败，${lossy} 个平台结果可能不完整`);
+        } else {
+          message.success(`成功转换到 ${results.length} 个平台！`);
+        }
       } else {
         // 转换到指定平台
-        const result = await invoke<string>('convert_query', {
+        const result = await invoke<ConversionResult>('convert_query', {
           query: normalizedQuery,
           fromPlatform: fromPlatform,
-          toPlatform: toPlatform
+          toPlatform: toPlatform,
+          strict: strictMode
         });
-        setConversionResults([{ platform: toPlatform, query: result }]);
-        message.success('转换成功！');
+        setConversionResults([result]);
+        if (result.warnings.length > 0) {
+          message.warning('转换完成，部分条件无法在目标平台表达');
+        } else {
+          message.success('转换成功！');
+        }
       }
       if (normalizedQuery !== query) {
         setQuery(normalizedQuery);
@@ -168,6 +253,7 @@ const QueryConverter: React.FC = () => {
     setQuery('');
     setConversionResults([]);
     setValidationResult(null);
+    setDialectScores([]);
   };
 
   // 示例查询语句
@@ -191,6 +277,21 @@ const QueryConverter: React.FC = () => {
       'ip="8.8.8.8"',
       'title="登录" && country="CN"',
       'body="powered by" && port="80"'
+    ],
+    shodan: [
+      'net:"8.8.8.8"',
+      'http.title:"login" country:"US"',
+      'http.html:"powered by" port:"80"'
+    ],
+    zoomeye: [
+      'ip="8.8.8.8"',
+      'title="登录" && country="CN"',
+      'app="nginx" && service="https"'
+    ],
+    censys: [
+      'ip: "8.8.8.8"',
+      'services.http.response.html_title: "login" AND location.country: "China"',
+      'services.port: 443 AND services.service_name: "HTTP"'
     ]
   };
 
@@ -223,6 +324,9 @@ const QueryConverter: React.FC = () => {
             <Row gutter={16}>
               <Col span={12}>
                 <Text strong>源平台：</Text>
+                <Button type="link" size="small" onClick={handleDetect} loading={detecting}>
+                  自动识别
+                </Button>
                 <Select
                   value={fromPlatform}
                   onChange={(value) => {
@@ -239,6 +343,24 @@ const QueryConverter: React.FC = () => {
                     </Option>
                   ))}
                 </Select>
+                {dialectScores.length > 0 && (
+                  <div style={{ marginTop: 8 }}>
+                    {dialectScores.slice(0, 3).map(score => (
+                      <Tooltip
+                        key={score.platform}
+                        title={score.errors > 0 ? `按该平台语法有 ${score.errors} 处错误` : '符合该平台语法'}
+                      >
+                        <Tag
+                          color={score.platform === fromPlatform ? platformColors[score.platform] : undefined}
+                          style={{ cursor: 'pointer' }}
+                          onClick={() => setFromPlatform(score.platform)}
+                        >
+                          {platformNames[score.platform] || score.platform} {(score.confidence * 100).toFixed(0)}%
+                        </Tag>
+                      </Tooltip>
+                    ))}
+                  </div>
+                )}
               </Col>
               <Col span={12}>
                 <Text strong>转换模式：</Text>
@@ -254,6 +376,12 @@ const QueryConverter: React.FC = () => {
               </Col>
             </Row>
 
+            <Space>
+              <Text strong>严格模式：</Text>
+              <Switch checked={strictMode} onChange={setStrictMode} />
+              <Text type="secondary">开启后，存在被丢弃或近似转换的条件时拒绝转换</Text>
+            </Space>
+
             {conversionMode === 'single' && (
               <div>
                 <Text strong>目标平台：</Text>
@@ -287,6 +415,7 @@ const QueryConverter: React.FC = () => {
                 onChange={(e) => {
                   setQuery(normalizeSmartPunctuation(e.target.value));
                   setValidationResult(null);
+                  setDialectScores([]);
                 }}
                 placeholder="输入查询语句，例如: ip=&quot;8.8.8.8&quot; && port=&quot;80&quot;"
                 rows={4}
@@ -302,8 +431,32 @@ const QueryConverter: React.FC = () => {
             {validationResult && (
               <Alert
                 message={validationResult.valid ? '语法验证通过' : '语法验证失败'}
-                description={!validationResult.valid ? validationResult.error : undefined}
-                type={validationResult.valid ? 'success' : 'error'}
+                description={
+                  validationResult.diagnostics?.length ? (
+                    <Space direction="vertical" size={4}>
+                      {validationResult.diagnostics.map((d, index) => {
+                        const start = byteToCharOffset(query, d.span.start);
+                        const end = byteToCharOffset(query, d.span.end);
+                        const snippet = query.slice(start, end);
+                        return (
+                          <Text key={index}>
+                            <Tag color={d.severity === 'error' ? 'red' : 'orange'}>
+                              {d.severity === 'error' ? '错误' : '警告'}
+                            </Tag>
+                            <Text code>第 {start + 1} 列{snippet ? ` "${snippet}"` : ''}</Text>{' '}
+                            {d.message}
+                            {d.suggestion != null && (
+                              <Text type="secondary">
+                                {' '}建议: {d.suggestion === '' ? '删除' : <Text code>{d.suggestion}</Text>}
+                              </Text>
+                            )}
+                          </Text>
+                        );
+                      })}
+                    </Space>
+                  ) : !validationResult.valid ? validationResult.error : undefined
+                }
+                type={validationResult.valid ? (validationResult.diagnostics?.length ? 'warning' : 'success') : 'error'}
                 icon={validationResult.valid ? <CheckCircleOutlined /> : <CloseCircleOutlined />}
                 showIcon
                 closable
@@ -364,19 +517,50 @@ const QueryConverter: React.FC = () => {
                       <Tag color={platformColors[result.platform]}>
                         {platformNames[result.platform] || result.platform}
                       </Tag>
+                      {result.error ? (
+                        <Tag color="error" icon={<CloseCircleOutlined />}>转换失败</Tag>
+                      ) : result.warnings.length > 0 && (
+                        <Tag color="warning" icon={<WarningOutlined />}>
+                          结果不完整（{result.warnings.length} 个条件）
+                        </Tag>
+                      )}
                     </Space>
                   }
                   extra={
                     <Button
                       type="link"
                       icon={<CopyOutlined />}
+                      disabled={!!result.error}
                       onClick={() => handleCopy(result.query, result.platform)}
                     >
                       复制
                     </Button>
                   }
                 >
-                  <TextArea
+                  {result.error && (
+                    <Alert type="error" showIcon message={result.error} style={{ marginBottom: 8 }} />
+                  )}
+                  {result.warnings.length > 0 && (
+                    <Alert
+                      type="warning"
+                      showIcon
+                      style={{ marginBottom: 8 }}
+                      message="以下条件无法在该平台准确表达"
+                      description={
+                        <Space direction="vertical" size={4}>
+                          {result.warnings.map((warning, index) => (
+                            <Text key={index}>
+                              <Tag color={warning.kind === 'dropped' ? 'red' : 'orange'}>
+                                {warning.kind === 'dropped' ? '已丢弃' : '近似转换'}
+                              </Tag>
+                              <Text code>{warning.predicate}</Text> {warning.message}
+                            </Text>
+                          ))}
+                        </Space>
+                      }
+                    />
+                  )}
+                  {!result.error && <TextArea
                     value={result.query}
                     readOnly
                     autoSize={{ minRows: 2, maxRows: 6 }}
@@ -387,7 +571,7 @@ const QueryConverter: React.FC = () => {
                     autoCorrect="off"
                     autoCapitalize="off"
                     spellCheck={false}
-                  />
+                  />}
                 </Card>
               ))}
             </Space>
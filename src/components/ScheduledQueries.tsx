@@ -0,0 +1,423 @@
+import React, { useState, useEffect, useCallback } from 'react';
+import {
+  Card,
+  Table,
+  Button,
+  Form,
+  Input,
+  InputNumber,
+  Modal,
+  Radio,
+  Select,
+  Space,
+  Switch,
+  Tag,
+  TimePicker,
+  Popconfirm,
+  message,
+  Tooltip,
+  Typography
+} from 'antd';
+import {
+  FieldTimeOutlined,
+  PlusOutlined,
+  ReloadOutlined,
+  EditOutlined,
+  DeleteOutlined,
+  PlayCircleOutlined
+} from '@ant-design/icons';
+import { invoke } from '@tauri-apps/api/core';
+import dayjs from 'dayjs';
+
+const { Title, Text } = Typography;
+
+type ScheduleRule =
+  | { type: 'daily'; time: string }
+  | { type: 'interval'; hours: number };
+
+interface ScheduleState {
+  nextRun?: string;
+  lastRun?: string;
+  lastSuccess?: boolean;
+  lastError?: string;
+  lastNewAssets: number;
+  runCount: number;
+}
+
+interface ScheduledQuery {
+  id: string;
+  name: string;
+  query: string;
+  platforms: string[];
+  pages: number;
+  pageSize: number;
+  creditBudget?: number | null;
+  exportFormat?: string | null;
+  schedule: ScheduleRule;
+  enabled: boolean;
+  state: ScheduleState;
+}
+
+interface ScheduleFormValues {
+  name: string;
+  query: string;
+  platforms: string[];
+  pages: number;
+  pageSize: number;
+  creditBudget?: number | null;
+  exportFormat: string;
+  ruleType: 'daily' | 'interval';
+  time?: dayjs.Dayjs;
+  hours?: number;
+  enabled: boolean;
+}
+
+interface ScheduledQueriesProps {
+  active?: boolean;
+}
+
+const platformOptions = [
+  { label: 'Hunter', value: 'hunter' },
+  { label: 'FOFA', value: 'fofa' },
+  { label: 'Quake', value: 'quake' },
+  { label: 'DayDayMap', value: 'daydaymap' },
+  { label: 'Shodan', value: 'shodan' },
+  { label: 'ZoomEye', value: 'zoomeye' },
+  { label: 'Censys', value: 'censys' },
+];
+
+const formatTime = (value?: string) => (value ? dayjs(value).format('YYYY-MM-DD HH:mm') : '-');
+
+const describeRule = (rule: ScheduleRule) =>
+  rule.type === 'daily' ? `每天 ${rule.time}` : `每 ${rule.hours} 小时`;
+
+const ScheduledQueries: React.FC<ScheduledQueriesProps> = ({ active = true }) => {
+  const [jobs, setJobs] = useState<ScheduledQuery[]>([]);
+  const [loading, setLoading] = useState<boolean>(false);
+  const [editing, setEditing] = useState<ScheduledQuery | null>(null);
+  const [modalVisible, setModalVisible] = useState<boolean>(false);
+  const [saving, setSaving] = useState<boolean>(false);
+  const [form] = Form.useForm<ScheduleFormValues>();
+  const ruleType = Form.useWatch('ruleType', form);
+
+  // 加载定时任务
+  const loadJobs = useCallback(async () => {
+    setLoading(true);
+    try {
+      setJobs(await invoke<ScheduledQuery[]>('list_scheduled_queries'));
+    } catch (error) {
+      message.error(`加载定时任务失败: ${error}`);
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    if (!active) {
+      return;
+    }
+
+    loadJobs();
+    // 任务在后台运行，定期刷新运行状态
+    const timer = window.setInterval(loadJobs, 30000);
+    return () => window.clearInterval(timer);
+  }, [active, loadJobs]);
+
+  const openEditor = (job: ScheduledQuery | null) => {
+    setEditing(job);
+    form.setFieldsValue(
+      job
+        ? {
+            name: job.name,
+            query: job.query,
+            platforms: job.platforms,
+            pages: job.pages,
+            pageSize: job.pageSize,
+            creditBudget: job.creditBudget ?? null,
+            exportFormat: job.exportFormat ?? 'none',
+            ruleType: job.schedule.type,
+            time: job.schedule.type === 'daily' ? dayjs(job.schedule.time, 'HH:mm') : dayjs('02:00', 'HH:mm'),
+            hours: job.schedule.type === 'interval' ? job.schedule.hours : 6,
+            enabled: job.enabled,
+          }
+        : {
+            name: '',
+            query: '',
+            platforms: ['fofa'],
+            pages: 5,
+            pageSize: 100,
+            creditBudget: null,
+            exportFormat: 'none',
+            ruleType: 'daily',
+            time: dayjs('02:00', 'HH:mm'),
+            hours: 6,
+            enabled: true,
+          }
+    );
+    setModalVisible(true);
+  };
+
+  const toJob = (values: ScheduleFormValues, base: ScheduledQuery | null): ScheduledQuery => ({
+    id: base?.id ?? '',
+    name: values.name,
+    query: values.query,
+    platforms: values.platforms,
+    pages: values.pages,
+    pageSize: values.pageSize,
+    creditBudget: values.creditBudget ?? null,
+    exportFormat: values.exportFormat === 'none' ? null : values.exportFormat,
+    schedule:
+      values.ruleType === 'daily'
+        ? { type: 'daily', time: (values.time ?? dayjs('02:00', 'HH:mm')).format('HH:mm') }
+        : { type: 'interval', hours: values.hours ?? 6 },
+    enabled: values.enabled,
+    state: base?.state ?? { lastNewAssets: 0, runCount: 0 },
+  });
+
+  const saveJob = async (job: ScheduledQuery) => {
+    await invoke<ScheduledQuery>('save_scheduled_query', { job });
+    loadJobs();
+  };
+
+  const submitEditor = async () => {
+    const values = await form.validateFields();
+    setSaving(true);
+    try {
+      await saveJob(toJob(values, editing));
+      message.success('定时任务已保存');
+      setModalVisible(false);
+    } catch (error) {
+      message.error(`保存失败: ${error}`);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  const toggleJob = async (job: ScheduledQuery, enabled: boolean) => {
+    try {
+      await saveJob({ ...job, enabled });
+    } catch (error) {
+      message.error(`保存失败: ${error}`);
+    }
+  };
+
+  const runNow = async (job: ScheduledQuery) => {
+    try {
+      await invoke('run_scheduled_query_now', { id: job.id });
+      message.success('任务已开始运行，完成后结果将写入历史记录');
+      loadJobs();
+    } catch (error) {
+      message.error(`运行失败: ${error}`);
+    }
+  };
+
+  const deleteJob = async (job: ScheduledQuery) => {
+    try {
+      await invoke('delete_scheduled_query', { id: job.id });
+      message.success('删除成功');
+      loadJobs();
+    } catch (error) {
+      message.error(`删除失败: ${error}`);
+    }
+  };
+
+  const columns = [
+    {
+      title: '任务',
+      key: 'name',
+      render: (_: any, job: ScheduledQuery) => (
+        <Space direction="vertical" size={0}>
+          <Text strong>{job.name}</Text>
+          <Tooltip title={job.query} placement="topLeft">
+            <Text code style={{ maxWidth: 320 }} ellipsis>{job.query}</Text>
+          </Tooltip>
+        </Space>
+      ),
+    },
+    {
+      title: '平台',
+      dataIndex: 'platforms',
+      key: 'platforms',
+      width: 180,
+      render: (platforms: string[]) => (
+        <Space size={[0, 4]} wrap>
+          {platforms.map((platform) => <Tag key={platform}>{platform.toUpperCase()}</Tag>)}
+        </Space>
+      ),
+    },
+    {
+      title: '周期',
+      key: 'schedule',
+      width: 120,
+      render: (_: any, job: ScheduledQuery) => describeRule(job.schedule),
+    },
+    {
+      title: '下次运行',
+      key: 'nextRun',
+      width: 150,
+      render: (_: any, job: ScheduledQuery) => (job.enabled ? formatTime(job.state.nextRun) : '-'),
+    },
+    {
+      title: '上次运行',
+      key: 'lastRun',
+      width: 200,
+      render: (_: any, job: ScheduledQuery) => {
+        if (!job.state.lastRun) {
+          return <Text type="secondary">尚未运行</Text>;
+        }
+        return (
+          <Tooltip title={job.state.lastError || ''}>
+            <Space>
+              <Text>{formatTime(job.state.lastRun)}</Text>
+              <Tag color={job.state.lastSuccess ? 'success' : 'error'}>
+                {job.state.lastSuccess ? `新增 ${job.state.lastNewAssets}` : '失败'}
+              </Tag>
+            </Space>
+          </Tooltip>
+        );
+      },
+    },
+    {
+      title: '启用',
+      dataIndex: 'enabled',
+      key: 'enabled',
+      width: 70,
+      render: (enabled: boolean, job: ScheduledQuery) => (
+        <Switch size="small" checked={enabled} onChange={(checked) => toggleJob(job, checked)} />
+      ),
+    },
+    {
+      title: '操作',
+      key: 'action',
+      width: 130,
+      render: (_: any, job: ScheduledQuery) => (
+        <Space size="small">
+          <Tooltip title="立即运行">
+            <Button
+              type="link"
+              size="small"
+              icon={<PlayCircleOutlined />}
+              onClick={() => runNow(job)}
+              disabled={!job.enabled}
+            />
+          </Tooltip>
+          <Tooltip title="编辑">
+            <Button type="link" size="small" icon={<EditOutlined />} onClick={() => openEditor(job)} />
+          </Tooltip>
+          <Popconfirm
+            title="确定要删除这个定时任务吗？"
+            onConfirm={() => deleteJob(job)}
+            okText="确定"
+            cancelText="取消"
+          >
+            <Tooltip title="删除">
+              <Button type="link" size="small" danger icon={<DeleteOutlined />} />
+            </Tooltip>
+          </Popconfirm>
+        </Space>
+      ),
+    },
+  ];
+
+  return (
+    <div className="scheduled-queries">
+      <Card
+        className="glass-effect"
+        bordered={false}
+        title={
+          <Space>
+            <FieldTimeOutlined />
+            <Title level={4} style={{ margin: 0 }}>定时任务</Title>
+          </Space>
+        }
+        extra={
+          <Space>
+            <Button icon={<ReloadOutlined />} onClick={loadJobs} loading={loading}>
+              刷新
+            </Button>
+            <Button type="primary" icon={<PlusOutlined />} onClick={() => openEditor(null)}>
+              新建任务
+            </Button>
+          </Space>
+        }
+      >
+        <Text type="secondary" style={{ display: 'block', marginBottom: 16 }}>
+          任务在应用运行期间按周期自动执行，结果写入历史记录与本地资产库；发现新资产时会弹出通知。
+        </Text>
+        <Table columns={columns} dataSource={jobs} rowKey="id" loading={loading} pagination={false} />
+      </Card>
+
+      <Modal
+        title={editing ? '编辑定时任务' : '新建定时任务'}
+        open={modalVisible}
+        onCancel={() => !saving && setModalVisible(false)}
+        onOk={submitEditor}
+        confirmLoading={saving}
+        okText="保存"
+        cancelText="取消"
+        width={600}
+      >
+        <Form form={form} layout="vertical">
+          <Form.Item name="name" label="任务名称" rules={[{ required: true, message: '请输入任务名称' }]}>
+            <Input placeholder="例如：每日暴露面巡检" />
+          </Form.Item>
+          <Form.Item name="query" label="查询语句" rules={[{ required: true, message: '请输入查询语句' }]}>
+            <Input.TextArea rows={3} placeholder="按任一平台语法填写，运行时自动转换到所选平台" />
+          </Form.Item>
+          <Form.Item name="platforms" label="平台" rules={[{ required: true, message: '至少选择一个平台' }]}>
+            <Select mode="multiple" options={platformOptions} />
+          </Form.Item>
+          <Space size={16} wrap>
+            <Form.Item name="pages" label="每个平台页数" rules={[{ required: true }]}>
+              <InputNumber min={1} max={100} />
+            </Form.Item>
+            <Form.Item name="pageSize" label="每页条数" rules={[{ required: true }]}>
+              <InputNumber min={1} max={1000} />
+            </Form.Item>
+            <Form.Item name="creditBudget" label="额度预算" tooltip="每次运行每个平台最多消耗的额度（按平台计费单位），下一页会超出时停止获取，留空表示不限">
+              <InputNumber min={1} placeholder="不限" />
+            </Form.Item>
+          </Space>
+          <Space size={16} wrap align="start">
+            <Form.Item name="ruleType" label="运行周期">
+              <Radio.Group
+                options={[
+                  { label: '每天', value: 'daily' },
+                  { label: '按间隔', value: 'interval' },
+                ]}
+                optionType="button"
+              />
+            </Form.Item>
+            {ruleType === 'interval' ? (
+              <Form.Item name="hours" label="间隔（小时）" rules={[{ required: true }]}>
+                <InputNumber min={1} max={720} />
+              </Form.Item>
+            ) : (
+              <Form.Item name="time" label="运行时间" rules={[{ required: true }]}>
+                <TimePicker format="HH:mm" />
+              </Form.Item>
+            )}
+          </Space>
+          <Space size={16} wrap>
+            <Form.Item name="exportFormat" label="同时导出">
+              <Select
+                style={{ width: 160 }}
+                options={[
+                  { label: '不导出', value: 'none' },
+                  { label: 'CSV', value: 'csv' },
+                  { label: 'JSON', value: 'json' },
+                  { label: 'XLSX', value: 'xlsx' },
+                ]}
+              />
+            </Form.Item>
+            <Form.Item name="enabled" label="启用" valuePropName="checked">
+              <Switch />
+            </Form.Item>
+          </Space>
+        </Form>
+      </Modal>
+    </div>
+  );
+};
+
+export default ScheduledQueries;
@@ -17,8 +17,41 @@ interface Settings {
   proxyUsername: string;
   proxyPassword: string;
   requestTimeout: number;
+  rateLimits: Record<string, RateLimit>;
+  retryPolicy: RetryPolicy;
+  keyRotation: Record<string, KeyRotation>;
 }
 
+interface RateLimit {
+  requestsPerSecond: number;
+  burst: number;
+}
+
+interface RetryPolicy {
+  maxAttempts: number;
+  baseDelayMs: number;
+  maxDelayMs: number;
+}
+
+type RotationStrategy = 'sequential' | 'roundRobin' | 'mostRemaining' | 'leastRecentlyUsed' | 'weighted';
+
+interface KeyRotation {
+  strategy: RotationStrategy;
+  sticky: boolean;
+  preferredTags: string[];
+}
+
+// 各平台默认频率限制，与后端默认值一致
+const DEFAULT_RATE_LIMITS: Record<string, RateLimit> = {
+  hunter: { requestsPerSecond: 0.5, burst: 3 },
+  fofa: { requestsPerSecond: 0.5, burst: 3 },
+  quake: { requestsPerSecond: 0.5, burst: 3 },
+  daydaymap: { requestsPerSecond: 0.5, burst: 3 },
+  shodan: { requestsPerSecond: 1, burst: 3 },
+  censys: { requestsPerSecond: 1, burst: 3 },
+  zoomeye: { requestsPerSecond: 1, burst: 3 },
+};
+
 const Settings: React.FC = () => {
   const [form] = Form.useForm();
   const [loading, setLoading] = useState<boolean>(false);
@@ -91,7 +124,19 @@ const Settings: React.FC = () => {
     { value: 'hunter', label: 'Hunter' },
     { value: 'fofa', label: 'FOFA' },
     { value: 'quake', label: 'Quake' },
-    { value: 'daydaymap', label: 'DayDayMap' }
+    { value: 'daydaymap', label: 'DayDayMap' },
+    { value: 'shodan', label: 'Shodan' },
+    { value: 'zoomeye', label: 'ZoomEye' },
+    { value: 'censys', label: 'Censys' }
+  ];
+
+  // key 轮询策略选项
+  const strategyOptions = [
+    { value: 'sequential', label: '顺序（耗尽后换下一个）' },
+    { value: 'roundRobin', label: '轮询' },
+    { value: 'mostRemaining', label: '剩余额度优先' },
+    { value: 'leastRecentlyUsed', label: '最久未用优先' },
+    { value: 'weighted', label: '按权重' }
   ];
 
   // 页码选项
@@ -134,6 +179,9 @@ const Settings: React.FC = () => {
           proxyUsername: '',
           proxyPassword: '',
           requestTimeout: 30,
+          rateLimits: DEFAULT_RATE_LIMITS,
+          retryPolicy: { maxAttempts: 3, baseDelayMs: 2000, maxDelayMs: 60000 },
+          keyRotation: {},
         }}
       >
         <Card title="基本设置" size="small" className="glass-effect" bordered={false}>
@@ -188,6 +236,60 @@ const Settings: React.FC = () => {
 
         <Divider />
 
+        <Card title="请求频率与重试" size="small" className="glass-effect" bordered={false}>
+          <p className="text-muted">每个平台的请求按令牌桶限速：空闲后最多连发“突发数”个请求，之后按每秒请求数匀速发送；平台返回 429 时按 Retry-After 暂停。</p>
+          {platformOptions.map(({ value, label }) => (
+            <Space key={value} align="baseline" style={{ display: 'flex' }}>
+              <span style={{ display: 'inline-block', width: 90 }}>{label}</span>
+              <Form.Item name={['rateLimits', value, 'requestsPerSecond']} label="每秒请求数">
+                <InputNumber min={0.05} max={20} step={0.1} />
+              </Form.Item>
+              <Form.Item name={['rateLimits', value, 'burst']} label="突发数">
+                <InputNumber min={1} max={20} precision={0} />
+              </Form.Item>
+            </Space>
+          ))}
+
+          <Space align="baseline" wrap>
+            <Form.Item name={['retryPolicy', 'maxAttempts']} label="最多尝试次数" extra="含首次请求">
+              <InputNumber min={1} max={10} precision={0} />
+            </Form.Item>
+            <Form.Item name={['retryPolicy', 'baseDelayMs']} label="初始退避（毫秒）" extra="之后每次翻倍并加入随机抖动">
+              <InputNumber min={100} max={60000} step={500} precision={0} />
+            </Form.Item>
+            <Form.Item name={['retryPolicy', 'maxDelayMs']} label="最长等待（毫秒）" extra="Retry-After 超过此值时不再重试">
+              <InputNumber min={1000} max={600000} step={1000} precision={0} />
+            </Form.Item>
+          </Space>
+        </Card>
+
+        <Divider />
+
+        <Card title="密钥轮询策略" size="small" className="glass-effect" bordered={false}>
+          <p className="text-muted">配置多个 API 密钥时按策略选择使用哪个；权重与标签在 API 密钥管理的密钥池状态中设置。优先标签的密钥都不可用时才使用其它密钥。</p>
+          {platformOptions.map(({ value, label }) => (
+            <Space key={value} align="baseline" style={{ display: 'flex' }} wrap>
+              <span style={{ display: 'inline-block', width: 90 }}>{label}</span>
+              <Form.Item name={['keyRotation', value, 'strategy']} label="策略">
+                <Select options={strategyOptions} placeholder="顺序（耗尽后换下一个）" style={{ width: 200 }} />
+              </Form.Item>
+              <Form.Item name={['keyRotation', value, 'preferredTags']} label="优先标签">
+                <Select mode="tags" placeholder="如 付费" style={{ width: 200 }} />
+              </Form.Item>
+              <Form.Item
+                name={['keyRotation', value, 'sticky']}
+                label="任务内固定密钥"
+                valuePropName="checked"
+                tooltip="同一导出任务始终使用同一个密钥，直到它额度耗尽，保证分页结果一致"
+              >
+                <Switch />
+              </Form.Item>
+            </Space>
+          ))}
+        </Card>
+
+        <Divider />
+
         <Card title="界面设置" size="small" className="glass-effect" bordered={false}>
           <Form.Item
             label="主题"
@@ -21,6 +21,8 @@ export function useExportProgress() {
     const [statusText, setStatusText] = useState('');
     const [logs, setLogs] = useState<ProgressLog[]>([]);
     const [summary, setSummary] = useState<SummaryItem[]>([]);
+    // 多平台任务中各平台（子任务）的状态
+    const [platforms, setPlatforms] = useState<Record<string, ProgressStatus>>({});
     const activeTaskIdRef = useRef<string | null>(null);
 
     // 组件挂载后保持一个轻量监听器，避免任务刚启动时错过后端立即发出的首个进度事件。
@@ -41,7 +43,13 @@ export function useExportProgress() {
                     return;
                 }
                 setPercent(data.percent);
-                setStatus(data.status);
+                // 单个平台（子任务）结束不代表整个任务结束
+                const isSubTask = data.taskId !== activeTaskId;
+                setStatus(isSubTask && data.status !== 'running' ? 'running' : data.status);
+                if (isSubTask && data.platform) {
+                    const platform = data.platform;
+                    setPlatforms(prev => ({ ...prev, [platform]: data.status }));
+                }
                 setStatusText(data.statusText);
 
                 if (data.logMessage) {
@@ -96,6 +104,7 @@ export function useExportProgress() {
             type: 'info',
         }]);
         setSummary([]);
+        setPlatforms({});
     }, []);
 
     const finishTask = useCallback(() => {
@@ -118,6 +127,7 @@ export function useExportProgress() {
         statusText, setStatusText,
         logs,
         summary,
+        platforms,
         startTask,
         finishTask,
         addLog,
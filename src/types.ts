@@ -15,4 +15,6 @@ export interface ProgressEventPayload {
     totalPages?: number;
     totalResults?: number;
     fetchedResults?: number;
+    // 多平台并发任务中事件所属的平台
+    platform?: string;
 }
@@ -0,0 +1,43 @@
+// 后端 ApiError 序列化后的结构
+export type ApiErrorKind =
+  | 'auth'
+  | 'quotaExhausted'
+  | 'rateLimited'
+  | 'badQuery'
+  | 'upstream'
+  | 'network'
+  | 'parse'
+  | 'config';
+
+export interface ApiError {
+  kind: ApiErrorKind;
+  message: string;
+  retryAfter?: number;
+  status?: number;
+}
+
+const KIND_HINTS: Record<ApiErrorKind, string> = {
+  auth: '请检查 API Key 是否正确',
+  quotaExhausted: '所有 Key 额度已用完，请添加新 Key 或等待额度重置',
+  rateLimited: '请求过于频繁，请稍后再试',
+  badQuery: '请检查查询语法',
+  upstream: '平台服务异常，请稍后重试',
+  network: '请检查网络或代理设置',
+  parse: '平台返回了无法识别的数据',
+  config: '请在 API 配置中完成设置',
+};
+
+export const isApiError = (error: unknown): error is ApiError =>
+  typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+
+// 将 invoke 抛出的错误转成展示文本，ApiError 会附带按类型的处理提示
+export const formatApiError = (error: unknown): string => {
+  if (typeof error === 'string') return error;
+  if (isApiError(error)) {
+    const hint = error.kind === 'rateLimited' && error.retryAfter
+      ? `请 ${error.retryAfter} 秒后再试`
+      : KIND_HINTS[error.kind];
+    return hint ? `${error.message}（${hint}）` : error.message;
+  }
+  return (error as any)?.message || '未知错误';
+};